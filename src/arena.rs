@@ -0,0 +1,96 @@
+use crate::character::class::{Category, Class};
+use crate::character::{enemy, Character};
+use crate::randomizer;
+use rand::prelude::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::cmp::min;
+
+/// Gold added to the arena pot per wave and hero level.
+pub const PAYOUT_PER_LEVEL: i32 = 25;
+
+/// Every this many waves the arena sends a legendary creature alone.
+pub const BOSS_WAVE: i32 = 10;
+
+/// From this wave on the arena sends rare creatures.
+pub const RARE_WAVE: i32 = 5;
+
+/// Most enemies fought in a single wave.
+const MAX_ENEMIES: i32 = 3;
+
+/// A run of arena waves fought at home. The pot collects the payout of
+/// each wave cleared, and is lost if the hero dies before cashing out.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Arena {
+    pub wave: i32,
+    pub pot: i32,
+}
+
+impl Arena {
+    /// Start the next wave, returning the enemies to fight in it.
+    pub fn next_wave(&mut self, player: &Character) -> Vec<Character> {
+        self.wave += 1;
+        let level = player.level + self.wave / 2;
+        if self.wave % BOSS_WAVE == 0 {
+            return vec![Character::new(contender(Category::Legendary), level)];
+        }
+
+        let category = if self.wave >= RARE_WAVE {
+            Category::Rare
+        } else {
+            Category::Common
+        };
+        let count = min(MAX_ENEMIES, 1 + (self.wave - 1) / 3);
+        (0..count)
+            .map(|_| Character::new(contender(category.clone()), level))
+            .collect()
+    }
+
+    /// Add the payout of the wave just cleared to the pot and return it.
+    /// It's based on the level the hero entered the wave with, so leveling
+    /// up during the fight doesn't change it.
+    pub fn wave_cleared(&mut self, level: i32) -> i32 {
+        let payout = self.wave * level * PAYOUT_PER_LEVEL;
+        self.pot += payout;
+        payout
+    }
+}
+
+/// A random enemy class of the given category to send to the arena. The
+/// bosses only show up where the story calls for them.
+fn contender(category: Category) -> Class {
+    let classes: Vec<_> = Class::enemies()
+        .into_iter()
+        .filter(|class| class.category == category && !enemy::is_boss(class))
+        .collect();
+    (*classes.choose(&mut randomizer::rng()).unwrap()).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waves() {
+        let player = Character::player();
+        let mut arena = Arena::default();
+
+        let enemies = arena.next_wave(&player);
+        assert_eq!(1, enemies.len());
+        assert_eq!(Category::Common, enemies[0].class.category);
+        assert_eq!(PAYOUT_PER_LEVEL, arena.wave_cleared(player.level));
+
+        for _ in 2..BOSS_WAVE {
+            arena.next_wave(&player);
+        }
+        let enemies = arena.next_wave(&player);
+        assert_eq!(BOSS_WAVE, arena.wave);
+        assert_eq!(1, enemies.len());
+        assert_eq!(Category::Legendary, enemies[0].class.category);
+        assert!(!enemy::is_boss(&enemies[0].class));
+        assert_eq!(1 + BOSS_WAVE / 2, enemies[0].level);
+
+        let enemies = arena.next_wave(&player);
+        assert_eq!(MAX_ENEMIES as usize, enemies.len());
+        assert_eq!(Category::Rare, enemies[0].class.category);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::game::Game;
+use crate::item::key::Key;
+use crate::log;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Distance from home of the deep dungeon where the lantern is found.
+pub const LANTERN_DISTANCE: i32 = 50;
+
+/// A unique legendary relic. Artifacts are kept across deaths, and once
+/// all of them are collected the true final boss can be faced.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum Artifact {
+    Amulet,
+    Crown,
+    Mirror,
+    Scroll,
+    Lantern,
+    Seal,
+}
+
+impl Artifact {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Artifact::Amulet => "the Amulet of Power",
+            Artifact::Crown => "the Guardian's Crown",
+            Artifact::Mirror => "the Shadow Mirror",
+            Artifact::Scroll => "the Source Scroll",
+            Artifact::Lantern => "the Deep Lantern",
+            Artifact::Seal => "the Seal of Gorthaur",
+        }
+    }
+
+    /// Where the artifact is hidden.
+    pub fn describe(&self) -> String {
+        match self {
+            Artifact::Amulet => String::from("find the amulet in a chest"),
+            Artifact::Crown => String::from("defeat the guardian of the amulet"),
+            Artifact::Mirror => String::from("defeat the shadow"),
+            Artifact::Scroll => String::from("defeat the dev"),
+            Artifact::Lantern => format!("reach {} directories away from home", LANTERN_DISTANCE),
+            Artifact::Seal => String::from("defeat gorthaur"),
+        }
+    }
+
+    fn is_found(&self, game: &Game) -> bool {
+        let stats = &game.stats;
+        let defeated = |enemy: &str| stats.bestiary.contains_key(enemy);
+        match self {
+            Artifact::Amulet => {
+                game.player.amulet.is_some() || game.inventory.contains_key(&Key::Amulet)
+            }
+            Artifact::Crown => defeated("guardian"),
+            Artifact::Mirror => defeated("shadow"),
+            Artifact::Scroll => defeated("dev"),
+            Artifact::Lantern => stats.deepest_distance >= LANTERN_DISTANCE,
+            Artifact::Seal => defeated("gorthaur"),
+        }
+    }
+}
+
+/// Add the newly found artifacts to the collection, announcing when
+/// it's complete.
+pub fn collect(game: &mut Game) {
+    for artifact in Artifact::iter() {
+        if !game.artifacts.contains(&artifact) && artifact.is_found(game) {
+            game.artifacts.push(artifact);
+            log::artifact_found(artifact);
+            if is_complete(game) {
+                log::artifacts_complete();
+            }
+        }
+    }
+}
+
+/// Whether all the artifacts were found, unlocking the true final boss.
+pub fn is_complete(game: &Game) -> bool {
+    game.artifacts.len() == Artifact::iter().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::amulet::Amulet;
+
+    #[test]
+    fn test_collect() {
+        let mut game = Game::new();
+        collect(&mut game);
+        assert!(game.artifacts.is_empty());
+
+        game.add_item(Box::new(Amulet::new()));
+        game.stats.bestiary.insert(String::from("shadow"), 1);
+        collect(&mut game);
+        assert_eq!(vec![Artifact::Amulet, Artifact::Mirror], game.artifacts);
+
+        // losing the amulet doesn't take it out of the collection
+        game.inventory.clear();
+        collect(&mut game);
+        assert_eq!(2, game.artifacts.len());
+        assert!(!is_complete(&game));
+
+        for boss in ["guardian", "dev", "gorthaur"] {
+            game.stats.bestiary.insert(String::from(boss), 1);
+        }
+        game.stats.deepest_distance = LANTERN_DISTANCE;
+        collect(&mut game);
+        assert!(is_complete(&game));
+    }
+}
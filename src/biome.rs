@@ -0,0 +1,57 @@
+use crate::location::Location;
+use crate::terrain;
+
+/// Subdirectories needed for a directory to be overgrown into a forest.
+pub const FOREST_SUBDIRECTORIES: usize = 6;
+
+/// The wilds a directory resembles, deciding which materials can be
+/// gathered in it. Independent from its terrain.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Biome {
+    Meadow,
+    /// Directories branching into many subdirectories.
+    Forest,
+    /// System directories and read-only mounts, where ore and gems are
+    /// mined.
+    Mountain,
+}
+
+impl Biome {
+    pub fn of(location: &Location) -> Self {
+        if terrain::is_elite_zone(location) || location.in_read_only_mount() {
+            Biome::Mountain
+        } else {
+            Self::overgrown(location)
+        }
+    }
+
+    /// Forest or meadow, depending on how much the directory branches out.
+    fn overgrown(location: &Location) -> Self {
+        if location.subdirectories().len() >= FOREST_SUBDIRECTORIES {
+            Biome::Forest
+        } else {
+            Biome::Meadow
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_biome() {
+        let root = std::env::temp_dir().join("rpg-test-biome");
+        for index in 0..FOREST_SUBDIRECTORIES {
+            let dir = root.join(format!("tree{}", index));
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        let location = |path: &std::path::Path| Location::from(path.to_str().unwrap()).unwrap();
+        // checked apart since the temporary directory may be root's
+        assert_eq!(Biome::Forest, Biome::overgrown(&location(&root)));
+        assert_eq!(
+            Biome::Meadow,
+            Biome::overgrown(&location(&root.join("tree0")))
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use crate::character::class::{Category, Class};
+use crate::config;
+use crate::error::{bail, Result};
+use crate::location::Location;
+use crate::quest::Event;
+use crate::randomizer;
+use core::fmt;
+use rand::prelude::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// How many contracts are offered on the board at a time.
+const OFFERS: usize = 3;
+
+/// How many contracts the hero can have accepted at once.
+pub const MAX_ACTIVE: usize = 3;
+
+/// Hours until the offered contracts are replaced by new ones.
+const ROTATION_HOURS: u64 = 24;
+
+/// Gold paid per level of the hero for each unit of work of a contract:
+/// an enemy killed or a step travelled.
+const GOLD_PER_LEVEL: i32 = 20;
+
+/// Percentage added to the gold of new contracts per reputation point.
+pub const REPUTATION_BONUS: i32 = 2;
+
+/// A job posted on the bounty board.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Contract {
+    /// Beat a number of enemies of the given class.
+    Kill { class: String, remaining: i32 },
+
+    /// Carry a parcel to the given location.
+    Delivery { destination: Location },
+
+    /// Reach a directory the given number of steps away from home.
+    Exploration { distance: i32 },
+}
+
+impl Contract {
+    /// Update the progress with the given event and return whether the
+    /// contract is fulfilled.
+    fn handle(&mut self, event: &Event) -> bool {
+        match (self, event) {
+            (Contract::Kill { class, remaining }, Event::BattleWon { enemy, .. }) => {
+                if enemy.name() == *class {
+                    *remaining -= 1;
+                }
+                *remaining <= 0
+            }
+            (Contract::Delivery { destination }, Event::Moved { location, .. }) => {
+                location == destination
+            }
+            (Contract::Exploration { distance }, Event::Moved { location, .. }) => {
+                location.distance_from_home().len() >= *distance
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Contract {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Contract::Kill { class, remaining } => write!(f, "kill {} more {}", remaining, class),
+            Contract::Delivery { destination } => {
+                write!(f, "deliver a parcel to {}", destination)
+            }
+            Contract::Exploration { distance } => {
+                write!(f, "reach a directory {} steps away from home", distance)
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Bounty {
+    pub contract: Contract,
+    pub gold: i32,
+    pub reputation: i32,
+    pub done: bool,
+}
+
+/// The contracts offered at home and the ones accepted by the hero,
+/// paid when turned in back at home.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Board {
+    pub offers: Vec<Bounty>,
+    pub active: Vec<Bounty>,
+    pub reputation: i32,
+    posted_at: u64,
+}
+
+impl Board {
+    /// Replace the offered contracts if they are due for rotation, or post
+    /// the first ones. Taking all the offers doesn't bring new ones early.
+    pub fn refresh(&mut self, level: i32) {
+        let elapsed_hours = config::now().saturating_sub(self.posted_at) / 60 / 60;
+        if self.posted_at != 0 && elapsed_hours < ROTATION_HOURS {
+            return;
+        }
+        self.posted_at = config::now();
+        self.offers = (0..OFFERS).map(|_| self.generate(level)).collect();
+    }
+
+    fn generate(&self, level: i32) -> Bounty {
+        let mut rng = randomizer::rng();
+        let (contract, work, reputation) = match rng.gen_range(0..3) {
+            0 => {
+                let rare = rng.gen_ratio(1, 3);
+                let category = if rare {
+                    Category::Rare
+                } else {
+                    Category::Common
+                };
+                let count = rng.gen_range(2..=5);
+                let class = Class::random(category).name.clone();
+                let contract = Contract::Kill {
+                    class,
+                    remaining: count,
+                };
+                (contract, count, 1 + i32::from(rare))
+            }
+            1 => match delivery_destination(rng.gen_range(2..=6)) {
+                Some((destination, steps)) => (Contract::Delivery { destination }, steps, 1),
+                None => exploration(level),
+            },
+            _ => exploration(level),
+        };
+        let gold = work * level * GOLD_PER_LEVEL;
+        Bounty {
+            contract,
+            gold: gold + gold * self.reputation * REPUTATION_BONUS / 100,
+            reputation,
+            done: false,
+        }
+    }
+
+    /// Take the offered contract at the given position, starting from 1.
+    pub fn accept(&mut self, number: usize) -> Result<&Bounty> {
+        if self.active.len() >= MAX_ACTIVE {
+            bail!("Can't take more than {} bounties at once.", MAX_ACTIVE);
+        }
+        if number == 0 || number > self.offers.len() {
+            bail!("There's no bounty number {} on the board.", number);
+        }
+        let bounty = self.offers.remove(number - 1);
+        self.active.push(bounty);
+        Ok(self.active.last().unwrap())
+    }
+
+    /// Remove the fulfilled contracts, returning their total gold and
+    /// reputation rewards.
+    pub fn turn_in(&mut self) -> Result<(i32, i32)> {
+        let (done, pending): (Vec<_>, Vec<_>) = self.active.drain(..).partition(|b| b.done);
+        self.active = pending;
+        if done.is_empty() {
+            bail!("No fulfilled bounties to turn in.");
+        }
+        let gold = done.iter().map(|bounty| bounty.gold).sum();
+        let reputation = done.iter().map(|bounty| bounty.reputation).sum();
+        self.reputation += reputation;
+        Ok((gold, reputation))
+    }
+
+    /// Pass the event to the accepted contracts, marking the fulfilled ones.
+    pub fn handle(&mut self, event: &Event) {
+        for bounty in self.active.iter_mut().filter(|bounty| !bounty.done) {
+            if bounty.contract.handle(event) {
+                bounty.done = true;
+            }
+        }
+    }
+}
+
+fn exploration(level: i32) -> (Contract, i32, i32) {
+    let distance = randomizer::rng().gen_range(3..=6) + level / 5;
+    (Contract::Exploration { distance }, distance, 1)
+}
+
+/// A directory reached by a random walk down from home, along with the
+/// steps it took, if home has any subdirectories.
+fn delivery_destination(max_steps: i32) -> Option<(Location, i32)> {
+    let mut rng = randomizer::rng();
+    let mut location = Location::home();
+    let mut steps = 0;
+    while steps < max_steps {
+        let Some(next) = location.subdirectories().choose(&mut rng).cloned() else {
+            break;
+        };
+        location = next;
+        steps += 1;
+    }
+    (steps > 0).then_some((location, steps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::Character;
+    use crate::location::tests::location_from;
+
+    #[test]
+    fn test_bounties() {
+        let mut board = Board::default();
+        board.refresh(1);
+        assert_eq!(OFFERS, board.offers.len());
+        let offers = board.offers.clone();
+        board.refresh(1);
+        assert_eq!(offers, board.offers);
+
+        board.offers = vec![
+            Bounty {
+                contract: Contract::Kill {
+                    class: String::from("wolf"),
+                    remaining: 1,
+                },
+                gold: 100,
+                reputation: 1,
+                done: false,
+            },
+            Bounty {
+                contract: Contract::Exploration { distance: 3 },
+                gold: 50,
+                reputation: 2,
+                done: false,
+            },
+        ];
+        assert!(board.accept(3).is_err());
+        board.accept(1).unwrap();
+        board.accept(1).unwrap();
+        assert!(board.offers.is_empty());
+        assert!(board.turn_in().is_err());
+        board.refresh(1);
+        assert!(board.offers.is_empty());
+
+        let wolf = Character::new(Class::enemy_by_name("wolf").unwrap().clone(), 1);
+        board.handle(&Event::BattleWon {
+            enemy: &wolf,
+            location: location_from("~/dungeon"),
+        });
+        assert_eq!((100, 1), board.turn_in().unwrap());
+        assert_eq!(1, board.active.len());
+
+        board.handle(&Event::Moved {
+            home: false,
+            location: location_from("~/1/2/3"),
+        });
+        assert_eq!((50, 2), board.turn_in().unwrap());
+        assert_eq!(3, board.reputation);
+    }
+}
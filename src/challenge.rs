@@ -0,0 +1,90 @@
+use crate::error::{bail, Result};
+use crate::naming;
+use serde::{Deserialize, Serialize};
+
+/// A seeded run with a set of modifiers, shareable as a compact code like
+/// `5f3a9c-his`: the seed in hex, followed by the enabled modifiers
+/// (h: hardcore, i: ironman, s: speedrun). Players starting from the same
+/// code get the same outcomes for the same sequence of commands.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Challenge {
+    pub seed: u64,
+    pub hardcore: bool,
+    pub ironman: bool,
+    pub speedrun: bool,
+
+    /// Commands run so far, mixed into the seed of each one.
+    turn: u64,
+}
+
+impl Challenge {
+    pub fn parse(code: &str) -> Result<Self> {
+        let (seed, modifiers) = code.split_once('-').unwrap_or((code, ""));
+        let Ok(seed) = u64::from_str_radix(seed, 16) else {
+            bail!("Invalid challenge code.");
+        };
+        if let Some(unknown) = modifiers.chars().find(|c| !"his".contains(*c)) {
+            bail!("Unknown challenge modifier {}.", unknown);
+        }
+
+        Ok(Self {
+            seed,
+            hardcore: modifiers.contains('h'),
+            ironman: modifiers.contains('i'),
+            speedrun: modifiers.contains('s'),
+            turn: 0,
+        })
+    }
+
+    pub fn code(&self) -> String {
+        let mut modifiers = String::new();
+        for (enabled, flag) in [
+            (self.hardcore, 'h'),
+            (self.ironman, 'i'),
+            (self.speedrun, 's'),
+        ] {
+            if enabled {
+                modifiers.push(flag);
+            }
+        }
+        if modifiers.is_empty() {
+            format!("{:x}", self.seed)
+        } else {
+            format!("{:x}-{}", self.seed, modifiers)
+        }
+    }
+
+    /// Advance to the next command and return the seed for it.
+    pub fn next_seed(&mut self) -> u64 {
+        self.turn += 1;
+        naming::fnv1a(format!("{}:{}", self.seed, self.turn).as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code() {
+        let challenge = Challenge::parse("5f3a9c-hs").unwrap();
+        assert_eq!(0x5f3a9c, challenge.seed);
+        assert!(challenge.hardcore && challenge.speedrun && !challenge.ironman);
+        assert_eq!("5f3a9c-hs", challenge.code());
+
+        assert_eq!("ff", Challenge::parse("ff").unwrap().code());
+        assert!(Challenge::parse("zz").is_err());
+        assert!(Challenge::parse("ff-x").is_err());
+    }
+
+    #[test]
+    fn test_next_seed() {
+        let mut first = Challenge::parse("ff").unwrap();
+        let mut second = Challenge::parse("ff").unwrap();
+        assert_eq!(first.next_seed(), second.next_seed());
+        assert_ne!(
+            first.next_seed(),
+            Challenge::parse("fe").unwrap().next_seed()
+        );
+    }
+}
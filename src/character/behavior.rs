@@ -0,0 +1,144 @@
+use super::{Buff, Character};
+use crate::randomizer::{random, Randomizer};
+use serde::{Deserialize, Serialize};
+
+/// Percentage of the max hp under which an enemy counts as wounded and
+/// changes its tactics.
+pub const WOUNDED_HP: i32 = 50;
+
+/// Percentage of the max hp under which a coward tries to run away.
+pub const COWARD_HP: i32 = 25;
+
+/// The attack boost of an enraged berserker.
+pub const RAGE: Buff = Buff {
+    attack: 50,
+    speed: 0,
+};
+
+/// Battle turns a berserker rage lasts.
+pub const RAGE_TURNS: i32 = 3;
+
+/// Percentage of the max hp a caster heals in its turn.
+pub const CASTER_HEAL: i32 = 25;
+
+/// Percentage of the max mp a caster spends to heal.
+pub const CASTER_HEAL_MP: i32 = 30;
+
+/// How an enemy class fights, deciding what it does in its turn.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Behavior {
+    /// Always attacks.
+    #[default]
+    Aggressive,
+    /// Flies into a rage when wounded, hitting harder.
+    Berserker,
+    /// Tries to run away when badly wounded, coming back with help.
+    Coward,
+    /// Spends its mp on healing spells when wounded.
+    Caster,
+    /// Braces every other turn when wounded, halving the damage taken.
+    Defensive,
+}
+
+/// What an enemy does in its battle turn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Attack,
+    /// Enrage and attack.
+    Rage,
+    Heal,
+    Brace,
+    Flee,
+}
+
+impl Action {
+    /// Whether the enemy attacks in this turn.
+    pub fn attacks(&self) -> bool {
+        matches!(self, Action::Attack | Action::Rage)
+    }
+}
+
+impl Behavior {
+    /// Pick the action of the enemy for this turn, given its state.
+    pub fn decide(&self, enemy: &Character) -> Action {
+        let wounded = |threshold| enemy.current_hp * 100 < enemy.max_hp() * threshold;
+        match self {
+            Behavior::Berserker if wounded(WOUNDED_HP) && enemy.boost.is_none() => Action::Rage,
+            Behavior::Coward if wounded(COWARD_HP) && random().enemy_flees() => Action::Flee,
+            Behavior::Caster if wounded(WOUNDED_HP) && enemy.current_mp >= heal_cost(enemy) => {
+                Action::Heal
+            }
+            Behavior::Defensive if wounded(WOUNDED_HP) && !enemy.defending => Action::Brace,
+            _ => Action::Attack,
+        }
+    }
+}
+
+/// The mp a caster spends to heal, at least one so enemies without mp
+/// can't cast.
+fn heal_cost(enemy: &Character) -> i32 {
+    std::cmp::max(1, enemy.max_mp() * CASTER_HEAL_MP / 100)
+}
+
+/// Carry out the action of the enemy, other than attacking or fleeing.
+/// Returns the hp healed, if any.
+pub fn act(enemy: &mut Character, action: Action) -> i32 {
+    match action {
+        Action::Rage => {
+            enemy.boost = Some((RAGE, RAGE_TURNS));
+            0
+        }
+        Action::Heal => {
+            enemy.update_mp(-heal_cost(enemy));
+            enemy.update_hp(enemy.max_hp() * CASTER_HEAL / 100).unwrap()
+        }
+        Action::Brace => {
+            enemy.defending = true;
+            0
+        }
+        Action::Attack | Action::Flee => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::class::Class;
+
+    fn enemy(name: &str) -> Character {
+        Character::new(Class::enemy_by_name(name).unwrap().clone(), 5)
+    }
+
+    #[test]
+    fn test_decide() {
+        let mut orc = enemy("orc");
+        assert_eq!(Behavior::Berserker, orc.class.behavior);
+        assert_eq!(Action::Attack, orc.class.behavior.decide(&orc));
+        orc.current_hp = 1;
+        assert_eq!(Action::Rage, orc.class.behavior.decide(&orc));
+        act(&mut orc, Action::Rage);
+        assert_eq!(Action::Attack, orc.class.behavior.decide(&orc));
+
+        let mut goblin = enemy("goblin");
+        goblin.current_hp = goblin.max_hp() / 3;
+        assert_eq!(Action::Attack, goblin.class.behavior.decide(&goblin));
+        goblin.current_hp = 1;
+        assert_eq!(Action::Flee, goblin.class.behavior.decide(&goblin));
+
+        let mut shaman = enemy("orc shaman");
+        shaman.current_hp = 1;
+        assert_eq!(Action::Heal, shaman.class.behavior.decide(&shaman));
+        let healed = act(&mut shaman, Action::Heal);
+        assert_eq!(shaman.max_hp() * CASTER_HEAL / 100, healed);
+        shaman.current_mp = 0;
+        assert_eq!(Action::Attack, shaman.class.behavior.decide(&shaman));
+
+        // defensive enemies alternate bracing and attacking
+        let mut golem = enemy("golem");
+        golem.current_hp = 1;
+        assert_eq!(Action::Brace, golem.class.behavior.decide(&golem));
+        act(&mut golem, Action::Brace);
+        assert_eq!(Action::Attack, golem.class.behavior.decide(&golem));
+    }
+}
@@ -1,4 +1,9 @@
+use super::behavior::Behavior;
+use crate::item::chest;
+use crate::item::key::Key;
+use crate::randomizer;
 use crate::randomizer::{random, Randomizer};
+use crate::terrain::Terrain;
 use once_cell::sync::OnceCell;
 use rand::prelude::SliceRandom;
 use serde::{Deserialize, Serialize};
@@ -43,8 +48,53 @@ pub struct Class {
 
     pub inflicts: Option<(super::StatusEffect, u32)>,
 
+    /// For player classes, the percentage bonus or penalty to the strength
+    /// of each kind of weapon. Opening shots with a ranged weapon the class
+    /// has a bonus for never miss.
+    #[serde(default)]
+    pub affinities: HashMap<Key, i32>,
+
+    /// For enemies, how they fight: when they use their skills, brace
+    /// or run away instead of attacking.
+    #[serde(default)]
+    pub behavior: Behavior,
+
     #[serde(default)]
     pub skills: Vec<Skill>,
+
+    /// ASCII art shown when a legendary enemy of this class appears.
+    #[serde(default)]
+    pub portrait: Option<String>,
+
+    /// Short icon or emoji shown next to the name, if icons are enabled.
+    #[serde(default)]
+    pub icon: Option<String>,
+
+    /// Name shown in the game output instead of the class name, which
+    /// is still used to refer to the class in commands and quests.
+    #[serde(default)]
+    pub display_name: Option<String>,
+
+    /// For advanced player classes, the base class that has to be mastered
+    /// before this one can be played.
+    #[serde(default)]
+    pub requires: Option<String>,
+
+    /// For enemies, the terrains they are found in. Anywhere if empty.
+    #[serde(default)]
+    pub terrains: Vec<Terrain>,
+
+    /// For enemies, items they can drop when defeated on top of the
+    /// regular battle loot.
+    #[serde(default)]
+    pub loot: Vec<Loot>,
+}
+
+/// An item an enemy drops with the given percentage chance.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Loot {
+    pub item: Key,
+    pub chance: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -54,6 +104,69 @@ pub struct Skill {
     pub skill_type: SkillType,
     pub level_requirement: i32,
     pub cost: i32, // MP cost for active skills
+
+    /// The lasting effects of a passive skill, once learned.
+    #[serde(default)]
+    pub passive: Passive,
+}
+
+/// Effects of a passive skill, applied to the character stats for as long
+/// as the skill is known. Percentages add up across skills.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Passive {
+    /// Percentage added to the defense.
+    pub defense: i32,
+
+    /// Percentage chance of dodging an attack, on top of the speed.
+    pub dodge: i32,
+
+    /// Percentage chance of parrying an attack with the shield, on top of
+    /// the shield and the speed.
+    pub parry: i32,
+
+    /// Percentage added to the gold won in battles.
+    pub gold_find: i32,
+
+    /// Percentage of the max hp recovered at the end of each battle round.
+    pub regeneration: i32,
+
+    /// Percentage of the damage taken out of the mp instead of the hp.
+    pub mana_shield: i32,
+
+    /// Always notice chest traps, and disarm them more often.
+    pub disarm_traps: bool,
+}
+
+/// How many times an active skill can be learned, each rank after the
+/// first making it stronger and cheaper.
+pub const MAX_SKILL_RANK: i32 = 3;
+
+/// Levels needed, on top of the skill requirement, for each rank after the first.
+const RANK_LEVELS: i32 = 5;
+
+/// How much stronger, as a percentage, each rank after the first makes a skill.
+const RANK_POWER: i32 = 50;
+
+/// How much cheaper, as a percentage of the base cost, each rank after the
+/// first makes a skill.
+const RANK_DISCOUNT: i32 = 20;
+
+impl Skill {
+    /// The level needed to learn the given rank of this skill.
+    pub fn rank_requirement(&self, rank: i32) -> i32 {
+        self.level_requirement + (rank - 1) * RANK_LEVELS
+    }
+
+    /// The mp cost of using this skill at the given rank.
+    pub fn rank_cost(&self, rank: i32) -> i32 {
+        self.cost - self.cost * (rank - 1) * RANK_DISCOUNT / 100
+    }
+
+    /// The given skill effect, e.g. damage or healing, at the given rank.
+    pub fn rank_effect(&self, rank: i32, value: i32) -> i32 {
+        value + value * (rank - 1) * RANK_POWER / 100
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -72,20 +185,44 @@ pub enum Category {
     Common,
     Rare,
     Legendary,
+    /// Enemies found only in the elite zones of the system directories.
+    Construct,
 }
 
 static CLASSES: OnceCell<HashMap<Category, Vec<Class>>> = OnceCell::new();
 
 impl Class {
+    /// The percentage bonus, or penalty if negative, to the strength of
+    /// the given kind of weapon when wielded by this class.
+    pub fn affinity(&self, weapon: &Key) -> i32 {
+        self.affinities.get(weapon).copied().unwrap_or_default()
+    }
+
     /// Returns whether this is a magic class, i.e. it can inflict
     /// magic damage.
     pub fn is_magic(&self) -> bool {
         self.mp.is_some()
     }
 
-    /// Customize the classes definitions based on an input yaml byte array.
-    pub fn load(bytes: &[u8]) {
-        CLASSES.set(from_bytes(bytes)).unwrap();
+    /// Returns whether this is an advanced player class, unlocked by
+    /// mastering a base class.
+    pub fn is_advanced(&self) -> bool {
+        self.requires.is_some()
+    }
+
+    /// Returns whether this enemy can be found in the given terrain.
+    pub fn found_in(&self, terrain: Terrain) -> bool {
+        self.terrains.is_empty() || self.terrains.contains(&terrain)
+    }
+
+    /// Customize the classes definitions based on an input yaml byte array,
+    /// if given, and merge the enemy classes of the given (name, yaml) packs
+    /// into them. Returns a warning for each pack or class left out.
+    pub fn load(bytes: Option<&[u8]>, packs: &[(String, Vec<u8>)]) -> Vec<String> {
+        let mut classes = bytes.map_or_else(default_classes, from_bytes);
+        let warnings = merge_packs(&mut classes, packs);
+        CLASSES.set(classes).unwrap();
+        warnings
     }
 
     /// The default player class, exposed for initialization and parameterization of
@@ -103,9 +240,29 @@ impl Class {
             .copied()
     }
 
+    pub fn enemy_by_name(name: &str) -> Option<&'static Self> {
+        Self::enemies().into_iter().find(|class| class.name == name)
+    }
+
+    /// A random class of the given category. Advanced classes are left out
+    /// since they have to be unlocked first.
     pub fn random(category: Category) -> &'static Self {
-        let mut rng = rand::thread_rng();
-        Self::of(category).choose(&mut rng).unwrap()
+        let mut rng = randomizer::rng();
+        Self::of(category)
+            .iter()
+            .filter(|class| !class.is_advanced())
+            .collect::<Vec<_>>()
+            .choose(&mut rng)
+            .copied()
+            .unwrap()
+    }
+
+    /// The advanced classes unlocked by mastering the given base class.
+    pub fn advanced(base: &str) -> Vec<&'static Self> {
+        Self::of(Category::Player)
+            .iter()
+            .filter(|class| class.requires.as_deref() == Some(base))
+            .collect()
     }
 
     pub fn names(category: Category) -> HashSet<String> {
@@ -123,6 +280,14 @@ impl Class {
         enemies
     }
 
+    /// The construct classes, if any are defined.
+    pub fn constructs() -> &'static [Class] {
+        CLASSES
+            .get_or_init(default_classes)
+            .get(&Category::Construct)
+            .map_or(&[], |classes| classes.as_slice())
+    }
+
     fn of(category: Category) -> &'static Vec<Class> {
         CLASSES.get_or_init(default_classes).get(&category).unwrap()
     }
@@ -132,6 +297,48 @@ fn default_classes() -> HashMap<Category, Vec<Class>> {
     from_bytes(include_bytes!("classes.yaml"))
 }
 
+/// Add the enemies of each pack to the classes. Packs that can't be parsed,
+/// player classes, names already taken and loot that can't be dropped are
+/// left out with a warning, so the first pack to use a name keeps it.
+fn merge_packs(
+    classes: &mut HashMap<Category, Vec<Class>>,
+    packs: &[(String, Vec<u8>)],
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for (pack, bytes) in packs {
+        let Ok(pack_classes) = serde_yaml::from_slice::<Vec<Class>>(bytes) else {
+            warnings.push(format!("enemy pack {} is invalid", pack));
+            continue;
+        };
+        for mut class in pack_classes {
+            let taken = classes.values().flatten().any(|c| c.name == class.name);
+            if class.category == Category::Player {
+                warnings.push(format!("{} in pack {} is not an enemy", class.name, pack));
+                continue;
+            }
+            if taken {
+                warnings.push(format!("{} in pack {} already exists", class.name, pack));
+                continue;
+            }
+            class.loot.retain(|loot| {
+                let droppable = chest::loot_item(&loot.item, 1).is_some();
+                if !droppable {
+                    warnings.push(format!(
+                        "{} in pack {} can't drop {}",
+                        class.name, pack, loot.item
+                    ));
+                }
+                droppable
+            });
+            classes
+                .entry(class.category.clone())
+                .or_default()
+                .push(class);
+        }
+    }
+    warnings
+}
+
 fn from_bytes(bytes: &[u8]) -> HashMap<Category, Vec<Class>> {
     // it would arguably be better for these module not to deal with deserialization
     // and yaml, but at this stage it's easier allow it to pick up defaults from
@@ -147,3 +354,62 @@ fn from_bytes(bytes: &[u8]) -> HashMap<Category, Vec<Class>> {
     }
     class_groups
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_packs() {
+        let pack = br#"
+- name: "sand worm"
+  category: "rare"
+  hp: [40, 10]
+  strength: [12, 3]
+  speed: [4, 1]
+  terrains: [swamp]
+  loot:
+    - item: "potion"
+      chance: 50
+    - item: "sword"
+      chance: 10
+- name: "goblin"
+  category: "common"
+  hp: [1, 1]
+  strength: [1, 1]
+  speed: [1, 1]
+- name: "bard"
+  category: "player"
+  hp: [1, 1]
+  strength: [1, 1]
+  speed: [1, 1]
+"#;
+        let mut classes = default_classes();
+        let packs = vec![
+            (String::from("desert.yaml"), pack.to_vec()),
+            (String::from("broken.yaml"), b"not a pack".to_vec()),
+        ];
+        let warnings = merge_packs(&mut classes, &packs);
+        assert_eq!(
+            vec![
+                "sand worm in pack desert.yaml can't drop sword",
+                "goblin in pack desert.yaml already exists",
+                "bard in pack desert.yaml is not an enemy",
+                "enemy pack broken.yaml is invalid",
+            ],
+            warnings
+        );
+
+        let worm = classes[&Category::Rare]
+            .iter()
+            .find(|class| class.name == "sand worm")
+            .unwrap();
+        assert_eq!(1, worm.loot.len());
+        assert!(worm.found_in(Terrain::Swamp));
+        assert!(!worm.found_in(Terrain::Plain));
+
+        // the original goblin is kept
+        let goblins = classes.values().flatten().filter(|c| c.name == "goblin");
+        assert_eq!(1, goblins.count());
+    }
+}
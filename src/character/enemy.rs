@@ -1,107 +1,682 @@
 use super::{class::Category, class::Class, Character};
+use crate::artifact;
+use crate::config::{self, Config};
+use crate::error::{bail, Result};
+use crate::git;
+use crate::item::equipment::Equipment;
 use crate::item::ring::Ring;
 use crate::location;
+use crate::location::Location;
 use crate::log;
+use crate::mercy;
+use crate::randomizer;
 use crate::randomizer::{random, Randomizer};
+use crate::terrain::{self, Terrain};
+use crate::weather;
 use rand::prelude::IteratorRandom;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How far from home the guardian starts showing up once its quest is unlocked.
+pub const GUARDIAN_DISTANCE: i32 = 10;
+
+/// Closest distance from home an invasion can start at.
+const INVASION_MIN_DISTANCE: i32 = 2;
+
+/// How many directory levels, besides the closest one, an invasion covers.
+const INVASION_SPREAD: i32 = 3;
+
+/// For how long, in seconds, an invasion lasts.
+const INVASION_DURATION: u64 = 2 * 60 * 60;
+
+/// How far from home gorthaur waits for the ruling ring bearer.
+pub const GORTHAUR_DISTANCE: i32 = 100;
+
+/// Levels morgoth has over the hero that faces it.
+const MORGOTH_LEVEL_BONUS: i32 = 10;
+
+/// The (numerator, denominator) chance of the shadow showing up at home.
+const SHADOW_ODDS: (u32, u32) = (1, 10);
+
+/// The chance of the shadow showing up at home while its quest is pending.
+pub const HUNTED_SHADOW_ODDS: (u32, u32) = (1, 4);
+
+/// How many directories ahead a boss is still hinted by the danger assessment.
+const BOSS_HINT_STEPS: i32 = 3;
+
+/// Uncommitted changes from which bugs turn into regressions.
+pub const REGRESSION_CHANGES: usize = 10;
+
+/// Most changed files counted towards the level of a bug.
+const MAX_BUG_CHANGES: usize = 30;
+
+/// How many levels above the hero pirates are.
+const PIRATE_LEVEL_BONUS: i32 = 2;
+
+/// How many directories away from the hero a hunt looks for tracks.
+const HUNT_RADIUS: i32 = 3;
+
+/// For how many encounters the hunted family shows up at the tracked location.
+const HUNT_ENCOUNTERS: i32 = 5;
+
+/// How many levels above the fallen hero its ghost is.
+pub const GHOST_LEVEL_BONUS: i32 = 2;
+
+/// Percentage of the gold dropped at death that the ghost carries.
+pub const GHOST_GOLD: i32 = 25;
+
+/// How many battles need to be won at a location to clear it.
+pub const CLEAR_VICTORIES: i32 = 5;
+
+/// The hero's progress clearing a location of enemies. A location is
+/// cleared after winning enough battles or defeating a boss there.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Clearing {
+    victories: i32,
+    cleared_at: Option<u64>,
+}
+
+/// Count a victory at the current location, clearing it if the enemy
+/// was a boss or enough battles were won.
+pub fn record_victory(game: &mut crate::game::Game, enemy: &Character) {
+    let clearing = game.clearings.entry(game.location.to_string()).or_default();
+    clearing.victories += 1;
+    if is_boss(&enemy.class) || clearing.victories >= CLEAR_VICTORIES {
+        clearing.victories = 0;
+        clearing.cleared_at = Some(config::now());
+        log::cleared(&game.location);
+    }
+}
+
+/// Forget the clearings whose enemies already respawned.
+pub fn forget_respawned(game: &mut crate::game::Game) {
+    game.clearings.retain(|_, clearing| {
+        if clearing
+            .cleared_at
+            .is_some_and(|cleared_at| Config::get().enemies_respawned(cleared_at))
+        {
+            clearing.cleared_at = None;
+        }
+        clearing.victories > 0 || clearing.cleared_at.is_some()
+    });
+}
+
+/// Whether the location was cleared and its enemies didn't respawn yet.
+pub fn is_cleared(game: &crate::game::Game, location: &Location) -> bool {
+    game.clearings
+        .get(&location.to_string())
+        .and_then(|clearing| clearing.cleared_at)
+        .is_some_and(|cleared_at| !Config::get().enemies_respawned(cleared_at))
+}
+
+/// A fallen hero haunting the place where it died. It fights with the
+/// class and equipment it had and drops part of its gold when defeated.
+#[derive(Serialize, Deserialize)]
+pub struct Ghost {
+    class: Class,
+    level: i32,
+    sword: Option<Equipment>,
+    shield: Option<Equipment>,
+    left_ring: Option<Ring>,
+    right_ring: Option<Ring>,
+    pub gold: i32,
+}
+
+impl Ghost {
+    /// Record the hero as it was at the moment of death.
+    pub fn new(player: &Character, gold: i32) -> Self {
+        let mut class = player.class.clone();
+        class.name = String::from("ghost");
+        class.category = Category::Rare;
+
+        Self {
+            class,
+            level: player.level + GHOST_LEVEL_BONUS,
+            sword: player.sword.clone(),
+            shield: player.shield.clone(),
+            left_ring: player.left_ring.clone(),
+            right_ring: player.right_ring.clone(),
+            gold: gold * GHOST_GOLD / 100,
+        }
+    }
+
+    fn to_character(&self) -> Character {
+        let mut ghost = Character::new(self.class.clone(), self.level);
+        ghost.sword = self.sword.clone();
+        ghost.shield = self.shield.clone();
+        ghost.left_ring = self.left_ring.clone();
+        ghost.right_ring = self.right_ring.clone();
+        ghost
+    }
+}
+
+/// An enemy family tracked down by the hero. Enemies of that family
+/// are guaranteed to appear at the hunt location for a few encounters.
+#[derive(Serialize, Deserialize)]
+pub struct Hunt {
+    pub family: String,
+    pub location: Location,
+    pub remaining: i32,
+}
+
+/// A temporary world event where an enemy family invades the directories
+/// within a range of distances from home. Invaders show up more often
+/// there and reward extra gold and experience.
+#[derive(Serialize, Deserialize)]
+pub struct Invasion {
+    pub family: String,
+    pub min_distance: i32,
+    pub max_distance: i32,
+    expires_at: u64,
+}
+
+impl Invasion {
+    /// Whether the invasion reaches the given location.
+    pub fn affects(&self, location: &Location) -> bool {
+        let distance = location.distance_from_home().len();
+        distance >= self.min_distance && distance <= self.max_distance
+    }
+
+    /// Whether the enemy belongs to the invading family and was met
+    /// inside the invaded area.
+    pub fn rewards(&self, enemy: &Character, location: &Location) -> bool {
+        self.affects(location) && family(&enemy.class) == self.family
+    }
+
+    /// Minutes left until the invasion ends.
+    pub fn minutes_left(&self) -> u64 {
+        self.expires_at.saturating_sub(config::now()) / 60
+    }
+}
+
+/// Occasionally start an invasion of a random enemy family, and end the
+/// current one once it expires.
+pub fn update_invasion(game: &mut crate::game::Game) {
+    if let Some(invasion) = &game.invasion {
+        if invasion.expires_at <= config::now() {
+            game.invasion = None;
+        }
+    } else if random().invasion_starts() {
+        let mut rng = randomizer::rng();
+        let family = families().into_keys().choose(&mut rng).unwrap();
+        let min_distance = INVASION_MIN_DISTANCE + random().range(INVASION_SPREAD);
+
+        game.invasion = Some(Invasion {
+            family: family.to_string(),
+            min_distance,
+            max_distance: min_distance + INVASION_SPREAD,
+            expires_at: config::now() + INVASION_DURATION,
+        });
+    }
+}
 
 /// Randomly spawn an enemy character at the given location, based on the
 /// current character stats.
 /// The distance from home will influence the enemy frequency and level.
 /// Under certain conditions, special (quest-related) enemies may be spawned.
-pub fn spawn(game: &crate::game::Game) -> Option<Character> {
+pub fn spawn(game: &mut crate::game::Game) -> Option<Character> {
+    let hunted = game
+        .hunt
+        .as_ref()
+        .filter(|hunt| hunt.location == game.location)
+        .map(|hunt| hunt.family.clone());
+    let invader = game
+        .invasion
+        .as_ref()
+        .filter(|invasion| invasion.affects(&game.location))
+        .map(|invasion| invasion.family.clone());
+
+    let weather_family = game.weather.family().filter(|_| {
+        let (numerator, denominator) = weather::FAMILY_ODDS;
+        randomizer::rng().gen_ratio(numerator, denominator)
+    });
+
+    let shadow_hunted = shadow_hunted(game);
+    let artifacts_collected = artifact::is_complete(game);
+
     let player = &game.player;
     let location = &game.location;
 
-    if player.enemies_evaded() {
+    let terrain = Terrain::of(location);
+    let anomaly = terrain == Terrain::Anomaly;
+    let elite = !anomaly && terrain::is_elite_zone(location);
+    let rolls = Config::get().spawn_rolls(location);
+    if rolls == 0
+        || player.enemies_evaded()
+        || game.pacified.contains(location)
+        || is_cleared(game, location)
+        || terrain == Terrain::Sanctuary
+    {
         return None;
     }
 
-    let distance = location.distance_from_home();
-    if random().should_enemy_appear(&distance) {
-        let guardian_quest_unlocked = game.quests.list().iter().any(|(completed, description)| {
-            !completed && description == "Defeat the Guardian."
-        });
+    // fallen heroes always show up where they died
+    if let Some(ghost) = game.ghosts.get(&location.to_string()) {
+        let mut enemy = ghost.to_character();
+        game.mutators.apply_to_enemy(&mut enemy);
+        log::enemy_appears(&enemy, location);
+        return Some(enemy);
+    }
 
-        let (class, level) = if guardian_quest_unlocked && distance.len() > 10 {
-            (Class::player_by_name("guardian").unwrap().clone(), player.level + 5)
+    let distance = location.distance_from_home();
+    // invaded areas get a second chance at spawning
+    let invaded = invader.is_some() && random().should_enemy_appear(&distance);
+    // spawn rules can roll more than once, or not at all
+    let rolled = (0..rolls).any(|_| random().should_enemy_appear(&distance));
+    // spared heroes meet fewer enemies near home, except for the hunted ones
+    let appears = (invaded || rolled) && !game.mercy.spares(&distance);
+    if hunted.is_some() || appears {
+        let (class, level) = if guardian_unlocked(game) && distance.len() > GUARDIAN_DISTANCE {
+            boss("guardian", player).unwrap()
         } else {
-            spawn_gorthaur(player, location)
-                .or_else(|| spawn_shadow(player, location))
+            spawn_morgoth(player, location, artifacts_collected)
+                .or_else(|| spawn_gorthaur(player, location))
+                .or_else(|| spawn_shadow(player, location, shadow_hunted))
                 .or_else(|| spawn_dev(player, location))
-                .unwrap_or_else(|| spawn_random(player, &distance))
+                .or_else(|| spawn_bug(player, &distance, git::changes(location)))
+                .or_else(|| anomaly.then(|| spawn_anomaly(player)))
+                .or_else(|| elite.then(|| spawn_construct(player, &distance)).flatten())
+                .unwrap_or_else(|| {
+                    let family = hunted.as_deref().or(invader.as_deref()).or(weather_family);
+                    spawn_random(player, &distance, terrain, family)
+                })
         };
 
+        let level = if game.weather.empowers(family(&class)) {
+            level + weather::EMPOWERED_LEVEL_BONUS
+        } else {
+            level
+        };
+        let level = level + terrain.level_bonus() + game.cycle * crate::game::CYCLE_LEVEL_BONUS;
+        let level = if elite {
+            std::cmp::max(level, terrain::ELITE_LEVEL_FLOOR)
+        } else {
+            level
+        };
         let level = random().enemy_level(level);
-        let enemy = Character::new(class, level);
+        let mut enemy = Character::new(class, level);
+        game.mutators.apply_to_enemy(&mut enemy);
         log::enemy_appears(&enemy, location);
+
+        if hunted.as_deref() == Some(family(&enemy.class)) {
+            let hunt = game.hunt.as_mut().unwrap();
+            hunt.remaining -= 1;
+            if hunt.remaining <= 0 {
+                game.hunt = None;
+            }
+        }
         Some(enemy)
     } else {
         None
     }
 }
 
+/// An estimation of the risk of the hero's current location, derived from
+/// the same rules used to spawn enemies.
+#[derive(Serialize)]
+pub struct Danger {
+    pub min_level: i32,
+    pub max_level: i32,
+
+    /// Chance of an enemy appearing, as a (numerator, denominator) tuple.
+    pub odds: (u32, u32),
+
+    /// Whether the hero recently cleared this location.
+    pub cleared: bool,
+
+    /// Whether this is an elite zone, with stronger enemies.
+    pub elite: bool,
+
+    /// Special enemies that can be found here or a few directories deeper,
+    /// along with how many steps away they are.
+    pub bosses: Vec<(&'static str, i32)>,
+}
+
+/// Assess the danger of the current location without spawning anything.
+pub fn danger(game: &crate::game::Game) -> Danger {
+    let player = &game.player;
+    let location = &game.location;
+    let distance = location.distance_from_home();
+
+    let terrain = Terrain::of(location);
+    let anomaly = terrain == Terrain::Anomaly;
+    let elite = !anomaly && terrain::is_elite_zone(location);
+    let level = random_level(player, &distance)
+        + terrain.level_bonus()
+        + game.cycle * crate::game::CYCLE_LEVEL_BONUS;
+    let level = if elite {
+        std::cmp::max(level, terrain::ELITE_LEVEL_FLOOR)
+    } else {
+        level
+    };
+    let cleared = is_cleared(game, location);
+    let rolls = Config::get().spawn_rolls(location);
+    // nothing spawns at home on the way to somewhere else
+    let odds = if rolls == 0
+        || location.is_home()
+        || player.enemies_evaded()
+        || game.pacified.contains(location)
+        || cleared
+        || terrain == Terrain::Sanctuary
+    {
+        (0, 1)
+    } else {
+        // invaded areas roll once more, the chance of any roll succeeding
+        let invaded = game
+            .invasion
+            .as_ref()
+            .is_some_and(|invasion| invasion.affects(location));
+        let rolls = rolls + u32::from(invaded);
+        let (numerator, denominator) = randomizer::enemy_odds(&distance);
+        let misses = (denominator - numerator).pow(rolls);
+        (denominator.pow(rolls) - misses, denominator.pow(rolls))
+    };
+    let odds = if game.mercy.applies(&distance) {
+        let (numerator, denominator) = mercy::SPAWN_ODDS;
+        (odds.0 * numerator, odds.1 * denominator)
+    } else {
+        odds
+    };
+
+    let mut bosses = Vec::new();
+    if location.is_home() {
+        bosses.push(("shadow", 0));
+    }
+    if location.is_rpg_dir() {
+        bosses.push(("dev", 0));
+    }
+    match git::changes(location) {
+        0 => {}
+        changes if changes >= REGRESSION_CHANGES => bosses.push(("regression", 0)),
+        _ => bosses.push(("bug", 0)),
+    }
+    if guardian_unlocked(game) {
+        let steps = std::cmp::max(0, GUARDIAN_DISTANCE + 1 - distance.len());
+        if steps <= BOSS_HINT_STEPS {
+            bosses.push(("guardian", steps));
+        }
+    }
+    if artifact::is_complete(game) {
+        let steps = std::cmp::max(0, GORTHAUR_DISTANCE - distance.len());
+        if steps <= BOSS_HINT_STEPS {
+            bosses.push(("morgoth", steps));
+        }
+    }
+    if player.left_ring == Some(Ring::Ruling) || player.right_ring == Some(Ring::Ruling) {
+        let steps = std::cmp::max(0, GORTHAUR_DISTANCE - distance.len());
+        if steps <= BOSS_HINT_STEPS {
+            bosses.push(("gorthaur", steps));
+        }
+    }
+
+    let (min_level, max_level) = if anomaly {
+        (1, player.level + terrain::ANOMALY_LEVEL_SPREAD)
+    } else {
+        (level, level)
+    };
+    Danger {
+        min_level: std::cmp::max(1, min_level - randomizer::ENEMY_LEVEL_VARIANCE),
+        max_level: max_level + randomizer::ENEMY_LEVEL_VARIANCE,
+        odds,
+        cleared,
+        elite,
+        bosses,
+    }
+}
+
+/// Look for the closest unexplored directory around the hero where the given
+/// enemy family can be tracked down. Return the hunt and how many steps away
+/// from the hero it is.
+pub fn hunt(game: &crate::game::Game, family: &str) -> Result<(Hunt, i32)> {
+    let family = family.to_lowercase();
+    if !families().contains_key(family.as_str()) {
+        bail!("Unknown enemy family.");
+    }
+
+    let found = game.location.find_nearby(HUNT_RADIUS, |location| {
+        !location.is_home() && !game.is_inspected(location)
+    });
+
+    if let Some((location, steps)) = found {
+        let hunt = Hunt {
+            family,
+            location,
+            remaining: HUNT_ENCOUNTERS,
+        };
+        Ok((hunt, steps))
+    } else {
+        bail!("No tracks found nearby.")
+    }
+}
+
+/// Enemy classes grouped by family, i.e. the first word of their name.
+/// Sorted by family so that seeded runs pick the same ones.
+fn families() -> BTreeMap<&'static str, Vec<&'static Class>> {
+    let mut groups: BTreeMap<&str, Vec<&Class>> = BTreeMap::new();
+    for enemy in Class::enemies() {
+        groups.entry(family(enemy)).or_default().push(enemy);
+    }
+    groups
+}
+
+/// The enemy families with only the members found in the given terrain.
+fn families_in(terrain: Terrain) -> BTreeMap<&'static str, Vec<&'static Class>> {
+    let mut groups = families();
+    for group in groups.values_mut() {
+        group.retain(|enemy| enemy.found_in(terrain));
+    }
+    groups.retain(|_, group| !group.is_empty());
+    groups
+}
+
+fn family(class: &Class) -> &str {
+    class.name.split(' ').next().unwrap()
+}
+
+/// The special enemies found only under certain conditions, which drop
+/// heirlooms when defeated.
+pub const BOSSES: [&str; 5] = ["shadow", "dev", "guardian", "gorthaur", "morgoth"];
+
+/// Whether the class is one of the bosses.
+pub fn is_boss(class: &Class) -> bool {
+    BOSSES.contains(&class.name.as_str())
+}
+
+/// The enemy families harmed by holy water, matched against each word of
+/// the class name.
+const UNDEAD: [&str; 5] = ["skeleton", "zombie", "vampire", "lich", "ghost"];
+
+/// Whether the class is one of the undead.
+pub fn is_undead(class: &Class) -> bool {
+    class.name.split(' ').any(|word| UNDEAD.contains(&word))
+}
+
+/// The class and level of the boss with the given name, as it would
+/// show up to fight the given player, regardless of its spawn conditions.
+pub fn boss(name: &str, player: &Character) -> Option<(Class, i32)> {
+    match name {
+        "shadow" => {
+            let mut class = player.class.clone();
+            class.name = String::from("shadow");
+            class.category = Category::Rare;
+            Some((class, player.level + 3))
+        }
+        "dev" => {
+            let mut class = Class::player_first().clone();
+            class.name = String::from("dev");
+            class.hp.0 /= 2;
+            class.strength.0 /= 2;
+            class.speed.0 /= 2;
+            class.category = Category::Rare;
+            Some((class, player.level))
+        }
+        "guardian" => Some((
+            Class::enemy_by_name("guardian").unwrap().clone(),
+            player.level + 5,
+        )),
+        "gorthaur" => {
+            let mut class = Class::player_first().clone();
+            class.name = String::from("gorthaur");
+            class.hp.0 *= 2;
+            class.strength.0 *= 2;
+            class.category = Category::Legendary;
+            Some((class, player.level))
+        }
+        "morgoth" => {
+            let mut class = Class::player_first().clone();
+            class.name = String::from("morgoth");
+            class.hp.0 *= 3;
+            class.strength.0 *= 3;
+            class.speed.0 *= 2;
+            class.category = Category::Legendary;
+            Some((class, player.level + MORGOTH_LEVEL_BONUS))
+        }
+        _ => None,
+    }
+}
+
+/// Whether the quest to beat the hero's own shadow is pending, which makes
+/// the shadow show up more often and drop the shadow ring.
+pub fn shadow_hunted(game: &crate::game::Game) -> bool {
+    game.quests
+        .list()
+        .iter()
+        .any(|(completed, description)| !completed && description == "beat your own shadow")
+}
+
+fn guardian_unlocked(game: &crate::game::Game) -> bool {
+    game.quests
+        .list()
+        .iter()
+        .any(|(completed, description)| !completed && description == "Defeat the Guardian.")
+}
+
+/// True final boss, appears where gorthaur does once all the artifacts
+/// were collected, no ring needed.
+fn spawn_morgoth(
+    player: &Character,
+    location: &location::Location,
+    collected: bool,
+) -> Option<(Class, i32)> {
+    if collected && location.distance_from_home().len() >= GORTHAUR_DISTANCE {
+        boss("morgoth", player)
+    } else {
+        None
+    }
+}
+
 /// Final boss, only appears at level +100 when wearing the ruling ring
 fn spawn_gorthaur(player: &Character, location: &location::Location) -> Option<(Class, i32)> {
     let wearing_ring =
         player.left_ring == Some(Ring::Ruling) || player.right_ring == Some(Ring::Ruling);
 
-    if wearing_ring && location.distance_from_home().len() >= 100 {
-        let mut class = Class::player_first().clone();
-        class.name = String::from("gorthaur");
-        class.hp.0 *= 2;
-        class.strength.0 *= 2;
-        class.category = Category::Legendary;
-        Some((class, player.level))
+    if wearing_ring && location.distance_from_home().len() >= GORTHAUR_DISTANCE {
+        boss("gorthaur", player)
     } else {
         None
     }
 }
 
-/// Player shadow, appears at home directory
-fn spawn_shadow(player: &Character, location: &location::Location) -> Option<(Class, i32)> {
-    let mut rng = rand::thread_rng();
-    if location.is_home() && rng.gen_ratio(1, 10) {
-        let mut class = player.class.clone();
-        class.name = String::from("shadow");
-        class.category = Category::Rare;
-        Some((class, player.level + 3))
+/// Player shadow, appears at home directory, more often while its quest is pending.
+fn spawn_shadow(
+    player: &Character,
+    location: &location::Location,
+    hunted: bool,
+) -> Option<(Class, i32)> {
+    let mut rng = randomizer::rng();
+    let (numerator, denominator) = if hunted {
+        HUNTED_SHADOW_ODDS
+    } else {
+        SHADOW_ODDS
+    };
+    if location.is_home() && rng.gen_ratio(numerator, denominator) {
+        boss("shadow", player)
     } else {
         None
     }
 }
 
+/// Bugs crawl out of git repositories with uncommitted changes, growing
+/// stronger with each changed file. Plenty of changes make a regression.
+fn spawn_bug(
+    player: &Character,
+    distance: &location::Distance,
+    changes: usize,
+) -> Option<(Class, i32)> {
+    if changes == 0 {
+        return None;
+    }
+    let mut class = Class::player_first().clone();
+    if changes >= REGRESSION_CHANGES {
+        class.name = String::from("regression");
+        class.category = Category::Rare;
+    } else {
+        class.name = String::from("bug");
+        class.hp.0 /= 2;
+        class.strength.0 /= 2;
+        class.category = Category::Common;
+    }
+    class.icon = Some(String::from("\u{1F41B}"));
+    class.display_name = None;
+    class.skills = Vec::new();
+
+    let level = random_level(player, distance) + std::cmp::min(changes, MAX_BUG_CHANGES) as i32;
+    Some((class, level))
+}
+
+/// Whether the class is one of the enemies spawned by uncommitted changes.
+pub fn is_bug(class: &Class) -> bool {
+    ["bug", "regression"].contains(&class.name.as_str())
+}
+
+/// Pirates attacking the hero at sea, a few levels above it.
+pub fn pirate(game: &crate::game::Game) -> Character {
+    let mut class = Class::player_first().clone();
+    class.name = String::from("pirate");
+    class.category = Category::Rare;
+    let level = random().enemy_level(game.player.level + PIRATE_LEVEL_BONUS);
+    let mut enemy = Character::new(class, level);
+    game.mutators.apply_to_enemy(&mut enemy);
+    enemy
+}
+
+/// A golem woken up by the noise of mining, as strong as the enemies
+/// around it.
+pub fn golem(game: &crate::game::Game) -> Character {
+    let class = Class::enemy_by_name("golem").unwrap().clone();
+    let distance = game.location.distance_from_home();
+    let level = random().enemy_level(random_level(&game.player, &distance));
+    let mut enemy = Character::new(class, level);
+    game.mutators.apply_to_enemy(&mut enemy);
+    enemy
+}
+
 /// Easter egg, appears at rpg data dir
 fn spawn_dev(player: &Character, location: &location::Location) -> Option<(Class, i32)> {
-    let mut rng = rand::thread_rng();
+    let mut rng = randomizer::rng();
 
     if location.is_rpg_dir() && rng.gen_ratio(1, 10) {
-        let mut class = Class::player_first().clone();
-        class.name = String::from("dev");
-        class.hp.0 /= 2;
-        class.strength.0 /= 2;
-        class.speed.0 /= 2;
-        class.category = Category::Rare;
-        Some((class, player.level))
+        boss("dev", player)
     } else {
         None
     }
 }
 
 /// Choose an enemy randomly, with higher chance to difficult enemies the further from home.
-fn spawn_random(player: &Character, distance: &location::Distance) -> (Class, i32) {
-    let mut rng = rand::thread_rng();
-    let enemies = Class::enemies();
-
-    let mut enemy_groups: std::collections::HashMap<String, Vec<&Class>> =
-        std::collections::HashMap::new();
-    for enemy in enemies {
-        let base_name = enemy.name.split(' ').next().unwrap().to_string();
-        enemy_groups.entry(base_name).or_default().push(enemy);
-    }
+/// If a family is given, the enemy is picked from it instead of a random one.
+fn spawn_random(
+    player: &Character,
+    distance: &location::Distance,
+    terrain: Terrain,
+    family: Option<&str>,
+) -> (Class, i32) {
+    let mut rng = randomizer::rng();
+    let enemy_groups = families_in(terrain);
 
-    let group_name = enemy_groups.keys().choose(&mut rng).unwrap();
+    let group_name = family
+        .filter(|name| enemy_groups.contains_key(name))
+        .unwrap_or_else(|| enemy_groups.keys().choose(&mut rng).unwrap());
     let enemy_group = &enemy_groups[group_name];
 
     let player_level = player.level;
@@ -119,8 +694,34 @@ fn spawn_random(player: &Character, distance: &location::Distance) -> (Class, i3
         .max_by_key(|e| e.hp.0)
         .unwrap_or(&enemy_group[0]);
 
-    let level = std::cmp::max(player.level / 10 + distance.len() - 1, 1);
-    ((*enemy).clone(), level)
+    ((*enemy).clone(), random_level(player, distance))
+}
+
+/// Any kind of enemy at any level up to well above the hero, for the
+/// anomalies.
+fn spawn_anomaly(player: &Character) -> (Class, i32) {
+    let mut rng = randomizer::rng();
+    let class = Class::enemies()
+        .into_iter()
+        .chain(Class::constructs())
+        .filter(|class| !is_boss(class))
+        .choose(&mut rng)
+        .unwrap();
+    let level = rng.gen_range(1..=player.level + terrain::ANOMALY_LEVEL_SPREAD);
+    (class.clone(), level)
+}
+
+/// A construct guarding a system directory, for the elite zones.
+fn spawn_construct(player: &Character, distance: &location::Distance) -> Option<(Class, i32)> {
+    let mut rng = randomizer::rng();
+    let class = Class::constructs().iter().choose(&mut rng)?;
+    Some((class.clone(), random_level(player, distance)))
+}
+
+/// The level of regular enemies before randomization, based primarily on
+/// the distance from home.
+fn random_level(player: &Character, distance: &location::Distance) -> i32 {
+    std::cmp::max(player.level / 10 + distance.len() - 1, 1)
 }
 
 #[cfg(test)]
@@ -135,37 +736,156 @@ mod tests {
         let d3 = location::Distance::from(3);
         let d10 = location::Distance::from(10);
 
-        assert_eq!(1, spawn_random(&player, &d1).1);
-        assert_eq!(1, spawn_random(&player, &d2).1);
-        assert_eq!(2, spawn_random(&player, &d3).1);
-        assert_eq!(9, spawn_random(&player, &d10).1);
+        assert_eq!(1, spawn_random(&player, &d1, Terrain::Plain, None).1);
+        assert_eq!(1, spawn_random(&player, &d2, Terrain::Plain, None).1);
+        assert_eq!(2, spawn_random(&player, &d3, Terrain::Plain, None).1);
+        assert_eq!(9, spawn_random(&player, &d10, Terrain::Plain, None).1);
 
         player.level = 5;
-        assert_eq!(1, spawn_random(&player, &d1).1);
-        assert_eq!(1, spawn_random(&player, &d2).1);
-        assert_eq!(2, spawn_random(&player, &d3).1);
-        assert_eq!(9, spawn_random(&player, &d10).1);
+        assert_eq!(1, spawn_random(&player, &d1, Terrain::Plain, None).1);
+        assert_eq!(1, spawn_random(&player, &d2, Terrain::Plain, None).1);
+        assert_eq!(2, spawn_random(&player, &d3, Terrain::Plain, None).1);
+        assert_eq!(9, spawn_random(&player, &d10, Terrain::Plain, None).1);
 
         player.level = 10;
-        assert_eq!(1, spawn_random(&player, &d1).1);
-        assert_eq!(2, spawn_random(&player, &d2).1);
-        assert_eq!(3, spawn_random(&player, &d3).1);
-        assert_eq!(10, spawn_random(&player, &d10).1);
+        assert_eq!(1, spawn_random(&player, &d1, Terrain::Plain, None).1);
+        assert_eq!(2, spawn_random(&player, &d2, Terrain::Plain, None).1);
+        assert_eq!(3, spawn_random(&player, &d3, Terrain::Plain, None).1);
+        assert_eq!(10, spawn_random(&player, &d10, Terrain::Plain, None).1);
+    }
+
+    #[test]
+    fn test_danger() {
+        let mut game = crate::game::Game::new();
+
+        let at_home = danger(&game);
+        assert_eq!(vec![("shadow", 0)], at_home.bosses);
+        assert_eq!((0, 1), at_home.odds);
+
+        let home = dirs::home_dir().unwrap().to_string_lossy().to_string();
+        game.location = location::tests::location_from(&format!("{}/1/2/3/4/5/6/7/8/9", home));
+        let deep = danger(&game);
+        assert!(deep.bosses.is_empty());
+        assert_eq!((1, 2), deep.odds);
+        assert_eq!(4, deep.min_level);
+        assert_eq!(12, deep.max_level);
+
+        game.player.equip_ring(Ring::Evade);
+        assert_eq!((0, 1), danger(&game).odds);
+    }
+
+    #[test]
+    fn test_morgoth() {
+        let player = Character::player();
+        let path: Vec<String> = (0..GORTHAUR_DISTANCE).map(|i| i.to_string()).collect();
+        let deep = location::tests::location_from(&format!("~/{}", path.join("/")));
+
+        assert!(spawn_morgoth(&player, &deep, false).is_none());
+        assert!(spawn_morgoth(&player, &Location::home(), true).is_none());
+        let (class, level) = spawn_morgoth(&player, &deep, true).unwrap();
+        assert_eq!("morgoth", class.name);
+        assert_eq!(player.level + MORGOTH_LEVEL_BONUS, level);
+    }
+
+    #[test]
+    fn test_hunt() {
+        let mut game = crate::game::Game::new();
+        assert!(hunt(&game, "choripan").is_err());
+
+        game.location = location::tests::location_from("/hunted");
+        game.hunt = Some(Hunt {
+            family: String::from("goblin"),
+            location: game.location.clone(),
+            remaining: 2,
+        });
+
+        let enemy = spawn(&mut game).unwrap();
+        assert_eq!("goblin", family(&enemy.class));
+        assert_eq!(1, game.hunt.as_ref().unwrap().remaining);
+
+        let enemy = spawn(&mut game).unwrap();
+        assert_eq!("goblin", family(&enemy.class));
+        assert!(game.hunt.is_none());
+    }
+
+    #[test]
+    fn test_invasion() {
+        let mut game = crate::game::Game::new();
+        game.invasion = Some(Invasion {
+            family: String::from("goblin"),
+            min_distance: 3,
+            max_distance: 6,
+            expires_at: config::now() + INVASION_DURATION,
+        });
+
+        let home = dirs::home_dir().unwrap().to_string_lossy().to_string();
+        game.location = location::tests::location_from(&format!("{}/1/2/3/4", home));
+        assert_eq!((5, 9), danger(&game).odds);
+        let enemy = spawn(&mut game).unwrap();
+        assert_eq!("goblin", family(&enemy.class));
+        assert!(game
+            .invasion
+            .as_ref()
+            .unwrap()
+            .rewards(&enemy, &game.location));
+
+        game.location = location::tests::location_from(&format!("{}/1", home));
+        assert!(!game.invasion.as_ref().unwrap().affects(&game.location));
+
+        game.invasion.as_mut().unwrap().expires_at = config::now();
+        update_invasion(&mut game);
+        assert!(game.invasion.is_none());
+    }
+
+    #[test]
+    fn test_clearing() {
+        let mut game = crate::game::Game::new();
+        game.location = location::tests::location_from("/cleared");
+        let enemy = Character::new(Class::random(Category::Common).clone(), 1);
+
+        for _ in 0..CLEAR_VICTORIES - 1 {
+            record_victory(&mut game, &enemy);
+        }
+        assert!(spawn(&mut game).is_some());
+
+        record_victory(&mut game, &enemy);
+        assert!(spawn(&mut game).is_none());
+        assert!(danger(&game).cleared);
+
+        game.location = location::tests::location_from("/boss");
+        let mut boss = enemy;
+        boss.class.name = String::from("shadow");
+        record_victory(&mut game, &boss);
+        assert!(spawn(&mut game).is_none());
+    }
+
+    #[test]
+    fn test_ghost() {
+        let mut game = crate::game::Game::new();
+        game.location = location::tests::location_from("/haunted");
+        game.player.level = 3;
+        game.ghosts
+            .insert(game.location.to_string(), Ghost::new(&game.player, 1000));
+
+        let enemy = spawn(&mut game).unwrap();
+        assert_eq!("ghost", enemy.name());
+        assert_eq!(5, enemy.level);
+        assert_eq!(250, game.ghosts[&game.location.to_string()].gold);
     }
 
     #[test]
     fn test_run_ring() {
-        let mut player = Character::player();
-        let location = location::tests::location_from("~/1/");
-        assert!(spawn(&location, &player).is_some());
+        let mut game = crate::game::Game::new();
+        game.location = location::tests::location_from("~/1/");
+        assert!(spawn(&mut game).is_some());
 
-        player.equip_ring(Ring::Evade);
-        assert!(spawn(&location, &player).is_none());
+        game.player.equip_ring(Ring::Evade);
+        assert!(spawn(&mut game).is_none());
 
-        player.equip_ring(Ring::Void);
-        assert!(spawn(&location, &player).is_none());
+        game.player.equip_ring(Ring::Void);
+        assert!(spawn(&mut game).is_none());
 
-        player.equip_ring(Ring::Void);
-        assert!(spawn(&location, &player).is_some());
+        game.player.equip_ring(Ring::Void);
+        assert!(spawn(&mut game).is_some());
     }
 }
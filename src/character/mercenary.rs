@@ -0,0 +1,46 @@
+use super::class::{Category, Class};
+use super::Character;
+use std::cmp::max;
+
+/// Gold asked upfront per level of the mercenary.
+const FEE_PER_LEVEL: i32 = 1000;
+
+/// Percentage of the battle gold the mercenary takes as wage.
+const GOLD_CUT: i32 = 25;
+
+/// The least a mercenary accepts after a battle, per level.
+const MIN_WAGE_PER_LEVEL: i32 = 10;
+
+/// Create a fighter of a random player class at the same level as the hero,
+/// to be hired as a second combatant.
+pub fn new(player: &Character) -> Character {
+    let class = Class::random(Category::Player).clone();
+    Character::new(class, player.level)
+}
+
+/// The upfront price to hire the given mercenary.
+pub fn fee(mercenary: &Character) -> i32 {
+    mercenary.level * FEE_PER_LEVEL
+}
+
+/// The mercenary's cut of the gold won in a battle.
+pub fn wage(mercenary: &Character, gold: i32) -> i32 {
+    max(gold * GOLD_CUT / 100, mercenary.level * MIN_WAGE_PER_LEVEL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wage() {
+        let mut player = Character::player();
+        player.level = 5;
+        let mercenary = new(&player);
+
+        assert_eq!(5, mercenary.level);
+        assert_eq!(5000, fee(&mercenary));
+        assert_eq!(250, wage(&mercenary, 1000));
+        assert_eq!(50, wage(&mercenary, 0));
+    }
+}
@@ -1,21 +1,29 @@
+use crate::error::{bail, GameError};
+use crate::item::amulet::Amulet;
 use crate::item::equipment;
 use crate::item::key::Key;
 use crate::item::ring::Ring;
 use crate::item::Item;
 use crate::log;
 use crate::randomizer::{random, Randomizer};
-use anyhow::bail;
+use crate::terrain;
+use crate::weather;
 use class::Class;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 
+pub mod behavior;
 pub mod class;
 pub mod enemy;
+pub mod mercenary;
+pub mod morale;
+pub mod mount;
 pub mod npc;
 use std::cmp::{max, min};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(default)]
 pub struct Character {
     pub class: Class,
@@ -35,11 +43,62 @@ pub struct Character {
     pub shield: Option<equipment::Equipment>,
     pub left_ring: Option<Ring>,
     pub right_ring: Option<Ring>,
+    pub amulet: Option<Amulet>,
+
+    /// The animal the character rides, if any.
+    pub mount: Option<mount::Mount>,
 
     pub status_effect: Option<StatusEffect>,
 
     pub skill_points: i32,
     pub unlocked_skills: std::collections::HashSet<String>,
+
+    /// The rank of the skills learned more than once.
+    pub skill_ranks: HashMap<String, i32>,
+
+    pub buff: Buff,
+
+    /// Temporary buff from a consumable, along with the battle turns left
+    /// until it wears off.
+    pub boost: Option<(Buff, i32)>,
+
+    /// Well fed from a cooked meal, along with the battles left until it's
+    /// digested.
+    pub meal: Option<(Buff, i32)>,
+
+    /// The status effect the weapon is coated with until the end of the
+    /// battle, on top of any other the attacks inflict.
+    pub coating: Option<StatusEffect>,
+
+    /// Out of rations on a long expedition, the character is weaker and
+    /// slower until it eats or gets back home.
+    pub fatigued: bool,
+
+    /// Carrying more than it can, the character is slower and has a
+    /// harder time running away.
+    pub encumbered: bool,
+
+    /// Confidence built up by victories and worn down by setbacks, from 0
+    /// to 100. High morale makes the character stronger, low morale
+    /// weaker and reluctant to take risks.
+    pub morale: i32,
+
+    /// Bracing for the next blow, which deals half the damage. Saved so
+    /// an enemy that braces in its turn is still braced in the next one.
+    pub defending: bool,
+}
+
+/// Temporary changes to the character stats, as percentages of their value.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+pub struct Buff {
+    pub attack: i32,
+    pub speed: i32,
+}
+
+impl Buff {
+    fn apply(percent: i32, value: i32) -> i32 {
+        max(0, value + value * percent / 100)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
@@ -71,6 +130,59 @@ impl fmt::Display for Dead {
 
 pub struct ClassNotFound;
 
+/// Base gold rewarded for each level the defeated enemy has over the hero.
+pub const GOLD_PER_LEVEL: i32 = 50;
+
+/// How much weaker and slower, as a percentage, a fatigued character is.
+pub const FATIGUE_PENALTY: i32 = 25;
+
+/// Percentage chance of parrying given by a shield of the character level
+/// or above, less for lower level ones.
+pub const PARRY_SHIELD: i32 = 10;
+
+/// Percentage chance of parrying added when faster than the attacker.
+pub const PARRY_SPEED: i32 = 5;
+
+/// The best chance of parrying, however good the shield and the skills.
+pub const MAX_PARRY: i32 = 50;
+
+/// How much slower, as a percentage, an encumbered character is.
+pub const ENCUMBRANCE_PENALTY: i32 = 25;
+
+/// The weight any character can carry, besides what its strength adds.
+const BASE_CAPACITY: i32 = 30;
+
+/// How a derived stat adds up from its sources, see Character::stat_breakdown.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct StatBreakdown {
+    pub name: &'static str,
+    pub base: i32,
+    pub level: i32,
+    pub equipment: i32,
+    pub rings: i32,
+    pub buffs: i32,
+}
+
+impl StatBreakdown {
+    /// Build from the running value of the stat after adding each source,
+    /// so rounding and class penalties end up in the source that caused them.
+    fn from_steps(name: &'static str, steps: [i32; 5]) -> Self {
+        let [base, level, equipment, rings, buffs] = steps;
+        Self {
+            name,
+            base,
+            level: level - base,
+            equipment: equipment - level,
+            rings: rings - equipment,
+            buffs: buffs - rings,
+        }
+    }
+
+    pub fn total(&self) -> i32 {
+        self.base + self.level + self.equipment + self.rings + self.buffs
+    }
+}
+
 impl Default for Character {
     fn default() -> Self {
         Character::player()
@@ -86,6 +198,14 @@ impl Character {
         self.class.name.to_string()
     }
 
+    /// The name shown in the game output.
+    pub fn display_name(&self) -> String {
+        self.class
+            .display_name
+            .clone()
+            .unwrap_or_else(|| self.name())
+    }
+
     pub fn is_player(&self) -> bool {
         self.class.category == class::Category::Player
     }
@@ -102,6 +222,8 @@ impl Character {
             shield: None,
             left_ring: None,
             right_ring: None,
+            amulet: None,
+            mount: None,
             level: 1,
             xp: 0,
             max_hp,
@@ -113,6 +235,15 @@ impl Character {
             status_effect: None,
             skill_points: 1,
             unlocked_skills: std::collections::HashSet::new(),
+            skill_ranks: HashMap::new(),
+            buff: Buff::default(),
+            boost: None,
+            meal: None,
+            coating: None,
+            fatigued: false,
+            encumbered: false,
+            morale: morale::NEUTRAL,
+            defending: false,
         };
 
         for _ in 1..level {
@@ -220,6 +351,7 @@ impl Character {
             increased_levels += 1;
             for_next = self.xp_for_next();
         }
+        self.update_morale(increased_levels * morale::LEVEL_UP);
         increased_levels
     }
 
@@ -245,10 +377,33 @@ impl Character {
         self.current_mp - previous
     }
 
-    /// Restore all health and magic points to their max and remove status effects
+    /// Raise or lower the morale by the given amount, keeping it between 0
+    /// and the max. Return the effectively changed amount.
+    pub fn update_morale(&mut self, amount: i32) -> i32 {
+        let previous = self.morale;
+        self.morale = (self.morale + amount).clamp(0, morale::MAX);
+        self.morale - previous
+    }
+
+    pub fn mood(&self) -> morale::Mood {
+        morale::Mood::of(self.morale)
+    }
+
+    /// Scale the max hp to the given percentage, fully healing the character.
+    pub fn scale_max_hp(&mut self, percent: i32) {
+        self.max_hp = self.max_hp * percent / 100;
+        self.current_hp = self.max_hp();
+    }
+
+    /// Restore all health and magic points to their max and remove status effects.
+    /// The mount, if any, is fully healed too, and low morale lifted.
     pub fn restore(&mut self) -> (i32, i32, bool) {
         let healed = self.status_effect.is_some();
         self.status_effect = None;
+        self.morale = max(self.morale, morale::NEUTRAL);
+        if let Some(mount) = self.mount.as_mut() {
+            mount.current_hp = mount.max_hp();
+        }
         (
             self.update_hp(self.max_hp()).unwrap(),
             self.update_mp(self.max_mp()),
@@ -256,6 +411,13 @@ impl Character {
         )
     }
 
+    /// The weight the character can carry without being encumbered,
+    /// growing with its strength and the load its mount carries.
+    pub fn carry_capacity(&self) -> i32 {
+        let mount = self.mount.as_ref().map_or(0, |mount| mount.capacity());
+        BASE_CAPACITY + self.strength * 2 + mount
+    }
+
     /// How many experience points are required to move to the next level.
     pub fn xp_for_next(&self) -> i32 {
         let exp = 1.5;
@@ -272,7 +434,21 @@ impl Character {
     }
 
     pub fn speed(&self) -> i32 {
-        self.modify_stat(self.speed, Ring::Speed)
+        let load = if self.encumbered {
+            ENCUMBRANCE_PENALTY
+        } else {
+            0
+        };
+        let load = load + self.shield.as_ref().map_or(0, |shield| shield.slowdown());
+        let speed = self.buffed(
+            self.total_buff().speed - load,
+            self.modify_stat(self.speed, Ring::Speed),
+        );
+        if self.is_player() {
+            terrain::current().speed(speed)
+        } else {
+            speed
+        }
     }
 
     /// Generate and log an attack of this character and apply its effects to
@@ -280,11 +456,24 @@ impl Character {
     /// Returns a tuple with the gained experience and a Err(Dead) result if
     /// the receiver died from the inflicted damage.
     pub fn attack(&mut self, receiver: &mut Self) -> (i32, Result<(), Dead>) {
+        let attack_type = self.attack_type(receiver);
+        self.strike(receiver, attack_type)
+    }
+
+    /// Attack a receiver caught off guard, which can't dodge it.
+    pub fn surprise_attack(&mut self, receiver: &mut Self) -> (i32, Result<(), Dead>) {
+        let attack_type = match self.attack_type(receiver) {
+            AttackType::Miss => AttackType::Regular,
+            attack_type => attack_type,
+        };
+        self.strike(receiver, attack_type)
+    }
+
+    fn strike(&mut self, receiver: &mut Self, attack_type: AttackType) -> (i32, Result<(), Dead>) {
         let (damage, mp_cost) = self.damage(receiver);
         let damage = random().damage(damage);
         let xp = self.xp_gained(receiver, damage);
 
-        let attack_type = self.attack_type(receiver);
         let (damage, xp) = match attack_type {
             AttackType::Regular => (damage, xp),
             AttackType::Critical => (damage * 2, xp),
@@ -296,12 +485,19 @@ impl Character {
 
         // The receiver can die from the damage. Return the result for
         // the caller to handle that scenario.
+        let damage = if receiver.defending {
+            damage / 2
+        } else {
+            damage
+        };
+        let damage = receiver.mana_shield(damage);
         let result = receiver.update_hp(-damage).map(|_| ());
         if let AttackType::Effect(status) = attack_type {
             receiver.status_effect = Some(status);
         }
 
         log::attack(receiver, &attack_type, damage, mp_cost);
+        log::narration(self, receiver, &attack_type);
 
         (xp, result)
     }
@@ -326,7 +522,8 @@ impl Character {
         }
     }
 
-    /// If the revive ring is equipped and the character died, restore 10% of its hp.
+    /// If the character died wearing a charged amulet, it survives at 1hp.
+    /// Otherwise if the revive ring is equipped, restore 10% of its hp.
     /// Intended to be used once per battle, with `already_revived` tracking whether
     /// it was used before.
     /// Returns Err(Dead) if can't be recovered from death, otherwise Ok(already_revived).
@@ -339,6 +536,12 @@ impl Character {
             self.left_ring == Some(Ring::Revive) || self.right_ring == Some(Ring::Revive);
         match died {
             Ok(()) => Ok(already_revived),
+            Err(Dead) if self.amulet.as_ref().is_some_and(|amulet| !amulet.spent) => {
+                self.amulet.as_mut().unwrap().spent = true;
+                self.current_hp = 1;
+                log::heal_item(self, "amulet", 1, 0, false);
+                Ok(already_revived)
+            }
             Err(Dead) if wearing_revive && !already_revived => {
                 let restored = max(1, self.max_hp() / 10);
                 self.current_hp = restored;
@@ -354,7 +557,10 @@ impl Character {
     fn attack_type(&self, receiver: &Self) -> AttackType {
         let inflicted_status = random().inflicted(self.inflicted_status_effect(receiver));
 
-        if random().is_miss(self.speed(), receiver) {
+        if random().is_miss(self.speed(), receiver)
+            || random().dodges(receiver.dodge())
+            || random().is_weather_miss(weather::current())
+        {
             AttackType::Miss
         } else if random().is_critical() {
             AttackType::Critical
@@ -391,7 +597,26 @@ impl Character {
     /// Amount of damage the character can inflict with physical atacks, given
     /// its strength and equipment. Magic using characters' strength is dimmed.
     pub fn physical_attack(&self) -> i32 {
-        let sword_str = self.sword.as_ref().map_or(0, |s| s.strength());
+        self.buffed(self.total_buff().attack, self.unbuffed_physical_attack())
+    }
+
+    /// The strength added by the weapon, raised or lowered by the class
+    /// affinity for its kind.
+    fn weapon_strength(&self, weapon: &equipment::Equipment) -> i32 {
+        Buff::apply(self.class.affinity(&weapon.key()), weapon.strength())
+    }
+
+    /// The weapon strength added to the physical attack or, for staves and
+    /// wands, to the magic one.
+    fn wielded_strength(&self, magic: bool) -> i32 {
+        self.sword
+            .as_ref()
+            .filter(|sword| sword.is_magic() == magic)
+            .map_or(0, |sword| self.weapon_strength(sword))
+    }
+
+    fn unbuffed_physical_attack(&self) -> i32 {
+        let sword_str = self.wielded_strength(false);
         let attack = self.modify_stat(self.strength, Ring::Attack) + sword_str;
         if self.class.is_magic() {
             attack / 3
@@ -403,8 +628,50 @@ impl Character {
     /// Amount of damage the character can inflict with magical attacks.
     /// Zero if the current character class is not magic.
     pub fn magic_attack(&self) -> i32 {
+        self.buffed(self.total_buff().attack, self.unbuffed_magic_attack())
+    }
+
+    /// The stat changes from the terrain along with the consumable, meal
+    /// and morale ones.
+    fn total_buff(&self) -> Buff {
+        let boost = self.boost.map_or(Buff::default(), |(boost, _)| boost);
+        let meal = self.meal.map_or(Buff::default(), |(meal, _)| meal);
+        let mood = self.mood().buff();
+        Buff {
+            attack: self.buff.attack + boost.attack + meal.attack + mood.attack,
+            speed: self.buff.speed + boost.speed + meal.speed + mood.speed,
+        }
+    }
+
+    /// Count down a battle turn of the consumable buff, if any.
+    pub fn wear_off_boost(&mut self) {
+        if let Some((_, turns)) = self.boost.as_mut() {
+            *turns -= 1;
+            if *turns <= 0 {
+                self.boost = None;
+            }
+        }
+    }
+
+    /// Count down a won battle of the meal buff, if any.
+    pub fn digest_meal(&mut self) {
+        if let Some((_, battles)) = self.meal.as_mut() {
+            *battles -= 1;
+            if *battles <= 0 {
+                self.meal = None;
+            }
+        }
+    }
+
+    /// Apply the given buff percentage to a stat, along with the fatigue.
+    fn buffed(&self, percent: i32, value: i32) -> i32 {
+        let fatigue = if self.fatigued { FATIGUE_PENALTY } else { 0 };
+        Buff::apply(percent - fatigue, value)
+    }
+
+    fn unbuffed_magic_attack(&self) -> i32 {
         if self.class.is_magic() {
-            let base = self.strength * 3;
+            let base = self.strength * 3 + self.wielded_strength(true);
             self.modify_stat(base, Ring::Magic)
         } else {
             0
@@ -412,15 +679,87 @@ impl Character {
     }
 
     pub fn deffense(&self) -> i32 {
+        let percent = self.passives().map(|passive| passive.defense).sum();
+        Buff::apply(percent, self.unbuffed_deffense())
+    }
+
+    fn unbuffed_deffense(&self) -> i32 {
         let shield_str = self.shield.as_ref().map_or(0, |s| s.strength());
         // base strength should be zero, subtract it from ring calculation
         shield_str + self.modify_stat(self.strength, Ring::Deffense) - self.strength
     }
 
+    /// Decompose each derived stat into the class base value, the growth
+    /// from leveling up, the equipment, the rings and temporary buffs,
+    /// including those of the terrain.
+    pub fn stat_breakdown(&self) -> Vec<StatBreakdown> {
+        let class = &self.class;
+        let sword_str = self.wielded_strength(false);
+        let shield_str = self.shield.as_ref().map_or(0, |s| s.strength());
+        let slowdown = self.shield.as_ref().map_or(0, |s| s.slowdown());
+        let attack_penalty = if class.is_magic() { 3 } else { 1 };
+
+        let hp = self.max_hp();
+        let attack = self.physical_attack();
+        let deffense = self.deffense();
+        let speed = self.speed();
+        let mut breakdown = vec![
+            StatBreakdown::from_steps("hp", [class.hp.0, self.max_hp, self.max_hp, hp, hp]),
+            StatBreakdown::from_steps(
+                "att",
+                [
+                    class.strength.0 / attack_penalty,
+                    self.strength / attack_penalty,
+                    (self.strength + sword_str) / attack_penalty,
+                    self.unbuffed_physical_attack(),
+                    attack,
+                ],
+            ),
+            StatBreakdown::from_steps(
+                "def",
+                [0, 0, shield_str, self.unbuffed_deffense(), deffense],
+            ),
+            StatBreakdown::from_steps(
+                "spd",
+                [
+                    class.speed.0,
+                    self.speed,
+                    Buff::apply(-slowdown, self.speed),
+                    Buff::apply(-slowdown, self.modify_stat(self.speed, Ring::Speed)),
+                    speed,
+                ],
+            ),
+        ];
+
+        if let Some(mp_stat) = &class.mp {
+            let mp = self.max_mp();
+            let magic = self.magic_attack();
+            breakdown.insert(
+                1,
+                StatBreakdown::from_steps("mp", [mp_stat.0, self.max_mp, self.max_mp, mp, mp]),
+            );
+            breakdown.insert(
+                3,
+                StatBreakdown::from_steps(
+                    "mag",
+                    [
+                        class.strength.0 * 3,
+                        self.strength * 3,
+                        self.strength * 3 + self.wielded_strength(true),
+                        self.unbuffed_magic_attack(),
+                        magic,
+                    ],
+                ),
+            );
+        }
+        breakdown
+    }
+
     /// How many experience points are gained by inflicting damage to an enemy.
+    /// Doubled if the shadow ring is equipped.
     fn xp_gained(&self, receiver: &Self, damage: i32) -> i32 {
         let class_multiplier = match receiver.class.category {
-            class::Category::Rare => 3,
+            class::Category::Rare | class::Category::Construct => 3,
             class::Category::Legendary => 5,
             _ => 1,
         };
@@ -428,14 +767,20 @@ impl Character {
         // don't consider xp beyond the actually inflicted damage, otherwise
         // the stronger the char, the more xp even if defeating a weak enemy.
         let damage = min(damage, receiver.current_hp);
+        let ring_multiplier =
+            if self.left_ring == Some(Ring::Shadow) || self.right_ring == Some(Ring::Shadow) {
+                2
+            } else {
+                1
+            };
 
         if self.level > receiver.level + 10 {
             // don't reward cheap victories
             0
         } else if receiver.level > self.level {
-            damage * (1 + receiver.level - self.level) * class_multiplier
+            damage * (1 + receiver.level - self.level) * class_multiplier * ring_multiplier
         } else {
-            damage / (1 + self.level - receiver.level) * class_multiplier
+            damage / (1 + self.level - receiver.level) * class_multiplier * ring_multiplier
         }
     }
 
@@ -451,10 +796,28 @@ impl Character {
             _ => None,
         };
 
-        let result = self.class.inflicts.or(ring_status);
+        let gem_status = self
+            .sword
+            .as_ref()
+            .and_then(|sword| sword.inflicts())
+            .map(|status| (status, 3));
+
+        let coating_status = self.coating.map(|status| (status, 3));
+
+        let result = self
+            .class
+            .inflicts
+            .or(coating_status)
+            .or(ring_status)
+            .or(gem_status);
         if let Some((status, _)) = result {
-            // don't double-inflict if already has the same status
-            if receiver.status_effect == Some(status) {
+            // don't double-inflict if already has the same status, nor
+            // through a shield with the right gems
+            let resisted = receiver
+                .shield
+                .as_ref()
+                .is_some_and(|shield| shield.resists(status));
+            if receiver.status_effect == Some(status) || resisted {
                 return None;
             }
         }
@@ -537,6 +900,13 @@ impl Character {
         }
     }
 
+    /// Restore the power of the amulet, once the battle is over.
+    pub fn recharge_amulet(&mut self) {
+        if let Some(amulet) = self.amulet.as_mut() {
+            amulet.spent = false;
+        }
+    }
+
     /// Return true if an evade ring is equipped, i.e. no enemies should appear.
     pub fn enemies_evaded(&self) -> bool {
         self.left_ring == Some(Ring::Evade) || self.right_ring == Some(Ring::Evade)
@@ -548,11 +918,74 @@ impl Character {
         self.left_ring == Some(Ring::Chest) || self.right_ring == Some(Ring::Chest)
     }
 
+    /// Return true if the character learned the skill to deal with the traps
+    /// hidden in chests.
+    pub fn disarms_traps(&self) -> bool {
+        self.passives().any(|passive| passive.disarm_traps)
+    }
+
+    /// The effects of the passive skills learned by the character.
+    fn passives(&self) -> impl Iterator<Item = &class::Passive> {
+        self.class
+            .skills
+            .iter()
+            .filter(|skill| skill.skill_type == class::SkillType::Passive)
+            .filter(|skill| self.unlocked_skills.contains(&skill.name))
+            .map(|skill| &skill.passive)
+    }
+
+    /// Percentage chance of dodging an attack given by the passive skills.
+    pub fn dodge(&self) -> i32 {
+        let shield = self.shield.as_ref().map_or(0, |shield| shield.dodge());
+        self.passives().map(|passive| passive.dodge).sum::<i32>() + shield
+    }
+
+    /// Percentage chance of parrying the attacker's blow with the shield,
+    /// better with a shield of the character level and when faster than the
+    /// attacker. None without a working shield.
+    pub fn parry(&self, attacker: &Self) -> i32 {
+        let Some(shield) = self.shield.as_ref().filter(|shield| !shield.is_broken()) else {
+            return 0;
+        };
+        let quality = min(
+            PARRY_SHIELD,
+            PARRY_SHIELD * shield.level() / max(1, self.level),
+        );
+        let speed = if self.speed() > attacker.speed() {
+            PARRY_SPEED
+        } else {
+            0
+        };
+        let skills: i32 = self.passives().map(|passive| passive.parry).sum();
+        min(MAX_PARRY, quality + speed + skills)
+    }
+
+    /// Recover the hp given by the passive skills, e.g. after a battle round.
+    pub fn regenerate(&mut self) {
+        let percent: i32 = self.passives().map(|passive| passive.regeneration).sum();
+        let recovered = self.max_hp() * percent / 100;
+        if recovered > 0 && self.current_hp < self.max_hp() {
+            let recovered = self.update_hp(recovered).unwrap_or_default();
+            log::heal_item(self, "regeneration", recovered, 0, false);
+        }
+    }
+
+    /// Take part of the given damage out of the mp, according to the passive
+    /// skills. Returns what's left to take out of the hp.
+    fn mana_shield(&mut self, damage: i32) -> i32 {
+        let percent: i32 = self.passives().map(|passive| passive.mana_shield).sum();
+        let absorbed = min(self.current_mp, damage * percent / 100);
+        self.update_mp(-absorbed);
+        damage - absorbed
+    }
+
     /// Return the gold that should be rewarded for beating an enemy of the given
     /// level. Doubled if the gold ring is equipped.
     pub fn gold_gained(&self, enemy_level: i32) -> i32 {
         let level = max(1, enemy_level - self.level);
-        let gold = random().gold_gained(level * 50);
+        let gold = random().gold_gained(level * GOLD_PER_LEVEL);
+        let gold_find = self.passives().map(|passive| passive.gold_find).sum();
+        let gold = Buff::apply(gold_find, gold);
 
         if self.level > enemy_level + 10 {
             // don't reward cheap victories
@@ -606,7 +1039,9 @@ impl Character {
         (base as f64 * factor).round() as i32
     }
 
-    pub fn learn_skill(&mut self, skill_name: &str) -> Result<(), anyhow::Error> {
+    /// Learn the given skill, or its next rank if already learned.
+    /// Returns the rank reached.
+    pub fn learn_skill(&mut self, skill_name: &str) -> Result<i32, GameError> {
         if self.skill_points <= 0 {
             bail!("Not enough skill points.");
         }
@@ -618,19 +1053,43 @@ impl Character {
             .find(|s| s.name.eq_ignore_ascii_case(skill_name));
 
         if let Some(skill) = skill_to_learn {
-            if self.level < skill.level_requirement {
-                bail!("Level not high enough to learn this skill.");
-            }
-            if self.unlocked_skills.contains(&skill.name) {
+            let rank = self.skill_rank(&skill.name) + 1;
+            if rank > 1 && skill.skill_type == class::SkillType::Passive {
                 bail!("Skill already unlocked.");
             }
+            if rank > class::MAX_SKILL_RANK {
+                bail!("Skill already at its highest rank.");
+            }
+            if self.level < skill.rank_requirement(rank) {
+                bail!("Level not high enough to learn this skill.");
+            }
 
             self.skill_points -= 1;
             self.unlocked_skills.insert(skill.name.clone());
-            Ok(())
+            if rank > 1 {
+                self.skill_ranks.insert(skill.name.clone(), rank);
+            }
+            Ok(rank)
         } else {
-            bail!("Skill not found.")
+            bail!(GameError::UnknownSkill)
+        }
+    }
+
+    /// Forget all the learned skills. Returns the skill points refunded,
+    /// one for each rank learned.
+    pub fn unlearn_skills(&mut self) -> i32 {
+        let extra_ranks: i32 = self.skill_ranks.drain().map(|(_, rank)| rank - 1).sum();
+        let points = self.unlocked_skills.drain().count() as i32 + extra_ranks;
+        self.skill_points += points;
+        points
+    }
+
+    /// The rank of the given skill, zero if it wasn't learned.
+    pub fn skill_rank(&self, skill_name: &str) -> i32 {
+        if !self.unlocked_skills.contains(skill_name) {
+            return 0;
         }
+        self.skill_ranks.get(skill_name).copied().unwrap_or(1)
     }
 }
 
@@ -1007,9 +1466,9 @@ mod tests {
         assert!(!hero.can_magic_attack());
         assert_eq!((base_strength, 0), hero.damage(&foe));
 
-        // warrior + sword, increased damage + mp = 0
+        // warrior + sword, increased damage by its affinity + mp = 0
         let sword = equipment::Equipment::sword(hero.level);
-        let sword_strength = sword.strength();
+        let sword_strength = hero.weapon_strength(&sword);
         hero.sword = Some(sword);
         assert_eq!((base_strength + sword_strength, 0), hero.damage(&foe));
 
@@ -1185,6 +1644,46 @@ mod tests {
         assert!(char.inflicted_status_effect(&another).is_none());
     }
 
+    #[test]
+    fn test_morale() {
+        let mut hero = new_char();
+        hero.strength = 100;
+        assert_eq!(morale::Mood::Steady, hero.mood());
+        assert_eq!(100, hero.physical_attack());
+
+        assert_eq!(50, hero.update_morale(1000));
+        assert_eq!(morale::MAX, hero.morale);
+        assert_eq!(110, hero.physical_attack());
+
+        hero.update_morale(-1000);
+        assert_eq!(morale::Mood::Shaken, hero.mood());
+        assert_eq!(90, hero.physical_attack());
+
+        // leveling up and resting lift the morale
+        hero.add_experience(hero.xp_for_next());
+        assert_eq!(morale::LEVEL_UP, hero.morale);
+        hero.restore();
+        assert_eq!(morale::NEUTRAL, hero.morale);
+    }
+
+    #[test]
+    fn test_coating() {
+        let mut char = new_plain_stats_char();
+        let another = new_plain_stats_char();
+        char.coating = Some(StatusEffect::Poison);
+        assert_eq!(
+            Some((StatusEffect::Poison, 3)),
+            char.inflicted_status_effect(&another)
+        );
+
+        // the oil takes precedence over the rings
+        char.left_ring = Some(Ring::Fire);
+        assert_eq!(
+            Some((StatusEffect::Poison, 3)),
+            char.inflicted_status_effect(&another)
+        );
+    }
+
     #[test]
     fn modify_stat() {
         let mut char = new_plain_stats_char();
@@ -1200,6 +1699,40 @@ mod tests {
         assert_eq!(20, char.modify_stat(10, Ring::HP));
     }
 
+    #[test]
+    fn stat_breakdown() {
+        let mut char = new_plain_stats_char();
+        char.sword = Some(equipment::Equipment::sword(1));
+        char.left_ring = Some(Ring::Attack);
+
+        let breakdown = char.stat_breakdown();
+        let names: Vec<_> = breakdown.iter().map(|b| b.name).collect();
+        assert_eq!(vec!["hp", "mp", "att", "mag", "def", "spd"], names);
+
+        // magic classes get a third of the physical attack from each
+        // source, and the mage's sword is weakened by its affinity
+        let attack = &breakdown[2];
+        assert_eq!(
+            (1, 2, 1, 2, 0),
+            (
+                attack.base,
+                attack.level,
+                attack.equipment,
+                attack.rings,
+                attack.buffs
+            )
+        );
+        assert_eq!(char.physical_attack(), attack.total());
+
+        let warrior = Character::player();
+        let breakdown = warrior.stat_breakdown();
+        assert_eq!(4, breakdown.len());
+        assert_eq!(warrior.max_hp(), breakdown[0].total());
+        assert_eq!(warrior.physical_attack(), breakdown[1].total());
+        assert_eq!(warrior.deffense(), breakdown[2].total());
+        assert_eq!(warrior.speed(), breakdown[3].total());
+    }
+
     #[test]
     fn magic_attacks() {
         let mut player = Character::player();
@@ -1365,6 +1898,140 @@ mod tests {
         assert_eq!(300, player.gold_gained(4));
     }
 
+    #[test]
+    fn test_skill_ranks() {
+        let warrior = Class::player_by_name("warrior").unwrap().clone();
+        let mut hero = Character::new(warrior, 2);
+        assert_eq!(0, hero.skill_rank("Power Strike"));
+        assert_eq!(1, hero.learn_skill("power strike").unwrap());
+        assert_eq!(1, hero.skill_rank("Power Strike"));
+
+        // the next rank takes more levels and skill points
+        hero.skill_points = 5;
+        assert!(hero.learn_skill("Power Strike").is_err());
+        hero.level = 7;
+        assert_eq!(2, hero.learn_skill("Power Strike").unwrap());
+        hero.level = 12;
+        assert_eq!(3, hero.learn_skill("Power Strike").unwrap());
+        assert!(hero.learn_skill("Power Strike").is_err());
+        assert_eq!(3, hero.skill_points);
+
+        // passive skills have a single rank
+        hero.learn_skill("Shield Block").unwrap();
+        assert!(hero.learn_skill("Shield Block").is_err());
+
+        let skill = &hero.class.skills[0];
+        assert_eq!(5, skill.rank_cost(1));
+        assert_eq!(3, skill.rank_cost(3));
+        assert_eq!(40, skill.rank_effect(3, 20));
+    }
+
+    #[test]
+    fn test_passive_skills() {
+        let class = |name| Class::player_by_name(name).unwrap().clone();
+
+        let mut thief = Character::new(class("thief"), 4);
+        thief.skill_points = 3;
+        assert_eq!(0, thief.dodge());
+        assert!(!thief.disarms_traps());
+        thief.learn_skill("Evasion").unwrap();
+        thief.learn_skill("Disarm Traps").unwrap();
+        assert_eq!(5, thief.dodge());
+        assert!(thief.disarms_traps());
+
+        let gold = thief.gold_gained(4);
+        thief.learn_skill("Scavenger").unwrap();
+        assert_eq!(gold + gold / 10, thief.gold_gained(4));
+
+        let mut warrior = Character::new(class("warrior"), 3);
+        warrior.shield = Some(equipment::Equipment::shield(10));
+        let deffense = warrior.deffense();
+        warrior.learn_skill("Shield Block").unwrap();
+        assert_eq!(deffense + deffense / 10, warrior.deffense());
+
+        let mut sorceress = Character::new(class("sorceress"), 3);
+        assert_eq!(20, sorceress.mana_shield(20));
+        sorceress.learn_skill("Mana Shield").unwrap();
+        let mp = sorceress.current_mp;
+        assert_eq!(15, sorceress.mana_shield(20));
+        assert_eq!(mp - 5, sorceress.current_mp);
+
+        let mut summoner = Character::new(class("summoner"), 3);
+        summoner.learn_skill("Aura of Vitality").unwrap();
+        summoner.current_hp = 1;
+        summoner.regenerate();
+        assert_eq!(1 + summoner.max_hp() / 20, summoner.current_hp);
+    }
+
+    #[test]
+    fn test_affinities() {
+        let class = |name| Class::player_by_name(name).unwrap().clone();
+        let warrior = Character::new(class("warrior"), 10);
+        let thief = Character::new(class("thief"), 10);
+        let sword = equipment::Equipment::sword(10);
+        assert_eq!(20, warrior.class.affinity(&Key::Sword));
+        assert_eq!(0, thief.class.affinity(&Key::Sword));
+        assert_eq!(
+            Buff::apply(20, sword.strength()),
+            warrior.weapon_strength(&sword)
+        );
+
+        // the thief is better off with a dagger, the warrior with an axe
+        let dagger = equipment::Equipment::weapon(Key::Dagger, 10);
+        let axe = equipment::Equipment::weapon(Key::Axe, 10);
+        assert!(thief.weapon_strength(&dagger) > dagger.strength());
+        assert!(thief.weapon_strength(&axe) < axe.strength());
+        assert!(warrior.weapon_strength(&axe) > axe.strength());
+
+        // staves add to the magic attack of magic classes only
+        let mut mage = Character::new(class("mage"), 10);
+        let magic = mage.magic_attack();
+        mage.sword = Some(equipment::Equipment::weapon(Key::Staff, 10));
+        assert!(mage.magic_attack() > magic);
+    }
+
+    #[test]
+    fn test_armor_weight() {
+        let mut char = new_plain_stats_char();
+        let (speed, dodge) = (char.speed(), char.dodge());
+
+        char.shield = Some(equipment::Equipment::armor(Key::Buckler, 1));
+        assert_eq!(speed, char.speed());
+        assert_eq!(dodge + equipment::LIGHT_DODGE, char.dodge());
+
+        char.shield = Some(equipment::Equipment::armor(Key::TowerShield, 1));
+        assert_eq!(dodge, char.dodge());
+        assert_eq!(Buff::apply(-equipment::HEAVY_SLOWDOWN, speed), char.speed());
+        assert_eq!(char.speed(), char.stat_breakdown()[5].total());
+    }
+
+    #[test]
+    fn test_parry() {
+        let class = |name| Class::player_by_name(name).unwrap().clone();
+        let mut warrior = Character::new(class("warrior"), 4);
+        let mut enemy = Character::new(class("warrior"), 4);
+        assert_eq!(0, warrior.parry(&enemy));
+
+        warrior.shield = Some(equipment::Equipment::shield(2));
+        assert_eq!(PARRY_SHIELD / 2, warrior.parry(&enemy));
+        warrior.shield = Some(equipment::Equipment::shield(10));
+        assert_eq!(PARRY_SHIELD, warrior.parry(&enemy));
+
+        enemy.encumbered = true;
+        assert_eq!(PARRY_SHIELD + PARRY_SPEED, warrior.parry(&enemy));
+
+        warrior.skill_points = 1;
+        warrior.learn_skill("Riposte").unwrap();
+        assert_eq!(PARRY_SHIELD + PARRY_SPEED + 10, warrior.parry(&enemy));
+
+        warrior
+            .shield
+            .as_mut()
+            .unwrap()
+            .wear(equipment::MAX_DURABILITY);
+        assert_eq!(0, warrior.parry(&enemy));
+    }
+
     // HELPERS
 
     fn new_char() -> Character {
@@ -1377,6 +2044,15 @@ mod tests {
                 strength: Stat(10, 3),
                 speed: Stat(10, 2),
                 inflicts: None,
+                affinities: HashMap::new(),
+                behavior: behavior::Behavior::default(),
+                skills: vec![],
+                portrait: None,
+                icon: None,
+                display_name: None,
+                requires: None,
+                terrains: vec![],
+                loot: vec![],
             },
             1,
         )
@@ -0,0 +1,61 @@
+use super::Buff;
+
+/// The morale of a fresh hero, and the one restored by resting at a safe
+/// place.
+pub const NEUTRAL: i32 = 50;
+
+pub const MAX: i32 = 100;
+
+/// Morale from which the hero is inspired.
+pub const INSPIRED: i32 = 75;
+
+/// Morale under which the hero is shaken.
+pub const SHAKEN: i32 = 25;
+
+/// Morale changes for each of the hero's ups and downs.
+pub const VICTORY: i32 = 3;
+pub const LEVEL_UP: i32 = 10;
+pub const FLEE: i32 = -5;
+pub const COMPANION_DIED: i32 = -15;
+pub const CLOSE_CALL: i32 = -10;
+pub const MEAL: i32 = 15;
+pub const ALE: i32 = 25;
+
+/// Percentage of the max hp under which getting out of a battle alive is
+/// a close call.
+pub const CLOSE_CALL_HP: i32 = 10;
+
+/// How the hero's morale affects its stats and its will to take risks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mood {
+    Inspired,
+    Steady,
+    /// Weaker and slower, and may refuse to take risks.
+    Shaken,
+}
+
+impl Mood {
+    pub fn of(morale: i32) -> Self {
+        if morale >= INSPIRED {
+            Mood::Inspired
+        } else if morale < SHAKEN {
+            Mood::Shaken
+        } else {
+            Mood::Steady
+        }
+    }
+
+    pub fn buff(&self) -> Buff {
+        match self {
+            Mood::Inspired => Buff {
+                attack: 10,
+                speed: 5,
+            },
+            Mood::Steady => Buff::default(),
+            Mood::Shaken => Buff {
+                attack: -10,
+                speed: -10,
+            },
+        }
+    }
+}
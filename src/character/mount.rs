@@ -0,0 +1,117 @@
+use crate::item::key::Key;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The hero level required to buy a drake.
+pub const DRAKE_LEVEL: i32 = 10;
+
+/// The animals the hero can ride.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Horse,
+    Drake,
+}
+
+/// A mount carries the hero and part of its load, riding past the enemies
+/// of the directories on the way to a destination. Enemies may strike it
+/// instead of the hero, and it bolts for good when its hp runs out.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Mount {
+    pub kind: Kind,
+    pub current_hp: i32,
+}
+
+impl Mount {
+    pub fn new(kind: Kind) -> Self {
+        let mut mount = Self {
+            kind,
+            current_hp: 0,
+        };
+        mount.current_hp = mount.max_hp();
+        mount
+    }
+
+    pub fn max_hp(&self) -> i32 {
+        match self.kind {
+            Kind::Horse => 30,
+            Kind::Drake => 80,
+        }
+    }
+
+    /// The percentage chance of riding past the enemies of a directory
+    /// on the way to the destination. Injured mounts are slower.
+    pub fn evasion(&self) -> i32 {
+        let evasion = match self.kind {
+            Kind::Horse => 40,
+            Kind::Drake => 70,
+        };
+        if self.is_injured() {
+            evasion / 2
+        } else {
+            evasion
+        }
+    }
+
+    /// The weight the mount carries on top of the hero's capacity.
+    pub fn capacity(&self) -> i32 {
+        match self.kind {
+            Kind::Horse => 20,
+            Kind::Drake => 40,
+        }
+    }
+
+    /// Whether the mount is under half its hp.
+    pub fn is_injured(&self) -> bool {
+        self.current_hp * 2 < self.max_hp()
+    }
+
+    /// Take the given damage, returning false if the mount bolts.
+    pub fn hurt(&mut self, damage: i32) -> bool {
+        self.current_hp = std::cmp::max(0, self.current_hp - damage);
+        self.current_hp > 0
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "rides past {}% of the enemies on the way, carries {} more weight, {}/{}hp",
+            self.evasion(),
+            self.capacity(),
+            self.current_hp,
+            self.max_hp()
+        )
+    }
+
+    pub fn key(&self) -> Key {
+        match self.kind {
+            Kind::Horse => Key::Horse,
+            Kind::Drake => Key::Drake,
+        }
+    }
+}
+
+impl fmt::Display for Mount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_injured() {
+        let mut horse = Mount::new(Kind::Horse);
+        assert_eq!(40, horse.evasion());
+        assert!(horse.hurt(10));
+        assert!(!horse.is_injured());
+
+        assert!(horse.hurt(10));
+        assert!(horse.is_injured());
+        assert_eq!(20, horse.evasion());
+
+        assert!(!horse.hurt(100));
+        assert_eq!(0, horse.current_hp);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::config::Config;
 use crate::game::Game;
 use crate::log;
 use crate::randomizer::{random, Randomizer};
@@ -8,14 +9,20 @@ pub enum Encounter {
     Gambler,
     Witch,
     GhostlyMaiden,
+    Blacksmith,
+    Merchant,
 }
 
 pub fn spawn(game: &mut Game) {
-    if random().should_enemy_appear(&game.location.distance_from_home()) {
-        let encounter = match random().range(3) {
+    let distance = game.location.distance_from_home();
+    let rolls = Config::get().spawn_rolls(&game.location);
+    if (0..rolls).any(|_| random().should_enemy_appear(&distance)) {
+        let encounter = match random().range(5) {
             0 => Some(Encounter::Gambler),
             1 => Some(Encounter::Witch),
             2 => Some(Encounter::GhostlyMaiden),
+            3 => Some(Encounter::Blacksmith),
+            4 => Some(Encounter::Merchant),
             _ => None,
         };
 
@@ -1,21 +1,46 @@
+use crate::artifact;
+use crate::challenge::Challenge;
 use crate::character;
 use crate::character::enemy;
-use crate::game::Game;
+use crate::config;
+use crate::cooking;
+#[cfg(unix)]
+use crate::daemon;
+use crate::error::{self, GameError};
+use crate::fuzzy;
+use crate::game::{Game, Hardcore, Progress};
+use crate::hint;
 use crate::item;
 use crate::item::key::Key;
 use crate::location::Location;
 use crate::log;
+use crate::manual;
+use crate::mutator::{Mutator, Mutators};
+use crate::randomizer;
 use crate::randomizer::Randomizer;
+use crate::simulation;
+use crate::speedrun::Speedrun;
+use crate::stats;
+use crate::terrain;
+use crate::title;
+use crate::weather;
 use anyhow::{anyhow, bail, Result};
+use strum::IntoEnumIterator;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
 #[command()]
 pub enum Command {
     /// Display stats for the given items. Defaults to displaying hero stats if no item is specified. [default]
     #[command(aliases=&["s", "status"], display_order=0)]
-    Stat { items: Vec<String> },
+    Stat {
+        items: Vec<String>,
+
+        /// Show how each hero stat adds up from the class, level, equipment, rings and buffs.
+        #[arg(long)]
+        detail: bool,
+    },
 
     /// Moves the hero to the supplied destination, potentially initiating battles along the way.
     #[command(name = "cd", display_order = 1)]
@@ -34,11 +59,40 @@ pub enum Command {
     #[command(name = "ls", display_order = 1)]
     Inspect,
 
+    /// Try to disarm the trap of a chest found at the current directory, and
+    /// open it. A failed attempt springs the trap.
+    #[command(display_order = 1)]
+    Disarm,
+
+    /// Search the current directory for herbs and food. Each place yields
+    /// less every time, and the noise may draw nearby enemies.
+    Forage,
+
+    /// Cook foraged ingredients into a meal that buffs the hero for the
+    /// next battles, at home or at a camp. Meals already in the cookbook
+    /// can be cooked by name. If the ingredients are omitted shows the
+    /// cookbook.
+    Cook { ingredients: Vec<String> },
+
+    /// Set a gem from the pouch in a free socket of the equipped sword or
+    /// shield. Rubies burn and emeralds poison, or protect from it when set
+    /// in a shield. Sapphires make the piece stronger.
+    Socket { item: String, gem: String },
+
+    /// Dig for ore and gems with a pickaxe, in system directories and
+    /// read-only mounts. Mining may wake a golem up.
+    Mine,
+
     /// Buys an item from the shop.
     /// If name is omitted lists the items available for sale.
     #[command(alias = "b", display_order = 2)]
     Buy { items: Vec<String> },
 
+    /// Trade an inventory item for items on sale, at the pawnshop at home
+    /// or with a travelling merchant. The better the bounty board
+    /// reputation, the better the exchange rate.
+    Trade { give: String, receive: String },
+
     /// Uses an item from the inventory.
     #[command(alias = "u", display_order = 3)]
     Use { items: Vec<String> },
@@ -52,10 +106,16 @@ pub enum Command {
         /// Reset data files, losing cross-hero progress.
         #[arg(long)]
         hard: bool,
+
+        /// Start a new game+ instead, keeping the hero in a harder world.
+        /// Unlocked by defeating gorthaur.
+        #[arg(long, conflicts_with = "hard")]
+        plus: bool,
     },
 
     /// Change the character class.
-    /// If name is omitted lists the available character classes.
+    /// If name is omitted lists the available character classes, and the
+    /// advanced ones unlocked by mastering them.
     Class { name: Option<String> },
 
     /// Prints the hero's current location
@@ -66,6 +126,15 @@ pub enum Command {
     #[command(alias = "a")]
     Attack,
 
+    /// Brace for the enemy's next blow, taking half the damage and
+    /// recovering some mp instead of attacking
+    #[command(alias = "d")]
+    Defend,
+
+    /// Estimate the chance of beating the enemy and the expected reward,
+    /// out of a few hundred simulated battles
+    Scout,
+
     /// Attempt to flee from the enemy
     Flee,
 
@@ -75,12 +144,16 @@ pub enum Command {
     /// List available skills
     Skills,
 
-    /// Learn a skill
+    /// Learn a skill, or upgrade an already learned one to its next rank
     Learn {
         #[arg(required = true)]
         skill_name: String,
     },
 
+    /// Unlearn all the skills at home, for a fee, getting back the skill
+    /// points spent on them.
+    Respec,
+
     /// Use a skill
     UseSkill {
         #[arg(required = true)]
@@ -93,15 +166,165 @@ pub enum Command {
         amount: i32,
     },
 
-    /// Ask the witch to brew a potion
-    Brew,
+    /// Ask the witch to brew a potion or elixir from the gathered
+    /// materials. If the recipe is omitted lists the recipes.
+    Brew { recipe: Option<String> },
+
+    /// Give the hero a new name. Only allowed at home.
+    Rename {
+        #[arg(required = true)]
+        name: String,
+    },
+
+    /// Choose the title shown next to the hero, or none to hide it.
+    /// If the name is omitted lists the titles, earned or not.
+    Title { name: Option<String> },
+
+    /// List the legendary artifacts, found or not.
+    Artifacts,
+
+    /// List the directories visited by the heroes, with how many battles
+    /// and deaths happened there.
+    Map {
+        /// Rank the most traveled and most deadly places instead.
+        #[arg(long)]
+        heat: bool,
+    },
+
+    /// Repair the hero's sword and shield, at home or with a blacksmith met
+    /// on the road. Costs gold for each missing durability point, more for
+    /// higher level pieces.
+    Repair,
 
     /// Listen to the ghostly maiden's story
     Listen,
 
+    /// Show the lore heard from the ghostly maiden, by topic.
+    Codex,
+
+    /// Walk random unexplored subdirectories of the current location,
+    /// fighting the enemies, searching for chests and meeting the
+    /// characters found along the way.
+    Explore {
+        /// How many directories to walk at most.
+        #[arg(long, default_value = "10")]
+        turns: i32,
+    },
+
+    /// Fight the enemies at the current location until reaching the given
+    /// level or gold, or until the hero is too hurt to go on.
+    Grind {
+        /// Stop when the hero reaches this level.
+        #[arg(long)]
+        until_level: Option<i32>,
+
+        /// Stop when the hero has this much gold.
+        #[arg(long)]
+        gold: Option<i32>,
+    },
+
     /// Potentially initiates a battle in the hero's current location.
     Battle,
 
+    /// Perform a costly ritual to keep enemies away from the current directory forever.
+    Banish,
+
+    /// Hire a mercenary to fight alongside the hero, for an upfront fee
+    /// plus a cut of the gold of each battle. Only allowed at home.
+    Hire,
+
+    /// Tell the story of the heroes' adventures so far.
+    Story,
+
+    /// Explain the game mechanics of the given topic: combat, death, rings,
+    /// classes or quests. If the topic is omitted lists the topics.
+    #[command(name = "help-topics")]
+    HelpTopics { topic: Option<String> },
+
+    /// Show lifetime statistics and the bestiary, or export them along
+    /// with the journal for external analysis.
+    Stats {
+        /// File to write the export to.
+        #[arg(long)]
+        export: Option<String>,
+
+        /// Export format: json or csv.
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+
+    /// Keep the game loaded and accept JSON requests on a local socket,
+    /// for prompt widgets and other tools to query and play the game.
+    Daemon,
+
+    /// Buy an insurance policy that returns half of the gold and the given
+    /// item type after death. Costs a premium after every battle won.
+    /// Only allowed at home.
+    Insure { item: Option<String> },
+
+    /// Move items and gold between the hero and the stash shared by all heroes.
+    /// If the action is omitted lists the stash contents. Only allowed at home.
+    Stash {
+        #[command(subcommand)]
+        action: Option<StashAction>,
+    },
+
+    /// Manage the roster of independent heroes.
+    /// If the action is omitted lists the heroes.
+    Hero {
+        #[command(subcommand)]
+        action: Option<HeroAction>,
+    },
+
+    /// Manage the heroes traveling together with the main one.
+    /// If the action is omitted lists the party members.
+    Party {
+        #[command(subcommand)]
+        action: Option<PartyAction>,
+    },
+
+    /// Manage the hero's camps away from home.
+    /// If the action is omitted lists the built outposts.
+    Outpost {
+        #[command(subcommand)]
+        action: Option<OutpostAction>,
+    },
+
+    /// Show the hero's guild rank and current guild quest.
+    Guild {
+        #[command(subcommand)]
+        action: Option<GuildAction>,
+    },
+
+    /// Fight the next wave of enemies in the arena at home, adding its payout
+    /// to the pot. Dying loses the pot, moving or cashing out collects it.
+    Arena {
+        #[arg(long)]
+        cash_out: bool,
+    },
+
+    /// Fight back to back every boss defeated before, with little healing
+    /// in between. Only allowed at home, once gorthaur is defeated.
+    BossRush,
+
+    /// Take contracts from the bounty board at home for gold and reputation.
+    /// If the action is omitted lists the bounties.
+    Bounty {
+        #[command(subcommand)]
+        action: Option<BountyAction>,
+    },
+
+    /// Track down an enemy family in the unexplored directories nearby.
+    Hunt {
+        #[arg(required = true)]
+        enemy: String,
+    },
+
+    /// Advance the world without moving the hero or starting battles:
+    /// weather, invasions, respawn timers and git quests.
+    /// Meant to be run periodically, e.g. from cron.
+    Tick,
+
     /// Save the current game
     #[command(display_order = 5)]
     Save,
@@ -110,30 +333,217 @@ pub enum Command {
     #[command(display_order = 6)]
     Load,
 
-    /// Set hardcore mode
+    /// Set what's lost on death: 1 the gold, 2 also the equipment and
+    /// items, 3 the whole hero, who starts over at level 1.
+    /// Prints the current tier if none is given.
+    #[command(display_order = 7)]
+    Hardcore {
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=3))]
+        tier: Option<u8>,
+    },
+
+    /// Set ironman mode, disabling manual save and load.
+    /// Once enabled it can't be disabled for the current hero.
     #[command(display_order = 7)]
-    Hardcore { on: bool },
+    Ironman {
+        #[arg(action = clap::ArgAction::Set)]
+        on: bool,
+    },
+
+    /// Set speedrun mode. Enabling it starts a new run timer, unless a run
+    /// is already going.
+    #[command(display_order = 7)]
+    Speedrun {
+        #[arg(action = clap::ArgAction::Set)]
+        on: bool,
+    },
+
+    /// Print the timeline of the current speedrun.
+    Splits,
+
+    /// Select the mutators for the run, e.g. `rpg mutators tough noshop`.
+    /// Only allowed before the hero gains any experience.
+    /// If no names are given lists the available mutators.
+    Mutators { mutators: Vec<String> },
+
+    /// Start a new seeded run from a challenge code, e.g. 5f3a9c-his:
+    /// a hex seed followed by the modifiers h (hardcore), i (ironman)
+    /// and s (speedrun). Players with the same code race identical worlds.
+    /// The progress kept across deaths carries over to the new run.
+    Challenge {
+        code: Option<String>,
+
+        /// Print the code of the current challenge run.
+        #[arg(long)]
+        current: bool,
+    },
 
 
     #[command(hide = true)]
     Idkfa { level: i32 },
 }
 
+#[derive(Subcommand)]
+pub enum OutpostAction {
+    /// Build an outpost at the current location. It heals the hero on arrival,
+    /// sells basic supplies and becomes the respawn point.
+    Build,
+}
+
+#[derive(Subcommand)]
+pub enum StashAction {
+    /// Put items and gold from the hero in the stash.
+    Deposit {
+        items: Vec<String>,
+
+        #[arg(long, default_value_t = 0)]
+        gold: i32,
+    },
+
+    /// Take items and gold from the stash.
+    Withdraw {
+        items: Vec<String>,
+
+        #[arg(long, default_value_t = 0)]
+        gold: i32,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum HeroAction {
+    /// Create a new hero and make it the active one.
+    New {
+        name: String,
+
+        #[arg(long, default_value = "warrior")]
+        class: String,
+    },
+
+    /// List the heroes in the roster, marking the active one.
+    List,
+
+    /// Make the hero with the given name the active one.
+    Switch { name: String },
+}
+
+#[derive(Subcommand)]
+pub enum PartyAction {
+    /// Create a new level 1 hero of the given class to join the party.
+    /// Only allowed at home.
+    Recruit { class: String },
+
+    /// Send away the party member of the given class.
+    Dismiss { class: String },
+}
+
+#[derive(Subcommand)]
+pub enum BountyAction {
+    /// List the contracts offered at home and the ones taken.
+    List,
+
+    /// Take the offered contract with the given number.
+    /// Only allowed at home.
+    Accept { number: usize },
+
+    /// Collect the rewards of the fulfilled contracts.
+    /// Only allowed at home.
+    TurnIn,
+}
+
+#[derive(Subcommand)]
+pub enum GuildAction {
+    /// Join one of the guilds: warriors, mages or thieves.
+    /// Only allowed at home.
+    Join { name: String },
+}
+
 pub fn run(cmd: Option<Command>, game: &mut Game) -> Result<bool> {
+    if let Some(challenge) = &mut game.challenge {
+        randomizer::seed(challenge.next_seed());
+    }
+
+    terrain::enter(&game.location);
+    game.update_load();
+
+    // pwd output is read by the shell, so it's kept free of announcements,
+    // and tick advances the world on its own terms
+    let announce = !matches!(cmd, Some(Command::PrintWorkDir | Command::Tick));
+    if announce {
+        enemy::update_invasion(game);
+        if let Some(invasion) = &game.invasion {
+            log::invasion(invasion);
+        }
+        if weather::update(game) {
+            log::weather(game.weather, true);
+        }
+        game.offline_progress();
+        game.fix_bugs();
+        game.check_commits();
+    }
+
     let mut save = true;
-    match cmd.unwrap_or(Command::Stat { items: vec![] }) {
-        Command::Stat { items } => stat(game, &items)?,
+    match cmd.unwrap_or(Command::Stat {
+        items: vec![],
+        detail: false,
+    }) {
+        Command::Stat { items, detail } => stat(game, &items, detail)?,
         Command::ChangeDir {
             destination,
             force,
         } => change_dir(game, &destination, force)?,
         Command::Inspect => game.inspect(),
+        Command::Disarm => game.disarm()?,
+        Command::Forage => game.forage()?,
+        Command::Mine => game.mine()?,
+        Command::Socket { item, gem } => game.socket(
+            &Key::carried(&item, game)?,
+            item::material::Material::from(&gem)?,
+        )?,
+        Command::Cook { ingredients } => {
+            if ingredients.is_empty() {
+                log::cookbook(game);
+            } else {
+                cooking::cook(game, &ingredients)?;
+            }
+        }
         Command::Class { name } => class(game, &name)?,
         Command::Battle => battle(game)?,
+        Command::Explore { turns } => explore(game, turns)?,
+        Command::Grind { until_level, gold } => grind(game, until_level, gold)?,
+        Command::Hunt { enemy } => hunt(game, &enemy)?,
+        Command::Banish => game.banish()?,
+        Command::Hire => game.hire_mercenary()?,
+        Command::Story => log::story(&game.journal.story()),
+        Command::HelpTopics { topic } => help_topics(topic)?,
+        Command::Stats { export, format } => stats(game, export, &format)?,
+        #[cfg(unix)]
+        Command::Daemon => daemon::serve(game)?,
+        #[cfg(not(unix))]
+        Command::Daemon => bail!("The daemon is only available on unix systems."),
+        Command::Tick => game.tick(),
+        Command::Insure { item } => {
+            let key = item.map(|item| Key::carried(&item, game)).transpose()?;
+            game.insure(key)?
+        }
+        Command::Stash { action } => stash(game, action)?,
+        Command::Hero { action } => hero(game, action)?,
+        Command::Party { action } => party(game, action)?,
+        Command::Outpost { action } => outpost(game, action)?,
+        Command::Guild { action } => guild(game, action)?,
+        Command::Bounty { action } => bounty(game, action)?,
+        Command::Arena { cash_out } => arena(game, cash_out)?,
+        Command::BossRush => {
+            let result = game.boss_rush();
+            handle_death(game, result)?
+        }
         Command::PrintWorkDir => println!("{}", game.location.path_string()),
+        Command::Reset { plus: true, .. } => game.new_game_plus()?,
         Command::Reset { .. } => game.reset(),
         Command::Buy { items } => shop(game, &items)?,
         Command::Use { items } => use_item(game, &items)?,
+        Command::Trade { give, receive } => {
+            item::shop::trade(game, &Key::carried(&give, game)?, &Key::from(&receive)?)?
+        }
         Command::Todo => {
             log::quest_list(game.quests.list());
         }
@@ -142,19 +552,44 @@ pub fn run(cmd: Option<Command>, game: &mut Game) -> Result<bool> {
             load_game(game)?;
             save = false;
         }
-        Command::Hardcore { on } => set_hardcore(game, on)?,
+        Command::Hardcore { tier } => set_hardcore(game, tier),
+        Command::Ironman { on } => set_ironman(game, on)?,
+        Command::Speedrun { on } => set_speedrun(game, on)?,
+        Command::Splits => splits(game)?,
+        Command::Challenge { code, current } => challenge(game, code, current)?,
+        Command::Mutators { mutators: names } => mutators(game, &names)?,
         Command::Attack => attack(game)?,
+        Command::Defend => defend(game)?,
+        Command::Scout => scout(game)?,
         Command::Flee => flee(game)?,
         Command::Bribe => bribe(game)?,
         Command::Skills => skills(game)?,
         Command::Learn { skill_name } => learn(game, &skill_name)?,
         Command::UseSkill { skill_name } => use_skill(game, &skill_name)?,
         Command::Bet { amount } => bet(game, amount)?,
-        Command::Brew => brew(game)?,
+        Command::Brew { recipe } => match recipe {
+            Some(recipe) => game.brew(&recipe)?,
+            None => log::recipes(game),
+        },
+        Command::Repair => game.repair()?,
+        Command::Respec => game.respec()?,
+        Command::Rename { name } => rename(game, name)?,
+        Command::Title { name } => match name {
+            Some(name) => title::choose(game, &name)?,
+            None => log::titles(game),
+        },
+        Command::Artifacts => log::artifacts(game),
+        Command::Map { heat } => log::map(&game.stats, heat),
         Command::Listen => listen(game)?,
+        Command::Codex => log::codex(&game.codex),
         Command::Idkfa { level } => debug_command(game, level),
     };
 
+    if announce {
+        title::award(game);
+        artifact::collect(game);
+        hint::show(game);
+    }
     Ok(save)
 }
 
@@ -177,27 +612,13 @@ fn bet(game: &mut Game, amount: i32) -> Result<()> {
     Ok(())
 }
 
-fn brew(game: &mut Game) -> Result<()> {
-    if let Some(character::npc::Encounter::Witch) = &game.in_encounter {
-        println!("The witch brews a bubbling potion and hands it to you.");
-        let potion = crate::item::Potion::new(game.player.level);
-        game.add_item(Box::new(potion));
-        game.in_encounter = None;
-    } else {
-        bail!("There is no witch here to brew a potion.");
-    }
-    Ok(())
-}
-
 fn listen(game: &mut Game) -> Result<()> {
     if let Some(character::npc::Encounter::GhostlyMaiden) = &game.in_encounter {
-        let lore = match crate::randomizer::random().range(3) {
-            0 => "She whispers of a hidden treasure in a nearby cave.",
-            1 => "She speaks of a great evil that slumbers deep within the earth.",
-            2 => "She warns of a powerful dragon that guards the mountain pass.",
-            _ => unreachable!(),
-        };
+        let (lore, secret) = game.codex.listen();
         println!("The ghostly maiden's voice echoes in your mind: '{}'", lore);
+        if let Some(secret) = secret {
+            log::secret(secret);
+        }
         game.in_encounter = None;
     } else {
         bail!("There is no one to listen to here.");
@@ -205,71 +626,91 @@ fn listen(game: &mut Game) -> Result<()> {
     Ok(())
 }
 
+/// The name of the hero's class skill closest to the given one. Unknown
+/// names are passed on as is, for the skill actions to report.
+fn resolve_skill(game: &Game, skill_name: &str) -> Result<String> {
+    let skills = &game.player.class.skills;
+    let found = fuzzy::resolve(skill_name, skills.iter().map(|s| s.name.as_str()))?;
+    Ok(found.unwrap_or(skill_name).to_string())
+}
+
+/// The name of the player class closest to the given one.
+fn resolve_class(class_name: &str) -> Result<String> {
+    let names = character::class::Class::names(character::class::Category::Player);
+    match fuzzy::resolve(class_name, names.iter().map(String::as_str))? {
+        Some(name) => Ok(name.to_string()),
+        None => bail!(GameError::UnknownClass),
+    }
+}
+
 fn skills(game: &mut Game) -> Result<()> {
     log::skill_list(&game.player);
     Ok(())
 }
 
 fn learn(game: &mut Game, skill_name: &str) -> Result<()> {
-    game.player.learn_skill(skill_name)?;
-    println!("Skill '{}' learned.", skill_name);
+    let skill_name = &resolve_skill(game, skill_name)?;
+    let rank = game.player.learn_skill(skill_name)?;
+    if rank > 1 {
+        println!("Skill '{}' upgraded to rank {}.", skill_name, rank);
+    } else {
+        println!("Skill '{}' learned.", skill_name);
+    }
     Ok(())
 }
 
 fn use_skill(game: &mut Game, skill_name: &str) -> Result<()> {
-    if let Err(err) = game.use_skill(skill_name) {
-        if err.downcast_ref::<character::Dead>().is_some() {
-            game.reset();
-            bail!("");
-        }
-        return Err(err);
-    }
-    Ok(())
+    let skill_name = resolve_skill(game, skill_name)?;
+    let result = game.use_skill(&skill_name).and_then(|_| game.resume_move());
+    handle_death(game, result)
 }
 
 
 fn attack(game: &mut Game) -> Result<()> {
-    if let Err(err) = game.battle_round() {
-        if err.downcast_ref::<character::Dead>().is_some() {
-            game.reset();
-            bail!("");
-        }
-        return Err(err);
-    }
+    let result = game.battle_round().and_then(|_| game.resume_move());
+    handle_death(game, result)
+}
+
+fn defend(game: &mut Game) -> Result<()> {
+    let result = game.defend().and_then(|_| game.resume_move());
+    handle_death(game, result)
+}
+
+fn scout(game: &Game) -> Result<()> {
+    let Some(enemy) = &game.in_combat else {
+        bail!(GameError::NotInCombat);
+    };
+    log::forecast(enemy, &simulation::forecast(game, enemy));
     Ok(())
 }
 
 fn flee(game: &mut Game) -> Result<()> {
-    if let Err(err) = game.player_flee() {
-        if err.downcast_ref::<character::Dead>().is_some() {
-            game.reset();
-            bail!("");
-        }
-        return Err(err);
-    }
-    Ok(())
+    let result = game.player_flee();
+    handle_death(game, result)
 }
 
 fn bribe(game: &mut Game) -> Result<()> {
-    if let Err(err) = game.player_bribe() {
-        if err.downcast_ref::<character::Dead>().is_some() {
-            game.reset();
-            bail!("");
-        }
-        return Err(err);
-    }
-    Ok(())
+    let result = game.player_bribe().and_then(|_| game.resume_move());
+    handle_death(game, result)
 }
 
-fn save_game(game: &Game) -> Result<()> {
+fn save_game(game: &mut Game) -> Result<()> {
+    if game.ironman {
+        bail!("Can't save in ironman mode.");
+    }
     crate::datafile::save(game)?;
     println!("Game saved.");
     Ok(())
 }
 
 fn load_game(game: &mut Game) -> Result<()> {
+    if game.ironman {
+        bail!("Can't load in ironman mode.");
+    }
     if let Some(loaded_game) = crate::datafile::load()? {
         *game = loaded_game;
+        // the time since the save was spent playing, not away
+        game.played_at = config::now();
         println!("Game loaded.");
     } else {
         bail!("No saved game found.");
@@ -277,20 +718,104 @@ fn load_game(game: &mut Game) -> Result<()> {
     Ok(())
 }
 
-fn set_hardcore(game: &mut Game, on: bool) -> Result<()> {
-    game.hardcore = on;
+fn set_hardcore(game: &mut Game, tier: Option<u8>) {
+    if let Some(hardcore) = tier.and_then(Hardcore::from_tier) {
+        game.hardcore = hardcore;
+    }
+    println!(
+        "Hardcore tier {}: {}.",
+        game.hardcore.tier(),
+        game.hardcore.describe()
+    );
+}
+
+fn set_ironman(game: &mut Game, on: bool) -> Result<()> {
+    if game.ironman && !on {
+        bail!("Ironman mode can't be disabled.");
+    }
+    game.ironman = on;
     if on {
-        println!("Hardcore mode enabled.");
+        println!("Ironman mode enabled, there's no going back.");
+    }
+    Ok(())
+}
+
+fn set_speedrun(game: &mut Game, on: bool) -> Result<()> {
+    if on {
+        if game.speedrun.is_some() {
+            bail!("A speedrun is already going, disable speedrun mode first to restart it.");
+        }
+        game.speedrun = Some(Speedrun::start());
+        println!("Speedrun mode enabled, the clock is ticking.");
     } else {
-        println!("Hardcore mode disabled.");
+        game.speedrun = None;
+        println!("Speedrun mode disabled.");
     }
     Ok(())
 }
 
+fn mutators(game: &mut Game, names: &[String]) -> Result<()> {
+    if names.is_empty() {
+        log::mutators(&game.mutators);
+        return Ok(());
+    }
+    if game.player.level > 1 || game.player.xp > 0 {
+        bail!("Mutators can only be selected at the start of a run.");
+    }
+
+    let mut enabled = Vec::new();
+    for name in names {
+        enabled.push(Mutator::from(name)?);
+    }
+    game.mutators = Mutators::new(enabled);
+    log::mutators(&game.mutators);
+    Ok(())
+}
+
+fn challenge(game: &mut Game, code: Option<String>, current: bool) -> Result<()> {
+    let code = match code {
+        Some(code) if !current => code,
+        _ => {
+            if let Some(challenge) = &game.challenge {
+                println!("{}", challenge.code());
+                return Ok(());
+            }
+            bail!("No challenge run in progress.");
+        }
+    };
+
+    if game.ironman {
+        bail!("Can't start a challenge in ironman mode.");
+    }
+    let challenge = Challenge::parse(&code)?;
+    *game = game.next_run();
+    game.hardcore = if challenge.hardcore {
+        Hardcore::Wipe
+    } else {
+        Hardcore::Equipment
+    };
+    game.ironman = challenge.ironman;
+    if challenge.speedrun {
+        game.speedrun = Some(Speedrun::start());
+    }
+    println!("Challenge {} started, good luck.", challenge.code());
+    game.challenge = Some(challenge);
+    Ok(())
+}
+
+fn splits(game: &Game) -> Result<()> {
+    if let Some(run) = &game.speedrun {
+        log::splits(run);
+        Ok(())
+    } else {
+        bail!("Speedrun mode is not enabled.")
+    }
+}
+
 /// Attempt to move the hero to the supplied location, possibly engaging
 /// in combat along the way.
 fn change_dir(game: &mut Game, dest: &str, force: bool) -> Result<()> {
-    let dest = Location::from(dest)?;
+    let dest = Location::from(dest).map_err(|_| GameError::InvalidDestination(dest.to_string()))?;
     let result = if force {
         // When change is force, skip enemies along the way
         // but still apply side-effects at destination
@@ -299,22 +824,14 @@ fn change_dir(game: &mut Game, dest: &str, force: bool) -> Result<()> {
         game.go_to(&dest)
     };
 
-    if let Err(err) = result {
-        if err.downcast_ref::<character::Dead>().is_some() {
-            game.reset();
-            bail!("");
-        }
-        return Err(err.into());
-    }
-
-    Ok(())
+    handle_death(game, result)
 }
 
 /// Potentially run a battle at the current location, independently from
 /// the hero's movement.
 fn battle(game: &mut Game) -> Result<()> {
     if game.in_combat.is_some() {
-        bail!("Already in combat.");
+        bail!(GameError::InCombat("look for another enemy"));
     }
     if let Some(enemy) = enemy::spawn(game) {
         log::enemy_appears(&enemy, &game.location);
@@ -325,33 +842,393 @@ fn battle(game: &mut Game) -> Result<()> {
     Ok(())
 }
 
+/// Below this hp percentage, the hero stops exploring or grinding.
+const SAFE_HP: i32 = 30;
+
+/// How many times grinding looks for an enemy before giving up.
+const GRIND_MAX_ATTEMPTS: i32 = 100;
+
+fn explore(game: &mut Game, turns: i32) -> Result<()> {
+    if game.in_combat.is_some() {
+        bail!(GameError::InCombat("explore"));
+    }
+    let start = game.location.clone();
+    let progress = Progress::of(game);
+
+    let mut directories = 0;
+    let mut result = Ok(());
+    for _ in 0..turns {
+        if too_hurt(game) {
+            println!("The hero is too hurt to keep exploring.");
+            break;
+        }
+        let Some(next) = game.next_unexplored(&start) else {
+            println!("There's nothing left to explore here.");
+            break;
+        };
+        result = explore_step(game, &next);
+        if result.is_err() {
+            break;
+        }
+        directories += 1;
+    }
+
+    log::exploration(directories, &progress.since(game));
+    handle_death(game, result)
+}
+
+fn grind(game: &mut Game, until_level: Option<i32>, gold: Option<i32>) -> Result<()> {
+    if until_level.is_none() && gold.is_none() {
+        bail!("Set a level or gold goal.");
+    }
+    if game.in_combat.is_some() {
+        bail!(GameError::InCombat("grind"));
+    }
+    if game.is_safe() {
+        bail!("There are no enemies to fight here.");
+    }
+    let progress = Progress::of(game);
+
+    let mut result = Ok(());
+    for _ in 0..GRIND_MAX_ATTEMPTS {
+        let level_reached = until_level.is_some_and(|level| game.player.level >= level);
+        let gold_reached = gold.is_some_and(|gold| game.gold >= gold);
+        if level_reached || gold_reached {
+            println!("Goal reached.");
+            break;
+        }
+        if too_hurt(game) {
+            println!("The hero is too hurt to keep fighting.");
+            break;
+        }
+        if enemy::is_cleared(game, &game.location) {
+            println!("There are no enemies left here.");
+            break;
+        }
+
+        if let Some(enemy) = enemy::spawn(game) {
+            game.in_combat = Some(enemy);
+            result = fight(game);
+            if result.is_err() {
+                break;
+            }
+        }
+    }
+
+    log::grind(&progress.since(game));
+    handle_death(game, result)
+}
+
+/// Whether the hero should stop an automated activity to recover.
+fn too_hurt(game: &Game) -> bool {
+    game.player.current_hp * 100 < game.player.max_hp() * SAFE_HP
+}
+
+/// Battle until the enemy or the hero falls. Below the auto defend hp the
+/// hero braces every other round, so the battle still moves on.
+fn fight(game: &mut Game) -> Result<()> {
+    let threshold = config::Config::get().auto_defend_hp;
+    let mut defended = false;
+    while game.in_combat.is_some() {
+        let hurt = game.player.current_hp * 100 < game.player.max_hp() * threshold;
+        if hurt && !defended {
+            game.defend()?;
+            defended = true;
+        } else {
+            game.battle_round()?;
+            defended = false;
+        }
+    }
+    game.resume_move()?;
+    Ok(())
+}
+
+/// Start over if the hero died, the error is kept to report it.
+fn handle_death(game: &mut Game, result: Result<(), impl Into<anyhow::Error>>) -> Result<()> {
+    let result = result.map_err(Into::into);
+    if let Err(err) = &result {
+        if error::is_dead(err) {
+            game.reset();
+        }
+    }
+    result
+}
+
+/// Move to the given location, resolving whatever is found there.
+fn explore_step(game: &mut Game, next: &Location) -> Result<()> {
+    game.go_to(next)?;
+    fight(game)?;
+    match game.in_encounter {
+        // the explorer brews a potion if it has the herbs for it
+        Some(character::npc::Encounter::Witch) => {
+            game.brew("potion").ok();
+            game.in_encounter = None;
+        }
+        Some(character::npc::Encounter::GhostlyMaiden) => listen(game)?,
+        // the explorer doesn't gamble or trade, and only repairs if it can afford it
+        Some(character::npc::Encounter::Gambler | character::npc::Encounter::Merchant) => {
+            game.in_encounter = None
+        }
+        Some(character::npc::Encounter::Blacksmith) => {
+            game.repair().ok();
+            game.in_encounter = None;
+        }
+        None => {}
+    }
+    game.inspect();
+    Ok(())
+}
+
+/// The stash is saved right away, even if moving some of the items fails,
+/// since the game is saved in that case too.
+fn stash(game: &mut Game, action: Option<StashAction>) -> Result<()> {
+    let mut stash = crate::datafile::load_stash()?;
+    let result = match action {
+        Some(StashAction::Deposit { items, gold }) => {
+            let keys = parse_keys(game, &items)?;
+            stash.deposit(game, &keys, gold)
+        }
+        Some(StashAction::Withdraw { items, gold }) => {
+            let keys = parse_keys(game, &items)?;
+            stash.withdraw(game, &keys, gold)
+        }
+        None => {
+            log::stash(&stash);
+            return Ok(());
+        }
+    };
+    crate::datafile::save_stash(&stash)?;
+    result?;
+    Ok(())
+}
+
+fn parse_keys(game: &Game, items: &[String]) -> Result<Vec<Key>> {
+    let keys = items
+        .iter()
+        .map(|item| Key::carried(item, game))
+        .collect::<Result<_, _>>()?;
+    Ok(keys)
+}
+
+/// Heroes other than the active one are kept in the datafile roster.
+/// Changing the active hero moves the current one to the roster first.
+fn hero(game: &mut Game, action: Option<HeroAction>) -> Result<()> {
+    match action {
+        Some(HeroAction::New { name, class }) => {
+            check_hero_name(game, &name)?;
+
+            let class = resolve_class(&class)?;
+            game.check_class_unlocked(&class)?;
+
+            let mut new_game = Game::new();
+            new_game.name = name;
+            new_game.stats.mastery = game.stats.mastery.clone();
+            new_game
+                .player
+                .change_class(&class)
+                .map_err(|_| GameError::UnknownClass)?;
+
+            crate::datafile::save_hero(game)?;
+            *game = new_game;
+            log::status(game);
+        }
+        Some(HeroAction::Switch { name }) => {
+            if name == game.name {
+                bail!("{} is already the active hero.", name);
+            }
+            check_hero_file_name(&name)?;
+            if let Some(other) = crate::datafile::load_hero(&name)? {
+                // only drop the roster file once the active hero is safe
+                crate::datafile::save_hero(game)?;
+                crate::datafile::remove_hero(&name)?;
+                *game = other;
+                log::status(game);
+            } else {
+                bail!("There's no hero named {}.", name);
+            }
+        }
+        Some(HeroAction::List) | None => {
+            log::hero_list(game, &crate::datafile::list_heroes());
+        }
+    }
+    Ok(())
+}
+
+/// Hero names are used as roster file names, so reject the ones that
+/// could point outside the roster directory.
+fn check_hero_file_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!("Hero names can only contain letters, numbers, '-' and '_'.");
+    }
+    Ok(())
+}
+
+/// New hero names must also be unique.
+fn check_hero_name(game: &Game, name: &str) -> Result<()> {
+    check_hero_file_name(name)?;
+    if name == game.name || crate::datafile::hero_exists(name) {
+        bail!("There's already a hero named {}.", name);
+    }
+    Ok(())
+}
+
+/// Give the active hero a new name. Only allowed at home.
+fn rename(game: &mut Game, name: String) -> Result<()> {
+    if !game.location.is_home() {
+        bail!(GameError::NotAtHome("rename a hero"));
+    }
+    check_hero_name(game, &name)?;
+    game.name = name;
+    log::status(game);
+    Ok(())
+}
+
+fn party(game: &mut Game, action: Option<PartyAction>) -> Result<()> {
+    match action {
+        Some(PartyAction::Recruit { class }) => game.recruit(&class.to_lowercase())?,
+        Some(PartyAction::Dismiss { class }) => game.dismiss(&class.to_lowercase())?,
+        None => log::party(game),
+    }
+    Ok(())
+}
+
+fn outpost(game: &mut Game, action: Option<OutpostAction>) -> Result<()> {
+    match action {
+        Some(OutpostAction::Build) => game.build_outpost()?,
+        None => log::outpost_list(&game.outposts),
+    }
+    Ok(())
+}
+
+fn arena(game: &mut Game, cash_out: bool) -> Result<()> {
+    if cash_out {
+        game.arena_cash_out()?;
+        Ok(())
+    } else {
+        let result = game.arena_wave();
+        handle_death(game, result)
+    }
+}
+
+fn bounty(game: &mut Game, action: Option<BountyAction>) -> Result<()> {
+    match action {
+        Some(BountyAction::Accept { number }) => game.accept_bounty(number)?,
+        Some(BountyAction::TurnIn) => game.turn_in_bounties()?,
+        Some(BountyAction::List) | None => {
+            if game.location.is_home() {
+                game.bounties.refresh(game.player.level);
+            }
+            log::bounties(&game.bounties);
+        }
+    }
+    Ok(())
+}
+
+fn guild(game: &mut Game, action: Option<GuildAction>) -> Result<()> {
+    match action {
+        Some(GuildAction::Join { name }) => game.join_guild(crate::guild::Guild::from(&name)?)?,
+        None => {
+            if let Some(membership) = &game.guild {
+                log::guild(membership);
+            } else {
+                bail!("Not a member of any guild.")
+            }
+        }
+    }
+    Ok(())
+}
+
+fn stats(game: &mut Game, export: Option<String>, format: &str) -> Result<()> {
+    game.stats.level_reached(game.player.level);
+    let Some(file) = export else {
+        log::stats(&game.stats);
+        return Ok(());
+    };
+
+    let data = match format {
+        "json" => stats::to_json(&game.stats, &game.journal),
+        "csv" => stats::to_csv(&game.stats, &game.journal),
+        _ => bail!("Unknown export format, expected json or csv."),
+    };
+    std::fs::write(&file, data)?;
+    log::exported(&file);
+    Ok(())
+}
+
+/// Look for the closest place where the given enemy family can be found
+/// and make it more likely to appear there.
+fn hunt(game: &mut Game, family: &str) -> Result<()> {
+    let (hunt, steps) = enemy::hunt(game, family)?;
+    log::hunt(&hunt, steps);
+    game.hunt = Some(hunt);
+    Ok(())
+}
+
 /// Set the class for the player character
 fn class(game: &mut Game, class_name: &Option<String>) -> Result<()> {
     if !game.location.is_home() {
-        bail!("Class change is only allowed at home.")
+        bail!(GameError::NotAtHome("change class"))
     }
 
     if let Some(class_name) = class_name {
-        let class_name = class_name.to_lowercase();
+        let class_name = resolve_class(class_name)?;
+        game.check_class_unlocked(&class_name)?;
         game.player
             .change_class(&class_name)
-            .map_err(|_| anyhow!("Unknown class name."))
+            .map_err(|_| anyhow!(GameError::UnknownClass))
     } else {
-        let player_classes: Vec<String> =
-            character::class::Class::names(character::class::Category::Player)
-                .iter()
-                .cloned()
-                .collect();
-        println!("Options: {}", player_classes.join(", "));
+        let mut base = Vec::new();
+        let mut advanced = Vec::new();
+        for name in character::class::Class::names(character::class::Category::Player) {
+            let class = character::class::Class::player_by_name(&name).unwrap();
+            if let Some(required) = &class.requires {
+                advanced.push(class_unlock(game, &name, required));
+            } else {
+                base.push(name);
+            }
+        }
+        base.sort();
+        advanced.sort();
+        println!("Options: {}", base.join(", "));
+        if !advanced.is_empty() {
+            println!("Advanced: {}", advanced.join(", "));
+        }
         Ok(())
     }
 }
 
+/// Describe an advanced class as unlocked or, if still locked, with the
+/// progress made towards mastering its base class.
+fn class_unlock(game: &Game, name: &str, required: &str) -> String {
+    if game.stats.mastered(required) {
+        return name.to_string();
+    }
+    let mastery = game
+        .stats
+        .mastery
+        .get(required)
+        .cloned()
+        .unwrap_or_default();
+    format!(
+        "{} (master {}: {}/{} levels, {}/{} bosses)",
+        name,
+        required,
+        mastery.levels,
+        stats::MASTERY_LEVELS,
+        mastery.bosses,
+        stats::MASTERY_BOSSES
+    )
+}
+
 /// Buy an item from the shop or list the available items if no item name is provided.
 /// Shopping is only allowed when the player is at the home directory.
 fn shop(game: &mut Game, items: &[String]) -> Result<()> {
     if items.is_empty() {
-        item::shop::list(game)
+        item::shop::list(game)?;
     } else {
         // parse items and break if any is invalid/unknown
         let mut keys = Vec::new();
@@ -359,17 +1236,31 @@ fn shop(game: &mut Game, items: &[String]) -> Result<()> {
             keys.push(Key::from(item)?);
         }
 
-        item::shop::buy(game, &keys)
+        item::shop::buy(game, &keys)?;
     }
+    Ok(())
 }
 
-fn stat(game: &mut Game, items: &[String]) -> Result<()> {
-    if items.is_empty() {
+fn help_topics(topic: Option<String>) -> Result<()> {
+    if let Some(topic) = topic {
+        log::manual(&manual::Topic::from(&topic)?.explain());
+    } else {
+        let topics: Vec<_> = manual::Topic::iter().map(|t| t.name()).collect();
+        println!("{}", topics.join(", "));
+    }
+    Ok(())
+}
+
+fn stat(game: &mut Game, items: &[String], detail: bool) -> Result<()> {
+    if detail {
+        log::stat_detail(&game.player.stat_breakdown());
+        Ok(())
+    } else if items.is_empty() {
         log::status(game);
         Ok(())
     } else {
         for item_name in items {
-            let item_name = Key::from(item_name)?;
+            let item_name = Key::carried(item_name, game)?;
             let (display, description) = game.describe(item_name)?;
             println!("{}: {}", display, description);
         }
@@ -383,7 +1274,7 @@ fn use_item(game: &mut Game, items: &[String]) -> Result<()> {
         println!("{}", log::format_inventory(game));
     } else {
         for item_name in items {
-            let item_name = Key::from(item_name)?;
+            let item_name = Key::carried(item_name, game)?;
             game.use_item(item_name)?
         }
     }
@@ -402,22 +1293,137 @@ fn debug_command(game: &mut Game, level: i32) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn ironman_disables_save() {
+        let mut game = Game::new();
+        assert!(run(Some(Command::Ironman { on: true }), &mut game).is_ok());
+        assert!(run(Some(Command::Save), &mut game).is_err());
+        assert!(run(Some(Command::Load), &mut game).is_err());
+        assert!(run(Some(Command::Ironman { on: false }), &mut game).is_err());
+
+        let cmd = Command::Challenge {
+            code: Some(String::from("ff")),
+            current: false,
+        };
+        assert!(run(Some(cmd), &mut game).is_err());
+        assert!(game.ironman);
+    }
+
+    #[test]
+    fn challenge_keeps_progress() {
+        let mut game = Game::new();
+        game.gold = 100;
+        game.stats.battles_won = 3;
+        game.bounties.reputation = 2;
+        game.completed = true;
+
+        let cmd = Command::Challenge {
+            code: Some(String::from("ff-i")),
+            current: false,
+        };
+        assert!(run(Some(cmd), &mut game).is_ok());
+        assert_eq!(0, game.gold);
+        assert_eq!(3, game.stats.battles_won);
+        assert_eq!(2, game.bounties.reputation);
+        assert!(game.completed && game.ironman);
+    }
+
+    #[test]
+    fn grind_goal() {
+        let mut game = Game::new();
+        assert!(grind(&mut game, Some(2), None).is_err());
+
+        game.location = Location::from(std::env::temp_dir().to_str().unwrap()).unwrap();
+        assert!(grind(&mut game, None, None).is_err());
+
+        // the goal is already met, no battles needed
+        game.gold = 100;
+        assert!(grind(&mut game, None, Some(100)).is_ok());
+        assert_eq!(0, game.stats.battles_won);
+    }
+
+    #[test]
+    fn hero_new_invalid_name() {
+        let mut game = Game::new();
+        let cmd = Command::Hero {
+            action: Some(HeroAction::New {
+                name: "../hero".to_string(),
+                class: "warrior".to_string(),
+            }),
+        };
+        assert!(run(Some(cmd), &mut game).is_err());
+
+        let cmd = Command::Hero {
+            action: Some(HeroAction::New {
+                name: game.name.clone(),
+                class: "warrior".to_string(),
+            }),
+        };
+        assert!(run(Some(cmd), &mut game).is_err());
+    }
+
+    #[test]
+    fn speedrun_restart() {
+        let mut game = Game::new();
+        run(Some(Command::Speedrun { on: true }), &mut game).unwrap();
+        assert!(run(Some(Command::Speedrun { on: true }), &mut game).is_err());
+
+        run(Some(Command::Speedrun { on: false }), &mut game).unwrap();
+        assert!(game.speedrun.is_none());
+        run(Some(Command::Speedrun { on: true }), &mut game).unwrap();
+        assert!(game.speedrun.is_some());
+    }
+
+    #[test]
+    fn hero_switch_invalid_name() {
+        let mut game = Game::new();
+        for name in ["../foo", "", ".."] {
+            let cmd = Command::Hero {
+                action: Some(HeroAction::Switch {
+                    name: name.to_string(),
+                }),
+            };
+            let err = run(Some(cmd), &mut game).unwrap_err();
+            assert!(err.to_string().starts_with("Hero names can only"));
+        }
+    }
+
+    #[test]
+    fn rename() {
+        let mut game = Game::new();
+        let cmd = Command::Rename {
+            name: "aria".to_string(),
+        };
+        run(Some(cmd), &mut game).unwrap();
+        assert_eq!("aria", game.name);
+
+        let cmd = Command::Rename {
+            name: "../aria".to_string(),
+        };
+        assert!(run(Some(cmd), &mut game).is_err());
+
+        game.location = crate::location::tests::location_from("~/tavern");
+        let cmd = Command::Rename {
+            name: "brin".to_string(),
+        };
+        assert!(run(Some(cmd), &mut game).is_err());
+    }
+
     #[test]
     fn change_dir_battle() {
         let mut game = Game::new();
         let cmd = Command::ChangeDir {
             destination: "~/..".to_string(),
-            run: false,
-            bribe: false,
             force: false,
         };
 
-        // increase level to ensure win
-        for _ in 0..5 {
+        // increase level to ensure win, the parent of home may be an
+        // elite zone
+        for _ in 0..terrain::ELITE_LEVEL_FLOOR + 5 {
             game.player.add_experience(game.player.xp_for_next());
         }
 
-        let result = run(Some(cmd), &mut game);
+        let result = run(Some(cmd), &mut game).and_then(|_| fight(&mut game));
 
         assert!(result.is_ok());
         assert!(game.player.xp > 0);
@@ -429,8 +1435,6 @@ mod tests {
         let mut game = Game::new();
         let cmd = Command::ChangeDir {
             destination: "~/..".to_string(),
-            run: false,
-            bribe: false,
             force: false,
         };
 
@@ -444,7 +1448,7 @@ mod tests {
         game.gold = 100;
         game.player.xp = 100;
 
-        let result = run(Some(cmd), &mut game);
+        let result = run(Some(cmd), &mut game).and_then(|_| fight(&mut game));
 
         assert!(result.is_err());
 
@@ -462,8 +1466,6 @@ mod tests {
         // using force prevents battle but effects should apply anyway
         let cmd = Command::ChangeDir {
             destination: "~/..".to_string(),
-            run: false,
-            bribe: false,
             force: true,
         };
 
@@ -498,8 +1500,6 @@ mod tests {
         // force move to a non home location
         let cmd = Command::ChangeDir {
             destination: "~/..".to_string(),
-            run: false,
-            bribe: false,
             force: true,
         };
 
@@ -512,8 +1512,6 @@ mod tests {
         // back home (without forcing)
         let cmd = Command::ChangeDir {
             destination: "~".to_string(),
-            run: false,
-            bribe: false,
             force: false,
         };
 
@@ -532,8 +1530,6 @@ mod tests {
         // force move to a non home location
         let cmd = Command::ChangeDir {
             destination: "~/..".to_string(),
-            run: false,
-            bribe: false,
             force: true,
         };
 
@@ -546,8 +1542,6 @@ mod tests {
         // force back home should restore hp
         let cmd = Command::ChangeDir {
             destination: "~".to_string(),
-            run: false,
-            bribe: false,
             force: true,
         };
 
@@ -565,8 +1559,6 @@ mod tests {
 
         let cmd = Command::ChangeDir {
             destination: "~/..".to_string(),
-            run: false,
-            bribe: false,
             force: false,
         };
 
@@ -574,7 +1566,9 @@ mod tests {
         game.player.current_hp = 1;
 
         game.gold = 100;
-        assert!(run(Some(cmd), &mut game).is_err());
+        assert!(run(Some(cmd), &mut game)
+            .and_then(|_| fight(&mut game))
+            .is_err());
 
         assert_eq!(0, game.gold);
         assert!(!game.tombstones.is_empty());
@@ -582,8 +1576,6 @@ mod tests {
         // force move to the previous dead location
         let cmd = Command::ChangeDir {
             destination: "~/..".to_string(),
-            run: false,
-            bribe: false,
             force: true,
         };
         run(Some(cmd), &mut game).unwrap();
@@ -594,8 +1586,9 @@ mod tests {
         assert!(result.is_ok());
         assert!(game.tombstones.is_empty());
 
-        // includes +200g for visit tombstone quest
-        assert_eq!(300, game.gold);
+        // includes +200g for visit tombstone quest, but not the gold
+        // carried by the hero's ghost
+        assert_eq!(275, game.gold);
     }
 
     #[test]
@@ -634,8 +1627,6 @@ mod tests {
         // not buy if not home
         let cmd = Command::ChangeDir {
             destination: "~/..".to_string(),
-            run: false,
-            bribe: false,
             force: true,
         };
         run(Some(cmd), &mut game).unwrap();
@@ -648,4 +1639,12 @@ mod tests {
         assert!(result.is_err());
         assert!(game.inventory().is_empty());
     }
+
+    /// Attack the enemy met on the way until the battle is over.
+    fn fight(game: &mut Game) -> Result<bool> {
+        while game.in_combat.is_some() {
+            run(Some(Command::Attack), game)?;
+        }
+        Ok(true)
+    }
 }
@@ -0,0 +1,275 @@
+use crate::item::key::Rarity;
+use crate::location::Location;
+use anyhow::{bail, Result};
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_HOUR: u64 = 60 * 60;
+
+/// Highest spawn rate multiplier honored by the spawn rules.
+const MAX_SPAWN_ROLLS: u32 = 10;
+
+static CONFIG: OnceCell<Config> = OnceCell::new();
+
+/// Game settings that can be customized in the config.yaml file of the
+/// game data directory. Missing settings take their default value.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Hours until enemies return to a cleared location.
+    pub enemy_respawn_hours: u64,
+
+    /// Hours until treasure can be found again at a searched location.
+    pub chest_respawn_hours: u64,
+
+    /// Screen reader friendly output, same as the --a11y flag.
+    pub a11y: bool,
+
+    /// Describe battle events with flavor text.
+    pub narration: bool,
+
+    /// Raise a desktop notification on deaths, level ups and other
+    /// milestones, for terminals that support them.
+    pub notifications: bool,
+
+    /// Show the class icons next to character names.
+    pub icons: bool,
+
+    /// Print tips based on how the game is going.
+    pub hints: bool,
+
+    /// Minimum hours between two tips.
+    pub hint_interval_hours: u64,
+
+    /// Use a potion during battle when the hero's hp falls below this
+    /// percentage of its maximum. Zero disables it.
+    pub auto_potion_hp: i32,
+
+    /// In the battles fought while exploring or grinding, defend every
+    /// other round when the hero's hp falls below this percentage of its
+    /// maximum. Zero disables it.
+    pub auto_defend_hp: i32,
+
+    /// Enemies met while moving stop the hero, who has to attack, flee or
+    /// bribe before reaching the destination.
+    pub blocking_enemies: bool,
+
+    /// Reward the commits made to the git repositories visited by the hero
+    /// with experience, and add quests for them.
+    pub git_quests: bool,
+
+    /// Milliseconds to wait between battle turns, so fights unfold
+    /// progressively. Zero prints them all at once.
+    pub battle_pace_ms: u64,
+
+    /// Spawn rates of enemies and other encounters by path glob, e.g.
+    /// `"**/node_modules/**": never` or `"~/dungeon/**": x3`. When several
+    /// patterns match a location, the longest one applies.
+    pub spawn_rules: BTreeMap<String, SpawnRate>,
+
+    /// Path globs of the only directories where enemies and encounters
+    /// show up, e.g. `["~/dungeon/**"]`. Moving anywhere else is like
+    /// moving with --force. Empty means everywhere.
+    pub dungeons: Vec<String>,
+
+    /// Custom command names mapped to the full invocation they stand for,
+    /// e.g. `heal: use potion potion`. Built-in commands can't be shadowed.
+    pub aliases: HashMap<String, String>,
+
+    /// Which items dropped by enemies or found in chests the hero picks up.
+    pub loot_filter: LootFilter,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            enemy_respawn_hours: 12,
+            chest_respawn_hours: 24,
+            a11y: false,
+            narration: false,
+            notifications: false,
+            icons: true,
+            hints: true,
+            hint_interval_hours: 1,
+            auto_potion_hp: 0,
+            auto_defend_hp: 0,
+            blocking_enemies: false,
+            git_quests: false,
+            battle_pace_ms: 0,
+            spawn_rules: BTreeMap::new(),
+            dungeons: Vec::new(),
+            aliases: HashMap::new(),
+            loot_filter: LootFilter::default(),
+        }
+    }
+}
+
+/// Thresholds for the loot worth picking up. Items below any of them are
+/// left behind, or sold off if `sell_junk` is set. Items the shop doesn't
+/// trade, like equipment, are always picked up.
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct LootFilter {
+    /// The least rarity picked up: common, uncommon, rare or legendary.
+    pub min_rarity: Rarity,
+
+    /// The least shop price picked up.
+    pub min_value: i32,
+
+    /// Trade the skipped items for gold at the pawnshop rate instead of
+    /// leaving them behind.
+    pub sell_junk: bool,
+}
+
+/// How often enemies and encounters show up somewhere, relative to the
+/// usual odds: `never`, or `xN` to roll for them N times.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(try_from = "String")]
+pub enum SpawnRate {
+    Never,
+    Times(u32),
+}
+
+impl TryFrom<String> for SpawnRate {
+    type Error = String;
+
+    fn try_from(rate: String) -> Result<Self, Self::Error> {
+        if rate == "never" {
+            return Ok(SpawnRate::Never);
+        }
+        rate.strip_prefix('x')
+            .and_then(|times| times.parse().ok())
+            .map(SpawnRate::Times)
+            .ok_or_else(|| format!("invalid spawn rate {}, expected never or xN", rate))
+    }
+}
+
+impl Config {
+    pub fn load(bytes: &[u8]) -> Result<()> {
+        if let Ok(config) = serde_yaml::from_slice(bytes) {
+            CONFIG.set(config).ok();
+            Ok(())
+        } else {
+            bail!("Invalid config file.");
+        }
+    }
+
+    pub fn get() -> &'static Self {
+        CONFIG.get_or_init(Self::default)
+    }
+
+    /// The command line arguments the given alias expands to, if defined.
+    pub fn alias(&self, name: &str) -> Option<Vec<String>> {
+        let invocation = self.aliases.get(name)?;
+        Some(invocation.split_whitespace().map(String::from).collect())
+    }
+
+    /// How many times to roll for an enemy or encounter at the given
+    /// location, according to the most specific spawn rule matching it.
+    /// None outside of the designated dungeons, if any.
+    pub fn spawn_rolls(&self, location: &Location) -> u32 {
+        if !self.in_dungeon(location) {
+            return 0;
+        }
+        let rule = self
+            .spawn_rules
+            .iter()
+            .filter(|(pattern, _)| location.matches(pattern))
+            .max_by_key(|(pattern, _)| pattern.len());
+        match rule {
+            Some((_, SpawnRate::Never)) => 0,
+            Some((_, SpawnRate::Times(times))) => std::cmp::min(*times, MAX_SPAWN_ROLLS),
+            None => 1,
+        }
+    }
+
+    /// Whether the location is one where the game is played, always true
+    /// unless dungeons are designated.
+    pub fn in_dungeon(&self, location: &Location) -> bool {
+        self.dungeons.is_empty() || self.dungeons.iter().any(|glob| location.matches(glob))
+    }
+
+    /// Whether enemies are back at a location cleared at the given time.
+    pub fn enemies_respawned(&self, cleared_at: u64) -> bool {
+        elapsed_hours(cleared_at) >= self.enemy_respawn_hours
+    }
+
+    /// Whether enough time passed since the last tip, given when it was shown.
+    pub fn hint_due(&self, hinted_at: u64) -> bool {
+        self.hints && elapsed_hours(hinted_at) >= self.hint_interval_hours
+    }
+
+    /// Whether treasure is back at a location searched at the given time.
+    pub fn chest_respawned(&self, inspected_at: u64) -> bool {
+        elapsed_hours(inspected_at) >= self.chest_respawn_hours
+    }
+}
+
+/// The current wall-clock time in seconds, used to track respawn timers.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn elapsed_hours(since: u64) -> u64 {
+    now().saturating_sub(since) / SECONDS_PER_HOUR
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_respawn() {
+        let config: Config = serde_yaml::from_slice(b"chest_respawn_hours: 1").unwrap();
+        assert_eq!(12, config.enemy_respawn_hours);
+
+        assert!(!config.chest_respawned(now()));
+        assert!(config.chest_respawned(now() - SECONDS_PER_HOUR));
+        assert!(!config.enemies_respawned(now() - SECONDS_PER_HOUR));
+    }
+
+    #[test]
+    fn test_alias() {
+        let config: Config =
+            serde_yaml::from_slice(b"aliases:\n  heal: use  potion potion").unwrap();
+        let expansion = config.alias("heal").unwrap();
+        assert_eq!(vec!["use", "potion", "potion"], expansion);
+        assert_eq!(None, config.alias("home"));
+    }
+
+    #[test]
+    fn test_spawn_rules() {
+        let yaml = b"spawn_rules:\n  '**/node_modules/**': never\n  '~/**': x3\n";
+        let config: Config = serde_yaml::from_slice(yaml).unwrap();
+        let home = Location::home().path_string();
+        let location =
+            |path: &str| crate::location::tests::location_from(&format!("{}/{}", home, path));
+        assert_eq!(3, config.spawn_rolls(&location("dungeon")));
+        assert_eq!(0, config.spawn_rolls(&location("app/node_modules")));
+        assert_eq!(
+            1,
+            config.spawn_rolls(&crate::location::tests::location_from("/tmp"))
+        );
+
+        let invalid: Result<Config, _> = serde_yaml::from_slice(b"spawn_rules:\n  '~': often");
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn test_dungeons() {
+        let home = Location::home().path_string();
+        let location =
+            |path: &str| crate::location::tests::location_from(&format!("{}/{}", home, path));
+        let config = Config::default();
+        assert_eq!(1, config.spawn_rolls(&location("work")));
+
+        let yaml = b"dungeons: ['~/dungeon/**']\nspawn_rules:\n  '~/**': x2\n";
+        let config: Config = serde_yaml::from_slice(yaml).unwrap();
+        assert_eq!(0, config.spawn_rolls(&location("work")));
+        assert_eq!(2, config.spawn_rolls(&location("dungeon/cave")));
+    }
+}
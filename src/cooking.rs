@@ -0,0 +1,146 @@
+use crate::character::{morale, Buff};
+use crate::error::{bail, Result};
+use crate::fuzzy;
+use crate::game::Game;
+use crate::item::material::Material;
+use crate::log;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// How many won battles a meal keeps the hero well fed for.
+pub const MEAL_BATTLES: i32 = 3;
+
+/// A dish cooked from foraged ingredients, buffing the hero for the next
+/// few battles. Its recipe has to be found out by trying ingredients out.
+#[derive(Serialize)]
+pub struct Meal {
+    pub name: &'static str,
+    pub ingredients: &'static [(Material, i32)],
+    pub buff: Buff,
+}
+
+pub const MEALS: [Meal; 3] = [
+    Meal {
+        name: "stew",
+        ingredients: &[(Material::Mushroom, 1), (Material::Fang, 1)],
+        buff: Buff {
+            attack: 20,
+            speed: 0,
+        },
+    },
+    Meal {
+        name: "tea",
+        ingredients: &[(Material::Herb, 1), (Material::Berry, 1)],
+        buff: Buff {
+            attack: 0,
+            speed: 20,
+        },
+    },
+    Meal {
+        name: "pie",
+        ingredients: &[(Material::Mushroom, 1), (Material::Berry, 2)],
+        buff: Buff {
+            attack: 10,
+            speed: 10,
+        },
+    },
+];
+
+/// Cook the given ingredients, at home or at a camp. The name of a meal
+/// already in the cookbook can be given instead of its ingredients.
+/// Ingredients that don't make for any meal are wasted.
+pub fn cook(game: &mut Game, ingredients: &[String]) -> Result<()> {
+    if !game.location.is_home() && !game.at_outpost() {
+        bail!("Meals can only be cooked at home or at a camp.");
+    }
+    let ingredients = match ingredients {
+        [] => bail!("Nothing to cook."),
+        [name] => match known_meal(game, name)? {
+            Some(meal) => meal.ingredients.iter().copied().collect(),
+            None => parse(ingredients)?,
+        },
+        _ => parse(ingredients)?,
+    };
+    for (material, count) in &ingredients {
+        if game.materials.get(material).copied().unwrap_or_default() < *count {
+            bail!("Not enough {} in the pouch.", material);
+        }
+    }
+    for (material, count) in &ingredients {
+        let left = game.materials.entry(*material).or_default();
+        *left -= count;
+        if *left <= 0 {
+            game.materials.remove(material);
+        }
+    }
+
+    let Some(meal) = MEALS.iter().find(|meal| matches(meal, &ingredients)) else {
+        log::burnt();
+        return Ok(());
+    };
+    let discovered = game.cookbook.insert(meal.name.to_string());
+    game.player.meal = Some((meal.buff, MEAL_BATTLES));
+    game.player.update_morale(morale::MEAL);
+    log::cooked(meal, discovered);
+    Ok(())
+}
+
+/// The cookbook meal closest to the given name, if any.
+fn known_meal(game: &Game, name: &str) -> Result<Option<&'static Meal>> {
+    let known = fuzzy::resolve(name, game.cookbook.iter().map(String::as_str))?;
+    Ok(known.and_then(|known| MEALS.iter().find(|meal| meal.name == known)))
+}
+
+/// The ingredients by material, counting the repeated ones.
+fn parse(names: &[String]) -> Result<BTreeMap<Material, i32>> {
+    let mut ingredients = BTreeMap::new();
+    for name in names {
+        *ingredients.entry(Material::from(name)?).or_default() += 1;
+    }
+    Ok(ingredients)
+}
+
+/// Whether the ingredients are exactly the ones of the meal.
+fn matches(meal: &Meal, ingredients: &BTreeMap<Material, i32>) -> bool {
+    meal.ingredients.len() == ingredients.len()
+        && meal
+            .ingredients
+            .iter()
+            .all(|(material, count)| ingredients.get(material) == Some(count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cook() {
+        let mut game = Game::new();
+        let names = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+        game.materials = BTreeMap::from([(Material::Mushroom, 2), (Material::Fang, 3)]);
+
+        // not a meal, the ingredients are lost
+        cook(&mut game, &names(&["fang", "fang"])).unwrap();
+        assert!(game.player.meal.is_none());
+        assert_eq!(1, game.materials[&Material::Fang]);
+
+        // the stew isn't known until cooked once
+        assert!(cook(&mut game, &names(&["stew"])).is_err());
+        cook(&mut game, &names(&["mushroom", "fang"])).unwrap();
+        assert!(game.cookbook.contains("stew"));
+        assert_eq!(Some((MEALS[0].buff, MEAL_BATTLES)), game.player.meal);
+        assert!(cook(&mut game, &names(&["stew"])).is_err());
+
+        game.materials = BTreeMap::from([(Material::Mushroom, 1), (Material::Fang, 1)]);
+        cook(&mut game, &names(&["stew"])).unwrap();
+        assert!(game.materials.is_empty());
+
+        game.location = crate::location::tests::location_from("~/woods");
+        assert!(cook(&mut game, &names(&["stew"])).is_err());
+    }
+}
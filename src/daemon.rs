@@ -0,0 +1,179 @@
+use crate::character::Character;
+use crate::command::{self, Command};
+use crate::datafile;
+use crate::game::Game;
+use crate::log;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+
+/// An action requested by a daemon client, one JSON object per line,
+/// e.g. `{"action": "move", "destination": "~/code"}`.
+#[derive(Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+enum Request {
+    Status,
+    Move { destination: String },
+    Battle,
+    Attack,
+    Flee,
+    Bribe,
+}
+
+/// The reply to every request: the outcome of the action, the game
+/// events it caused and the resulting hero status.
+#[derive(Serialize)]
+struct Response {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    events: Vec<serde_json::Value>,
+    status: Status,
+}
+
+#[derive(Serialize)]
+struct Status {
+    name: String,
+    class: String,
+    level: i32,
+    xp: i32,
+    xp_next: i32,
+    hp: i32,
+    max_hp: i32,
+    mp: i32,
+    max_mp: i32,
+    gold: i32,
+    location: String,
+    enemy: Option<Enemy>,
+}
+
+#[derive(Serialize)]
+struct Enemy {
+    name: String,
+    level: i32,
+    hp: i32,
+    max_hp: i32,
+}
+
+impl Status {
+    fn from(game: &Game) -> Self {
+        let player = &game.player;
+        Self {
+            name: game.name.clone(),
+            class: player.name(),
+            level: player.level,
+            xp: player.xp,
+            xp_next: player.xp_for_next(),
+            hp: player.current_hp,
+            max_hp: player.max_hp(),
+            mp: player.current_mp,
+            max_mp: player.max_mp(),
+            gold: game.gold,
+            location: game.location.path_string(),
+            enemy: game.in_combat.as_ref().map(Enemy::from),
+        }
+    }
+}
+
+impl Enemy {
+    fn from(enemy: &Character) -> Self {
+        Self {
+            name: enemy.name(),
+            level: enemy.level,
+            hp: enemy.current_hp,
+            max_hp: enemy.max_hp(),
+        }
+    }
+}
+
+/// Keep the game loaded and serve requests from the local socket until
+/// the process is stopped. The game is reloaded before every request and
+/// saved after every action that changes it, so the daemon and regular
+/// commands don't overwrite each other's progress.
+pub fn serve(game: &mut Game) -> Result<()> {
+    let socket = datafile::socket_file();
+    // makes sure the data directory exists before binding the socket in it
+    datafile::save(game)?;
+    if socket.exists() {
+        // left over from a previous run that wasn't shut down cleanly
+        std::fs::remove_file(&socket)?;
+    }
+    let listener = UnixListener::bind(&socket)?;
+    println!("listening on {}", socket.display());
+
+    for stream in listener.incoming().flatten() {
+        // a client hanging up mid-request shouldn't stop the daemon
+        let _ = handle_client(stream, game);
+    }
+    Ok(())
+}
+
+fn handle_client(stream: UnixStream, game: &mut Game) -> Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        // pick up whatever regular commands saved since the last request
+        if let Some(saved) = datafile::load()? {
+            *game = saved;
+        }
+        let response = handle(&line, game);
+        writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+    }
+    Ok(())
+}
+
+fn handle(line: &str, game: &mut Game) -> Response {
+    let (result, events) = log::record(|| {
+        serde_json::from_str(line)
+            .map_err(anyhow::Error::from)
+            .and_then(|request| execute(request, game))
+    });
+
+    Response {
+        ok: result.is_ok(),
+        error: result.err().map(|err| err.to_string()),
+        events,
+        status: Status::from(game),
+    }
+}
+
+fn execute(request: Request, game: &mut Game) -> Result<()> {
+    let cmd = match request {
+        Request::Status => return Ok(()),
+        Request::Move { destination } => Command::ChangeDir {
+            destination,
+            force: false,
+        },
+        Request::Battle => Command::Battle,
+        Request::Attack => Command::Attack,
+        Request::Flee => Command::Flee,
+        Request::Bribe => Command::Bribe,
+    };
+
+    // the game is saved even on errors, e.g. when the hero dies
+    let result = command::run(Some(cmd), game);
+    datafile::save(game)?;
+    result.map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handle() {
+        let mut game = Game::new();
+
+        let response = handle(r#"{"action": "status"}"#, &mut game);
+        assert!(response.ok);
+        assert_eq!(1, response.status.level);
+        assert!(response.status.enemy.is_none());
+        assert!(response.events.is_empty());
+
+        let response = handle(r#"{"action": "dance"}"#, &mut game);
+        assert!(!response.ok);
+        assert!(response.error.is_some());
+    }
+}
@@ -1,5 +1,9 @@
 use crate::character::class;
+use crate::config::Config;
 use crate::game;
+use crate::item::custom;
+use crate::item::stash::Stash;
+use crate::log;
 use anyhow::{bail, Result};
 use std::{fs, io, path};
 
@@ -23,6 +27,72 @@ pub fn save(game: &game::Game) -> Result<(), io::Error> {
     write(data_file(), data)
 }
 
+/// Load the stash shared by all heroes, or an empty one if there's none yet.
+pub fn load_stash() -> Result<Stash> {
+    match read(stash_file()) {
+        Err(NotFound) => Ok(Stash::default()),
+        Ok(data) => {
+            if let Ok(stash) = serde_json::from_slice(&data) {
+                Ok(stash)
+            } else {
+                bail!("Invalid stash data file.");
+            }
+        }
+    }
+}
+
+pub fn save_stash(stash: &Stash) -> Result<(), io::Error> {
+    let data = serde_json::to_vec(stash).unwrap();
+    write(stash_file(), data)
+}
+
+/// Store an inactive hero in the roster, under its name.
+pub fn save_hero(game: &game::Game) -> Result<(), io::Error> {
+    let heroes_dir = heroes_dir();
+    if !heroes_dir.exists() {
+        fs::create_dir_all(&heroes_dir)?;
+    }
+    let data = serde_json::to_vec(game).unwrap();
+    fs::write(heroes_dir.join(&game.name), data)
+}
+
+/// Load the hero with the given name from the roster.
+pub fn load_hero(name: &str) -> Result<Option<game::Game>> {
+    match read(heroes_dir().join(name)) {
+        Err(NotFound) => Ok(None),
+        Ok(data) => {
+            if let Ok(game) = serde_json::from_slice(&data) {
+                Ok(Some(game))
+            } else {
+                bail!("Invalid data file for hero {}.", name);
+            }
+        }
+    }
+}
+
+/// Take the hero with the given name out of the roster, once it's active.
+pub fn remove_hero(name: &str) -> Result<(), io::Error> {
+    fs::remove_file(heroes_dir().join(name))
+}
+
+/// Load all the inactive heroes in the roster, sorted by name.
+pub fn list_heroes() -> Vec<game::Game> {
+    let mut heroes: Vec<game::Game> = fs::read_dir(heroes_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| fs::read(entry.ok()?.path()).ok())
+                .filter_map(|data| serde_json::from_slice(&data).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    heroes.sort_by(|a: &game::Game, b| a.name.cmp(&b.name));
+    heroes
+}
+
+pub fn hero_exists(name: &str) -> bool {
+    heroes_dir().join(name).exists()
+}
+
 pub fn remove() {
     let rpg_dir = rpg_dir();
     if rpg_dir.exists() {
@@ -30,9 +100,48 @@ pub fn remove() {
     }
 }
 
+/// Load the customized classes, if any, along with the enemy packs found
+/// in the enemies directory, in file name order.
 pub fn load_classes() {
-    if let Ok(bytes) = read(classes_file()) {
-        class::Class::load(&bytes)
+    let bytes = read(classes_file()).ok();
+    let mut files: Vec<path::PathBuf> = fs::read_dir(enemies_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default();
+    files.retain(|file| {
+        file.extension()
+            .is_some_and(|ext| ext == "yaml" || ext == "yml")
+    });
+    files.sort();
+
+    let packs: Vec<(String, Vec<u8>)> = files
+        .into_iter()
+        .filter_map(|file| {
+            let name = file.file_name()?.to_string_lossy().to_string();
+            fs::read(&file).ok().map(|bytes| (name, bytes))
+        })
+        .collect();
+    for warning in class::Class::load(bytes.as_deref(), &packs) {
+        log::warning(&warning);
+    }
+}
+
+/// Load the custom consumables defined in the items file, if any.
+pub fn load_items() {
+    let bytes = read(items_file()).ok();
+    for warning in custom::load(bytes.as_deref()) {
+        log::warning(&warning);
+    }
+}
+
+pub fn load_config() -> Result<()> {
+    match read(config_file()) {
+        Err(NotFound) => Ok(()),
+        Ok(bytes) => Config::load(&bytes),
     }
 }
 
@@ -63,10 +172,35 @@ fn data_file() -> path::PathBuf {
     rpg_dir().join("data")
 }
 
+fn stash_file() -> path::PathBuf {
+    rpg_dir().join("stash")
+}
+
+fn heroes_dir() -> path::PathBuf {
+    rpg_dir().join("heroes")
+}
+
 fn classes_file() -> path::PathBuf {
     rpg_dir().join("classes.yaml")
 }
 
+#[cfg(unix)]
+pub fn socket_file() -> path::PathBuf {
+    rpg_dir().join("daemon.sock")
+}
+
+fn items_file() -> path::PathBuf {
+    rpg_dir().join("items.toml")
+}
+
+fn enemies_dir() -> path::PathBuf {
+    rpg_dir().join("enemies")
+}
+
+fn config_file() -> path::PathBuf {
+    rpg_dir().join("config.yaml")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,134 @@
+use crate::character::Dead;
+use std::fmt;
+
+/// The errors of the game logic. The ones scripts may want to tell apart
+/// are mapped to their own process exit code.
+#[derive(Debug, PartialEq)]
+pub enum GameError {
+    /// The hero died and couldn't be revived.
+    Dead,
+    /// The action isn't allowed during a battle. Holds what was attempted.
+    InCombat(&'static str),
+    /// The action is only allowed at home. Holds what was attempted.
+    NotAtHome(&'static str),
+    /// A battle action was attempted outside of a battle.
+    NotInCombat,
+    InvalidDestination(String),
+    InsufficientGold,
+    UnknownItem(String),
+    UnknownClass,
+    UnknownSkill,
+    /// A shaken hero trembled instead of taking a risk.
+    LowMorale,
+    /// Any other failure, holding the message shown to the player.
+    Other(String),
+}
+
+pub type Result<T, E = GameError> = std::result::Result<T, E>;
+
+/// Return early with the given GameError, or with one holding the
+/// formatted message, the same way anyhow's bail! does.
+macro_rules! bail {
+    ($msg:literal $(,)?) => {
+        return Err($crate::error::GameError::Other(format!($msg)))
+    };
+    ($err:expr $(,)?) => {
+        return Err($err)
+    };
+    ($fmt:expr, $($arg:tt)*) => {
+        return Err($crate::error::GameError::Other(format!($fmt, $($arg)*)))
+    };
+}
+pub(crate) use bail;
+
+impl std::error::Error for GameError {}
+
+impl From<Dead> for GameError {
+    fn from(_: Dead) -> Self {
+        GameError::Dead
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::Dead => write!(f, "{}", Dead),
+            GameError::InCombat(action) => write!(f, "Can't {} in the middle of a battle.", action),
+            GameError::NotAtHome(action) => write!(f, "Can only {} at home.", action),
+            GameError::NotInCombat => write!(f, "Not in combat."),
+            GameError::InvalidDestination(dest) => write!(f, "No such directory: {}.", dest),
+            GameError::InsufficientGold => write!(f, "Not enough gold."),
+            GameError::UnknownItem(item) => write!(f, "Item {} not found.", item),
+            GameError::UnknownClass => write!(f, "Unknown class name."),
+            GameError::UnknownSkill => write!(f, "Skill not found."),
+            GameError::LowMorale => write!(f, "The hero's morale is too low to take the risk."),
+            GameError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// The process exit codes of failed commands, stable so shell scripts can
+/// rely on them. Successful commands exit with 0.
+pub mod exit_code {
+    pub const ERROR: i32 = 1;
+    pub const DEAD: i32 = 2;
+    pub const IN_COMBAT: i32 = 3;
+    pub const INVALID_DESTINATION: i32 = 4;
+    pub const NOT_ENOUGH_GOLD: i32 = 5;
+    pub const NOT_AT_HOME: i32 = 6;
+    pub const NOT_IN_COMBAT: i32 = 7;
+    pub const UNKNOWN_NAME: i32 = 8;
+}
+
+/// Whether the command failed because the hero died.
+pub fn is_dead(err: &anyhow::Error) -> bool {
+    matches!(err.downcast_ref::<GameError>(), Some(GameError::Dead))
+}
+
+/// The exit code for a failed command, according to its error.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    match err.downcast_ref::<GameError>() {
+        Some(GameError::Dead) => exit_code::DEAD,
+        Some(GameError::InCombat(_)) => exit_code::IN_COMBAT,
+        Some(GameError::InvalidDestination(_)) => exit_code::INVALID_DESTINATION,
+        Some(GameError::InsufficientGold) => exit_code::NOT_ENOUGH_GOLD,
+        Some(GameError::NotAtHome(_)) => exit_code::NOT_AT_HOME,
+        Some(GameError::NotInCombat) => exit_code::NOT_IN_COMBAT,
+        Some(GameError::UnknownItem(_) | GameError::UnknownClass | GameError::UnknownSkill) => {
+            exit_code::UNKNOWN_NAME
+        }
+        Some(GameError::LowMorale | GameError::Other(_)) | None => exit_code::ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(exit_code::DEAD, exit_code(&anyhow!(GameError::from(Dead))));
+        assert_eq!(
+            exit_code::IN_COMBAT,
+            exit_code(&anyhow!(GameError::InCombat("explore")))
+        );
+        assert_eq!(
+            exit_code::NOT_ENOUGH_GOLD,
+            exit_code(&anyhow!(GameError::InsufficientGold))
+        );
+        assert_eq!(
+            exit_code::UNKNOWN_NAME,
+            exit_code(&anyhow!(GameError::UnknownItem(String::from("sword"))))
+        );
+        assert_eq!(exit_code::ERROR, exit_code(&anyhow!("item not found.")));
+        assert_eq!(
+            exit_code::ERROR,
+            exit_code(&anyhow!(GameError::Other(String::from("Nothing to cook."))))
+        );
+        assert_eq!(
+            "Can't explore in the middle of a battle.",
+            GameError::InCombat("explore").to_string()
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use crate::error::{bail, Result};
+
+/// Resolve a name typed by the user against the known ones. Exact matches
+/// win, then unambiguous prefixes, then the closest name within a couple of
+/// typos. Several equally good matches are reported as an error suggesting
+/// them; no match at all returns None for the caller to report.
+pub fn resolve<'a>(
+    input: &str,
+    names: impl IntoIterator<Item = &'a str>,
+) -> Result<Option<&'a str>> {
+    let input = input.to_lowercase();
+    let names: Vec<&str> = names.into_iter().collect();
+    if let Some(name) = names.iter().find(|name| name.eq_ignore_ascii_case(&input)) {
+        return Ok(Some(name));
+    }
+
+    let prefixed: Vec<&str> = names
+        .iter()
+        .copied()
+        .filter(|name| name.to_lowercase().starts_with(&input))
+        .collect();
+    if !prefixed.is_empty() {
+        return pick(&input, prefixed);
+    }
+
+    let tolerance = max_typos(&input);
+    let distances: Vec<(&str, usize)> = names
+        .iter()
+        .map(|name| (*name, distance(&input, &name.to_lowercase())))
+        .filter(|(_, distance)| *distance <= tolerance)
+        .collect();
+    let Some(closest) = distances.iter().map(|(_, distance)| *distance).min() else {
+        return Ok(None);
+    };
+    let closest = distances
+        .into_iter()
+        .filter(|(_, distance)| *distance == closest)
+        .map(|(name, _)| name)
+        .collect();
+    pick(&input, closest)
+}
+
+/// The only candidate, or a did-you-mean error listing all of them.
+fn pick<'a>(input: &str, mut candidates: Vec<&'a str>) -> Result<Option<&'a str>> {
+    if candidates.len() == 1 {
+        return Ok(candidates.pop());
+    }
+    candidates.sort_unstable();
+    bail!(
+        "{} is ambiguous, did you mean {}?",
+        input,
+        candidates.join(", ")
+    )
+}
+
+/// How many typos are forgiven, none for very short names where any
+/// name would be close.
+fn max_typos(input: &str) -> usize {
+    match input.chars().count() {
+        0..=2 => 0,
+        3..=4 => 1,
+        _ => 2,
+    }
+}
+
+/// The Levenshtein edit distance between the given strings.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            let insertion = current[j] + 1;
+            let deletion = previous[j + 1] + 1;
+            current.push(substitution.min(insertion).min(deletion));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let names = ["warrior", "wizard", "thief", "mage"];
+        let resolve = |input| resolve(input, names);
+
+        assert_eq!(Some("thief"), resolve("Thief").unwrap());
+        assert_eq!(Some("warrior"), resolve("war").unwrap());
+        assert_eq!(Some("wizard"), resolve("wizrad").unwrap());
+        assert_eq!(Some("mage"), resolve("maje").unwrap());
+        assert_eq!(None, resolve("paladin").unwrap());
+        assert_eq!(None, resolve("mu").unwrap());
+
+        let err = resolve("w").unwrap_err();
+        assert_eq!(
+            "w is ambiguous, did you mean warrior, wizard?",
+            err.to_string()
+        );
+    }
+}
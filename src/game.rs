@@ -1,19 +1,48 @@
+use crate::arena::Arena;
+use crate::artifact::Artifact;
+use crate::biome::Biome;
+use crate::bounty;
+use crate::challenge::Challenge;
 use crate::character;
+use crate::character::behavior::{self, Action};
 use crate::character::enemy;
-use crate::character::Character;
+use crate::character::mercenary;
+use crate::character::morale::{self, Mood};
+use crate::character::npc;
+use crate::character::{Buff, Character};
+use crate::config::{self, Config};
+use crate::error::{bail, GameError, Result};
+use crate::fuzzy;
+use crate::git;
+use crate::guild;
+use crate::insurance;
 use crate::item::chest::Chest;
+use crate::item::equipment::MAX_DURABILITY;
+use crate::item::filter;
+use crate::item::heirloom::Heirloom;
 use crate::item::key::Key;
+use crate::item::material::{self, Material, Recipe};
 use crate::item::ring::Ring;
-use crate::item::Item;
+use crate::item::{Item, Ration};
+use crate::journal::{self, Journal};
 use crate::location::Location;
 use crate::log;
+use crate::lore;
+use crate::mercy::Mercy;
+use crate::mutator::Mutators;
 use crate::quest;
 use crate::quest::QuestList;
+use crate::randomizer;
 use crate::randomizer::random;
 use crate::randomizer::Randomizer;
-use anyhow::{bail, Result};
+use crate::speedrun::{Speedrun, Split};
+use crate::stats::{self, Stats};
+use crate::terrain::{self, Terrain};
+use crate::title::Title;
+use crate::weather::{self, Weather};
+use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 /// Carries all the game state that is saved between commands and exposes
 /// the high-level interface for gameplay: moving across directories and
@@ -21,10 +50,17 @@ use std::collections::{HashMap, HashSet};
 #[derive(Serialize, Deserialize)]
 #[serde(default)]
 pub struct Game {
+    /// Tells the hero apart from the others in the roster.
+    pub name: String,
     pub player: Character,
     pub location: Location,
     pub gold: i32,
-    pub hardcore: bool,
+
+    /// What's lost when the hero dies.
+    pub hardcore: Hardcore,
+
+    /// Manual save and load are disabled, so every consequence sticks.
+    pub ironman: bool,
     pub in_combat: Option<Character>,
     pub in_encounter: Option<character::npc::Encounter>,
     battle_xp: i32,
@@ -32,10 +68,26 @@ pub struct Game {
     /// Items currently carried and unequipped
     pub inventory: HashMap<Key, Vec<Box<dyn Item>>>,
 
+    /// Crafting materials gathered by the hero, and how many of each.
+    pub materials: BTreeMap<Material, i32>,
+
+    /// The meals the hero found out how to cook, kept across deaths.
+    pub cookbook: BTreeSet<String>,
+
     /// Locations where chest have already been looked for, and therefore
     /// can't be found again.
     inspected: HashSet<Location>,
 
+    /// When each location was last searched, to let its treasure respawn.
+    inspected_at: HashMap<String, u64>,
+
+    /// How many times each location was foraged since its treasure last
+    /// respawned.
+    foraged: HashMap<String, i32>,
+
+    /// How deep the hero has dug into the vein of each location.
+    mined: HashMap<String, i32>,
+
     /// Chests left at the location where the player dies.
     pub tombstones: HashMap<String, Chest>,
 
@@ -47,74 +99,559 @@ pub struct Game {
     pub ring_pool: HashSet<Ring>,
 
     pub quests: QuestList,
+
+    /// Enemy family currently being tracked down by the hero, if any.
+    pub hunt: Option<enemy::Hunt>,
+
+    /// The ongoing world invasion event, if any.
+    pub invasion: Option<enemy::Invasion>,
+
+    /// The weather across the filesystem, and until when it holds.
+    pub weather: Weather,
+    pub weather_until: u64,
+
+    /// Locations where enemies were banished and won't appear anymore.
+    pub pacified: HashSet<Location>,
+
+    /// Camps built by the hero away from home, in construction order.
+    /// They heal the hero, offer a partial shop and the latest one is used
+    /// as the respawn point.
+    pub outposts: Vec<Location>,
+
+    /// The guild the hero belongs to, if any, along with its rank.
+    pub guild: Option<guild::Membership>,
+
+    /// The contracts on the bounty board and the ones taken by the hero.
+    pub bounties: bounty::Board,
+
+    /// The arena run in progress at home, if any.
+    pub arena: Option<Arena>,
+
+    /// Whether a boss rush is being fought. Its bosses are rematches, so
+    /// they don't give the rewards of defeating them for real.
+    #[serde(skip)]
+    pub in_boss_rush: bool,
+
+    /// A fighter hired to join the hero's battles for a cut of the gold.
+    pub mercenary: Option<Character>,
+
+    /// Heroes recruited to travel with the main one. They share the gold
+    /// and take turns to attack in battle.
+    pub party: Vec<Character>,
+
+    /// Covers part of the hero's gold and an item in case of death.
+    pub insurance: Option<insurance::Policy>,
+
+    /// Fallen heroes haunting the locations where they died.
+    pub ghosts: HashMap<String, enemy::Ghost>,
+
+    /// Notable events of the heroes' adventures, told by `rpg story`.
+    pub journal: Journal,
+
+    /// Lore heard from the ghostly maiden, kept across deaths.
+    pub codex: lore::Codex,
+
+    /// Progress clearing each location of enemies.
+    pub clearings: HashMap<String, enemy::Clearing>,
+
+    /// Lifetime statistics, kept across deaths.
+    pub stats: Stats,
+
+    /// The timed run, when speedrun mode is enabled.
+    pub speedrun: Option<Speedrun>,
+
+    /// The seeded run started from a challenge code, if any.
+    pub challenge: Option<Challenge>,
+
+    /// Optional rules chosen at the start of the run.
+    pub mutators: Mutators,
+
+    /// When the last tip was shown, to avoid repeating them too often.
+    pub hinted_at: u64,
+
+    /// Where the hero was headed when an enemy blocked the way, in
+    /// blocking enemies mode. The move completes after the battle.
+    pub pending_move: Option<Location>,
+
+    /// An enemy the hero fled from that gave chase. It catches up and
+    /// ambushes the hero at the next directory.
+    pub pursuer: Option<Character>,
+
+    /// Who gets a free strike at the start of the next battle round.
+    pub surprise: Option<Surprise>,
+
+    /// Enemy attacks left that are forced onto the hero by a taunt.
+    pub taunted: i32,
+
+    /// The enemy was blinded by flash powder, so the next attempt to flee
+    /// succeeds.
+    pub blinded: bool,
+
+    /// The git repositories followed for new commits, in git quests mode.
+    pub commits: git::Commits,
+
+    /// When the world was last advanced by the tick command.
+    pub ticked_at: u64,
+
+    /// When the game was last played, to grant the offline progress.
+    pub played_at: u64,
+
+    /// Directories traveled away from home since the hero last ate.
+    pub steps_since_meal: i32,
+
+    /// Titles earned by the heroes, kept across deaths.
+    pub titles: Vec<Title>,
+
+    /// The earned title shown next to the hero, if any.
+    pub title: Option<Title>,
+
+    /// Legendary artifacts found by the heroes, kept across deaths.
+    pub artifacts: Vec<Artifact>,
+
+    /// Whether gorthaur was ever defeated, which unlocks the post-game:
+    /// the boss rush and new game+.
+    pub completed: bool,
+
+    /// How many times a new game+ was started, each one making the
+    /// enemies stronger.
+    pub cycle: i32,
+
+    /// Help for the hero after a streak of early deaths, tracked across deaths.
+    pub mercy: Mercy,
+
+    /// Chests left closed at each location after noticing their trap.
+    pub trapped: HashMap<String, Chest>,
 }
 
+/// The side that caught the other off guard, striking first without a
+/// response.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Surprise {
+    /// A hero much faster than the enemy found it unprepared.
+    Hero,
+    /// The enemy took advantage of a failed escape.
+    Enemy,
+    /// The hero shoots its bow or wand before the enemy closes in.
+    Ranged,
+}
+
+/// What the hero loses on death, by tier. Heirlooms are always kept.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Hardcore {
+    /// Tier 1: only the gold is dropped at the tombstone.
+    Gold,
+    /// Tier 2: the gold, equipment and items are dropped, but the hero
+    /// keeps its level.
+    Equipment,
+    /// Tier 3: the hero is gone and a new one starts over at level 1.
+    Wipe,
+}
+
+impl Hardcore {
+    pub fn from_tier(tier: u8) -> Option<Self> {
+        match tier {
+            1 => Some(Hardcore::Gold),
+            2 => Some(Hardcore::Equipment),
+            3 => Some(Hardcore::Wipe),
+            _ => None,
+        }
+    }
+
+    pub fn tier(&self) -> u8 {
+        match self {
+            Hardcore::Gold => 1,
+            Hardcore::Equipment => 2,
+            Hardcore::Wipe => 3,
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Hardcore::Gold => "dying loses the gold",
+            Hardcore::Equipment => "dying loses the gold, equipment and items",
+            Hardcore::Wipe => "dying loses the hero",
+        }
+    }
+}
+
+impl Serialize for Hardcore {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.tier())
+    }
+}
+
+impl<'de> Deserialize<'de> for Hardcore {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // games saved before the tiers hold an on/off flag
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Format {
+            Flag(bool),
+            Tier(u8),
+        }
+
+        match Format::deserialize(deserializer)? {
+            Format::Flag(true) => Ok(Hardcore::Wipe),
+            Format::Flag(false) => Ok(Hardcore::Equipment),
+            Format::Tier(tier) => Hardcore::from_tier(tier)
+                .ok_or_else(|| serde::de::Error::custom("invalid hardcore tier")),
+        }
+    }
+}
+
+/// A snapshot of the hero's achievements, to report what was gained
+/// over a series of automated actions.
+#[derive(Serialize)]
+pub struct Progress {
+    pub battles_won: i32,
+    pub chests_found: i32,
+    pub gold: i32,
+    pub level: i32,
+}
+
+impl Progress {
+    pub fn of(game: &Game) -> Self {
+        Self {
+            battles_won: game.stats.battles_won,
+            chests_found: game.stats.chests_found,
+            gold: game.gold,
+            level: game.player.level,
+        }
+    }
+
+    /// The difference between the current game and this snapshot.
+    pub fn since(&self, game: &Game) -> Self {
+        let now = Self::of(game);
+        Self {
+            battles_won: now.battles_won - self.battles_won,
+            chests_found: now.chests_found - self.chests_found,
+            gold: now.gold - self.gold,
+            level: now.level - self.level,
+        }
+    }
+}
+
+/// Maximum amount of heroes in the party, including the main one.
+const PARTY_SIZE: usize = 3;
+
+/// Gold paid to build an outpost.
+const OUTPOST_COST: i32 = 10_000;
+
+/// Extra gold and xp percentage for defeating invaders.
+const INVASION_BONUS: i32 = 50;
+
+/// Gold paid for the banishing ritual.
+const BANISH_COST: i32 = 5000;
+
+/// Percentage of the battle gold reward an enemy asks for as a bribe.
+pub const BRIBE_COST: i32 = 50;
+
+/// Gold paid to sail to an overseas continent.
+pub const VOYAGE_FARE: i32 = 200;
+
+/// The name of heroes that weren't given one.
+pub const DEFAULT_NAME: &str = "hero";
+
+/// Seconds between ticks that can start new world events. Ticking more
+/// often only expires the current ones, so running it again is harmless.
+const TICK_INTERVAL: u64 = SECONDS_PER_HOUR;
+
+const SECONDS_PER_HOUR: u64 = 60 * 60;
+
+/// Durability points lost by the hero's equipment in each battle won.
+const BATTLE_WEAR: i32 = 2;
+
+/// How many directories away from home the hero travels on each ration.
+pub const RATION_DISTANCE: i32 = 5;
+
+/// The least hours away from the game that count as offline progress.
+const MIN_OFFLINE_HOURS: u64 = 1;
+
+/// The most hours of offline progress granted at once.
+pub const MAX_OFFLINE_HOURS: u64 = 24;
+
+/// Gold collected by each outpost per hour away.
+pub const OUTPOST_GOLD_PER_HOUR: i32 = 10;
+
+/// Experience gained by each ally, training per hour away.
+pub const TRAINING_XP_PER_HOUR: i32 = 5;
+
+/// Experience gained for each commit, in git quests mode.
+pub const COMMIT_XP: i32 = 20;
+
+/// Most commits rewarded at once, so pulling a busy repository doesn't
+/// level up the hero on its own.
+const MAX_REWARDED_COMMITS: usize = 10;
+
+/// Gold charged for each hero level to unlearn its skills.
+pub const RESPEC_COST_PER_LEVEL: i32 = 100;
+
+/// Damage, as a percentage of the hero's max hp, done by a chest trap.
+pub const TRAP_DAMAGE: i32 = 20;
+
+/// Percentage of the max hp recovered between the fights of a boss rush.
+pub const BOSS_RUSH_HEAL: i32 = 20;
+
+/// How many of the latest journal entries are told in the victory epilogue.
+const EPILOGUE_ENTRIES: usize = 8;
+
+/// Levels added to the enemies on each new game+ cycle.
+pub const CYCLE_LEVEL_BONUS: i32 = 10;
+
+/// Most herbs, minus one, picked when searching a forest.
+const FOREST_HERBS: i32 = 2;
+
+/// Percentage of the max mp recovered when defending in battle.
+const DEFEND_MP: i32 = 10;
+
+/// Enemy attacks drawn to the hero by a first rank taunt.
+const TAUNT_ROUNDS: i32 = 3;
+
+/// How many times a location can be foraged before it's picked clean.
+const FORAGE_LIMIT: i32 = 3;
+
+/// Most ore, minus one, dug on each swing of the pickaxe.
+const MINE_ORE: i32 = 2;
+
+/// Every how many swings at the same location a gem is found.
+const GEM_DEPTH: i32 = 3;
+
+/// Stones that can be offered in the banishing ritual, in order of preference.
+const BANISH_OFFERINGS: [Key; 5] = [
+    Key::HealthStone,
+    Key::MagicStone,
+    Key::PowerStone,
+    Key::SpeedStone,
+    Key::LevelStone,
+];
+
 impl Game {
     pub fn new() -> Self {
         let quests = QuestList::new();
 
         // There's one instance of each ring exiting per game.
-        // The diamond ring is the only one that's found in the shop
-        // instead of chests
+        // The diamond ring is found in the shop and the shadow ring
+        // is a quest reward, instead of chests
         let mut ring_pool = Ring::set();
         ring_pool.remove(&Ring::Diamond);
+        ring_pool.remove(&Ring::Shadow);
 
         Self {
+            name: String::from(DEFAULT_NAME),
             location: Location::home(),
             player: Character::player(),
             gold: 0,
-            hardcore: true,
+            hardcore: Hardcore::Wipe,
+            ironman: false,
             in_combat: None,
             in_encounter: None,
             battle_xp: 0,
             inventory: HashMap::new(),
+            materials: BTreeMap::new(),
+            cookbook: BTreeSet::new(),
             tombstones: HashMap::new(),
             inspected: HashSet::new(),
+            inspected_at: HashMap::new(),
+            foraged: HashMap::new(),
+            mined: HashMap::new(),
             amulet_quest_item_generated: false,
             quests,
             ring_pool,
+            hunt: None,
+            invasion: None,
+            weather: Weather::default(),
+            weather_until: 0,
+            pacified: HashSet::new(),
+            outposts: Vec::new(),
+            guild: None,
+            bounties: bounty::Board::default(),
+            arena: None,
+            in_boss_rush: false,
+            mercenary: None,
+            party: Vec::new(),
+            insurance: None,
+            ghosts: HashMap::new(),
+            journal: Journal::default(),
+            codex: lore::Codex::default(),
+            clearings: HashMap::new(),
+            stats: Stats::default(),
+            speedrun: None,
+            challenge: None,
+            mutators: Mutators::default(),
+            hinted_at: 0,
+            pending_move: None,
+            pursuer: None,
+            surprise: None,
+            taunted: 0,
+            blinded: false,
+            commits: git::Commits::default(),
+            ticked_at: 0,
+            played_at: 0,
+            steps_since_meal: 0,
+            titles: Vec::new(),
+            title: None,
+            artifacts: Vec::new(),
+            completed: false,
+            cycle: 0,
+            mercy: Mercy::default(),
+            trapped: HashMap::new(),
         }
     }
 
     /// Remove the game data and reset this reference.
     /// Progress is preserved across games.
     pub fn reset(&mut self) {
-        if !self.hardcore {
+        if self.hardcore != Hardcore::Wipe {
+            self.collect_insurance();
             return;
         }
 
-        let mut new_game = Self::new();
-        // preserve tombstones and quests across hero's lifes
-        std::mem::swap(&mut new_game.tombstones, &mut self.tombstones);
-        std::mem::swap(&mut new_game.quests, &mut self.quests);
-        std::mem::swap(&mut new_game.ring_pool, &mut self.ring_pool);
+        let spared = self.mercy.hero_died(self.player.level) && !self.ironman;
+
+        let mut new_game = self.next_run();
+        std::mem::swap(&mut new_game.speedrun, &mut self.speedrun);
+        std::mem::swap(&mut new_game.challenge, &mut self.challenge);
+        std::mem::swap(&mut new_game.mutators, &mut self.mutators);
+        new_game.mercy.active = spared;
+
+        // the new hero starts at the frontier camp if there's one
+        if let Some(outpost) = new_game.outposts.last() {
+            new_game.location = outpost.clone();
+        }
+
+        new_game.ironman = self.ironman;
 
         // remember last selected class
         new_game.player = character::Character::new(self.player.class.clone(), 1);
 
+        // heirlooms are passed on to the next hero, worn ones included
+        if let Some(mut amulet) = self.player.amulet.take() {
+            amulet.spent = false;
+            let amulet: Box<dyn Item> = Box::new(amulet);
+            self.inventory.entry(amulet.key()).or_default().push(amulet);
+        }
+        let heirlooms: Vec<Box<dyn Item>> = self
+            .inventory
+            .drain()
+            .flat_map(|(_, items)| items)
+            .collect();
+        for heirloom in heirlooms.into_iter().filter(|item| item.is_heirloom()) {
+            new_game
+                .inventory
+                .entry(heirloom.key())
+                .or_default()
+                .push(heirloom);
+        }
+
+        // a pending claim is paid to the next hero
+        new_game.insurance = self.insurance.take();
+
         // replace the current, finished game with the new one
         *self = new_game;
+        self.collect_insurance();
+        if spared {
+            log::mercy();
+        }
 
         quest::game_reset(self);
     }
 
+    /// A new game for the next run, taking over the progress that outlives
+    /// the hero: the world it leaves behind, the history of past heroes
+    /// and everything they unlocked.
+    pub fn next_run(&mut self) -> Self {
+        let mut new_game = Self::new();
+        // preserve tombstones and quests across hero's lifes
+        std::mem::swap(&mut new_game.tombstones, &mut self.tombstones);
+        std::mem::swap(&mut new_game.ghosts, &mut self.ghosts);
+        std::mem::swap(&mut new_game.journal, &mut self.journal);
+        std::mem::swap(&mut new_game.codex, &mut self.codex);
+        std::mem::swap(&mut new_game.stats, &mut self.stats);
+        std::mem::swap(&mut new_game.quests, &mut self.quests);
+        std::mem::swap(&mut new_game.ring_pool, &mut self.ring_pool);
+        std::mem::swap(&mut new_game.pacified, &mut self.pacified);
+        std::mem::swap(&mut new_game.clearings, &mut self.clearings);
+        std::mem::swap(&mut new_game.invasion, &mut self.invasion);
+        std::mem::swap(&mut new_game.weather, &mut self.weather);
+        std::mem::swap(&mut new_game.weather_until, &mut self.weather_until);
+        std::mem::swap(&mut new_game.outposts, &mut self.outposts);
+        std::mem::swap(&mut new_game.guild, &mut self.guild);
+        std::mem::swap(&mut new_game.commits, &mut self.commits);
+        std::mem::swap(&mut new_game.ticked_at, &mut self.ticked_at);
+        std::mem::swap(&mut new_game.titles, &mut self.titles);
+        std::mem::swap(&mut new_game.title, &mut self.title);
+        std::mem::swap(&mut new_game.artifacts, &mut self.artifacts);
+        new_game.bounties.reputation = self.bounties.reputation;
+        new_game.completed = self.completed;
+        new_game.cycle = self.cycle;
+        std::mem::swap(&mut new_game.mercy, &mut self.mercy);
+        new_game.name = std::mem::take(&mut self.name);
+        new_game.cookbook = std::mem::take(&mut self.cookbook);
+        new_game
+    }
+
     /// Move the hero's location towards the given destination, one directory
     /// at a time, with some chance of enemies appearing on each one.
-    pub fn go_to(&mut self, dest: &Location) -> Result<(), anyhow::Error> {
+    /// In blocking enemies mode, an enemy stops the hero before entering
+    /// the directory, and the move is resumed once the battle is over.
+    pub fn go_to(&mut self, dest: &Location) -> Result<()> {
+        let blocking = Config::get().blocking_enemies;
+        if blocking && self.in_combat.is_some() {
+            bail!(GameError::InCombat("move"));
+        }
+        let deeper = dest.distance_from_home().len() > self.location.distance_from_home().len();
+        self.check_nerve(deeper)?;
+
         while self.location != *dest {
-            self.visit(self.location.go_to(dest))?;
+            let next = self.location.go_to(dest);
+            let riding = self.rides_past(&next, dest);
+            if blocking && !riding {
+                // look for enemies ahead, staying in place if one shows up
+                let current = std::mem::replace(&mut self.location, next.clone());
+                let enemy = self.encounter_enemy();
+                self.location = current;
+                if let Some(enemy) = enemy {
+                    self.engage(enemy);
+                    self.pending_move = Some(dest.clone());
+                    break;
+                }
+            }
+            let voyage = self.sets_sail(&next);
+            if voyage {
+                if self.gold < VOYAGE_FARE {
+                    bail!(GameError::InsufficientGold);
+                }
+                self.gold -= VOYAGE_FARE;
+                log::voyage(&next, VOYAGE_FARE);
+            }
+            self.visit(next)?;
+            if self.in_combat.is_some() {
+                // caught up by an enemy
+                break;
+            }
+            // pirates follow the spawn rules of the shore reached
+            let pirates = voyage && Config::get().spawn_rolls(&self.location) > 0;
+            if pirates && random().pirates_attack() {
+                let pirate = enemy::pirate(self);
+                log::enemy_appears(&pirate, &self.location);
+                self.engage(pirate);
+                break;
+            }
 
-            if !self.location.is_home() {
-                if self.in_combat.is_none() && self.in_encounter.is_none() {
-                    if let Some(enemy) = enemy::spawn(self) {
-                        log::enemy_appears(&enemy, &self.location);
-                        self.in_combat = Some(enemy);
+            if !self.is_safe() && self.in_combat.is_none() && self.in_encounter.is_none() {
+                let enemy = if blocking || riding {
+                    None
+                } else {
+                    self.encounter_enemy()
+                };
+                if let Some(enemy) = enemy {
+                    log::enemy_appears(&enemy, &self.location);
+                    self.engage(enemy);
+                    break;
+                } else {
+                    character::npc::spawn(self);
+                    if self.in_encounter.is_some() {
                         break;
-                    } else {
-                        character::npc::spawn(self);
-                        if self.in_encounter.is_some() {
-                            break;
-                        }
                     }
                 }
             }
@@ -122,10 +659,221 @@ impl Game {
         Ok(())
     }
 
+    /// Advance the world without the hero: expire invasions and weather,
+    /// maybe start a new invasion, forget the locations whose enemies and
+    /// treasure respawned and reward new commits. Only changes are reported.
+    pub fn tick(&mut self) {
+        let due = self.ticked_at + TICK_INTERVAL <= config::now();
+        if due || self.invasion.is_some() {
+            let invaded = self.invasion.is_some();
+            enemy::update_invasion(self);
+            if let Some(invasion) = self.invasion.as_ref().filter(|_| !invaded) {
+                log::invasion(invasion);
+            }
+        }
+        if due {
+            self.ticked_at = config::now();
+        }
+        if weather::update(self) {
+            log::weather(self.weather, true);
+        }
+
+        enemy::forget_respawned(self);
+        let respawned: Vec<String> = self
+            .inspected_at
+            .iter()
+            .filter(|(_, inspected_at)| Config::get().chest_respawned(**inspected_at))
+            .map(|(location, _)| location.clone())
+            .collect();
+        for location in respawned {
+            self.inspected_at.remove(&location);
+            self.foraged.remove(&location);
+            self.inspected
+                .retain(|inspected| inspected.to_string() != location);
+        }
+
+        self.check_commits();
+    }
+
+    /// Grant the progress made while the game wasn't played: the outposts
+    /// collect gold and the allies train, proportionally to the hours away,
+    /// up to a day. Speedruns are played against the clock, so they get none.
+    pub fn offline_progress(&mut self) {
+        let now = config::now();
+        let hours = now.saturating_sub(self.played_at) / SECONDS_PER_HOUR;
+        let first_play = self.played_at == 0;
+        self.played_at = now;
+        if first_play || hours < MIN_OFFLINE_HOURS || self.speedrun.is_some() {
+            return;
+        }
+
+        let hours = std::cmp::min(hours, MAX_OFFLINE_HOURS) as i32;
+        let gold = hours * OUTPOST_GOLD_PER_HOUR * self.outposts.len() as i32;
+        if gold > 0 {
+            self.gold += gold;
+            log::offline_gold(hours, gold);
+        }
+
+        let xp = hours * TRAINING_XP_PER_HOUR;
+        for ally in self.party.iter_mut().chain(self.mercenary.iter_mut()) {
+            let levels_up = ally.add_experience(xp);
+            log::party_xp(ally, xp, levels_up);
+        }
+    }
+
+    /// End the battle with a bug once its repository has no uncommitted
+    /// changes left.
+    pub fn fix_bugs(&mut self) {
+        let fighting_bug = self
+            .in_combat
+            .as_ref()
+            .is_some_and(|enemy| enemy::is_bug(&enemy.class));
+        if fighting_bug && git::changes(&self.location) == 0 {
+            let bug = self.in_combat.take().unwrap();
+            log::bug_fixed(&bug);
+            self.battle_xp = 0;
+            self.surprise = None;
+        }
+    }
+
+    /// In git quests mode, reward the commits made since the last check to
+    /// the repositories the hero visited.
+    pub fn check_commits(&mut self) {
+        if !Config::get().git_quests {
+            return;
+        }
+        if !self.commits.started {
+            self.commits.started = true;
+            if let Some(tip) = self.quests.start_git() {
+                log::tip(tip);
+            }
+        }
+        self.commits.track(&self.location);
+
+        let count = self.commits.check();
+        if count > 0 {
+            self.reward_commits(count);
+        }
+    }
+
+    fn reward_commits(&mut self, count: usize) {
+        let xp = std::cmp::min(count, MAX_REWARDED_COMMITS) as i32 * COMMIT_XP;
+        let levels_up = self.player.add_experience(xp);
+        log::commits(&self.player, count, xp, levels_up);
+        self.stats.level_reached(self.player.level);
+        self.track_mastery(levels_up, 0);
+        quest::committed(self, count);
+        if levels_up > 0 {
+            quest::level_up(self, levels_up);
+        }
+    }
+
+    /// Whether moving to the given location, one step away, means crossing
+    /// the sea onto another continent.
+    fn sets_sail(&self, next: &Location) -> bool {
+        let continent = next.continent();
+        continent.is_some() && continent != self.location.continent()
+    }
+
+    /// Start a battle with the given enemy, which a fast enough hero may
+    /// catch by surprise, or else shoot at with a ranged weapon.
+    fn engage(&mut self, enemy: Character) {
+        if random().hero_ambushes(self.player.speed(), enemy.speed()) {
+            self.surprise = Some(Surprise::Hero);
+        } else if self
+            .player
+            .sword
+            .as_ref()
+            .is_some_and(|sword| sword.is_ranged())
+        {
+            self.surprise = Some(Surprise::Ranged);
+        }
+        self.in_combat = Some(enemy);
+    }
+
+    /// Whether the hero rides its mount past the enemies of the next
+    /// directory, which is only on the way to the destination.
+    fn rides_past(&self, next: &Location, dest: &Location) -> bool {
+        next != dest
+            && self
+                .player
+                .mount
+                .as_ref()
+                .is_some_and(|mount| random().rides_past(mount.evasion()))
+    }
+
+    /// Spawn an enemy at the current location, unless it's a safe place or
+    /// the hero is busy.
+    fn encounter_enemy(&mut self) -> Option<Character> {
+        if self.is_safe() || self.in_combat.is_some() || self.in_encounter.is_some() {
+            None
+        } else {
+            enemy::spawn(self)
+        }
+    }
+
+    /// Complete the move blocked by an enemy, if the battle is over.
+    /// The hero enters the directory where the enemy was, and goes on.
+    pub fn resume_move(&mut self) -> Result<()> {
+        if self.in_combat.is_some() {
+            return Ok(());
+        }
+        if let Some(dest) = self.pending_move.take() {
+            self.visit(self.location.go_to(&dest))?;
+            self.go_to(&dest)?;
+        }
+        Ok(())
+    }
+
+    /// The next step when exploring the directories under the given start:
+    /// a random subdirectory not searched yet or, if there's none, the
+    /// parent one, without leaving the start directory.
+    pub fn next_unexplored(&self, start: &Location) -> Option<Location> {
+        let unexplored: Vec<Location> = self
+            .location
+            .subdirectories()
+            .into_iter()
+            .filter(|location| !self.is_inspected(location))
+            .collect();
+        if let Some(location) = unexplored.choose(&mut randomizer::rng()) {
+            Some(location.clone())
+        } else if self.location != *start {
+            self.location
+                .parent()
+                .filter(|parent| parent.is_within(start))
+        } else {
+            None
+        }
+    }
+
     /// Set the hero's location to the one given, and apply related side effects.
-    pub fn visit(&mut self, location: Location) -> Result<(), anyhow::Error> {
+    pub fn visit(&mut self, location: Location) -> Result<()> {
+        // any move, even in place, leaves the arena before the hero heals
+        self.arena_cash_out().ok();
+
+        let moved = self.location != location;
         self.location = location;
-        if self.location.is_home() {
+        terrain::enter(&self.location);
+        if moved {
+            if self.location.is_home() {
+                self.gold = self.mutators.gold_at_home(self.gold);
+            }
+            self.shift_stats();
+            self.eat();
+            if Config::get().git_quests {
+                self.commits.track(&self.location);
+            }
+        }
+        quest::moved(self);
+        self.stats
+            .distance_reached(self.location.distance_from_home().len());
+        if moved {
+            self.stats.place_visited(&self.location.to_string());
+        }
+        if self.is_safe() {
+            for ally in self.party.iter_mut().chain(self.mercenary.iter_mut()) {
+                ally.restore();
+            }
             let (recovered_hp, recovered_mp, healed) = self.player.restore();
             log::heal(
                 &self.player,
@@ -139,43 +887,530 @@ impl Game {
         // In location is home, already healed of negative status
         let result = self.player.apply_status_effects();
 
-        if let Err(character::Dead) = result {
-            // drops tombstone
-            self.battle_lost();
-            return Err(anyhow::anyhow!(character::Dead));
+        if let Err(character::Dead) = result {
+            // drops tombstone
+            self.battle_lost(None);
+            return Err(GameError::Dead);
+        }
+
+        if moved {
+            self.pursue()?;
+        }
+        Ok(())
+    }
+
+    /// Count a step of the expedition, eating a ration every few of them.
+    /// The hero gets fatigued when there's nothing left to eat, until it
+    /// eats or gets back to a safe place.
+    fn eat(&mut self) {
+        if self.is_safe() {
+            self.steps_since_meal = 0;
+            self.player.fatigued = false;
+            return;
+        }
+        self.steps_since_meal += 1;
+        if self.steps_since_meal < RATION_DISTANCE {
+            return;
+        }
+        self.steps_since_meal = 0;
+        if self.take_item(&Key::Ration).is_some() {
+            self.player.fatigued = false;
+        } else if !self.player.fatigued {
+            self.player.fatigued = true;
+            log::fatigued(&self.player);
+        }
+    }
+
+    /// Scramble the hero stats when entering an anomaly, and restore them
+    /// once out of it.
+    fn shift_stats(&mut self) {
+        if terrain::current() == Terrain::Anomaly {
+            self.player.buff = terrain::anomaly_buff();
+            log::anomaly(&self.player.buff);
+        } else {
+            self.player.buff = Buff::default();
+        }
+    }
+
+    /// Enemies may follow the hero out of a battle, either walking away
+    /// from it or after fleeing, attacking first when they catch up.
+    /// They don't follow into safe places.
+    fn pursue(&mut self) -> Result<()> {
+        self.surprise = None;
+        let enemy = match (self.in_combat.take(), self.pursuer.take()) {
+            (Some(enemy), _) => {
+                if random().enemy_pursues(self.player.speed(), enemy.speed()) {
+                    Some(enemy)
+                } else {
+                    log::escape(&enemy);
+                    None
+                }
+            }
+            (None, pursuer) => pursuer,
+        };
+
+        if let Some(mut enemy) = enemy {
+            if self.is_safe() {
+                log::escape(&enemy);
+            } else {
+                log::ambush(&enemy, &self.location);
+                let (_, died) = enemy.surprise_attack(&mut self.player);
+                if let Err(character::Dead) = self.player.maybe_revive(died, false) {
+                    self.battle_lost(Some(&enemy));
+                    self.battle_xp = 0;
+                    return Err(GameError::Dead);
+                }
+                self.in_combat = Some(enemy);
+                return Ok(());
+            }
+        }
+        self.battle_xp = 0;
+        Ok(())
+    }
+
+    /// Look for chests and tombstones at the current location and report
+    /// how dangerous it is.
+    /// Remembers previously visited locations for consistency.
+    pub fn inspect(&mut self) {
+        if let Some(mut chest) = self.tombstones.remove(&self.location.to_string()) {
+            let (items, gold) = chest.pick_up(self);
+            log::tombstone(chest.owner.as_deref(), &items, gold);
+            quest::tombstone(self);
+        }
+
+        if !self.is_inspected(&self.location) {
+            self.inspected.insert(self.location.clone());
+            self.inspected_at
+                .insert(self.location.to_string(), config::now());
+            if let Some(chest) = Chest::generate(self) {
+                if !random().chest_trapped() {
+                    self.open_chest(chest);
+                } else if random().trap_detected(self.player.disarms_traps()) {
+                    self.trapped.insert(self.location.to_string(), chest);
+                } else {
+                    self.spring_trap();
+                    self.open_chest(chest);
+                }
+            }
+            if Biome::of(&self.location) == Biome::Forest {
+                self.gather(Material::Herb, 1 + random().range(FOREST_HERBS));
+            }
+        }
+        if self.trapped.contains_key(&self.location.to_string()) {
+            log::trap_detected();
+        }
+
+        let mut danger = enemy::danger(self);
+        if self.weather.hides_bosses() {
+            danger.bosses.clear();
+        }
+        log::danger(&danger);
+        log::terrain(Terrain::of(&self.location));
+        log::weather(self.weather, false);
+    }
+
+    /// Search the current location for the ingredients and food its biome
+    /// provides: herbs and mushrooms in forests, berries and rations in
+    /// meadows. Each location yields less every time, and the noise may draw
+    /// a nearby enemy.
+    pub fn forage(&mut self) -> Result<()> {
+        if self.in_combat.is_some() {
+            bail!(GameError::InCombat("forage"));
+        }
+        if self.location.is_home() {
+            bail!("There's nothing to forage at home.");
+        }
+        let biome = Biome::of(&self.location);
+        if biome == Biome::Mountain {
+            bail!("Nothing grows in the mountains, try mining instead.");
+        }
+        let foraged = self.foraged.entry(self.location.to_string()).or_default();
+        let left = FORAGE_LIMIT - *foraged;
+        if left <= 0 {
+            bail!("This place has been picked clean.");
+        }
+        *foraged += 1;
+
+        let found = 1 + random().range(left);
+        if biome == Biome::Forest {
+            self.gather(Material::Herb, found);
+            self.gather(Material::Mushroom, found / 2);
+        } else {
+            let rations = found / 2;
+            self.gather(Material::Berry, found - rations);
+            for _ in 0..rations {
+                self.add_item(Box::new(Ration::new()));
+            }
+            log::foraged(rations);
+        }
+
+        // the noise may draw an enemy around, which announces itself
+        if random().forage_ambushed() {
+            if let Some(enemy) = self.encounter_enemy() {
+                self.engage(enemy);
+            }
+        }
+        Ok(())
+    }
+
+    /// Dig the current location with a pickaxe for ore, finding a gem
+    /// every few swings at the same vein. Only mountains can be mined, and
+    /// the noise may wake a golem up.
+    pub fn mine(&mut self) -> Result<()> {
+        if self.in_combat.is_some() {
+            bail!(GameError::InCombat("mine"));
+        }
+        if Biome::of(&self.location) != Biome::Mountain {
+            bail!("There's nothing to mine here, look for mountains in the system directories.");
+        }
+        if !self.inventory.contains_key(&Key::Pickaxe) {
+            bail!("A pickaxe is needed to mine.");
+        }
+        self.dig();
+        Ok(())
+    }
+
+    /// Swing the pickaxe at the current location, regardless of its biome.
+    fn dig(&mut self) {
+        let depth = self.mined.entry(self.location.to_string()).or_default();
+        *depth += 1;
+        let found_gem = *depth % GEM_DEPTH == 0;
+        self.gather(Material::Ore, 1 + random().range(MINE_ORE));
+        if found_gem {
+            let gem = *material::GEMS.choose(&mut randomizer::rng()).unwrap();
+            self.gather(gem, 1);
+        }
+
+        if random().pickaxe_breaks() {
+            self.take_item(&Key::Pickaxe);
+            log::pickaxe_broke();
+        }
+        if random().golem_wakes() {
+            let golem = enemy::golem(self);
+            log::enemy_appears(&golem, &self.location);
+            self.engage(golem);
+        }
+    }
+
+    /// Put the given materials in the hero's pouch.
+    pub fn gather(&mut self, material: Material, count: i32) {
+        if count <= 0 {
+            return;
+        }
+        *self.materials.entry(material).or_default() += count;
+        log::gathered(material, count);
+    }
+
+    /// Have the witch met on the road brew the given recipe with the
+    /// gathered materials. The witch leaves afterwards.
+    pub fn brew(&mut self, recipe: &str) -> Result<()> {
+        if !matches!(self.in_encounter, Some(npc::Encounter::Witch)) {
+            bail!("There is no witch here to brew a potion.");
+        }
+        let names = material::RECIPES.iter().map(|recipe| recipe.name);
+        let Some(recipe) = fuzzy::resolve(recipe, names)?.and_then(Recipe::by_name) else {
+            bail!("The witch doesn't know how to brew {}.", recipe);
+        };
+        if !recipe.can_brew(&self.materials) {
+            bail!("Not enough materials to brew {}.", recipe.name);
+        }
+
+        let item = recipe.brew(&mut self.materials, self.player.level);
+        log::brewed(&item.to_string());
+        self.add_item(item);
+        self.in_encounter = None;
+        Ok(())
+    }
+
+    /// Try to disarm the trap of the chest found at the current location.
+    /// The chest is opened either way, but a failed attempt springs the trap.
+    pub fn disarm(&mut self) -> Result<()> {
+        if self.in_combat.is_some() {
+            bail!(GameError::InCombat("disarm a trap"));
+        }
+        let Some(chest) = self.trapped.remove(&self.location.to_string()) else {
+            bail!("There's no trapped chest here.");
+        };
+        if random().disarm_succeeds(self.player.disarms_traps()) {
+            log::trap_disarmed(&self.player);
+        } else {
+            self.spring_trap();
+        }
+        self.open_chest(chest);
+        Ok(())
+    }
+
+    fn open_chest(&mut self, mut chest: Chest) {
+        let mut skipped = filter::Skipped::default();
+        chest.filter(self, &mut skipped);
+        let (items, gold) = chest.pick_up(self);
+        log::chest(&items, gold);
+        log::loot_skipped(&self.player, &skipped);
+        let event = journal::Event::Chest;
+        self.journal
+            .record(&self.name, &self.player, &self.location, event);
+        self.stats.chests_found += 1;
+        quest::chest(self);
+    }
+
+    /// Hurt and poison the hero with a chest trap. Traps are never deadly
+    /// by themselves, but the poison may be.
+    fn spring_trap(&mut self) {
+        let damage = self.player.max_hp() * TRAP_DAMAGE / 100;
+        let damage = std::cmp::min(damage, self.player.current_hp - 1);
+        self.player.current_hp -= damage;
+        self.player.status_effect = Some(character::StatusEffect::Poison);
+        log::trap_sprung(&self.player, damage);
+    }
+
+    /// Perform the banishing ritual at the current location, consuming gold
+    /// and a stone so that enemies never appear here again.
+    pub fn banish(&mut self) -> Result<()> {
+        if self.pacified.contains(&self.location) {
+            bail!("This place is already pacified.");
+        }
+        if self.in_combat.is_some() {
+            bail!(GameError::InCombat("perform the ritual"));
+        }
+        if self.gold < BANISH_COST {
+            bail!(GameError::InsufficientGold);
+        }
+
+        let offering = BANISH_OFFERINGS
+            .iter()
+            .find(|key| self.inventory.contains_key(key));
+        if let Some(offering) = offering {
+            let items = self.inventory.get_mut(offering).unwrap();
+            items.pop();
+            if items.is_empty() {
+                self.inventory.remove(offering);
+            }
+        } else {
+            bail!("A stone is required as offering.");
+        }
+
+        self.gold -= BANISH_COST;
+        self.pacified.insert(self.location.clone());
+        log::banish(&self.location, BANISH_COST);
+        Ok(())
+    }
+
+    /// Build a camp at the current location that works as a secondary home.
+    pub fn build_outpost(&mut self) -> Result<()> {
+        if self.is_safe() {
+            bail!("There's already a safe place here.");
+        }
+        if self.in_combat.is_some() {
+            bail!(GameError::InCombat("build an outpost"));
+        }
+        if self.gold < OUTPOST_COST {
+            bail!(GameError::InsufficientGold);
+        }
+
+        self.gold -= OUTPOST_COST;
+        self.outposts.push(self.location.clone());
+        log::outpost_built(&self.location, OUTPOST_COST);
+        Ok(())
+    }
+
+    /// Become a member of the given guild. Only allowed at home.
+    pub fn join_guild(&mut self, guild: guild::Guild) -> Result<()> {
+        if !self.location.is_home() {
+            bail!(GameError::NotAtHome("join a guild"));
+        }
+        if let Some(membership) = &self.guild {
+            bail!("Already a member of the {} guild.", membership.guild);
+        }
+
+        let membership = guild::Membership::new(guild);
+        log::guild(&membership);
+        self.guild = Some(membership);
+        Ok(())
+    }
+
+    /// Fight the next wave of the arena run, starting one if needed, and
+    /// add its payout to the pot. The pot is lost if the hero dies.
+    /// Only allowed at home.
+    pub fn arena_wave(&mut self) -> Result<()> {
+        if !self.location.is_home() {
+            bail!(GameError::NotAtHome("enter the arena"));
+        }
+        if self.in_combat.is_some() {
+            bail!(GameError::InCombat("enter the arena"));
+        }
+
+        let level = self.player.level;
+        let arena = self.arena.get_or_insert_with(Arena::default);
+        let enemies = arena.next_wave(&self.player);
+        log::arena_wave(arena.wave);
+        for enemy in enemies {
+            log::enemy_appears(&enemy, &self.location);
+            self.in_combat = Some(enemy);
+            while self.in_combat.is_some() {
+                if let Err(err) = self.battle_round() {
+                    self.arena = None;
+                    return Err(err);
+                }
+            }
+        }
+
+        let arena = self.arena.as_mut().unwrap();
+        let payout = arena.wave_cleared(level);
+        log::arena_wave_cleared(arena, payout);
+        self.stats.arena_wave_reached(arena.wave);
+        Ok(())
+    }
+
+    /// End the arena run, collecting the gold in the pot.
+    pub fn arena_cash_out(&mut self) -> Result<()> {
+        let Some(arena) = self.arena.take() else {
+            bail!("Not in the arena.");
+        };
+        self.gold += arena.pot;
+        log::arena_cashed_out(&arena);
+        Ok(())
+    }
+
+    /// Fight back to back every boss defeated before, recovering only part
+    /// of the hp in between. Only allowed at home.
+    pub fn boss_rush(&mut self) -> Result<()> {
+        if !self.completed {
+            bail!("The boss rush is unlocked by defeating gorthaur.");
+        }
+        if !self.location.is_home() {
+            bail!(GameError::NotAtHome("start a boss rush"));
+        }
+        if self.in_combat.is_some() {
+            bail!(GameError::InCombat("start a boss rush"));
+        }
+        let bosses: Vec<&str> = enemy::BOSSES
+            .into_iter()
+            .filter(|name| self.stats.bestiary.contains_key(*name))
+            .collect();
+        if bosses.is_empty() {
+            bail!("No bosses defeated yet.");
+        }
+
+        let mut rounds = 0;
+        for (index, name) in bosses.iter().enumerate() {
+            if index > 0 {
+                let recovered = self.player.max_hp() * BOSS_RUSH_HEAL / 100;
+                self.player.update_hp(recovered).unwrap();
+            }
+            let (class, level) = enemy::boss(name, &self.player).unwrap();
+            let enemy = Character::new(class, level);
+            log::enemy_appears(&enemy, &self.location);
+            self.in_combat = Some(enemy);
+            self.in_boss_rush = true;
+            while self.in_combat.is_some() {
+                let result = self.battle_round();
+                rounds += 1;
+                if result.is_err() {
+                    self.in_boss_rush = false;
+                    return result;
+                }
+            }
+        }
+        self.in_boss_rush = false;
+
+        log::boss_rush_cleared(bosses.len(), rounds);
+        self.stats.boss_rush_cleared(rounds);
+        if let Some(run) = &mut self.speedrun {
+            if let Some(elapsed) = run.split(Split::BossRush) {
+                log::split(Split::BossRush, elapsed);
+            }
         }
         Ok(())
     }
 
-    /// Look for chests and tombstones at the current location.
-    /// Remembers previously visited locations for consistency.
-    pub fn inspect(&mut self) {
-        if let Some(mut chest) = self.tombstones.remove(&self.location.to_string()) {
-            let (items, gold) = chest.pick_up(self);
-            log::tombstone(&items, gold);
-            quest::tombstone(self);
+    /// Take the bounty at the given position of the board. Only allowed at home.
+    pub fn accept_bounty(&mut self, number: usize) -> Result<()> {
+        if !self.location.is_home() {
+            bail!(GameError::NotAtHome("take bounties"));
         }
+        self.bounties.refresh(self.player.level);
+        let bounty = self.bounties.accept(number)?;
+        log::bounty_accepted(bounty);
+        Ok(())
+    }
 
-        if !self.inspected.contains(&self.location) {
-            self.inspected.insert(self.location.clone());
-            if let Some(mut chest) = Chest::generate(self) {
-                let (items, gold) = chest.pick_up(self);
-                log::chest(&items, gold);
-                quest::chest(self);
-            }
+    /// Collect the rewards of the fulfilled bounties. Only allowed at home.
+    pub fn turn_in_bounties(&mut self) -> Result<()> {
+        if !self.location.is_home() {
+            bail!(GameError::NotAtHome("turn in bounties"));
         }
+        let (gold, reputation) = self.bounties.turn_in()?;
+        self.gold += gold;
+        log::bounties_turned_in(gold, reputation, self.bounties.reputation);
+        Ok(())
+    }
+
+    /// Whether the hero is at one of the outposts.
+    pub fn at_outpost(&self) -> bool {
+        self.outposts.contains(&self.location)
+    }
+
+    /// Whether the hero is at home or at an outpost, where it heals and
+    /// no enemies are met on the way.
+    pub fn is_safe(&self) -> bool {
+        self.location.is_home() || self.at_outpost()
+    }
+
+    /// Whether the given location was already searched for chests
+    /// and its treasure didn't respawn yet.
+    pub fn is_inspected(&self, location: &Location) -> bool {
+        let respawned = self
+            .inspected_at
+            .get(&location.to_string())
+            .is_some_and(|inspected_at| Config::get().chest_respawned(*inspected_at));
+        self.inspected.contains(location) && !respawned
     }
 
     pub fn add_item(&mut self, item: Box<dyn Item>) {
         let key = item.key();
         let entry = self.inventory.entry(item.key()).or_default();
         entry.push(item);
+        self.update_load();
         quest::item_added(self, key);
     }
 
+    /// The total weight of the hero's items, including the equipped ones.
+    pub fn load(&self) -> i32 {
+        let carried: i32 = self
+            .inventory
+            .iter()
+            .map(|(key, items)| key.weight() * items.len() as i32)
+            .sum();
+        let sword = self
+            .player
+            .sword
+            .as_ref()
+            .map_or(0, |sword| sword.key().weight());
+        let shield = self
+            .player
+            .shield
+            .as_ref()
+            .map_or(0, |shield| shield.key().weight());
+        let amulet = self
+            .player
+            .amulet
+            .as_ref()
+            .map_or(0, |_| Key::Amulet.weight());
+        carried + sword + shield + amulet
+    }
+
+    /// Check whether the hero carries more than it can, after the
+    /// inventory changes.
+    pub fn update_load(&mut self) {
+        self.player.encumbered = self.load() > self.player.carry_capacity();
+    }
+
     pub fn use_item(&mut self, name: Key) -> Result<()> {
         // get all items of that type and use one
         // if there are no remaining, drop the type from the inventory
+        if name.is_throwable() && self.inventory.contains_key(&name) && self.in_combat.is_none() {
+            bail!(GameError::NotInCombat);
+        }
         if let Some(mut items) = self.inventory.remove(&name) {
             if let Some(mut item) = items.pop() {
                 item.apply(self);
@@ -185,6 +1420,7 @@ impl Game {
             if !items.is_empty() {
                 self.inventory.insert(name, items);
             }
+            self.update_load();
 
             Ok(())
         } else if let Some(ring) = self.player.unequip_ring(&name) {
@@ -195,9 +1431,26 @@ impl Game {
             quest::item_used(self, ring.key());
             self.add_item(Box::new(ring));
             Ok(())
+        } else if name == Key::Amulet && self.player.amulet.is_some() {
+            // like rings, using the worn amulet takes it off
+            let amulet = self.player.amulet.take().unwrap();
+            quest::item_used(self, amulet.key());
+            self.add_item(Box::new(amulet));
+            Ok(())
         } else {
-            bail!("item not found.")
+            bail!(GameError::UnknownItem(name.to_string()))
+        }
+    }
+
+    /// Remove one item of the given type from the inventory.
+    pub fn take_item(&mut self, key: &Key) -> Option<Box<dyn Item>> {
+        let items = self.inventory.get_mut(key)?;
+        let item = items.pop();
+        if items.is_empty() {
+            self.inventory.remove(key);
         }
+        self.update_load();
+        item
     }
 
     pub fn inventory(&self) -> HashMap<&Key, usize> {
@@ -209,30 +1462,51 @@ impl Game {
 
     pub fn describe(&self, key: Key) -> Result<(String, String)> {
         let (display, description) = match key {
-            Key::Sword if self.player.sword.is_some() => self
-                .player
-                .sword
-                .as_ref()
-                .map(|s| (s.to_string(), s.describe()))
-                .unwrap(),
-            Key::Shield if self.player.shield.is_some() => self
-                .player
-                .shield
-                .as_ref()
-                .map(|s| (s.to_string(), s.describe()))
-                .unwrap(),
+            key if key.is_weapon()
+                && self.player.sword.as_ref().is_some_and(|w| w.key() == key) =>
+            {
+                self.player
+                    .sword
+                    .as_ref()
+                    .map(|s| (s.to_string(), s.describe()))
+                    .unwrap()
+            }
+            key if key.is_shield()
+                && self.player.shield.as_ref().is_some_and(|s| s.key() == key) =>
+            {
+                self.player
+                    .shield
+                    .as_ref()
+                    .map(|s| (s.to_string(), s.describe()))
+                    .unwrap()
+            }
             Key::Ring(ref ring) if self.player.left_ring.as_ref() == Some(ring) => {
                 (ring.to_string(), ring.describe())
             }
             Key::Ring(ref ring) if self.player.right_ring.as_ref() == Some(ring) => {
                 (ring.to_string(), ring.describe())
             }
+            Key::Horse | Key::Drake
+                if self.player.mount.as_ref().is_some_and(|m| m.key() == key) =>
+            {
+                self.player
+                    .mount
+                    .as_ref()
+                    .map(|m| (m.to_string(), m.describe()))
+                    .unwrap()
+            }
+            Key::Amulet if self.player.amulet.is_some() => self
+                .player
+                .amulet
+                .as_ref()
+                .map(|a| (a.to_string(), a.describe()))
+                .unwrap(),
             _ => {
                 if let Some(items) = self.inventory.get(&key) {
                     let item = items.first().unwrap();
                     (item.to_string(), item.describe())
                 } else {
-                    bail!("item {} not found.", key)
+                    bail!(GameError::UnknownItem(key.to_string()))
                 }
             }
         };
@@ -244,11 +1518,42 @@ impl Game {
     /// and start a battle if that fails.
     /// Return Ok(true) if a battle took place, Ok(false) if it was avoided,
     /// Err<Dead> if the character dies.
-    pub fn battle_round(&mut self) -> Result<(), anyhow::Error> {
+    pub fn battle_round(&mut self) -> Result<()> {
+        self.round(false)
+    }
+
+    /// Play a battle round where the hero braces instead of attacking,
+    /// taking half the damage from the enemy and recovering some mp.
+    pub fn defend(&mut self) -> Result<()> {
+        self.round(true)
+    }
+
+    fn round(&mut self, defend: bool) -> Result<()> {
+        if let Some(enemy) = &self.in_combat {
+            let risky = !defend && !self.cornered() && enemy.level > self.player.level;
+            self.check_nerve(risky)?;
+        }
         if let Some(mut enemy) = self.in_combat.take() {
-            // Player attacks
-            let (xp, _) = self.player.attack(&mut enemy);
-            self.battle_xp += xp;
+            self.auto_potion(Config::get().auto_potion_hp);
+            self.initiative(&mut enemy)?;
+
+            // Player attacks, or braces for the enemy blow
+            if defend {
+                let recovered = self.player.max_mp() * DEFEND_MP / 100;
+                let recovered = self.player.update_mp(recovered);
+                log::defend(&self.player, recovered);
+            } else if enemy.current_hp > 0 {
+                let (xp, _) = self.player.attack(&mut enemy);
+                self.battle_xp += xp;
+            }
+
+            // Party members and mercenary attack
+            for ally in self.party.iter_mut().chain(self.mercenary.iter_mut()) {
+                if enemy.current_hp > 0 {
+                    // enemy death is checked below
+                    let _ = ally.attack(&mut enemy);
+                }
+            }
 
             if enemy.current_hp <= 0 {
                 self.battle_won(&enemy, self.battle_xp);
@@ -256,108 +1561,789 @@ impl Game {
                 return Ok(());
             }
 
-            // Enemy attacks
-            let (_, died) = enemy.attack(&mut self.player);
-            if let Err(character::Dead) = self.player.maybe_revive(died, false) {
-                self.battle_lost();
-                self.battle_xp = 0;
-                return Err(anyhow::anyhow!(character::Dead));
+            // Enemy acts according to its behavior, attacking either the hero
+            // or one of its allies unless it does something else
+            let action = self.enemy_action(&mut enemy);
+            if action == Action::Flee {
+                return Ok(());
+            }
+            if action.attacks() {
+                if let Some(index) = self.enemy_target() {
+                    self.ally_attacked(index, &mut enemy);
+                } else {
+                    self.player.defending = defend;
+                    let died = self.enemy_attacks(&mut enemy);
+                    self.player.defending = false;
+                    if let Err(character::Dead) = self.player.maybe_revive(died, false) {
+                        self.battle_lost(Some(&enemy));
+                        self.battle_xp = 0;
+                        return Err(GameError::Dead);
+                    }
+                    if self.countered(&enemy) {
+                        return Ok(());
+                    }
+                }
             }
 
             // Status effects
             if let Err(character::Dead) = self.player.apply_status_effects() {
-                self.battle_lost();
+                self.battle_lost(Some(&enemy));
                 self.battle_xp = 0;
-                return Err(anyhow::anyhow!(character::Dead));
+                return Err(GameError::Dead);
             }
             enemy.apply_status_effects().unwrap_or_default();
+            self.player.regenerate();
+            self.player.wear_off_boost();
+            enemy.wear_off_boost();
 
             // Battle is not over, put the enemy back
             self.in_combat = Some(enemy);
             log::status(self);
         } else {
-            bail!("Not in combat.");
+            bail!(GameError::NotInCombat);
+        }
+        Ok(())
+    }
+
+    /// The enemy strikes the hero's mount, which bolts for good if it
+    /// runs out of hp.
+    fn mount_attacked(&mut self, enemy: &Character) {
+        let (damage, _) = enemy.damage(&self.player);
+        if let Some(mut mount) = self.player.mount.take() {
+            let alive = mount.hurt(damage);
+            log::mount_hurt(&self.player, &mount, damage);
+            if alive {
+                self.player.mount = Some(mount);
+            } else {
+                self.update_load();
+            }
+        }
+    }
+
+    /// A shaken hero may refuse to take a risk, like attacking a stronger
+    /// enemy or venturing further from home.
+    fn check_nerve(&self, risky: bool) -> Result<()> {
+        if risky && self.player.mood() == Mood::Shaken && random().trembles() {
+            log::trembles(&self.player);
+            bail!(GameError::LowMorale);
         }
         Ok(())
     }
 
-    pub fn player_flee(&mut self) -> Result<(), anyhow::Error> {
+    /// Getting out of a battle barely alive shakes the hero.
+    fn close_call(&mut self) {
+        if self.player.current_hp * 100 < self.player.max_hp() * morale::CLOSE_CALL_HP {
+            self.player.update_morale(morale::CLOSE_CALL);
+        }
+    }
+
+    /// Whether the battle is an arena or boss rush fight, which neither
+    /// side can back out of.
+    fn cornered(&self) -> bool {
+        self.arena.is_some() || self.in_boss_rush
+    }
+
+    /// Play the enemy turn according to its class behavior, returning
+    /// the chosen action. A fleeing enemy ends the battle and comes back
+    /// with a fresh ally to ambush the hero. Cornered cowards attack
+    /// instead.
+    fn enemy_action(&mut self, enemy: &mut Character) -> Action {
+        let mut action = enemy.class.behavior.decide(enemy);
+        if action == Action::Flee && self.cornered() {
+            action = Action::Attack;
+        }
+        enemy.defending = false;
+        let healed = behavior::act(enemy, action);
+        match action {
+            Action::Attack => {}
+            Action::Rage => log::rage(enemy),
+            Action::Heal => log::heal_item(enemy, "Heal", healed, 0, false),
+            Action::Brace => log::defend(enemy, 0),
+            Action::Flee => {
+                log::enemy_flees(enemy);
+                self.battle_over();
+                self.pursuer = Some(Character::new(enemy.class.clone(), enemy.level));
+            }
+        }
+        action
+    }
+
+    /// Reset the battle state when it ends without a winner.
+    fn battle_over(&mut self) {
+        self.battle_xp = 0;
+        self.surprise = None;
+        self.taunted = 0;
+        self.blinded = false;
+        self.player.recharge_amulet();
+        self.player.coating = None;
+    }
+
+    /// Hurt the enemy in battle with a thrown item, winning the battle if
+    /// it falls.
+    pub fn hurt_enemy(&mut self, damage: i32) {
         if let Some(mut enemy) = self.in_combat.take() {
-            let success = random().run_away_succeeds(
-                self.player.level,
-                enemy.level,
-                self.player.speed(),
-                enemy.speed(),
-            );
-            log::run_away(&self.player, success);
-            if success {
+            log::attack(&enemy, &character::AttackType::Regular, damage, 0);
+            if let Err(character::Dead) = enemy.update_hp(-damage) {
+                self.battle_won(&enemy, self.battle_xp);
                 self.battle_xp = 0;
             } else {
-                // enemy attacks
-                let (_, died) = enemy.attack(&mut self.player);
+                self.in_combat = Some(enemy);
+            }
+        }
+    }
+
+    /// The ally the enemy goes for, as an index into the party followed by
+    /// the mercenary, or None to strike the hero. Enemies prey on whoever
+    /// has the least hp left, unless taunted into attacking the hero.
+    fn enemy_target(&mut self) -> Option<usize> {
+        if self.taunted > 0 {
+            self.taunted -= 1;
+            return None;
+        }
+        let weakest = self
+            .party
+            .iter()
+            .chain(self.mercenary.iter())
+            .enumerate()
+            .min_by_key(|(_, ally)| ally.current_hp)?;
+        if weakest.1.current_hp < self.player.current_hp {
+            Some(weakest.0)
+        } else {
+            None
+        }
+    }
+
+    /// The enemy strikes the hero, who may parry the blow with its shield
+    /// and strike back instead. Some blows land on the hero's mount.
+    fn enemy_attacks(&mut self, enemy: &mut Character) -> Result<(), character::Dead> {
+        if self.player.mount.is_some() && random().mount_hit() {
+            self.mount_attacked(enemy);
+            return Ok(());
+        }
+        if random().parries(self.player.parry(enemy)) {
+            log::parry(&self.player);
+            let (xp, _) = self.player.attack(enemy);
+            self.battle_xp += xp;
+            return Ok(());
+        }
+        let (_, died) = enemy.attack(&mut self.player);
+        died
+    }
+
+    /// Win the battle if the enemy fell to a counterattack. Returns whether
+    /// it did.
+    fn countered(&mut self, enemy: &Character) -> bool {
+        if enemy.current_hp > 0 {
+            return false;
+        }
+        self.battle_won(enemy, self.battle_xp);
+        self.battle_xp = 0;
+        true
+    }
+
+    /// Resolve the free strike of a surprised battle, if any.
+    fn initiative(&mut self, enemy: &mut Character) -> Result<()> {
+        match self.surprise.take() {
+            Some(Surprise::Hero) => {
+                log::surprise(&self.player);
+                let (xp, _) = self.player.surprise_attack(enemy);
+                self.battle_xp += xp;
+            }
+            Some(Surprise::Ranged) => self.shoot(enemy),
+            Some(Surprise::Enemy) => {
+                log::surprise(enemy);
+                let (_, died) = enemy.surprise_attack(&mut self.player);
                 if let Err(character::Dead) = self.player.maybe_revive(died, false) {
-                    self.battle_lost();
+                    self.battle_lost(Some(enemy));
                     self.battle_xp = 0;
-                    return Err(anyhow::anyhow!(character::Dead));
+                    return Err(GameError::Dead);
+                }
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Open the battle with a shot of the ranged weapon, as long as there
+    /// are arrows left for a bow. Classes trained with the weapon never
+    /// miss it.
+    fn shoot(&mut self, enemy: &mut Character) {
+        let Some(weapon) = self.player.sword.as_ref().map(|sword| sword.key()) else {
+            return;
+        };
+        if weapon == Key::Bow && self.take_item(&Key::Arrow).is_none() {
+            return;
+        }
+        log::shoot(&self.player, &weapon);
+        let (xp, _) = if self.player.class.affinity(&weapon) > 0 {
+            self.player.surprise_attack(enemy)
+        } else {
+            self.player.attack(enemy)
+        };
+        self.battle_xp += xp;
+    }
+
+    /// Drink a potion if the hero's hp is below the given percentage of
+    /// its maximum, so battles started by moving around can use them too.
+    fn auto_potion(&mut self, threshold: i32) {
+        if self.player.current_hp * 100 < self.player.max_hp() * threshold
+            && self.inventory.contains_key(&Key::Potion)
+        {
+            log::auto_potion(&self.player, threshold);
+            self.use_item(Key::Potion).unwrap();
+        }
+    }
+
+    /// The enemy attacks the party member at the given index, or the
+    /// mercenary if past the party members. Killed allies leave the party.
+    fn ally_attacked(&mut self, index: usize, enemy: &mut Character) {
+        if let Some(member) = self.party.get_mut(index) {
+            let (_, died) = enemy.attack(member);
+            if died.is_err() {
+                log::battle_lost(member);
+                self.party.remove(index);
+                self.player.update_morale(morale::COMPANION_DIED);
+            }
+        } else if let Some(mercenary) = &mut self.mercenary {
+            let (_, died) = enemy.attack(mercenary);
+            if died.is_err() {
+                log::mercenary_died(mercenary);
+                self.mercenary = None;
+                self.player.update_morale(morale::COMPANION_DIED);
+            }
+        }
+    }
+
+    /// Create a new hero of the given class to join the party.
+    /// Only allowed at home.
+    pub fn recruit(&mut self, class_name: &str) -> Result<()> {
+        if !self.location.is_home() {
+            bail!(GameError::NotAtHome("recruit heroes"));
+        }
+        if self.party.len() + 1 >= PARTY_SIZE {
+            bail!("The party is full.");
+        }
+
+        self.check_class_unlocked(class_name)?;
+        if let Some(class) = character::class::Class::player_by_name(class_name) {
+            let member = Character::new(class.clone(), 1);
+            log::party_joined(&member);
+            self.party.push(member);
+            Ok(())
+        } else {
+            bail!(GameError::UnknownClass);
+        }
+    }
+
+    /// Fail if the given class is an advanced one whose base class wasn't
+    /// mastered yet. Unknown classes are left for the caller to report.
+    pub fn check_class_unlocked(&self, class_name: &str) -> Result<()> {
+        if let Some(class) = character::class::Class::player_by_name(class_name) {
+            if !self.stats.unlocks(class) {
+                bail!(
+                    "The {} class is locked until the {} class is mastered.",
+                    class.name,
+                    class.requires.as_ref().unwrap()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Count the levels gained and bosses beaten towards mastering the hero
+    /// class, announcing the advanced classes it unlocks once mastered.
+    pub fn track_mastery(&mut self, levels: i32, bosses: i32) {
+        let class = &self.player.class.name;
+        if self.stats.class_played(class, levels, bosses) {
+            log::class_mastered(class, &character::class::Class::advanced(class));
+        }
+    }
+
+    /// Remove the first party member of the given class.
+    pub fn dismiss(&mut self, class_name: &str) -> Result<()> {
+        if let Some(index) = self.party.iter().position(|m| m.name() == class_name) {
+            let member = self.party.remove(index);
+            log::party_left(&member);
+            Ok(())
+        } else {
+            bail!("No {} in the party.", class_name);
+        }
+    }
+
+    /// Buy an insurance policy covering part of the gold and, optionally,
+    /// one item type. Only allowed at home.
+    pub fn insure(&mut self, item: Option<Key>) -> Result<()> {
+        if !self.location.is_home() {
+            bail!(GameError::NotAtHome("buy insurance"));
+        }
+        if self.insurance.is_some() {
+            bail!("Already insured.");
+        }
+        let price = insurance::price(&self.player);
+        if self.gold < price {
+            bail!(GameError::InsufficientGold);
+        }
+
+        self.gold -= price;
+        let policy = insurance::Policy::new(item);
+        log::insurance(&policy, price);
+        self.insurance = Some(policy);
+        Ok(())
+    }
+
+    /// Charge the insurance premium after a battle. If the hero can't
+    /// afford it, the policy lapses.
+    fn pay_premium(&mut self) {
+        if self.insurance.is_some() {
+            let premium = insurance::premium(&self.player);
+            if self.gold >= premium {
+                self.gold -= premium;
+            } else {
+                log::insurance_lapsed();
+                self.insurance = None;
+            }
+        }
+    }
+
+    /// Return the covered gold and item after a death, ending the policy.
+    fn collect_insurance(&mut self) {
+        let claim = self
+            .insurance
+            .as_mut()
+            .and_then(|policy| policy.take_claim());
+        if let Some((gold, item)) = claim {
+            self.insurance = None;
+            self.gold += gold;
+            let mut items = HashMap::new();
+            if let Some(item) = item {
+                items.insert(item.key(), 1);
+                self.add_item(item);
+            }
+            log::insurance_claim(&items, gold);
+        }
+    }
+
+    /// Pay the mercenary its cut of the battle gold. If the hero can't
+    /// afford it, the mercenary leaves.
+    fn pay_mercenary(&mut self, gold: i32) {
+        if let Some(mercenary) = &self.mercenary {
+            let wage = mercenary::wage(mercenary, gold);
+            if self.gold >= wage {
+                self.gold -= wage;
+                log::mercenary_paid(mercenary, wage);
+            } else {
+                log::mercenary_left(mercenary);
+                self.mercenary = None;
+            }
+        }
+    }
+
+    /// Hire a mercenary to fight alongside the hero. Only allowed at home.
+    pub fn hire_mercenary(&mut self) -> Result<()> {
+        if !self.location.is_home() {
+            bail!(GameError::NotAtHome("hire mercenaries"));
+        }
+        if self.mercenary.is_some() {
+            bail!("A mercenary is already hired.");
+        }
+
+        let mercenary = mercenary::new(&self.player);
+        let fee = mercenary::fee(&mercenary);
+        if self.gold < fee {
+            bail!(GameError::InsufficientGold);
+        }
+
+        self.gold -= fee;
+        log::mercenary_hired(&mercenary, fee);
+        self.mercenary = Some(mercenary);
+        Ok(())
+    }
+
+    pub fn player_flee(&mut self) -> Result<()> {
+        if let Some(mut enemy) = self.in_combat.take() {
+            // a pending free strike lands before trying again
+            if self.surprise == Some(Surprise::Enemy) {
+                self.initiative(&mut enemy)?;
+            }
+            let success = std::mem::take(&mut self.blinded)
+                || random().run_away_succeeds(
+                    self.player.level,
+                    enemy.level,
+                    self.player.speed(),
+                    enemy.speed(),
+                    self.weather.flee_bonus() - self.player.encumbered as i32,
+                );
+            log::run_away(&self.player, success);
+            if success {
+                self.pending_move = None;
+                self.battle_over();
+                self.player.update_morale(morale::FLEE);
+                self.close_call();
+                if random().enemy_pursues(self.player.speed(), enemy.speed()) {
+                    log::pursuit(&enemy);
+                    self.pursuer = Some(enemy);
                 }
+            } else {
+                // the enemy opens the next round
+                self.surprise = Some(Surprise::Enemy);
                 self.in_combat = Some(enemy);
             }
         } else {
-            bail!("Not in combat.");
+            bail!(GameError::NotInCombat);
         }
         Ok(())
     }
 
-    pub fn player_bribe(&mut self) -> Result<(), anyhow::Error> {
+    pub fn player_bribe(&mut self) -> Result<()> {
         if let Some(mut enemy) = self.in_combat.take() {
-            let bribe_cost = self.player.gold_gained(enemy.level) / 2;
+            let bribe_cost = self.player.gold_gained(enemy.level) * BRIBE_COST / 100;
             if self.gold >= bribe_cost && random().bribe_succeeds() {
                 self.gold -= bribe_cost;
+                self.stats.bribes += 1;
                 log::bribe(&self.player, bribe_cost);
-                self.battle_xp = 0;
+                self.battle_over();
             } else {
                 log::bribe(&self.player, 0);
                 // enemy attacks
-                let (_, died) = enemy.attack(&mut self.player);
+                let died = self.enemy_attacks(&mut enemy);
                 if let Err(character::Dead) = self.player.maybe_revive(died, false) {
-                    self.battle_lost();
+                    self.battle_lost(Some(&enemy));
                     self.battle_xp = 0;
-                    return Err(anyhow::anyhow!(character::Dead));
+                    return Err(GameError::Dead);
+                }
+                if !self.countered(&enemy) {
+                    self.in_combat = Some(enemy);
                 }
-                self.in_combat = Some(enemy);
             }
         } else {
-            bail!("Not in combat.");
+            bail!(GameError::NotInCombat);
         }
         Ok(())
     }
 
     fn battle_won(&mut self, enemy: &Character, xp: i32) {
-        let gold = self.player.gold_gained(enemy.level);
+        self.surprise = None;
+        self.taunted = 0;
+        self.blinded = false;
+        self.stats.place_fought(&self.location.to_string());
+        self.player.recharge_amulet();
+        self.player.digest_meal();
+        self.player.coating = None;
+        let mut gold = self.player.gold_gained(enemy.level);
+        if enemy.name() == "ghost" {
+            if let Some(ghost) = self.ghosts.remove(&self.location.to_string()) {
+                gold += ghost.gold;
+            }
+        }
+        let mut xp = xp;
+        if self
+            .invasion
+            .as_ref()
+            .is_some_and(|invasion| invasion.rewards(enemy, &self.location))
+        {
+            gold += gold * INVASION_BONUS / 100;
+            xp += xp * INVASION_BONUS / 100;
+        }
+        let mut gold = Terrain::of(&self.location).gold(gold);
+        if let Some(membership) = &self.guild {
+            membership
+                .guild
+                .battle_perk(&mut self.player, &mut gold, &mut xp);
+        }
+        let gold = self.mercy.reward(gold);
+        let xp = self.mercy.reward(xp);
         self.gold += gold;
         let levels_up = self.player.add_experience(xp);
-
-        let reward_items =
-            Chest::battle_loot(self).map_or(HashMap::new(), |mut chest| chest.pick_up(self).0);
+        self.player.update_morale(morale::VICTORY);
+        self.close_call();
+        self.mercy.level_reached(self.player.level);
+        self.wear_equipment();
+
+        let mut skipped = filter::Skipped::default();
+        let mut reward_items = Chest::battle_loot(self).map_or(HashMap::new(), |mut chest| {
+            chest.filter(self, &mut skipped);
+            chest.pick_up(self).0
+        });
+
+        let bosses = i32::from(enemy::is_boss(&enemy.class) && !self.in_boss_rush);
+        self.track_mastery(levels_up, bosses);
+        let loot = crate::item::chest::enemy_loot(enemy);
+        let filter = &Config::get().loot_filter;
+        for item in filter::apply(self, filter, loot, &mut skipped) {
+            *reward_items.entry(item.key()).or_insert(0) += 1;
+            self.add_item(item);
+        }
+        if enemy.name() == "shadow" && !self.in_boss_rush && enemy::shadow_hunted(self) {
+            *reward_items.entry(Key::Ring(Ring::Shadow)).or_insert(0) += 1;
+            self.add_item(Box::new(Ring::Shadow));
+        }
+        if bosses > 0 {
+            let heirloom = Heirloom::boss_drop();
+            *reward_items.entry(heirloom.key()).or_insert(0) += 1;
+            self.add_item(Box::new(heirloom));
+        }
 
         log::battle_won(self, xp, levels_up, gold, &reward_items);
+        log::loot_skipped(&self.player, &skipped);
+        if let Some(material) = material::dropped_by(enemy) {
+            self.gather(material, 1);
+        }
+        self.journal
+            .record_victory(&self.name, &self.player, &self.location, enemy);
+        // arena and boss rush fights don't count towards clearing home
+        if !self.location.is_home() {
+            enemy::record_victory(self, enemy);
+        }
+        self.stats.battle_won(&enemy.name(), gold);
+        self.stats.level_reached(self.player.level);
+        self.speedrun_splits(enemy);
+        if levels_up > 0 {
+            let level = self.player.level;
+            let event = journal::Event::LevelUp { level };
+            self.journal
+                .record(&self.name, &self.player, &self.location, event);
+        }
+        for member in &mut self.party {
+            let levels_up = member.add_experience(xp);
+            log::party_xp(member, xp, levels_up);
+        }
+        self.pay_mercenary(gold);
+        self.pay_premium();
         quest::battle_won(self, enemy, levels_up);
+
+        // boss rush rematches don't count
+        if enemy.name() == "gorthaur" && !self.in_boss_rush {
+            self.victory();
+        }
+    }
+
+    /// Defeating gorthaur wins the game: the hero enters the hall of fame
+    /// and the post-game content is unlocked. Defeating it again in the
+    /// same cycle doesn't add the hero to the hall of fame twice.
+    fn victory(&mut self) {
+        let unlocked = !self.completed;
+        self.completed = true;
+        let champion = stats::Champion {
+            name: self.name.clone(),
+            class: self.player.name(),
+            level: self.player.level,
+            deaths: self.stats.deaths,
+            cycle: self.cycle,
+        };
+        let story = self.journal.story();
+        let epilogue = &story[story.len().saturating_sub(EPILOGUE_ENTRIES)..];
+        let hall = &mut self.stats.hall_of_fame;
+        let number = match hall
+            .iter()
+            .position(|entry| entry.name == champion.name && entry.cycle == champion.cycle)
+        {
+            Some(index) => index + 1,
+            None => {
+                hall.push(champion);
+                hall.len()
+            }
+        };
+        log::victory(epilogue, number, unlocked);
+    }
+
+    /// Start over a harder world, one where enemies are stronger and the
+    /// dead are forgotten, keeping the hero with its level, equipment and
+    /// items. Only allowed at home once the game is won.
+    pub fn new_game_plus(&mut self) -> Result<()> {
+        if !self.completed {
+            bail!("New game+ is unlocked by defeating gorthaur.");
+        }
+        if !self.location.is_home() {
+            bail!(GameError::NotAtHome("start a new game+"));
+        }
+        if self.in_combat.is_some() {
+            bail!(GameError::InCombat("start a new game+"));
+        }
+        self.cycle += 1;
+        self.tombstones.clear();
+        self.ghosts.clear();
+        self.pacified.clear();
+        self.clearings.clear();
+        self.invasion = None;
+        self.hunt = None;
+        log::new_game_plus(self.cycle);
+        Ok(())
+    }
+
+    /// Wear off the hero's sword and shield after a battle.
+    fn wear_equipment(&mut self) {
+        let player = &mut self.player;
+        let broken: Vec<Key> = player
+            .sword
+            .iter_mut()
+            .chain(player.shield.iter_mut())
+            .filter_map(|piece| piece.wear(BATTLE_WEAR).then(|| piece.key()))
+            .collect();
+        for key in broken {
+            log::equipment_broke(&self.player, &key);
+        }
+    }
+
+    /// Unlearn the hero's skills, refunding the skill points spent on them
+    /// for a fee that grows with the level. Only allowed at home.
+    pub fn respec(&mut self) -> Result<()> {
+        if !self.location.is_home() {
+            bail!(GameError::NotAtHome("unlearn skills"));
+        }
+        if self.player.unlocked_skills.is_empty() {
+            bail!("No skills to unlearn.");
+        }
+        let cost = self.player.level * RESPEC_COST_PER_LEVEL;
+        if cost > self.gold {
+            bail!(GameError::InsufficientGold);
+        }
+
+        self.gold -= cost;
+        let points = self.player.unlearn_skills();
+        log::respec(&self.player, points, cost);
+        Ok(())
     }
 
-    fn battle_lost(&mut self) {
+    /// Fully repair the hero's equipment, paying for each missing
+    /// durability point. Only allowed at home or with a blacksmith.
+    pub fn repair(&mut self) -> Result<()> {
+        let blacksmith = matches!(self.in_encounter, Some(npc::Encounter::Blacksmith));
+        if !self.location.is_home() && !blacksmith {
+            bail!("Equipment can only be repaired at home or by a blacksmith.");
+        }
+        let player = &mut self.player;
+        let cost: i32 = player
+            .sword
+            .iter()
+            .chain(player.shield.iter())
+            .map(|piece| piece.repair_cost())
+            .sum();
+        if cost == 0 {
+            bail!("The equipment doesn't need repairs.");
+        }
+        if cost > self.gold {
+            bail!(GameError::InsufficientGold);
+        }
+
+        self.gold -= cost;
+        for piece in player.sword.iter_mut().chain(player.shield.iter_mut()) {
+            piece.repair(MAX_DURABILITY);
+        }
+        log::repaired(&self.player, cost);
+        if blacksmith {
+            self.in_encounter = None;
+        }
+        Ok(())
+    }
+
+    /// Set a gem from the pouch in a free socket of the equipped sword or
+    /// shield, for good.
+    pub fn socket(&mut self, piece: &Key, gem: Material) -> Result<()> {
+        if self.in_combat.is_some() {
+            bail!(GameError::InCombat("set gems"));
+        }
+        if !material::GEMS.contains(&gem) {
+            bail!("Only gems can be set in sockets.");
+        }
+        if !self.materials.contains_key(&gem) {
+            bail!("No {} in the pouch.", gem);
+        }
+        let equipped = match piece {
+            key if key.is_weapon() => self.player.sword.as_mut().filter(|w| w.key() == *key),
+            key if key.is_shield() => self.player.shield.as_mut().filter(|s| s.key() == *key),
+            _ => bail!("Only weapons and shields have sockets."),
+        };
+        let Some(equipped) = equipped else {
+            bail!("No {} equipped.", piece);
+        };
+        if !equipped.socket(gem) {
+            bail!("The {} has no free sockets.", piece);
+        }
+
+        log::socketed(equipped, gem);
+        let left = self.materials.entry(gem).or_default();
+        *left -= 1;
+        if *left <= 0 {
+            self.materials.remove(&gem);
+        }
+        Ok(())
+    }
+
+    /// Record the speedrun milestones reached with this victory, if any.
+    fn speedrun_splits(&mut self, enemy: &Character) {
+        if let Some(run) = &mut self.speedrun {
+            let mut reached = Vec::new();
+            if self.player.level >= 10 {
+                reached.push(Split::Level10);
+            }
+            if enemy.class.category == character::class::Category::Legendary {
+                reached.push(Split::LegendaryKill);
+            }
+            if enemy.name() == "gorthaur" {
+                reached.push(Split::Gorthaur);
+            }
+            for split in reached {
+                if let Some(elapsed) = run.split(split) {
+                    log::split(split, elapsed);
+                }
+            }
+        }
+    }
+
+    /// Drop the hero's belongings at the current location. If the hero was
+    /// killed in battle, a quest to take revenge on the killer is assigned.
+    fn battle_lost(&mut self, killer: Option<&Character>) {
+        self.pending_move = None;
+        self.surprise = None;
+        self.taunted = 0;
+        self.blinded = false;
+        self.player.coating = None;
+
+        // Set aside what's covered by the insurance, if any
+        if let Some(mut policy) = self.insurance.take() {
+            let item = policy.item.as_ref().and_then(|key| self.take_item(key));
+            policy.file_claim(self.gold, item);
+            self.gold -= self.gold * insurance::GOLD_COVERAGE / 100;
+            self.insurance = Some(policy);
+        }
+
+        // A hero that is gone for good will haunt this location, taking
+        // part of its gold out of the tombstone
+        let location = self.location.to_string();
+        if self.hardcore == Hardcore::Wipe {
+            let ghost = enemy::Ghost::new(&self.player, self.gold);
+            self.gold -= ghost.gold;
+            self.ghosts.insert(location.clone(), ghost);
+        }
+
         // Drop hero items in the location. If there was a previous tombstone
         // merge the contents of both chests
         let mut tombstone = Chest::drop(self);
-        let location = self.location.to_string();
         if let Some(previous) = self.tombstones.remove(&location) {
             tombstone.extend(previous);
         }
         self.tombstones.insert(location, tombstone);
 
+        // the pouch is lost along with the gold
+        self.materials.clear();
+
         log::battle_lost(&self.player);
+        self.stats.deaths += 1;
+        let place = self.location.to_string();
+        self.stats.place_fought(&place);
+        self.stats.place_died(&place);
+        self.journal.record(
+            &self.name,
+            &self.player,
+            &self.location,
+            journal::Event::Death,
+        );
+        if let Some(killer) = killer {
+            quest::revenge(self, killer);
+        }
     }
 
-    pub fn use_skill(&mut self, skill_name: &str) -> Result<(), anyhow::Error> {
+    pub fn use_skill(&mut self, skill_name: &str) -> Result<()> {
         if let Some(mut enemy) = self.in_combat.take() {
             let skill = self
                 .player
@@ -367,19 +2353,22 @@ impl Game {
                 .find(|s| s.name.eq_ignore_ascii_case(skill_name));
 
             if let Some(skill) = skill {
-                if !self.player.unlocked_skills.contains(&skill.name) {
+                let rank = self.player.skill_rank(&skill.name);
+                if rank == 0 {
                     bail!("Skill not unlocked.");
                 }
 
-                if self.player.current_mp < skill.cost {
+                let cost = skill.rank_cost(rank);
+                if self.player.current_mp < cost {
                     bail!("Not enough MP to use this skill.");
                 }
-                self.player.current_mp -= skill.cost;
+                self.player.current_mp -= cost;
+                self.stats.skills_used += 1;
 
                 match skill.name.as_str() {
                     "Power Strike" => {
                         let (damage, _) = self.player.damage(&enemy);
-                        let damage = damage * 2;
+                        let damage = skill.rank_effect(rank, damage * 2);
                         log::attack(&enemy, &crate::character::AttackType::Regular, damage, 0);
                         if let Err(character::Dead) = enemy.update_hp(-damage) {
                             self.battle_won(&enemy, self.battle_xp);
@@ -388,26 +2377,45 @@ impl Game {
                         }
                     }
                     "Heal" => {
-                        let heal_amount = self.player.max_hp() / 4;
+                        let heal_amount = skill.rank_effect(rank, self.player.max_hp() / 4);
                         self.player.update_hp(heal_amount).unwrap();
                         log::heal_item(&self.player, "Heal", heal_amount, 0, false);
                     }
+                    "Taunt" => {
+                        self.taunted = skill.rank_effect(rank, TAUNT_ROUNDS);
+                        log::taunt(&self.player, self.taunted);
+                    }
                     _ => bail!("Unknown skill."),
                 }
             } else {
-                bail!("Skill not found.");
+                bail!(GameError::UnknownSkill);
             }
 
-            // Enemy attacks
-            let (_, died) = enemy.attack(&mut self.player);
+            // Enemy acts, either attacking the hero or one of its allies
+            let action = self.enemy_action(&mut enemy);
+            if action == Action::Flee {
+                return Ok(());
+            }
+            if !action.attacks() {
+                self.in_combat = Some(enemy);
+                return Ok(());
+            }
+            if let Some(index) = self.enemy_target() {
+                self.ally_attacked(index, &mut enemy);
+                self.in_combat = Some(enemy);
+                return Ok(());
+            }
+            let died = self.enemy_attacks(&mut enemy);
             if let Err(character::Dead) = self.player.maybe_revive(died, false) {
-                self.battle_lost();
+                self.battle_lost(Some(&enemy));
                 self.battle_xp = 0;
-                return Err(anyhow::anyhow!(character::Dead));
+                return Err(GameError::Dead);
+            }
+            if !self.countered(&enemy) {
+                self.in_combat = Some(enemy);
             }
-            self.in_combat = Some(enemy);
         } else {
-            bail!("Not in combat.");
+            bail!(GameError::NotInCombat);
         }
         Ok(())
     }
@@ -419,11 +2427,220 @@ impl Default for Game {
     }
 }
 
+#[cfg(test)]
+impl Game {
+    /// Engage the given enemy and run battle rounds until one of the
+    /// characters dies. Test-only shortcut for the attack command loop.
+    pub fn battle(&mut self, enemy: Character) -> Result<()> {
+        self.in_combat = Some(enemy);
+        while self.in_combat.is_some() {
+            self.battle_round()?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::character::class;
+    use crate::character::mount::{self, Mount};
     use crate::item;
+    use crate::location::tests::location_from;
+    use crate::mercy;
+
+    #[test]
+    fn test_next_unexplored() {
+        let root = std::env::temp_dir().join("rpg-test-explore");
+        std::fs::create_dir_all(root.join("a").join("b")).unwrap();
+        let location = |path: &std::path::Path| Location::from(path.to_str().unwrap()).unwrap();
+        let start = location(&root);
+        let a = location(&root.join("a"));
+        let b = location(&root.join("a").join("b"));
+
+        let mut game = Game::new();
+        game.location = start.clone();
+        assert_eq!(Some(a.clone()), game.next_unexplored(&start));
+
+        game.location = a.clone();
+        assert_eq!(Some(b.clone()), game.next_unexplored(&start));
+
+        // go back once the subdirectories are searched, but not past the start
+        game.inspected.insert(b);
+        assert_eq!(Some(start.clone()), game.next_unexplored(&start));
+        game.inspected.insert(a);
+        game.location = start.clone();
+        assert_eq!(None, game.next_unexplored(&start));
+    }
+
+    #[test]
+    fn test_pursuit() {
+        let root = std::env::temp_dir().join("rpg-test-pursuit");
+        std::fs::create_dir_all(root.join("a")).unwrap();
+        let location = |path: &std::path::Path| Location::from(path.to_str().unwrap()).unwrap();
+        let enemy = || Character::new(class::Class::random(class::Category::Common).clone(), 1);
+
+        let mut game = Game::new();
+        game.location = location(&root);
+
+        // staying in place keeps the battle going
+        game.in_combat = Some(enemy());
+        game.visit(location(&root)).unwrap();
+        assert!(game.in_combat.is_some());
+
+        // walking away, the test enemy never follows
+        game.visit(location(&root.join("a"))).unwrap();
+        assert!(game.in_combat.is_none());
+
+        // an enemy already giving chase attacks on arrival
+        game.pursuer = Some(enemy());
+        game.visit(location(&root)).unwrap();
+        assert!(game.pursuer.is_none());
+        assert!(game.in_combat.is_some());
+        assert!(game.player.current_hp < game.player.max_hp());
+
+        // but doesn't follow the hero home
+        game.in_combat = None;
+        game.pursuer = Some(enemy());
+        game.visit(Location::home()).unwrap();
+        assert!(game.in_combat.is_none());
+    }
+
+    #[test]
+    fn test_surprise() {
+        let enemy = || {
+            let mut enemy = Character::new(class::Class::enemy_by_name("orc").unwrap().clone(), 1);
+            enemy.current_hp = 1000;
+            enemy
+        };
+        let mut game = Game::new();
+
+        // a failed escape leaves the enemy a free strike for the next round
+        game.in_combat = Some(enemy());
+        game.player_flee().unwrap();
+        assert_eq!(Some(Surprise::Enemy), game.surprise);
+        let hp = game.player.current_hp;
+        game.battle_round().unwrap();
+        assert!(game.surprise.is_none());
+        let enemy_damage = game.in_combat.as_ref().unwrap().damage(&game.player).0;
+        assert_eq!(hp - 2 * enemy_damage, game.player.current_hp);
+
+        // the hero's free strike adds to its regular attack
+        game.surprise = Some(Surprise::Hero);
+        let enemy_hp = game.in_combat.as_ref().unwrap().current_hp;
+        game.battle_round().unwrap();
+        let hero_damage = game.player.damage(game.in_combat.as_ref().unwrap()).0;
+        assert_eq!(
+            enemy_hp - 2 * hero_damage,
+            game.in_combat.as_ref().unwrap().current_hp
+        );
+    }
+
+    #[test]
+    fn test_defend() {
+        let enemy = Character::new(class::Class::enemy_by_name("orc").unwrap().clone(), 5);
+        let enemy_hp = enemy.current_hp;
+        let mut game = Game::new();
+        game.player = Character::new(class::Class::player_by_name("mage").unwrap().clone(), 5);
+        let enemy_damage = enemy.damage(&game.player).0;
+        game.in_combat = Some(enemy);
+
+        game.player.current_mp = 0;
+        let hp = game.player.current_hp;
+        game.defend().unwrap();
+        assert_eq!(hp - enemy_damage / 2, game.player.current_hp);
+        assert_eq!(
+            game.player.max_mp() * DEFEND_MP / 100,
+            game.player.current_mp
+        );
+        assert_eq!(enemy_hp, game.in_combat.as_ref().unwrap().current_hp);
+        assert!(!game.player.defending);
+
+        let hp = game.player.current_hp;
+        game.battle_round().unwrap();
+        assert_eq!(hp - enemy_damage, game.player.current_hp);
+    }
+
+    #[test]
+    fn test_anomaly() {
+        let root = std::env::temp_dir().join("rpg-test-anomaly");
+        std::fs::create_dir_all(&root).unwrap();
+        let mut game = Game::new();
+
+        game.visit(Location::from(root.to_str().unwrap()).unwrap())
+            .unwrap();
+        assert!(game.player.buff.attack.abs() <= terrain::ANOMALY_STAT_SHIFT);
+
+        // stats go back to normal out of the anomaly
+        game.player.buff.attack = 10;
+        game.visit(Location::home()).unwrap();
+        assert_eq!(Buff::default(), game.player.buff);
+    }
+
+    #[test]
+    fn test_commits() {
+        let mut game = Game::new();
+        game.quests = QuestList::default();
+        game.quests.start_git();
+
+        let mut hero = Character::player();
+        hero.add_experience(3 * COMMIT_XP);
+        game.reward_commits(3);
+        assert_eq!((hero.level, hero.xp), (game.player.level, game.player.xp));
+        assert_eq!(100, game.gold);
+        assert_eq!(
+            (false, "make 10 git commits 3/10".to_string()),
+            game.quests.list()[1]
+        );
+
+        // big batches of commits are capped
+        game.reward_commits(50);
+        assert_eq!(1100, game.gold);
+        hero.add_experience(MAX_REWARDED_COMMITS as i32 * COMMIT_XP);
+        assert_eq!((hero.level, hero.xp), (game.player.level, game.player.xp));
+    }
+
+    #[test]
+    fn test_resume_move() {
+        let root = std::env::temp_dir().join("rpg-test-resume");
+        std::fs::create_dir_all(root.join("a").join("b")).unwrap();
+        let location = |path: &std::path::Path| Location::from(path.to_str().unwrap()).unwrap();
+        let dest = location(&root.join("a").join("b"));
+
+        let mut game = Game::new();
+        game.location = location(&root);
+        game.pending_move = Some(dest.clone());
+
+        // still blocked while the enemy is there
+        game.in_combat = Some(Character::player());
+        game.resume_move().unwrap();
+        assert_eq!(location(&root), game.location);
+
+        // the hero at least enters the directory where the battle was,
+        // other enemies may show up further along the way
+        game.in_combat = None;
+        game.resume_move().unwrap();
+        assert!(game.pending_move.is_none());
+        assert!(game.location.is_within(&location(&root.join("a"))));
+    }
+
+    #[test]
+    fn test_auto_potion() {
+        let mut game = Game::new();
+        game.player.current_hp = game.player.max_hp() / 4;
+
+        // no potions, nothing to do
+        game.auto_potion(30);
+        assert_eq!(game.player.max_hp() / 4, game.player.current_hp);
+
+        game.add_item(Box::new(item::Potion::new(1)));
+        game.auto_potion(0);
+        assert_eq!(1, game.inventory().len());
+
+        game.auto_potion(30);
+        assert!(game.player.current_hp > game.player.max_hp() / 4);
+        assert_eq!(0, game.inventory().len());
+    }
 
     #[test]
     fn test_inventory() {
@@ -522,6 +2739,834 @@ mod tests {
         assert_eq!(base_hp, game.player.max_hp());
     }
 
+    #[test]
+    fn test_banish() {
+        let mut game = Game::new();
+        game.location = crate::location::tests::location_from("/banished");
+        assert!(enemy::spawn(&mut game).is_some());
+
+        // requires gold and a stone
+        assert_eq!(Err(GameError::InsufficientGold), game.banish());
+        game.gold = BANISH_COST;
+        assert!(game.banish().is_err());
+        game.add_item(Box::new(item::stone::Speed));
+        game.add_item(Box::new(item::stone::Health));
+
+        assert!(game.banish().is_ok());
+        assert_eq!(0, game.gold);
+        assert!(!game.inventory().contains_key(&Key::HealthStone));
+        assert!(game.inventory().contains_key(&Key::SpeedStone));
+        assert!(enemy::spawn(&mut game).is_none());
+        assert!(game.banish().is_err());
+
+        // survives the hero's death
+        game.reset();
+        game.location = crate::location::tests::location_from("/banished");
+        assert!(enemy::spawn(&mut game).is_none());
+    }
+
+    #[test]
+    fn test_outpost() {
+        let mut game = Game::new();
+        let camp = crate::location::tests::location_from("/camp");
+
+        // can't build at home nor without gold
+        assert!(game.build_outpost().is_err());
+        game.location = camp.clone();
+        assert!(game.build_outpost().is_err());
+
+        game.gold = OUTPOST_COST;
+        assert!(game.build_outpost().is_ok());
+        assert_eq!(0, game.gold);
+        assert!(game.at_outpost());
+        assert!(game.build_outpost().is_err());
+
+        // heals on arrival
+        game.player.current_hp = 1;
+        game.visit(camp.clone()).unwrap();
+        assert_eq!(game.player.max_hp(), game.player.current_hp);
+
+        // respawn at the outpost
+        game.reset();
+        assert_eq!(camp, game.location);
+        assert!(game.at_outpost());
+    }
+
+    #[test]
+    fn test_traps() {
+        let mut game = Game::new();
+        assert!(game.disarm().is_err());
+
+        let chest = || {
+            let mut owner = Game::new();
+            owner.gold = 100;
+            Chest::drop(&mut owner)
+        };
+
+        // a failed attempt springs the trap, but the chest is opened anyway
+        game.trapped.insert(game.location.to_string(), chest());
+        game.disarm().unwrap();
+        assert!(game.gold >= 100);
+        assert!(game.player.current_hp < game.player.max_hp());
+        assert_eq!(
+            Some(character::StatusEffect::Poison),
+            game.player.status_effect
+        );
+        assert!(game.disarm().is_err());
+
+        // traps alone never kill the hero
+        game.player.current_hp = 1;
+        game.trapped.insert(game.location.to_string(), chest());
+        game.disarm().unwrap();
+        assert_eq!(1, game.player.current_hp);
+
+        // the skilled hero disarms it
+        let thief = class::Class::player_by_name("thief").unwrap();
+        game.player = Character::new(thief.clone(), 2);
+        game.player.learn_skill("Disarm Traps").unwrap();
+        let gold = game.gold;
+        game.trapped.insert(game.location.to_string(), chest());
+        game.disarm().unwrap();
+        assert_eq!(gold + 100, game.gold);
+        assert_eq!(game.player.max_hp(), game.player.current_hp);
+        assert_eq!(None, game.player.status_effect);
+    }
+
+    #[test]
+    fn test_hardcore_tiers() {
+        // only the top tier replaces the hero
+        let mut game = Game::new();
+        game.hardcore = Hardcore::Equipment;
+        game.player.add_experience(1000);
+        let level = game.player.level;
+        game.reset();
+        assert_eq!(level, game.player.level);
+        game.hardcore = Hardcore::Wipe;
+        game.reset();
+        assert_eq!(1, game.player.level);
+
+        // games saved with the on/off flag keep their behavior
+        let tier = |json: &str| serde_json::from_str::<Hardcore>(json).ok();
+        assert_eq!(Some(Hardcore::Wipe), tier("true"));
+        assert_eq!(Some(Hardcore::Equipment), tier("false"));
+        assert_eq!(Some(Hardcore::Gold), tier("1"));
+        assert_eq!(None, tier("4"));
+        assert_eq!("2", serde_json::to_string(&Hardcore::Equipment).unwrap());
+    }
+
+    #[test]
+    fn test_ghost() {
+        let mut game = Game::new();
+        game.gold = 1000;
+        game.battle_lost(None);
+        let location = game.location.to_string();
+        assert_eq!(250, game.ghosts[&location].gold);
+        let mut tombstone = game.tombstones.remove(&location).unwrap();
+        assert_eq!(750, tombstone.pick_up(&mut game).1);
+
+        // a hero that lives on doesn't haunt anything
+        let mut game = Game::new();
+        game.hardcore = Hardcore::Equipment;
+        game.battle_lost(None);
+        assert!(game.ghosts.is_empty());
+    }
+
+    #[test]
+    fn test_mercy() {
+        let mut game = Game::new();
+        for _ in 0..mercy::DEATHS {
+            assert!(!game.mercy.active);
+            game.reset();
+        }
+        assert!(game.mercy.active);
+
+        // lasts for a single hero
+        game.reset();
+        assert!(!game.mercy.active);
+
+        // not granted in ironman mode
+        game.ironman = true;
+        for _ in 0..mercy::DEATHS {
+            game.reset();
+        }
+        assert!(!game.mercy.active);
+    }
+
+    #[test]
+    fn test_mercenary() {
+        let mut game = Game::new();
+        assert!(game.hire_mercenary().is_err());
+
+        game.gold = 1000;
+        assert!(game.hire_mercenary().is_ok());
+        assert_eq!(0, game.gold);
+        assert!(game.hire_mercenary().is_err());
+
+        // the enemy targets the mercenary, which dies on the first hit
+        game.mercenary.as_mut().unwrap().current_hp = 1;
+        let mut enemy = Character::player();
+        enemy.current_hp = 100;
+        game.in_combat = Some(enemy);
+        game.battle_round().unwrap();
+        assert!(game.mercenary.is_none());
+        assert_eq!(game.player.max_hp(), game.player.current_hp);
+
+        // leaves if the wage can't be paid
+        game.mercenary = Some(mercenary::new(&game.player));
+        game.pay_mercenary(0);
+        assert!(game.mercenary.is_none());
+    }
+
+    #[test]
+    fn test_taunt() {
+        let mut game = Game::new();
+        game.player.add_experience(1000);
+        game.player.learn_skill("Taunt").unwrap();
+        let mage = class::Class::player_by_name("mage").unwrap().clone();
+        let mut mercenary = Character::new(mage, 1);
+        mercenary.current_hp = 1;
+        game.mercenary = Some(mercenary);
+        let warrior = class::Class::player_by_name("warrior").unwrap().clone();
+        game.in_combat = Some(Character::new(warrior, 10));
+
+        // taunted enemies spare the weakened mercenary
+        game.use_skill("taunt").unwrap();
+        assert_eq!(TAUNT_ROUNDS - 1, game.taunted);
+        assert!(game.player.current_hp < game.player.max_hp());
+        assert_eq!(1, game.mercenary.as_ref().unwrap().current_hp);
+
+        game.taunted = 0;
+        game.battle_round().unwrap();
+        assert!(game.mercenary.is_none());
+    }
+
+    #[test]
+    fn test_enemy_behavior() {
+        let mut game = Game::new();
+        game.player.add_experience(1000);
+        let goblin = class::Class::enemy_by_name("goblin").unwrap().clone();
+        let mut enemy = Character::new(goblin, 1);
+        enemy.scale_max_hp(10000);
+        enemy.current_hp = enemy.max_hp() / 5;
+        game.in_combat = Some(enemy);
+
+        // the wounded coward runs away and comes back with help
+        game.battle_round().unwrap();
+        assert!(game.in_combat.is_none());
+        let pursuer = game.pursuer.as_ref().unwrap();
+        assert_eq!(pursuer.max_hp(), pursuer.current_hp);
+
+        // but there's no running away from the arena
+        game.pursuer = None;
+        game.arena = Some(Arena::default());
+        let goblin = class::Class::enemy_by_name("goblin").unwrap().clone();
+        let mut enemy = Character::new(goblin, 1);
+        enemy.scale_max_hp(10000);
+        enemy.current_hp = enemy.max_hp() / 5;
+        game.in_combat = Some(enemy);
+        game.battle_round().unwrap();
+        assert!(game.in_combat.is_some());
+        assert!(game.pursuer.is_none());
+        game.in_combat = None;
+        game.arena = None;
+
+        // the wounded golem braces, taking half the damage of the next blow
+        let golem = class::Class::enemy_by_name("golem").unwrap().clone();
+        let mut enemy = Character::new(golem, 1);
+        enemy.scale_max_hp(10000);
+        enemy.current_hp = enemy.max_hp() / 3;
+        game.in_combat = Some(enemy);
+        let hero_hp = game.player.current_hp;
+        game.battle_round().unwrap();
+        let enemy = game.in_combat.as_ref().unwrap();
+        assert!(enemy.defending);
+        assert_eq!(hero_hp, game.player.current_hp);
+    }
+
+    #[test]
+    fn test_mount() {
+        let mut game = Game::new();
+        let dest = location_from(&format!("{}/a/b/c", game.location.path_string()));
+
+        // on foot the hero meets an enemy in the first directory
+        game.go_to(&dest).unwrap();
+        assert!(game.in_combat.is_some());
+        assert_ne!(dest, game.location);
+
+        // riding, it only stops at the destination
+        let mut game = Game::new();
+        game.player.mount = Some(Mount::new(mount::Kind::Horse));
+        game.go_to(&dest).unwrap();
+        assert!(game.in_combat.is_some());
+        assert_eq!(dest, game.location);
+
+        // enemy blows may kill the mount, which bolts
+        let warrior = class::Class::player_by_name("warrior").unwrap().clone();
+        let enemy = Character::new(warrior, 20);
+        game.mount_attacked(&enemy);
+        assert!(game.player.mount.is_none());
+    }
+
+    #[test]
+    fn test_morale() {
+        let mut game = Game::new();
+        game.player.morale = morale::SHAKEN - 1;
+        let troll = class::Class::enemy_by_name("troll").unwrap().clone();
+        game.in_combat = Some(Character::new(troll, 10));
+
+        // shaken heroes won't attack stronger enemies or go further away
+        let hp = game.in_combat.as_ref().unwrap().current_hp;
+        assert_eq!(Err(GameError::LowMorale), game.battle_round());
+        assert_eq!(hp, game.in_combat.as_ref().unwrap().current_hp);
+        assert!(game.defend().is_ok());
+
+        // a narrow escape brings it further down
+        game.player.current_hp = 1;
+        game.blinded = true;
+        game.player_flee().unwrap();
+        let expected = morale::SHAKEN - 1 + morale::FLEE + morale::CLOSE_CALL;
+        assert_eq!(expected, game.player.morale);
+        let dest = location_from(&format!("{}/a", game.location.path_string()));
+        assert!(game.go_to(&dest).is_err());
+
+        // a pint of ale lifts it
+        game.add_item(Box::new(item::Ale::new()));
+        game.use_item(Key::Ale).unwrap();
+        assert_eq!(expected + morale::ALE, game.player.morale);
+
+        // but there's no backing out of the arena
+        game.player.morale = morale::SHAKEN - 1;
+        game.arena = Some(Arena::default());
+        let troll = class::Class::enemy_by_name("troll").unwrap().clone();
+        game.in_combat = Some(Character::new(troll, 10));
+        assert_ne!(Err(GameError::LowMorale), game.battle_round());
+    }
+
+    #[test]
+    fn test_chest_respawn() {
+        let mut game = Game::new();
+        game.location = crate::location::tests::location_from("/chests");
+        game.inspect();
+        assert!(game.is_inspected(&game.location));
+
+        let day_ago = config::now() - 24 * 60 * 60;
+        game.inspected_at.insert(game.location.to_string(), day_ago);
+        assert!(!game.is_inspected(&game.location));
+    }
+
+    #[test]
+    fn test_rations() {
+        let mut game = Game::new();
+        game.add_item(Box::new(item::Ration::new()));
+        let attack = game.player.physical_attack();
+
+        let mut path = String::from("~");
+        let mut walk = |game: &mut Game, steps: i32| {
+            for _ in 0..steps {
+                path.push_str("/dir");
+                game.visit(crate::location::tests::location_from(&path))
+                    .unwrap();
+            }
+        };
+
+        // the only ration is eaten after a few directories
+        walk(&mut game, RATION_DISTANCE);
+        assert!(!game.player.fatigued);
+        assert_eq!(0, game.inventory().len());
+
+        walk(&mut game, RATION_DISTANCE);
+        assert!(game.player.fatigued);
+        assert!(game.player.physical_attack() < attack);
+
+        game.add_item(Box::new(item::Ration::new()));
+        game.use_item(Key::Ration).unwrap();
+        assert!(!game.player.fatigued);
+
+        walk(&mut game, RATION_DISTANCE);
+        assert!(game.player.fatigued);
+        game.visit(Location::home()).unwrap();
+        assert!(!game.player.fatigued);
+        assert_eq!(attack, game.player.physical_attack());
+    }
+
+    #[test]
+    fn test_respec() {
+        let mut game = Game::new();
+        assert!(game.respec().is_err());
+
+        game.player.add_experience(1000);
+        let points = game.player.skill_points;
+        game.player.learn_skill("Power Strike").unwrap();
+        game.player.learn_skill("Shield Block").unwrap();
+        assert!(game.respec().is_err());
+
+        game.gold = game.player.level * RESPEC_COST_PER_LEVEL;
+        game.location = crate::location::tests::location_from("~/dungeon");
+        assert!(game.respec().is_err());
+        game.location = Location::home();
+        game.respec().unwrap();
+        assert_eq!(0, game.gold);
+        assert_eq!(points, game.player.skill_points);
+        assert_eq!(0, game.player.skill_rank("Power Strike"));
+        assert_eq!(0, game.player.skill_rank("Shield Block"));
+    }
+
+    #[test]
+    fn test_arena() {
+        let mut game = Game::new();
+        assert!(game.arena_cash_out().is_err());
+        game.location = crate::location::tests::location_from("~/dungeon");
+        assert!(game.arena_wave().is_err());
+
+        game.location = Location::home();
+        game.arena_wave().unwrap();
+        let pot = game.arena.as_ref().unwrap().pot;
+        assert_eq!(crate::arena::PAYOUT_PER_LEVEL, pot);
+        assert_eq!(1, game.stats.best_arena_wave);
+
+        // moving collects the pot before healing
+        let gold = game.gold;
+        game.visit(Location::home()).unwrap();
+        assert!(game.arena.is_none());
+        assert_eq!(gold + pot, game.gold);
+    }
+
+    #[test]
+    fn test_victory() {
+        let mut game = Game::new();
+        assert!(game.new_game_plus().is_err());
+
+        // a boss rush rematch doesn't win the game
+        let (class, level) = enemy::boss("gorthaur", &game.player).unwrap();
+        let gorthaur = Character::new(class, level);
+        game.in_boss_rush = true;
+        game.battle_won(&gorthaur, 0);
+        assert!(!game.completed);
+        game.in_boss_rush = false;
+
+        game.location = crate::location::tests::location_from("~/mordor");
+        game.battle_won(&gorthaur, 0);
+        assert!(game.completed);
+        assert_eq!(1, game.stats.hall_of_fame.len());
+        assert_eq!(game.player.level, game.stats.hall_of_fame[0].level);
+
+        // defeating it again in the same cycle doesn't add another entry
+        game.battle_won(&gorthaur, 0);
+        assert_eq!(1, game.stats.hall_of_fame.len());
+
+        // the post-game outlives the hero
+        game.reset();
+        assert!(game.completed);
+        game.location = crate::location::tests::location_from("~/mordor");
+        assert!(game.new_game_plus().is_err());
+        game.location = Location::home();
+        let level = game.player.level;
+        game.new_game_plus().unwrap();
+        assert_eq!(1, game.cycle);
+        assert_eq!(level, game.player.level);
+        assert!(game.tombstones.is_empty());
+    }
+
+    #[test]
+    fn test_boss_rush() {
+        let mut game = Game::new();
+        game.speedrun = Some(Speedrun::start());
+        assert!(game.boss_rush().is_err());
+        game.completed = true;
+        assert!(game.boss_rush().is_err());
+
+        game.stats.battle_won("dev", 0);
+        game.boss_rush().unwrap();
+        assert!(game.in_combat.is_none());
+        assert!(!game.in_boss_rush);
+        assert!(game.stats.best_boss_rush > 0);
+        // rematches don't count towards mastering the class
+        let mastery = &game.stats.mastery[&game.player.class.name];
+        assert_eq!(0, mastery.bosses);
+        assert_eq!(Split::BossRush, game.speedrun.unwrap().splits()[0].0);
+    }
+
+    #[test]
+    fn test_shadow_quest() {
+        let mut game = Game::new();
+        let mut class = game.player.class.clone();
+        class.name = String::from("shadow");
+        let shadow = Character::new(class, 1);
+
+        // the quest isn't pending until mid levels
+        assert!(!enemy::shadow_hunted(&game));
+        game.player.level = 10;
+        quest::level_up(&mut game, 1);
+        assert!(enemy::shadow_hunted(&game));
+
+        game.battle_won(&shadow, 0);
+        assert!(game.inventory().contains_key(&Key::Ring(Ring::Shadow)));
+        assert!(!enemy::shadow_hunted(&game));
+
+        // only one ring per quest
+        game.battle_won(&shadow, 0);
+        assert_eq!(1, game.inventory()[&Key::Ring(Ring::Shadow)]);
+    }
+
+    #[test]
+    fn test_class_mastery() {
+        let mut game = Game::new();
+        assert!(game.recruit("paladin").is_err());
+        assert!(game.check_class_unlocked("thief").is_ok());
+
+        game.track_mastery(crate::stats::MASTERY_LEVELS, 0);
+        assert!(game.check_class_unlocked("paladin").is_err());
+        game.track_mastery(0, crate::stats::MASTERY_BOSSES);
+        assert!(game.check_class_unlocked("paladin").is_ok());
+        assert!(game.check_class_unlocked("archmage").is_err());
+        game.recruit("paladin").unwrap();
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut game = Game::new();
+        game.player.sword = Some(item::equipment::Equipment::sword(1));
+        game.wear_equipment();
+        assert!(game.repair().is_err());
+
+        game.gold = 1000;
+        game.repair().unwrap();
+        assert_eq!(1000 - BATTLE_WEAR * item::equipment::REPAIR_COST, game.gold);
+        assert!(game.repair().is_err());
+
+        // away from home, only the blacksmith repairs
+        game.wear_equipment();
+        game.location = crate::location::tests::location_from("~/forge");
+        assert!(game.repair().is_err());
+        game.in_encounter = Some(npc::Encounter::Blacksmith);
+        game.repair().unwrap();
+        assert!(game.in_encounter.is_none());
+    }
+
+    #[test]
+    fn test_forage() {
+        let mut game = Game::new();
+        assert!(game.forage().is_err());
+
+        game.location = crate::location::tests::location_from("~/meadow");
+        let rations = |game: &Game| game.inventory().get(&Key::Ration).copied();
+        game.forage().unwrap();
+        assert_eq!(Some(2), rations(&game));
+        game.forage().unwrap();
+        game.forage().unwrap();
+        assert_eq!(Some(4), rations(&game));
+        assert!(game.forage().is_err());
+    }
+
+    #[test]
+    fn test_mine() {
+        let mut game = Game::new();
+        game.location = crate::location::tests::location_from("~/meadow");
+        game.add_item(Box::new(item::Pickaxe::new()));
+        assert!(game.mine().is_err());
+
+        // whether a real directory is a mountain depends on the host
+        for _ in 0..GEM_DEPTH {
+            game.dig();
+        }
+        assert_eq!(GEM_DEPTH * (1 + MINE_ORE), game.materials[&Material::Ore]);
+        let gems: i32 = material::GEMS
+            .iter()
+            .filter_map(|gem| game.materials.get(gem))
+            .sum();
+        assert_eq!(1, gems);
+        assert!(game.inventory().contains_key(&Key::Pickaxe));
+    }
+
+    #[test]
+    fn test_socket() {
+        let mut game = Game::new();
+        game.player.sword = Some(item::equipment::Equipment::sword(1));
+        assert!(game.socket(&Key::Sword, Material::Ruby).is_err());
+
+        game.gather(Material::Ruby, 1);
+        game.gather(Material::Ore, 1);
+        assert!(game.socket(&Key::Sword, Material::Ore).is_err());
+        assert!(game.socket(&Key::Shield, Material::Ruby).is_err());
+        assert!(game.socket(&Key::Potion, Material::Ruby).is_err());
+
+        game.socket(&Key::Sword, Material::Ruby).unwrap();
+        assert!(!game.materials.contains_key(&Material::Ruby));
+        let sword = game.player.sword.as_ref().unwrap();
+        assert_eq!(vec![Material::Ruby], sword.gems().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_shoot() {
+        let mut game = Game::new();
+        game.player.sword = Some(item::equipment::Equipment::bow(1));
+        let orc = class::Class::enemy_by_name("orc").unwrap().clone();
+        game.engage(Character::new(orc, 5));
+        assert_eq!(Some(Surprise::Ranged), game.surprise);
+
+        // no shot without arrows
+        let mut enemy = game.in_combat.take().unwrap();
+        let max_hp = enemy.current_hp;
+        game.shoot(&mut enemy);
+        assert_eq!(max_hp, enemy.current_hp);
+
+        game.add_item(Box::new(item::Arrow::new()));
+        game.shoot(&mut enemy);
+        assert!(enemy.current_hp < max_hp);
+        assert!(!game.inventory().contains_key(&Key::Arrow));
+
+        // wands need no ammo
+        let hp = enemy.current_hp;
+        game.player.sword = Some(item::equipment::Equipment::wand(1));
+        game.shoot(&mut enemy);
+        assert!(enemy.current_hp < hp);
+    }
+
+    #[test]
+    fn test_throwables() {
+        use item::throwable::{Throwable, BOMB_DAMAGE};
+
+        let mut game = Game::new();
+        game.add_item(Box::new(Throwable::Bomb));
+        assert!(game.use_item(Key::Bomb).is_err());
+        assert!(game.inventory().contains_key(&Key::Bomb));
+
+        let orc = class::Class::enemy_by_name("orc").unwrap().clone();
+        let orc = Character::new(orc, 5);
+        let max_hp = orc.max_hp();
+        game.in_combat = Some(orc);
+        game.use_item(Key::Bomb).unwrap();
+        let enemy = game.in_combat.as_ref().unwrap();
+        assert_eq!(max_hp - max_hp * BOMB_DAMAGE / 100, enemy.current_hp);
+
+        // holy water only hurts the undead
+        game.add_item(Box::new(Throwable::HolyWater));
+        game.use_item(Key::HolyWater).unwrap();
+        let enemy = game.in_combat.as_ref().unwrap();
+        assert_eq!(max_hp - max_hp * BOMB_DAMAGE / 100, enemy.current_hp);
+
+        let zombie = class::Class::enemy_by_name("zombie").unwrap().clone();
+        let mut zombie = Character::new(zombie, 5);
+        zombie.current_hp = 1;
+        game.in_combat = Some(zombie);
+        game.add_item(Box::new(Throwable::HolyWater));
+        game.use_item(Key::HolyWater).unwrap();
+        assert!(game.in_combat.is_none());
+
+        // bosses aren't hurt
+        let (class, level) = enemy::boss("dev", &game.player).unwrap();
+        let dev = Character::new(class, level);
+        let hp = dev.current_hp;
+        game.in_combat = Some(dev);
+        game.add_item(Box::new(Throwable::Bomb));
+        game.use_item(Key::Bomb).unwrap();
+        assert_eq!(hp, game.in_combat.as_ref().unwrap().current_hp);
+
+        // flash powder guarantees the next escape
+        game.in_combat = Some(Character::player());
+        game.player_flee().unwrap();
+        assert!(game.in_combat.is_some());
+        game.add_item(Box::new(Throwable::FlashPowder));
+        game.use_item(Key::FlashPowder).unwrap();
+        game.player_flee().unwrap();
+        assert!(game.in_combat.is_none());
+        assert!(!game.blinded);
+    }
+
+    #[test]
+    fn test_coating() {
+        let mut game = Game::new();
+        game.add_item(Box::new(item::Coating::new(character::StatusEffect::Burn)));
+        game.use_item(Key::FireOil).unwrap();
+        assert_eq!(Some(character::StatusEffect::Burn), game.player.coating);
+
+        // wears off once the battle is over
+        let mut enemy = Character::player();
+        enemy.current_hp = 1;
+        game.battle(enemy).unwrap();
+        assert!(game.player.coating.is_none());
+    }
+
+    #[test]
+    fn test_brew() {
+        let mut game = Game::new();
+        assert!(game.brew("potion").is_err());
+
+        game.in_encounter = Some(npc::Encounter::Witch);
+        assert!(game.brew("potion").is_err());
+        assert!(game.brew("nectar").is_err());
+
+        let potions = |game: &Game| game.inventory().get(&Key::Potion).copied();
+        let before = potions(&game).unwrap_or_default();
+        game.gather(Material::Herb, 3);
+        game.brew("pot").unwrap();
+        assert_eq!(Some(before + 1), potions(&game));
+        assert_eq!(1, game.materials[&Material::Herb]);
+        assert!(game.in_encounter.is_none());
+    }
+
+    #[test]
+    fn test_encumbrance() {
+        let mut game = Game::new();
+        let speed = game.player.speed();
+        let stones = game.player.carry_capacity() / Key::PowerStone.weight();
+
+        for _ in 0..stones {
+            game.add_item(Box::new(item::stone::Power));
+        }
+        assert!(!game.player.encumbered);
+
+        game.add_item(Box::new(item::Potion::new(1)));
+        assert!(game.player.encumbered);
+        assert!(game.player.speed() < speed);
+
+        game.use_item(Key::Potion).unwrap();
+        assert!(!game.player.encumbered);
+        assert_eq!(speed, game.player.speed());
+    }
+
+    #[test]
+    fn test_offline_progress() {
+        let mut game = Game::new();
+        game.offline_progress();
+        assert_eq!(0, game.gold);
+
+        game.outposts.push(Location::home());
+        game.recruit("warrior").unwrap();
+        game.played_at = config::now() - 3 * SECONDS_PER_HOUR;
+        game.offline_progress();
+        assert_eq!(3 * OUTPOST_GOLD_PER_HOUR, game.gold);
+        assert_eq!(3 * TRAINING_XP_PER_HOUR, game.party[0].xp);
+
+        // progress is capped, and only granted once
+        game.played_at = config::now() - 100 * SECONDS_PER_HOUR;
+        game.offline_progress();
+        game.offline_progress();
+        assert_eq!(
+            (3 + MAX_OFFLINE_HOURS as i32) * OUTPOST_GOLD_PER_HOUR,
+            game.gold
+        );
+    }
+
+    #[test]
+    fn test_tick() {
+        let mut game = Game::new();
+        game.location = crate::location::tests::location_from("/chests");
+        game.inspect();
+        let searched = game.location.clone();
+        game.location = Location::home();
+
+        game.tick();
+        assert!(game.ticked_at > 0);
+        assert!(game.weather_until > 0);
+        assert!(game.is_inspected(&searched));
+
+        // respawned treasure is forgotten, without moving the hero
+        let day_ago = config::now() - 24 * 60 * 60;
+        game.inspected_at.insert(searched.to_string(), day_ago);
+        game.tick();
+        assert!(game.inspected.is_empty());
+        assert!(game.inspected_at.is_empty());
+        assert!(game.location.is_home());
+    }
+
+    #[test]
+    fn test_insurance() {
+        let mut game = Game::new();
+        assert!(game.insure(Some(Key::Potion)).is_err());
+
+        game.gold = 1200;
+        assert!(game.insure(Some(Key::Potion)).is_ok());
+        assert_eq!(1000, game.gold);
+        assert!(game.insure(None).is_err());
+
+        game.add_item(Box::new(crate::item::Potion::new(1)));
+        game.add_item(Box::new(crate::item::Ether::new(1)));
+        game.battle_lost(None);
+        game.reset();
+
+        assert_eq!(500, game.gold);
+        assert_eq!(1, game.inventory().len());
+        assert_eq!(1, *game.inventory().get(&Key::Potion).unwrap());
+        assert!(game.insurance.is_none());
+    }
+
+    #[test]
+    fn test_heirloom() {
+        let mut game = Game::new();
+        game.add_item(Box::new(Heirloom::boss_drop()));
+        game.add_item(Box::new(crate::item::Potion::new(1)));
+
+        game.battle_lost(None);
+        game.reset();
+        assert_eq!(1, game.inventory().len());
+        assert_eq!(1, *game.inventory().get(&Key::SpeedStone).unwrap());
+    }
+
+    #[test]
+    fn test_amulet() {
+        let mut game = Game::new();
+        game.add_item(Box::new(item::amulet::Amulet::new()));
+        game.use_item(Key::Amulet).unwrap();
+        assert!(game.player.amulet.is_some());
+        assert!(game.inventory().is_empty());
+
+        // survives the first killing blow of the battle only
+        assert!(game
+            .player
+            .maybe_revive(Err(character::Dead), false)
+            .is_ok());
+        assert_eq!(1, game.player.current_hp);
+        assert!(game
+            .player
+            .maybe_revive(Err(character::Dead), false)
+            .is_err());
+        game.player.recharge_amulet();
+        assert!(game
+            .player
+            .maybe_revive(Err(character::Dead), false)
+            .is_ok());
+
+        // the worn amulet is still an heirloom
+        game.battle_lost(None);
+        game.reset();
+        assert!(game.player.amulet.is_none());
+        assert_eq!(1, *game.inventory().get(&Key::Amulet).unwrap());
+
+        game.use_item(Key::Amulet).unwrap();
+        game.use_item(Key::Amulet).unwrap();
+        assert!(game.player.amulet.is_none());
+        assert_eq!(1, *game.inventory().get(&Key::Amulet).unwrap());
+    }
+
+    #[test]
+    fn test_party() {
+        let mut game = Game::new();
+        assert!(game.recruit("choripan").is_err());
+        assert!(game.recruit("mage").is_ok());
+        assert!(game.recruit("thief").is_ok());
+        assert!(game.recruit("warrior").is_err());
+
+        assert!(game.dismiss("warrior").is_err());
+        assert!(game.dismiss("mage").is_ok());
+        assert_eq!(1, game.party.len());
+
+        // the last ally is targeted, then the hero once alone
+        game.party[0].current_hp = 1;
+        let mut enemy = Character::player();
+        enemy.current_hp = 100;
+        game.in_combat = Some(enemy);
+        game.battle_round().unwrap();
+        assert!(game.party.is_empty());
+        assert_eq!(game.player.max_hp(), game.player.current_hp);
+
+        game.battle_round().unwrap();
+        assert!(game.player.current_hp < game.player.max_hp());
+
+        game.location = crate::location::tests::location_from("/party");
+        assert!(game.recruit("mage").is_err());
+    }
+
     #[test]
     fn battle_won() {
         let enemy_base = class::Class::random(class::Category::Common);
@@ -529,9 +3574,10 @@ mod tests {
             speed: class::Stat(1, 1),
             hp: class::Stat(16, 1),
             strength: class::Stat(5, 1),
+            behavior: behavior::Behavior::Aggressive,
             ..enemy_base.clone()
         };
-        let mut enemy = character::Character::new(enemy_class.clone(), 1);
+        let enemy = character::Character::new(enemy_class.clone(), 1);
 
         let mut game = Game::new();
         let player_class = class::Class {
@@ -547,7 +3593,7 @@ mod tests {
         // player - 5 hp
         // enemy - 10hp (but has 3 remaining)
 
-        let result = game.battle(&mut enemy, false, false);
+        let result = game.battle(enemy);
         assert!(result.is_ok());
         assert_eq!(15, game.player.current_hp);
         assert_eq!(1, game.player.level);
@@ -555,11 +3601,13 @@ mod tests {
         // extra 100g for the completed quest
         assert_eq!(150, game.gold);
 
-        let mut enemy = character::Character::new(enemy_class, 1);
+        let enemy = character::Character::new(enemy_class, 1);
+        // keep the amulet quest out of the battle loot
+        game.amulet_quest_item_generated = true;
 
         // same turns, added xp increases level
 
-        let result = game.battle(&mut enemy, false, false);
+        let result = game.battle(enemy);
         assert!(result.is_ok());
         assert_eq!(2, game.player.level);
         assert_eq!(2, game.player.xp);
@@ -567,12 +3615,35 @@ mod tests {
         assert_eq!(300, game.gold);
     }
 
+    #[test]
+    fn test_pouch_lost() {
+        let mut game = Game::new();
+        game.hardcore = Hardcore::Gold;
+        game.gather(Material::Herb, 2);
+        game.battle_lost(None);
+        assert!(game.materials.is_empty());
+    }
+
+    #[test]
+    fn test_gold_decay() {
+        let mut game = Game::new();
+        game.mutators = Mutators::new(vec![crate::mutator::Mutator::GoldDecay]);
+        game.gold = 100;
+
+        // only coming back home takes the toll
+        game.visit(Location::home()).unwrap();
+        game.visit(location_from("~/decay")).unwrap();
+        assert_eq!(100, game.gold);
+        game.visit(Location::home()).unwrap();
+        assert_eq!(50, game.gold);
+    }
+
     #[test]
     fn battle_lost() {
         let mut game = Game::new();
         let enemy_class = class::Class::random(class::Category::Common);
-        let mut enemy = character::Character::new(enemy_class.clone(), 10);
-        let result = game.battle(&mut enemy, false, false);
+        let enemy = character::Character::new(enemy_class.clone(), 20);
+        let result = game.battle(enemy);
         assert!(result.is_err());
     }
 }
@@ -0,0 +1,137 @@
+use crate::config;
+use crate::location::Location;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+/// Seconds between looks for new commits, to avoid running git on every
+/// command.
+const CHECK_INTERVAL: u64 = 5 * 60;
+
+/// The latest commit seen at each repository within home the hero visited,
+/// to reward the new ones made since.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Commits {
+    /// Whether the git quests were already handed out.
+    pub started: bool,
+    heads: HashMap<String, String>,
+    #[serde(default)]
+    checked_at: u64,
+}
+
+impl Commits {
+    /// Start following the repository at the given location, if it's one
+    /// within home that isn't followed already.
+    pub fn track(&mut self, location: &Location) {
+        if !location.is_within(&Location::home()) || !is_repo(location.path()) {
+            return;
+        }
+        let key = location.path().to_string_lossy().to_string();
+        if let Entry::Vacant(entry) = self.heads.entry(key) {
+            if let Some(head) = head(location.path()) {
+                entry.insert(head);
+            }
+        }
+    }
+
+    /// Count the commits made to the followed repositories since the last
+    /// check. Repositories that are gone are no longer followed. Checking
+    /// again too soon counts nothing.
+    pub fn check(&mut self) -> usize {
+        let now = config::now();
+        if self.checked_at + CHECK_INTERVAL > now {
+            return 0;
+        }
+        self.checked_at = now;
+
+        let mut count = 0;
+        self.heads.retain(|path, last| {
+            let path = Path::new(path);
+            let Some(head) = head(path) else {
+                return false;
+            };
+            if head != *last {
+                count += commits_between(path, last, &head);
+                *last = head;
+            }
+            true
+        });
+        count
+    }
+}
+
+fn is_repo(path: &Path) -> bool {
+    path.join(".git").exists()
+}
+
+/// How many uncommitted changes a repository in the given location has.
+/// Zero if it's not the root of a git repository or git isn't available.
+pub fn changes(location: &Location) -> usize {
+    if !is_repo(location.path()) {
+        return 0;
+    }
+    git(location.path(), &["status", "--porcelain"]).map_or(0, |status| count_changes(&status))
+}
+
+/// The hash of the latest commit of the repository at the given path.
+fn head(path: &Path) -> Option<String> {
+    git(path, &["rev-parse", "HEAD"]).map(|hash| hash.trim().to_string())
+}
+
+/// How many commits lead from one to the other. If the first one is gone,
+/// e.g. after a rebase, only the latest commit is counted.
+fn commits_between(path: &Path, from: &str, to: &str) -> usize {
+    git(path, &["rev-list", "--count", &format!("{}..{}", from, to)])
+        .and_then(|count| count.trim().parse().ok())
+        .unwrap_or(1)
+}
+
+/// Run a git command at the given path, returning its output if it succeeds.
+fn git(path: &Path, args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Count the changed files listed in the porcelain status output.
+fn count_changes(status: &str) -> usize {
+    status
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_changes() {
+        assert_eq!(0, count_changes(""));
+        assert_eq!(2, count_changes(" M src/main.rs\n?? notes.txt\n"));
+
+        let location = Location::from(std::env::temp_dir().to_str().unwrap()).unwrap();
+        assert_eq!(0, changes(&location));
+    }
+
+    #[test]
+    fn test_check_throttled() {
+        let gone = "/rpg-test-gone-repo".to_string();
+        let mut commits = Commits::default();
+        commits.heads.insert(gone.clone(), "abc".to_string());
+        assert_eq!(0, commits.check());
+        assert!(commits.heads.is_empty());
+
+        // checking again right away doesn't even look at the repositories
+        commits.heads.insert(gone.clone(), "abc".to_string());
+        assert_eq!(0, commits.check());
+        assert!(commits.heads.contains_key(&gone));
+    }
+}
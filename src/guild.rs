@@ -0,0 +1,130 @@
+use crate::character::Character;
+use crate::error::{bail, Result};
+use crate::log;
+use crate::quest;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// Gold rewarded per rank when completing a guild quest.
+pub const RANK_REWARD: i32 = 500;
+
+/// The guilds the hero can join at home. Each one grants a passive perk,
+/// sells an exclusive item and assigns its own line of quests.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Guild {
+    Warriors,
+    Mages,
+    Thieves,
+}
+
+impl Guild {
+    pub fn from(name: &str) -> Result<Self> {
+        let guild = match name.to_lowercase().as_str() {
+            "warriors" | "warrior" => Guild::Warriors,
+            "mages" | "mage" => Guild::Mages,
+            "thieves" | "thief" => Guild::Thieves,
+            name => bail!("guild {} not found", name),
+        };
+        Ok(guild)
+    }
+
+    /// Apply the guild perk after a battle is won, adjusting the gold and
+    /// xp that will be rewarded to the hero.
+    pub fn battle_perk(&self, player: &mut Character, gold: &mut i32, xp: &mut i32) {
+        match self {
+            // +20% experience
+            Guild::Warriors => *xp += *xp / 5,
+            // recover a quarter of the mp
+            Guild::Mages => {
+                player.update_mp(player.max_mp() / 4);
+            }
+            // +20% gold
+            Guild::Thieves => *gold += *gold / 5,
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Guild::Warriors => "+20% xp from battles",
+            Guild::Mages => "recover mp after battles",
+            Guild::Thieves => "+20% gold from battles",
+        }
+    }
+}
+
+impl fmt::Display for Guild {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Guild::Warriors => "warriors",
+            Guild::Mages => "mages",
+            Guild::Thieves => "thieves",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The hero's standing in a guild: its rank and the current quest
+/// that needs to be completed to reach the next one.
+#[derive(Serialize, Deserialize)]
+pub struct Membership {
+    pub guild: Guild,
+    pub rank: i32,
+    quest: Box<dyn quest::Quest>,
+}
+
+impl Membership {
+    pub fn new(guild: Guild) -> Self {
+        Self {
+            guild,
+            rank: 1,
+            quest: quest::guild_quest(guild, 1),
+        }
+    }
+
+    pub fn quest(&self) -> String {
+        self.quest.description()
+    }
+
+    /// Pass the event to the current guild quest. When it's finished the
+    /// hero is promoted, the next quest of the rotation is assigned and
+    /// the gold reward is returned.
+    pub fn handle(&mut self, event: &quest::Event) -> i32 {
+        if !self.quest.handle(event) {
+            return 0;
+        }
+
+        let reward = self.rank * RANK_REWARD;
+        self.rank += 1;
+        self.quest = quest::guild_quest(self.guild, self.rank);
+        log::guild_rank_up(self, reward);
+        reward
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_up() {
+        let mut membership = Membership::new(Guild::Thieves);
+        assert_eq!("find 2 chests 0/2", membership.quest());
+
+        assert_eq!(0, membership.handle(&quest::Event::ChestFound));
+        assert_eq!(500, membership.handle(&quest::Event::ChestFound));
+        assert_eq!(2, membership.rank);
+        assert_eq!("win 4 battles 0/4", membership.quest());
+    }
+
+    #[test]
+    fn test_battle_perk() {
+        let mut player = Character::player();
+        let (mut gold, mut xp) = (100, 100);
+        Guild::Thieves.battle_perk(&mut player, &mut gold, &mut xp);
+        assert_eq!((120, 100), (gold, xp));
+
+        let (mut gold, mut xp) = (100, 100);
+        Guild::Warriors.battle_perk(&mut player, &mut gold, &mut xp);
+        assert_eq!((100, 120), (gold, xp));
+    }
+}
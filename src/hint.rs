@@ -0,0 +1,125 @@
+use crate::config::{self, Config};
+use crate::game::Game;
+use crate::item::shop;
+use crate::log;
+
+/// How many recent deaths are checked for a dangerous distance.
+const DEATHS_CHECKED: usize = 3;
+
+/// Battles won without using a skill before suggesting them.
+const SKILL_HINT_BATTLES: i32 = 10;
+
+/// Print a tip if the game shows a pattern worth pointing out, unless
+/// hints are disabled or one was shown recently.
+pub fn show(game: &mut Game) {
+    if !Config::get().hint_due(game.hinted_at) {
+        return;
+    }
+    if let Some(hint) = pick(game) {
+        log::hint(&hint);
+        game.hinted_at = config::now();
+    }
+}
+
+/// The most relevant tip for the current game, if any.
+fn pick(game: &Game) -> Option<String> {
+    repeated_deaths(game)
+        .or_else(|| starving(game))
+        .or_else(|| unused_skills(game))
+        .or_else(|| hoarded_gold(game))
+}
+
+/// The last heroes fell at about the same distance from home.
+fn repeated_deaths(game: &Game) -> Option<String> {
+    let distances = game.journal.death_distances(DEATHS_CHECKED);
+    let closest = *distances.iter().min()?;
+    let furthest = *distances.iter().max()?;
+    if distances.len() == DEATHS_CHECKED && furthest - closest <= 1 && furthest > 0 {
+        Some(format!(
+            "the last {} deaths happened around {} directories from home, gain a few levels closer to home before going back",
+            DEATHS_CHECKED, furthest
+        ))
+    } else {
+        None
+    }
+}
+
+/// The hero ran out of rations away from home.
+fn starving(game: &Game) -> Option<String> {
+    game.player.fatigued.then(|| {
+        String::from(
+            "the hero is fatigued, eat with `rpg use ration` or go back home and buy some more",
+        )
+    })
+}
+
+/// Plenty of battles fought without ever using a skill.
+fn unused_skills(game: &Game) -> Option<String> {
+    if game.stats.skills_used > 0 || game.stats.battles_won < SKILL_HINT_BATTLES {
+        return None;
+    }
+    let player = &game.player;
+    if !player.unlocked_skills.is_empty() {
+        Some(String::from(
+            "skills can turn a battle around, try `rpg use-skill <name>` next time",
+        ))
+    } else if player.skill_points > 0 {
+        Some(String::from(
+            "there are skill points to spend, see the options with `rpg skills` and pick one with `rpg learn <name>`",
+        ))
+    } else {
+        None
+    }
+}
+
+/// Gold saved at home while the shop has equipment upgrades on sale.
+fn hoarded_gold(game: &Game) -> Option<String> {
+    let upgrade = shop::affordable_upgrades(game).into_iter().next()?;
+    Some(format!(
+        "there's gold for a better {0} in the shop, get it with `rpg buy {0}`",
+        upgrade
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::Event;
+    use crate::location::tests::location_from;
+
+    fn away(names: &[&str]) -> crate::location::Location {
+        let path = dirs::home_dir().unwrap().join(names.join("/"));
+        location_from(path.to_str().unwrap())
+    }
+
+    #[test]
+    fn test_pick() {
+        let mut game = Game::new();
+        assert!(pick(&game).is_none());
+
+        // upgrades are only suggested at home
+        game.gold = 1000;
+        assert!(pick(&game).unwrap().contains("sword"));
+        game.location = away(&["a"]);
+        assert!(pick(&game).is_none());
+
+        // deaths at about the same distance take precedence
+        for path in [&["a", "b"][..], &["a", "b", "c"], &["b", "c"]] {
+            game.journal
+                .record(&game.name, &game.player, &away(path), Event::Death);
+        }
+        game.location = crate::location::Location::home();
+        assert!(pick(&game).unwrap().contains("around 3 directories"));
+    }
+
+    #[test]
+    fn test_unused_skills() {
+        let mut game = Game::new();
+        game.stats.battles_won = SKILL_HINT_BATTLES;
+        game.player.skill_points = 1;
+        assert!(unused_skills(&game).unwrap().contains("rpg learn"));
+
+        game.stats.skills_used = 1;
+        assert!(unused_skills(&game).is_none());
+    }
+}
@@ -0,0 +1,43 @@
+use crate::character::Character;
+use crate::item::key::Key;
+use crate::item::Item;
+use serde::{Deserialize, Serialize};
+
+/// Percentage of the hero's gold returned after death.
+pub const GOLD_COVERAGE: i32 = 50;
+
+/// A policy sold at home. In exchange for a premium paid upfront and after
+/// every battle won, part of the gold and one chosen item are returned
+/// after the hero dies instead of going to the tombstone.
+#[derive(Serialize, Deserialize)]
+pub struct Policy {
+    pub item: Option<Key>,
+
+    /// What's returned to the hero after a death, waiting for the reset.
+    claim: Option<(i32, Option<Box<dyn Item>>)>,
+}
+
+impl Policy {
+    pub fn new(item: Option<Key>) -> Self {
+        Self { item, claim: None }
+    }
+
+    /// Set aside the covered gold and item before they are dropped in the tombstone.
+    pub fn file_claim(&mut self, gold: i32, item: Option<Box<dyn Item>>) {
+        self.claim = Some((gold * GOLD_COVERAGE / 100, item));
+    }
+
+    pub fn take_claim(&mut self) -> Option<(i32, Option<Box<dyn Item>>)> {
+        self.claim.take()
+    }
+}
+
+/// The upfront price of a policy.
+pub fn price(player: &Character) -> i32 {
+    player.level * 200
+}
+
+/// What needs to be paid after every battle won to keep the policy.
+pub fn premium(player: &Character) -> i32 {
+    player.level * 10
+}
@@ -3,12 +3,18 @@ use crate::game::Game;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Amulet;
+/// The amulet of power, worn around the neck. Once per battle it saves
+/// the hero from a killing blow, leaving it at 1hp.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Amulet {
+    /// Whether the power was already used in the current battle.
+    pub spent: bool,
+}
 
 impl Amulet {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
     }
 }
 
@@ -24,12 +30,18 @@ impl Item for Amulet {
         Key::Amulet
     }
 
-
-    fn apply(&mut self, _game: &mut Game) {
-        // The amulet's power is passive and checked in quests.
+    /// When used, the amulet is equipped in the neck slot.
+    fn apply(&mut self, game: &mut Game) {
+        if let Some(worn) = game.player.amulet.replace(self.clone()) {
+            game.add_item(Box::new(worn));
+        }
     }
 
     fn describe(&self) -> String {
-        "A mysterious amulet that hums with ancient power.".to_string()
+        "survive a killing blow at 1hp once per battle".to_string()
+    }
+
+    fn is_heirloom(&self) -> bool {
+        true
     }
 }
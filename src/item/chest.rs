@@ -1,15 +1,23 @@
 use super::amulet::Amulet;
 use super::equipment::Equipment;
+use super::filter;
 use super::key::Key;
 use super::ring;
 use super::stone;
-use super::{Escape, Ether, Item, Potion, Remedy};
+use super::{Escape, Ether, Item, Potion, Ration, Remedy, RepairKit};
+use crate::character::Character;
+use crate::config::Config;
 use crate::game;
+use crate::location::Distance;
+use crate::randomizer;
 use crate::randomizer::random;
 use crate::randomizer::Randomizer;
+use crate::terrain::{self, Terrain};
 use rand::prelude::{IteratorRandom, SliceRandom};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use strum::IntoEnumIterator;
 
 /// A chest is a bag of items that can be picked up by the hero.
 /// It can randomly appear at a location upon inspection, or dropped
@@ -20,6 +28,10 @@ pub struct Chest {
     sword: Option<Equipment>,
     shield: Option<Equipment>,
     gold: i32,
+
+    /// The name of the hero whose belongings these are, for tombstones.
+    #[serde(default)]
+    pub owner: Option<String>,
 }
 
 impl Chest {
@@ -39,7 +51,15 @@ impl Chest {
             return None;
         }
 
-        let distance = &game.location.distance_from_home();
+        let mut distance = game.location.distance_from_home();
+        // system directories are as rewarding as the deepest dungeons
+        if Terrain::of(&game.location) == Terrain::Anomaly {
+            let len = randomizer::rng().gen_range(1..=terrain::ANOMALY_MAX_DISTANCE);
+            distance = Distance::from(len);
+        } else if terrain::is_elite_zone(&game.location) {
+            distance = Distance::from(std::cmp::max(distance.len(), terrain::ELITE_LEVEL_FLOOR));
+        }
+        let distance = &distance;
 
         // don't reward cheap victories
         if game.player.level > distance.len() + 10 {
@@ -65,7 +85,8 @@ impl Chest {
         let mut chest = Self::default();
 
         if gold_chest {
-            chest.gold = game.player.gold_gained(game.player.level + distance.len());
+            let gold = game.player.gold_gained(game.player.level + distance.len());
+            chest.gold = Terrain::of(&game.location).gold(gold);
         }
         if equipment_chest {
             let (sword, shield) = random_equipment(distance.len());
@@ -114,9 +135,32 @@ impl Chest {
     }
 
     /// Remove the gold, items and equipment from a hero and return them as a new chest.
+    /// Heirloom items are kept in the hero's inventory, and only the gold is
+    /// removed at the lowest hardcore tier.
     pub fn drop(game: &mut game::Game) -> Self {
+        if game.hardcore == game::Hardcore::Gold {
+            let gold = game.gold;
+            game.gold = 0;
+            return Self {
+                items: Vec::new(),
+                sword: None,
+                shield: None,
+                gold,
+                owner: Some(game.name.clone()),
+            };
+        }
+
         let items: HashMap<Key, Vec<Box<dyn Item>>> = game.inventory.drain().collect();
-        let mut items: Vec<Box<dyn Item>> = items.into_values().flatten().collect();
+        let (heirlooms, mut items): (Vec<_>, Vec<_>) = items
+            .into_values()
+            .flatten()
+            .partition(|item| item.is_heirloom());
+        for heirloom in heirlooms {
+            game.inventory
+                .entry(heirloom.key())
+                .or_default()
+                .push(heirloom);
+        }
         let sword = game.player.sword.take();
         let shield = game.player.shield.take();
 
@@ -136,20 +180,29 @@ impl Chest {
             sword,
             shield,
             gold,
+            owner: Some(game.name.clone()),
         }
     }
 
+    /// Leave behind, or sell, the items skipped by the configured loot
+    /// filter.
+    pub fn filter(&mut self, game: &mut game::Game, skipped: &mut filter::Skipped) {
+        let items = std::mem::take(&mut self.items);
+        self.items = filter::apply(game, &Config::get().loot_filter, items, skipped);
+    }
+
     /// Add the items of this chest to the current game/hero
     /// Return a picked up (item counts, gold) tuple.
     pub fn pick_up(&mut self, game: &mut game::Game) -> (HashMap<Key, i32>, i32) {
         let mut item_counts = HashMap::new();
 
         // the equipment is picked up only if it's better than the current one
-        if maybe_upgrade(&mut game.player.sword, &mut self.sword) {
-            item_counts.insert(Key::Sword, 1);
+        let magic = game.player.class.is_magic();
+        if let Some(key) = maybe_equip(&mut game.player.sword, &mut self.sword, magic) {
+            item_counts.insert(key, 1);
         }
-        if maybe_upgrade(&mut game.player.shield, &mut self.shield) {
-            item_counts.insert(Key::Shield, 1);
+        if let Some(key) = maybe_equip(&mut game.player.shield, &mut self.shield, magic) {
+            item_counts.insert(key, 1);
         }
 
         // items and gold are always picked up
@@ -184,8 +237,30 @@ fn maybe_upgrade(current: &mut Option<Equipment>, other: &mut Option<Equipment>)
     false
 }
 
+/// Equips the other piece if it's an upgrade of the same kind as the current
+/// one, and usable by the hero: staves and wands need magic. Otherwise it's
+/// left where it was, so a sword never replaces a staff and its gems.
+/// Return the key of the equipped piece.
+fn maybe_equip(
+    current: &mut Option<Equipment>,
+    other: &mut Option<Equipment>,
+    magic: bool,
+) -> Option<Key> {
+    let piece = other.as_ref()?;
+    let same_kind = current
+        .as_ref()
+        .is_none_or(|equip| equip.key() == piece.key());
+    let usable = !piece.is_magic() || magic;
+    if same_kind && usable && piece.is_upgrade_from(current) {
+        let key = piece.key();
+        *current = other.take();
+        return Some(key);
+    }
+    None
+}
+
 fn random_equipment(distance: i32) -> (Option<Equipment>, Option<Equipment>) {
-    let mut rng = rand::thread_rng();
+    let mut rng = randomizer::rng();
 
     let level = std::cmp::max(1, (distance / 5) * 5);
 
@@ -202,12 +277,44 @@ fn random_equipment(distance: i32) -> (Option<Equipment>, Option<Equipment>) {
     .1
 }
 
+/// Roll the loot table of the given enemy, returning the items it drops.
+pub fn enemy_loot(enemy: &Character) -> Vec<Box<dyn Item>> {
+    let mut rng = randomizer::rng();
+    enemy
+        .class
+        .loot
+        .iter()
+        .filter(|loot| rng.gen_ratio(std::cmp::min(loot.chance, 100), 100))
+        .filter_map(|loot| loot_item(&loot.item, enemy.level))
+        .collect()
+}
+
+/// An item of the given kind dropped by an enemy, for the consumable kinds.
+pub fn loot_item(key: &Key, level: i32) -> Option<Box<dyn Item>> {
+    let item: Box<dyn Item> = match key {
+        Key::Potion => Box::new(Potion::new(level)),
+        Key::Ether => Box::new(Ether::new(level)),
+        Key::Remedy => Box::new(Remedy::new()),
+        Key::Escape => Box::new(Escape::new()),
+        Key::Ration => Box::new(Ration::new()),
+        Key::RepairKit => Box::new(RepairKit::new()),
+        Key::HealthStone => Box::new(stone::Health),
+        Key::MagicStone => Box::new(stone::Magic),
+        Key::PowerStone => Box::new(stone::Power),
+        Key::SpeedStone => Box::new(stone::Speed),
+        Key::LevelStone => Box::new(stone::Level),
+        _ => return None,
+    };
+    Some(item)
+}
+
 /// Return a weigthed random item.
 fn random_item(level: i32) -> Box<dyn Item> {
     let mut choices: Vec<(i32, Box<dyn Item>)> = vec![
         (150, Box::new(Potion::new(level))),
         (10, Box::new(Remedy::new())),
         (10, Box::new(Escape::new())),
+        (10, Box::new(RepairKit::new())),
         (50, Box::new(Ether::new(level))),
         (5, Box::new(stone::Health)),
         (5, Box::new(stone::Magic)),
@@ -220,7 +327,7 @@ fn random_item(level: i32) -> Box<dyn Item> {
     // with the resulting index
     let indexed_weights: Vec<_> = choices.iter().map(|(w, _)| w).enumerate().collect();
 
-    let mut rng = rand::thread_rng();
+    let mut rng = randomizer::rng();
     let index = indexed_weights
         .choose_weighted(&mut rng, |c| c.1)
         .unwrap()
@@ -229,8 +336,10 @@ fn random_item(level: i32) -> Box<dyn Item> {
 }
 
 fn random_ring(game: &mut game::Game) -> Option<ring::Ring> {
-    let mut rng = rand::thread_rng();
-    if let Some(ring) = game.ring_pool.iter().choose(&mut rng).cloned() {
+    let mut rng = randomizer::rng();
+    // iterate in a fixed order so seeded runs find the same rings
+    let pool = ring::Ring::iter().filter(|ring| game.ring_pool.contains(ring));
+    if let Some(ring) = pool.choose(&mut rng) {
         game.ring_pool.take(&ring)
     } else {
         None
@@ -287,6 +396,24 @@ mod tests {
         assert_eq!(2, *game.inventory().get(&Key::Potion).unwrap());
     }
 
+    #[test]
+    fn test_gold_only_drop() {
+        let mut game = game::Game::new();
+        game.hardcore = game::Hardcore::Gold;
+        game.add_item(Box::new(Potion::new(1)));
+        game.player.sword = Some(Equipment::sword(1));
+        game.gold = 100;
+
+        let tomb = Chest::drop(&mut game);
+
+        assert_eq!(100, tomb.gold);
+        assert_eq!(0, game.gold);
+        assert!(tomb.sword.is_none());
+        assert!(tomb.items.is_empty());
+        assert!(game.player.sword.is_some());
+        assert_eq!(1, *game.inventory().get(&Key::Potion).unwrap());
+    }
+
     #[test]
     fn test_pickup_extends() {
         let mut game = game::Game::new();
@@ -318,6 +445,37 @@ mod tests {
         assert_eq!(3, *game.inventory().get(&Key::Potion).unwrap());
     }
 
+    #[test]
+    fn test_pickup_same_kind() {
+        let mut game = game::Game::new();
+        game.player.sword = Some(Equipment::bow(1));
+        game.player.shield = Some(Equipment::armor(Key::Buckler, 1));
+
+        // better pieces of another kind are left in the chest
+        let mut chest = Chest {
+            items: Vec::new(),
+            sword: Some(Equipment::sword(10)),
+            shield: Some(Equipment::shield(10)),
+            gold: 0,
+            owner: None,
+        };
+        assert!(chest.pick_up(&mut game).0.is_empty());
+        assert_eq!(Key::Bow, game.player.sword.as_ref().unwrap().key());
+        assert_eq!(Key::Buckler, game.player.shield.as_ref().unwrap().key());
+        assert!(chest.sword.is_some() && chest.shield.is_some());
+
+        // and magic weapons are only picked up by magic classes
+        game.player.sword = None;
+        chest.sword = Some(Equipment::wand(10));
+        assert!(chest.pick_up(&mut game).0.is_empty());
+        assert!(game.player.sword.is_none());
+
+        chest.sword = Some(Equipment::bow(10));
+        let (items, _) = chest.pick_up(&mut game);
+        assert_eq!(Some(&1), items.get(&Key::Bow));
+        assert_eq!(10, game.player.sword.as_ref().unwrap().level());
+    }
+
     #[test]
     fn test_merge() {
         let items: Vec<Box<dyn Item>> = vec![Box::new(Potion::new(1)), Box::new(Potion::new(1))];
@@ -326,6 +484,7 @@ mod tests {
             sword: Some(Equipment::sword(1)),
             shield: Some(Equipment::shield(10)),
             gold: 100,
+            owner: None,
         };
 
         let items: Vec<Box<dyn Item>> = vec![Box::new(Potion::new(1)), Box::new(Escape::new())];
@@ -334,6 +493,7 @@ mod tests {
             sword: Some(Equipment::sword(10)),
             shield: Some(Equipment::shield(1)),
             gold: 100,
+            owner: None,
         };
 
         chest1.extend(chest2);
@@ -0,0 +1,261 @@
+use super::key::Key;
+use super::Item;
+use crate::character::Buff;
+use crate::game;
+use crate::location;
+use crate::log;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+static ITEMS: OnceCell<Vec<Definition>> = OnceCell::new();
+
+/// The stats a custom item can buff.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Stat {
+    Attack,
+    Speed,
+}
+
+/// A percentage change to a stat that lasts some battle turns.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Boost {
+    pub stat: Stat,
+    pub percent: i32,
+    pub turns: i32,
+}
+
+/// A consumable defined by the player in the items file, e.g.:
+///
+/// [[item]]
+/// name = "elixir"
+/// price = 800
+/// heal = 100
+/// cure = true
+/// buff = { stat = "attack", percent = 20, turns = 3 }
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct Definition {
+    pub name: String,
+    pub price: i32,
+
+    /// Whether the item is on sale at the home shop.
+    pub shop: bool,
+
+    /// Health points restored.
+    pub heal: i32,
+
+    /// Whether it removes status ailments.
+    pub cure: bool,
+
+    pub buff: Option<Boost>,
+
+    /// Whether it transports the hero back home.
+    pub teleport_home: bool,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ItemsFile {
+    item: Vec<Definition>,
+}
+
+/// Load the custom item definitions from the given toml contents, if any.
+/// Returns warnings for the definitions that had to be skipped.
+pub fn load(bytes: Option<&[u8]>) -> Vec<String> {
+    let (items, warnings) = parse(bytes);
+    ITEMS.set(items).unwrap();
+    warnings
+}
+
+/// Parse the item definitions, skipping those that clash with game items
+/// or earlier definitions, or that have a price or heal out of range.
+fn parse(bytes: Option<&[u8]>) -> (Vec<Definition>, Vec<String>) {
+    let mut warnings = Vec::new();
+    let file: ItemsFile = match bytes.map(toml::from_slice).transpose() {
+        Ok(file) => file.unwrap_or_default(),
+        Err(err) => {
+            warnings.push(format!("items file is invalid: {}", err));
+            ItemsFile::default()
+        }
+    };
+
+    let mut items: Vec<Definition> = Vec::new();
+    for mut item in file.item {
+        item.name = item.name.to_lowercase();
+        if item.name.is_empty() || item.name.contains(char::is_whitespace) {
+            warnings.push(format!("custom item '{}' has an invalid name", item.name));
+        } else if Key::builtin(&item.name).is_some() || items.iter().any(|i| i.name == item.name) {
+            warnings.push(format!("custom item {} already exists", item.name));
+        } else if item.price <= 0 {
+            warnings.push(format!("custom item {} needs a positive price", item.name));
+        } else if item.heal < 0 {
+            warnings.push(format!(
+                "custom item {} can't have a negative heal",
+                item.name
+            ));
+        } else {
+            items.push(item);
+        }
+    }
+    (items, warnings)
+}
+
+/// All the custom item definitions.
+pub fn all() -> &'static [Definition] {
+    ITEMS.get_or_init(Vec::new)
+}
+
+/// The custom item definition with the given name, if any.
+pub fn find(name: &str) -> Option<&'static Definition> {
+    all().iter().find(|item| item.name == name)
+}
+
+/// A consumable item built from its player-made definition.
+/// The whole definition is stored with the item so it keeps working in
+/// saved games even if the items file changes.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Custom {
+    pub definition: Definition,
+}
+
+impl Custom {
+    pub fn new(definition: Definition) -> Self {
+        Self { definition }
+    }
+}
+
+impl fmt::Display for Custom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.definition.name)
+    }
+}
+
+#[typetag::serde]
+impl Item for Custom {
+    fn apply(&mut self, game: &mut game::Game) {
+        let item = &self.definition;
+        let recovered = if item.heal > 0 {
+            game.player.update_hp(item.heal).unwrap_or_default()
+        } else {
+            0
+        };
+        let cured = item.cure && game.player.status_effect.take().is_some();
+        log::heal_item(&game.player, &item.name, recovered, 0, cured);
+
+        if let Some(boost) = &item.buff {
+            let buff = match boost.stat {
+                Stat::Attack => Buff {
+                    attack: boost.percent,
+                    speed: 0,
+                },
+                Stat::Speed => Buff {
+                    attack: 0,
+                    speed: boost.percent,
+                },
+            };
+            game.player.boost = Some((buff, boost.turns));
+            log::boosted(&item.name, &buff, boost.turns);
+        }
+
+        if item.teleport_home {
+            game.visit(location::Location::home()).unwrap_or_default();
+        }
+    }
+
+    fn key(&self) -> Key {
+        Key::Custom(self.definition.name.clone())
+    }
+
+    fn price(&self) -> Option<i32> {
+        Some(self.definition.price)
+    }
+
+    fn describe(&self) -> String {
+        let item = &self.definition;
+        let mut effects = Vec::new();
+        if item.heal > 0 {
+            effects.push(format!("restores {}hp", item.heal));
+        }
+        if item.cure {
+            effects.push(String::from("removes status ailments"));
+        }
+        if let Some(boost) = &item.buff {
+            let stat = match boost.stat {
+                Stat::Attack => "attack",
+                Stat::Speed => "speed",
+            };
+            effects.push(format!(
+                "{} {:+}% for {} turns",
+                stat, boost.percent, boost.turns
+            ));
+        }
+        if item.teleport_home {
+            effects.push(String::from("transports the player back home"));
+        }
+        if effects.is_empty() {
+            String::from("does nothing")
+        } else {
+            effects.join(", ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_item() {
+        let (items, warnings) = parse(Some(
+            br#"
+            [[item]]
+            name = "Elixir"
+            price = 800
+            shop = true
+            heal = 10
+            cure = true
+            buff = { stat = "attack", percent = 20, turns = 2 }
+
+            [[item]]
+            name = "elixir"
+
+            [[item]]
+            name = "potion"
+
+            [[item]]
+            name = "freebie"
+
+            [[item]]
+            name = "poison"
+            price = 10
+            heal = -10
+            "#,
+        ));
+        assert_eq!(4, warnings.len());
+        assert_eq!(1, items.len());
+        let elixir = Custom::new(items[0].clone());
+        assert!(elixir.definition.shop);
+
+        let mut game = game::Game::new();
+        game.player.current_hp = 1;
+        game.player.status_effect = Some(crate::character::StatusEffect::Poison);
+        let attack = game.player.physical_attack();
+        game.add_item(Box::new(elixir));
+
+        // the items file doesn't define it, but the carried copy is found
+        let key = Key::carried("elixr", &game).unwrap();
+        assert_eq!(Key::Custom(String::from("elixir")), key);
+        assert_eq!(Some(800), crate::item::shop::appraise(&game, &key));
+        game.use_item(key).unwrap();
+
+        assert_eq!(11, game.player.current_hp);
+        assert!(game.player.status_effect.is_none());
+        assert!(game.player.physical_attack() > attack);
+        game.player.wear_off_boost();
+        game.player.wear_off_boost();
+        assert!(game.player.boost.is_none());
+        assert_eq!(attack, game.player.physical_attack());
+    }
+}
@@ -1,21 +1,189 @@
 use core::fmt;
 
 use super::key::Key;
+use super::material::Material;
 use crate::character::class::Class;
+use crate::character::StatusEffect;
+use crate::randomizer::{random, Randomizer};
 use serde::{Deserialize, Serialize};
 
+/// The durability of new equipment.
+pub const MAX_DURABILITY: i32 = 100;
+
+/// Gold charged for each missing durability point, per level of the piece.
+pub const REPAIR_COST: i32 = 2;
+
+/// Most sockets a piece can be carved with.
+pub const MAX_SOCKETS: usize = 2;
+
+/// Strength added to a piece by each sapphire set in it, as a percentage.
+pub const SAPPHIRE_BONUS: i32 = 25;
+
+/// Strength of bows and wands, as a percentage of a sword of the same
+/// level, in exchange for their opening shot.
+pub const RANGED_STRENGTH: i32 = 75;
+
+/// Strength of axes, as a percentage of a sword of the same level. They
+/// are heavier to carry.
+pub const AXE_STRENGTH: i32 = 125;
+
+/// Strength of daggers, as a percentage of a sword of the same level.
+/// They are lighter to carry.
+pub const DAGGER_STRENGTH: i32 = 70;
+
+/// How much of a shield's defense each weight class keeps, as a
+/// percentage of a medium one of the same level.
+pub const LIGHT_DEFENSE: i32 = 60;
+pub const HEAVY_DEFENSE: i32 = 150;
+
+/// Chance to dodge added by a light shield, as a percentage.
+pub const LIGHT_DODGE: i32 = 5;
+
+/// Speed lost carrying a heavy shield, as a percentage.
+pub const HEAVY_SLOWDOWN: i32 = 15;
+
+/// The weight class of a shield, trading defense for speed and evasion.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ArmorWeight {
+    /// Bucklers: less defense, but easier to dodge with.
+    Light,
+    Medium,
+    /// Tower shields: the most defense, but they slow the hero down.
+    Heavy,
+}
+
 /// Equipment piece with a strength contribution based on
-/// a level. Used to generically represent swords and shields.
+/// a level. Used to generically represent weapons and shields.
+/// It wears off in battle, and a broken piece adds nothing until repaired.
+/// Some pieces come with sockets to set gems in.
 #[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Equipment(Key, i32);
+pub struct Equipment(
+    Key,
+    i32,
+    #[serde(default)] i32,
+    #[serde(default)] Vec<Option<Material>>,
+);
 
 impl Equipment {
     pub fn sword(level: i32) -> Self {
-        Self(Key::Sword, level)
+        Self(Key::Sword, level, 0, vec![None; random().sockets()])
+    }
+
+    /// A weapon of the given kind, e.g. an axe or a bow.
+    pub fn weapon(kind: Key, level: i32) -> Self {
+        Self(kind, level, 0, vec![None; random().sockets()])
+    }
+
+    pub fn bow(level: i32) -> Self {
+        Self::weapon(Key::Bow, level)
+    }
+
+    pub fn wand(level: i32) -> Self {
+        Self::weapon(Key::Wand, level)
     }
 
     pub fn shield(level: i32) -> Self {
-        Self(Key::Shield, level)
+        Self::armor(Key::Shield, level)
+    }
+
+    /// A shield of the given weight class, e.g. a buckler.
+    pub fn armor(kind: Key, level: i32) -> Self {
+        Self(kind, level, 0, vec![None; random().sockets()])
+    }
+
+    /// The weight class of a shield, None for weapons.
+    pub fn armor_weight(&self) -> Option<ArmorWeight> {
+        match self.0 {
+            Key::Buckler => Some(ArmorWeight::Light),
+            Key::Shield => Some(ArmorWeight::Medium),
+            Key::TowerShield => Some(ArmorWeight::Heavy),
+            _ => None,
+        }
+    }
+
+    /// The chance to dodge added by a working light shield.
+    pub fn dodge(&self) -> i32 {
+        if self.armor_weight() == Some(ArmorWeight::Light) && !self.is_broken() {
+            LIGHT_DODGE
+        } else {
+            0
+        }
+    }
+
+    /// The speed percentage lost to a heavy shield, broken or not.
+    pub fn slowdown(&self) -> i32 {
+        if self.armor_weight() == Some(ArmorWeight::Heavy) {
+            HEAVY_SLOWDOWN
+        } else {
+            0
+        }
+    }
+
+    /// The gems set in the piece.
+    pub fn gems(&self) -> impl Iterator<Item = Material> + '_ {
+        self.3.iter().flatten().copied()
+    }
+
+    /// Set the gem in the first free socket. Returns false if there was none.
+    pub fn socket(&mut self, gem: Material) -> bool {
+        if let Some(socket) = self.3.iter_mut().find(|socket| socket.is_none()) {
+            *socket = Some(gem);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether the piece is a bow or a wand, shooting at the enemy before
+    /// the melee rounds begin.
+    pub fn is_ranged(&self) -> bool {
+        matches!(self.0, Key::Bow | Key::Wand)
+    }
+
+    /// Whether the piece is a staff or a wand, adding to the magic attack
+    /// instead of the physical one.
+    pub fn is_magic(&self) -> bool {
+        matches!(self.0, Key::Staff | Key::Wand)
+    }
+
+    /// The status effect inflicted by the gems of a weapon, if any.
+    pub fn inflicts(&self) -> Option<StatusEffect> {
+        if !self.key().is_weapon() || self.is_broken() {
+            return None;
+        }
+        self.gems().find_map(element)
+    }
+
+    /// Whether the gems of a shield protect against the status effect.
+    pub fn resists(&self, status: StatusEffect) -> bool {
+        self.key().is_shield()
+            && !self.is_broken()
+            && self.gems().any(|gem| element(gem) == Some(status))
+    }
+
+    pub fn durability(&self) -> i32 {
+        MAX_DURABILITY - self.2
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.durability() <= 0
+    }
+
+    /// Lose the given durability points. Returns whether the piece broke.
+    pub fn wear(&mut self, points: i32) -> bool {
+        let was_broken = self.is_broken();
+        self.2 = std::cmp::min(MAX_DURABILITY, self.2 + points);
+        !was_broken && self.is_broken()
+    }
+
+    /// Recover the given durability points.
+    pub fn repair(&mut self, points: i32) {
+        self.2 = std::cmp::max(0, self.2 - points);
+    }
+
+    /// Gold charged to fully repair the piece.
+    pub fn repair_cost(&self) -> i32 {
+        self.2 * self.level() * REPAIR_COST
     }
 
     pub fn level(&self) -> i32 {
@@ -29,11 +197,24 @@ impl Equipment {
     /// How many strength points get added to the player when
     /// the item is equipped.
     pub fn strength(&self) -> i32 {
+        if self.is_broken() {
+            return 0;
+        }
         // get the base strength of the hero at this level
         let player_strength = Class::player_first().strength.at(self.level());
 
         // calculate the added strength as a function of the player strength
-        (player_strength as f64 * 0.5).round() as i32
+        let strength = (player_strength as f64 * 0.5).round() as i32;
+        let strength = match self.0 {
+            Key::Axe => strength * AXE_STRENGTH / 100,
+            Key::Dagger => strength * DAGGER_STRENGTH / 100,
+            Key::Bow | Key::Wand => strength * RANGED_STRENGTH / 100,
+            Key::Buckler => strength * LIGHT_DEFENSE / 100,
+            Key::TowerShield => strength * HEAVY_DEFENSE / 100,
+            _ => strength,
+        };
+        let sapphires = self.gems().filter(|gem| *gem == Material::Sapphire).count() as i32;
+        strength + strength * sapphires * SAPPHIRE_BONUS / 100
     }
 
     /// Return true if the other weapon either is None or has lower level than this one.
@@ -46,17 +227,163 @@ impl Equipment {
     }
 
     pub fn describe(&self) -> String {
-        let stat = if let Key::Sword = self.key() {
-            "physical attack"
-        } else {
+        let stat = if self.is_magic() {
+            "magic attack"
+        } else if self.key().is_shield() {
             "defense"
+        } else {
+            "physical attack"
         };
-        format!("increases {} by {}", stat, self.strength())
+        let mut description = format!("increases {} by {}", stat, self.strength());
+        if self.is_ranged() {
+            description.push_str(", shoots first in battle");
+        }
+        if self.dodge() > 0 {
+            description.push_str(&format!(", dodge +{}%", self.dodge()));
+        }
+        if self.slowdown() > 0 {
+            description.push_str(&format!(", speed -{}%", self.slowdown()));
+        }
+        if let Some(status) = self.inflicts() {
+            description.push_str(&format!(", inflicts {}", status_name(status)));
+        }
+        for status in [StatusEffect::Burn, StatusEffect::Poison] {
+            if self.resists(status) {
+                description.push_str(&format!(", resists {}", status_name(status)));
+            }
+        }
+        if !self.3.is_empty() {
+            let sockets: Vec<String> = self
+                .3
+                .iter()
+                .map(|socket| socket.map_or(String::from("-"), |gem| gem.to_string()))
+                .collect();
+            description.push_str(&format!(", sockets:[{}]", sockets.join(",")));
+        }
+        description
+    }
+}
+
+/// The status effect channeled by the gem: rubies burn, emeralds poison.
+fn element(gem: Material) -> Option<StatusEffect> {
+    match gem {
+        Material::Ruby => Some(StatusEffect::Burn),
+        Material::Emerald => Some(StatusEffect::Poison),
+        _ => None,
+    }
+}
+
+fn status_name(status: StatusEffect) -> &'static str {
+    match status {
+        StatusEffect::Burn => "burn",
+        StatusEffect::Poison => "poison",
     }
 }
 
 impl fmt::Display for Equipment {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}[{}]", self.key(), self.level())
+        if self.2 > 0 {
+            write!(
+                f,
+                "{}[{}]({}%)",
+                self.key(),
+                self.level(),
+                self.durability() * 100 / MAX_DURABILITY
+            )
+        } else {
+            write!(f, "{}[{}]", self.key(), self.level())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_durability() {
+        // pieces saved before durability existed are as good as new
+        let mut sword: Equipment = serde_json::from_str(r#"["sword",5]"#).unwrap();
+        assert_eq!(MAX_DURABILITY, sword.durability());
+        let strength = sword.strength();
+
+        assert!(!sword.wear(MAX_DURABILITY - 1));
+        assert_eq!(5 * REPAIR_COST * (MAX_DURABILITY - 1), sword.repair_cost());
+        assert!(sword.wear(10));
+        assert!(!sword.wear(10));
+        assert_eq!(0, sword.strength());
+
+        sword.repair(MAX_DURABILITY);
+        assert_eq!(strength, sword.strength());
+        assert_eq!("sword[5]", sword.to_string());
+    }
+
+    #[test]
+    fn test_sockets() {
+        let mut sword = Equipment::sword(5);
+        let strength = sword.strength();
+        assert!(sword.socket(Material::Ruby));
+        assert!(sword.socket(Material::Sapphire));
+        assert!(!sword.socket(Material::Emerald));
+        assert_eq!(Some(StatusEffect::Burn), sword.inflicts());
+        assert_eq!(strength + strength * SAPPHIRE_BONUS / 100, sword.strength());
+        assert!(sword
+            .describe()
+            .ends_with("inflicts burn, sockets:[ruby,sapphire]"));
+
+        let mut shield = Equipment::shield(5);
+        shield.socket(Material::Emerald);
+        assert_eq!(None, shield.inflicts());
+        assert!(shield.resists(StatusEffect::Poison));
+        assert!(!shield.resists(StatusEffect::Burn));
+
+        // pieces saved before sockets existed have none
+        let mut shield: Equipment = serde_json::from_str(r#"["shield",5,0]"#).unwrap();
+        assert!(!shield.socket(Material::Ruby));
+    }
+
+    #[test]
+    fn test_ranged() {
+        let sword = Equipment::sword(10);
+        let bow = Equipment::bow(10);
+        assert!(!sword.is_ranged());
+        assert!(bow.is_ranged());
+        assert!(Equipment::wand(10).is_ranged());
+        assert_eq!(sword.strength() * RANGED_STRENGTH / 100, bow.strength());
+        assert!(bow.describe().contains("shoots first"));
+    }
+
+    #[test]
+    fn test_armor_weight() {
+        let shield = Equipment::shield(10);
+        let buckler = Equipment::armor(Key::Buckler, 10);
+        let tower = Equipment::armor(Key::TowerShield, 10);
+        assert_eq!(Some(ArmorWeight::Medium), shield.armor_weight());
+        assert_eq!(None, Equipment::sword(10).armor_weight());
+
+        assert_eq!(shield.strength() * LIGHT_DEFENSE / 100, buckler.strength());
+        assert_eq!(shield.strength() * HEAVY_DEFENSE / 100, tower.strength());
+        assert_eq!((0, 0), (shield.dodge(), shield.slowdown()));
+        assert_eq!((LIGHT_DODGE, 0), (buckler.dodge(), buckler.slowdown()));
+        assert_eq!((0, HEAVY_SLOWDOWN), (tower.dodge(), tower.slowdown()));
+        assert!(tower
+            .describe()
+            .contains(&format!("speed -{}%", HEAVY_SLOWDOWN)));
+    }
+
+    #[test]
+    fn test_weapon_kinds() {
+        let sword = Equipment::sword(10);
+        let axe = Equipment::weapon(Key::Axe, 10);
+        let dagger = Equipment::weapon(Key::Dagger, 10);
+        assert_eq!(sword.strength() * AXE_STRENGTH / 100, axe.strength());
+        assert_eq!(sword.strength() * DAGGER_STRENGTH / 100, dagger.strength());
+        assert!(Key::Axe.weight() > Key::Sword.weight());
+        assert!(Key::Dagger.weight() < Key::Sword.weight());
+
+        let staff = Equipment::weapon(Key::Staff, 10);
+        assert!(staff.is_magic());
+        assert!(!staff.is_ranged());
+        assert!(staff.describe().starts_with("increases magic attack"));
     }
 }
@@ -0,0 +1,104 @@
+use super::key::Key;
+use super::shop;
+use super::Item;
+use crate::config::LootFilter;
+use crate::game::Game;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The items the loot filter skipped, along with the gold they were sold
+/// for, if any.
+#[derive(Serialize, Default, Debug)]
+pub struct Skipped {
+    pub items: HashMap<Key, i32>,
+    pub gold: i32,
+}
+
+impl Skipped {
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+/// Whether the filter lets the hero pick up the item. Items the shop
+/// doesn't trade are always worth keeping.
+pub fn keeps(game: &Game, filter: &LootFilter, key: &Key) -> bool {
+    let Some(price) = shop::appraise(game, key) else {
+        return true;
+    };
+    key.rarity() >= filter.min_rarity && price >= filter.min_value
+}
+
+/// Sort out the given loot, returning the items to pick up. The rest are
+/// recorded as skipped and, if the filter says so, sold for gold.
+pub fn apply(
+    game: &mut Game,
+    filter: &LootFilter,
+    items: Vec<Box<dyn Item>>,
+    skipped: &mut Skipped,
+) -> Vec<Box<dyn Item>> {
+    let (kept, junk): (Vec<_>, Vec<_>) = items
+        .into_iter()
+        .partition(|item| item.is_heirloom() || keeps(game, filter, &item.key()));
+    for item in junk {
+        let key = item.key();
+        if filter.sell_junk {
+            let gold = shop::trade_value(game, &key).unwrap_or_default();
+            game.gold += gold;
+            skipped.gold += gold;
+        }
+        *skipped.items.entry(key).or_insert(0) += 1;
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::StatusEffect;
+    use crate::item::key::Rarity;
+    use crate::item::{Coating, Potion, Tome};
+
+    fn loot() -> Vec<Box<dyn Item>> {
+        vec![
+            Box::new(Potion::new(1)),
+            Box::new(Potion::new(1)),
+            Box::new(Coating::new(StatusEffect::Poison)),
+            Box::new(Tome::new()),
+        ]
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut game = Game::new();
+        let mut skipped = Skipped::default();
+
+        // the default filter keeps everything
+        let kept = apply(&mut game, &LootFilter::default(), loot(), &mut skipped);
+        assert_eq!(4, kept.len());
+        assert!(skipped.is_empty());
+
+        let filter = LootFilter {
+            min_rarity: Rarity::Uncommon,
+            ..LootFilter::default()
+        };
+        let kept = apply(&mut game, &filter, loot(), &mut skipped);
+        assert_eq!(2, kept.len());
+        assert_eq!(Some(&2), skipped.items.get(&Key::Potion));
+        assert_eq!(0, skipped.gold);
+
+        // potions and oils are cheaper than this, and get sold
+        let mut skipped = Skipped::default();
+        let filter = LootFilter {
+            min_value: 1000,
+            sell_junk: true,
+            ..LootFilter::default()
+        };
+        let gold = game.gold;
+        let kept = apply(&mut game, &filter, loot(), &mut skipped);
+        assert_eq!(Key::Tome, kept[0].key());
+        assert_eq!(3, skipped.items.values().sum::<i32>());
+        assert!(skipped.gold > 0);
+        assert_eq!(gold + skipped.gold, game.gold);
+    }
+}
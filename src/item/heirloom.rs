@@ -0,0 +1,48 @@
+use super::{key::Key, stone, Item};
+use crate::game::Game;
+use crate::randomizer::{random, Randomizer};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// An item that isn't lost when the hero dies: it's left out of the
+/// tombstone and passed on to the next hero after a reset.
+#[derive(Serialize, Deserialize)]
+pub struct Heirloom(Box<dyn Item>);
+
+impl Heirloom {
+    /// The reward for defeating a boss: a random stat stone as heirloom.
+    pub fn boss_drop() -> Self {
+        let stone: Box<dyn Item> = match random().range(4) {
+            0 => Box::new(stone::Health),
+            1 => Box::new(stone::Magic),
+            2 => Box::new(stone::Power),
+            _ => Box::new(stone::Speed),
+        };
+        Self(stone)
+    }
+}
+
+impl fmt::Display for Heirloom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[typetag::serde]
+impl Item for Heirloom {
+    fn apply(&mut self, game: &mut Game) {
+        self.0.apply(game);
+    }
+
+    fn key(&self) -> Key {
+        self.0.key()
+    }
+
+    fn describe(&self) -> String {
+        format!("{} (heirloom)", self.0.describe())
+    }
+
+    fn is_heirloom(&self) -> bool {
+        true
+    }
+}
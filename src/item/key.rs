@@ -1,8 +1,12 @@
+use super::custom;
 use super::ring::Ring;
-use anyhow::{bail, Result};
+use crate::error::{bail, GameError, Result};
+use crate::fuzzy;
+use crate::game::Game;
 use serde::{Deserialize, Serialize};
 use std::convert::From;
 use std::fmt;
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug, EnumIter)]
@@ -12,15 +16,49 @@ pub enum Key {
     Escape,
     Remedy,
     Ether,
+    Ration,
+    RepairKit,
+    Pickaxe,
+    Tome,
+    Ale,
+    MightElixir,
+    HasteElixir,
+    PoisonOil,
+    FireOil,
+    Bomb,
+    FlashPowder,
+    HolyWater,
     HealthStone,
     MagicStone,
     PowerStone,
     SpeedStone,
     LevelStone,
     Sword,
+    Axe,
+    Dagger,
+    Staff,
+    Bow,
+    Wand,
+    Buckler,
     Shield,
+    TowerShield,
+    Arrow,
+    Horse,
+    Drake,
     Ring(Ring),
     Amulet,
+    Custom(String),
+}
+
+/// How hard an item is to come by, from everyday supplies to unique finds.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Legendary,
 }
 
 impl From<&str> for Key {
@@ -30,14 +68,183 @@ impl From<&str> for Key {
 }
 
 impl Key {
+    /// Whether the item is a weapon, wielded in the sword slot.
+    pub fn is_weapon(&self) -> bool {
+        matches!(
+            self,
+            Key::Sword | Key::Axe | Key::Dagger | Key::Staff | Key::Bow | Key::Wand
+        )
+    }
+
+    /// Whether the item is a shield, worn in the shield slot.
+    pub fn is_shield(&self) -> bool {
+        matches!(self, Key::Buckler | Key::Shield | Key::TowerShield)
+    }
+
+    /// Whether the item is thrown at the enemy, so it can only be used in
+    /// battle.
+    pub fn is_throwable(&self) -> bool {
+        matches!(self, Key::Bomb | Key::FlashPowder | Key::HolyWater)
+    }
+
+    pub fn rarity(&self) -> Rarity {
+        match self {
+            Key::Potion
+            | Key::Ether
+            | Key::Remedy
+            | Key::Ration
+            | Key::Ale
+            | Key::Arrow
+            | Key::RepairKit
+            | Key::Pickaxe => Rarity::Common,
+            Key::Escape
+            | Key::PoisonOil
+            | Key::FireOil
+            | Key::Bomb
+            | Key::FlashPowder
+            | Key::HolyWater
+            | Key::Custom(_) => Rarity::Uncommon,
+            Key::Sword
+            | Key::Axe
+            | Key::Dagger
+            | Key::Staff
+            | Key::Bow
+            | Key::Wand
+            | Key::Buckler
+            | Key::Shield
+            | Key::TowerShield
+            | Key::Horse
+            | Key::Drake => Rarity::Uncommon,
+            Key::Tome
+            | Key::MightElixir
+            | Key::HasteElixir
+            | Key::HealthStone
+            | Key::MagicStone
+            | Key::PowerStone
+            | Key::SpeedStone => Rarity::Rare,
+            Key::Ring(Ring::Ruling | Ring::Diamond | Ring::Shadow) => Rarity::Legendary,
+            Key::Ring(_) => Rarity::Rare,
+            Key::LevelStone | Key::Amulet => Rarity::Legendary,
+        }
+    }
+
+    /// How much an item of this kind weighs in the hero's pack.
+    pub fn weight(&self) -> i32 {
+        match self {
+            Key::Ring(_) | Key::Arrow | Key::Horse | Key::Drake => 0,
+            Key::RepairKit => 2,
+            Key::Dagger | Key::Buckler => 5,
+            Key::Axe => 15,
+            Key::TowerShield => 20,
+            Key::Pickaxe => 3,
+            Key::Potion
+            | Key::Ether
+            | Key::Remedy
+            | Key::Escape
+            | Key::Ration
+            | Key::Tome
+            | Key::Ale
+            | Key::MightElixir
+            | Key::HasteElixir
+            | Key::PoisonOil
+            | Key::FireOil
+            | Key::Bomb
+            | Key::FlashPowder
+            | Key::HolyWater
+            | Key::Amulet
+            | Key::Custom(_) => 1,
+            Key::HealthStone
+            | Key::MagicStone
+            | Key::PowerStone
+            | Key::SpeedStone
+            | Key::LevelStone => 5,
+            Key::Sword | Key::Staff | Key::Bow | Key::Wand | Key::Shield => 10,
+        }
+    }
+
+    /// The key of the item with the given name, alias, or a close enough
+    /// match of its name.
     pub fn from(name: &str) -> Result<Self> {
-        let key = match name.to_lowercase().as_str() {
+        Self::resolve(name, &[])
+    }
+
+    /// Like `from`, but also matching the custom items the hero carries,
+    /// which keep working after their definition leaves the items file.
+    pub fn carried(name: &str, game: &Game) -> Result<Self> {
+        let carried: Vec<String> = game
+            .inventory
+            .keys()
+            .filter_map(|key| match key {
+                Key::Custom(name) if custom::find(name).is_none() => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+        Self::resolve(name, &carried)
+    }
+
+    fn resolve(name: &str, carried: &[String]) -> Result<Self> {
+        let name = name.to_lowercase();
+        if let Some(key) = Key::builtin(&name) {
+            return Ok(key);
+        }
+        if custom::find(&name).is_some() || carried.contains(&name) {
+            return Ok(Key::Custom(name));
+        }
+
+        let names: Vec<String> = Key::all_builtin()
+            .iter()
+            .map(Key::to_string)
+            .chain(custom::all().iter().map(|item| item.name.clone()))
+            .chain(carried.iter().cloned())
+            .collect();
+        match fuzzy::resolve(&name, names.iter().map(String::as_str))? {
+            Some(found) => Self::resolve(found, carried),
+            None => bail!(GameError::UnknownItem(name)),
+        }
+    }
+
+    /// The keys of all the game items, one per ring.
+    fn all_builtin() -> Vec<Self> {
+        Key::iter()
+            .flat_map(|key| match key {
+                Key::Ring(_) => Ring::iter().map(Key::Ring).collect(),
+                Key::Custom(_) => Vec::new(),
+                key => vec![key],
+            })
+            .collect()
+    }
+
+    /// The key of the game item with the given lowercase name or alias.
+    pub fn builtin(name: &str) -> Option<Self> {
+        let key = match name {
             "potion" | "p" => Key::Potion,
             "ether" | "e" => Key::Ether,
             "remedy" | "r" => Key::Remedy,
             "escape" | "es" => Key::Escape,
+            "ration" | "ra" => Key::Ration,
+            "repair-kit" | "kit" => Key::RepairKit,
+            "pickaxe" | "pick" => Key::Pickaxe,
+            "tome" | "to" => Key::Tome,
+            "ale" => Key::Ale,
+            "might-elixir" | "might" => Key::MightElixir,
+            "haste-elixir" | "haste" => Key::HasteElixir,
+            "poison-oil" | "poil" => Key::PoisonOil,
+            "fire-oil" | "foil" => Key::FireOil,
+            "bomb" | "bo" => Key::Bomb,
+            "flash-powder" | "flash" => Key::FlashPowder,
+            "holy-water" | "holy" => Key::HolyWater,
             "sword" | "sw" => Key::Sword,
+            "axe" => Key::Axe,
+            "dagger" | "da" => Key::Dagger,
+            "staff" => Key::Staff,
+            "bow" => Key::Bow,
+            "wand" | "wa" => Key::Wand,
+            "arrow" | "arrows" | "ar" => Key::Arrow,
+            "buckler" | "bu" => Key::Buckler,
             "shield" | "sh" => Key::Shield,
+            "tower-shield" | "tower" => Key::TowerShield,
+            "horse" => Key::Horse,
+            "drake" => Key::Drake,
             "hp-stone" | "hp" => Key::HealthStone,
             "mp-stone" | "mp" => Key::MagicStone,
             "str-stone" | "str" | "strength" => Key::PowerStone,
@@ -67,10 +274,11 @@ impl Key {
             "chest-rng" | "chest" | "chest-ring" => Key::Ring(Ring::Chest),
             "gold-rng" | "gold" | "gold-ring" => Key::Ring(Ring::Gold),
             "diamond-rng" | "diamond" | "diamond-ring" => Key::Ring(Ring::Diamond),
+            "shadow-rng" | "shadow" | "shadow-ring" => Key::Ring(Ring::Shadow),
             "amulet" => Key::Amulet,
-            key => bail!("item {} not found", key),
+            _ => return None,
         };
-        Ok(key)
+        Some(key)
     }
 }
 
@@ -81,13 +289,35 @@ impl fmt::Display for Key {
             Key::Escape => "escape",
             Key::Remedy => "remedy",
             Key::Ether => "ether",
+            Key::Ration => "ration",
+            Key::RepairKit => "repair-kit",
+            Key::Pickaxe => "pickaxe",
+            Key::Tome => "tome",
+            Key::Ale => "ale",
+            Key::MightElixir => "might-elixir",
+            Key::HasteElixir => "haste-elixir",
+            Key::PoisonOil => "poison-oil",
+            Key::FireOil => "fire-oil",
+            Key::Bomb => "bomb",
+            Key::FlashPowder => "flash-powder",
+            Key::HolyWater => "holy-water",
             Key::HealthStone => "hp-stone",
             Key::MagicStone => "mp-stone",
             Key::PowerStone => "str-stone",
             Key::SpeedStone => "spd-stone",
             Key::LevelStone => "lvl-stone",
             Key::Sword => "sword",
+            Key::Axe => "axe",
+            Key::Dagger => "dagger",
+            Key::Staff => "staff",
+            Key::Bow => "bow",
+            Key::Wand => "wand",
+            Key::Arrow => "arrow",
+            Key::Buckler => "buckler",
             Key::Shield => "shield",
+            Key::TowerShield => "tower-shield",
+            Key::Horse => "horse",
+            Key::Drake => "drake",
             Key::Ring(Ring::Void) => "void-rng",
             Key::Ring(Ring::Attack) => "att-rng",
             Key::Ring(Ring::Deffense) => "def-rng",
@@ -108,7 +338,9 @@ impl fmt::Display for Key {
             Key::Ring(Ring::Chest) => "chest-rng",
             Key::Ring(Ring::Gold) => "gold-rng",
             Key::Ring(Ring::Diamond) => "diamond-rng",
+            Key::Ring(Ring::Shadow) => "shadow-rng",
             Key::Amulet => "amulet",
+            Key::Custom(name) => name,
         };
 
         write!(f, "{}", name)
@@ -116,10 +348,12 @@ impl fmt::Display for Key {
 }
 
 // these From impls together with the serde try_from/into config
-// allow Key variants to be used as keys in JSON objects for serialization
+// allow Key variants to be used as keys in JSON objects for serialization.
+// Names that aren't game items belong to custom items, which are kept in
+// the save even if removed from the items file.
 impl From<String> for Key {
     fn from(key: String) -> Self {
-        Key::from(&key).unwrap()
+        Key::builtin(&key).unwrap_or(Key::Custom(key))
     }
 }
 
@@ -132,14 +366,15 @@ impl From<Key> for String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use strum::IntoEnumIterator;
 
     #[test]
     fn from_into() {
         // verify that all existing keys can be parsed from strings
         // otherwise deserialization wouldn't be possible
         for key in Key::iter() {
-            if let Key::Ring(_) = key {
+            if let Key::Custom(_) = key {
+                continue;
+            } else if let Key::Ring(_) = key {
                 for ring in Ring::iter() {
                     let ring_key = Key::Ring(ring);
                     let parsed = Key::from(String::from(ring_key.clone()).as_str()).unwrap();
@@ -151,4 +386,13 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn fuzzy_names() {
+        assert_eq!(Key::Potion, Key::from("pot").unwrap());
+        assert_eq!(Key::Remedy, Key::from("remdy").unwrap());
+        assert_eq!(Key::Ring(Ring::Diamond), Key::from("diamond-r").unwrap());
+        assert!(Key::from("s").is_err());
+        assert!(Key::from("excalibur").is_err());
+    }
 }
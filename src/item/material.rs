@@ -0,0 +1,206 @@
+use super::custom::Stat;
+use super::{Coating, Elixir, Ether, Item, Potion, Remedy};
+use crate::character::{Character, StatusEffect};
+use crate::error::{bail, Result};
+use crate::fuzzy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Crafting ingredients gathered while exploring. They are kept in a pouch
+/// apart from the inventory, and lost when the hero dies.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum Material {
+    /// Picked in forests, along with the mushrooms.
+    Herb,
+    Mushroom,
+    /// Picked in meadows.
+    Berry,
+    Fang,
+    Ember,
+    Bone,
+    /// Mined in mountains, along with the gems.
+    Ore,
+    Ruby,
+    Sapphire,
+    Emerald,
+}
+
+/// The gems found deep in the mountain veins.
+pub const GEMS: [Material; 3] = [Material::Ruby, Material::Sapphire, Material::Emerald];
+
+impl fmt::Display for Material {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Material::Herb => "herb",
+            Material::Mushroom => "mushroom",
+            Material::Berry => "berry",
+            Material::Fang => "fang",
+            Material::Ember => "ember",
+            Material::Bone => "bone",
+            Material::Ore => "ore",
+            Material::Ruby => "ruby",
+            Material::Sapphire => "sapphire",
+            Material::Emerald => "emerald",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Material {
+    /// The material with the given name, or a close enough match of it.
+    pub fn from(name: &str) -> Result<Self> {
+        let names: Vec<String> = Material::iter()
+            .map(|material| material.to_string())
+            .collect();
+        let Some(found) = fuzzy::resolve(name, names.iter().map(String::as_str))? else {
+            bail!("Unknown material {}.", name);
+        };
+        Ok(Material::iter()
+            .find(|material| material.to_string() == found)
+            .unwrap())
+    }
+}
+
+/// The material left behind by each enemy family, matched against the
+/// enemy name.
+const DROPS: [(&str, Material); 13] = [
+    ("wolf", Material::Fang),
+    ("snake", Material::Fang),
+    ("vampire", Material::Fang),
+    ("basilisk", Material::Fang),
+    ("fire elemental", Material::Ember),
+    ("dragon", Material::Ember),
+    ("wyvern", Material::Ember),
+    ("skeleton", Material::Bone),
+    ("zombie", Material::Bone),
+    ("lich", Material::Bone),
+    ("ice elemental", Material::Sapphire),
+    ("storm elemental", Material::Emerald),
+    ("chimera", Material::Ruby),
+];
+
+/// The material dropped by the given enemy when defeated, if any.
+pub fn dropped_by(enemy: &Character) -> Option<Material> {
+    let name = enemy.name();
+    DROPS
+        .iter()
+        .find(|(family, _)| name.contains(family))
+        .map(|(_, material)| *material)
+}
+
+/// A potion or elixir the witch can brew from materials.
+pub struct Recipe {
+    pub name: &'static str,
+    pub ingredients: &'static [(Material, i32)],
+    brew: fn(i32) -> Box<dyn Item>,
+}
+
+impl Recipe {
+    pub fn by_name(name: &str) -> Option<&'static Self> {
+        RECIPES.iter().find(|recipe| recipe.name == name)
+    }
+
+    /// Whether the pouch holds all the ingredients.
+    pub fn can_brew(&self, materials: &BTreeMap<Material, i32>) -> bool {
+        self.ingredients
+            .iter()
+            .all(|(material, count)| materials.get(material).copied().unwrap_or_default() >= *count)
+    }
+
+    /// Take the ingredients from the pouch and return the brewed item,
+    /// as strong as the hero level.
+    pub fn brew(&self, materials: &mut BTreeMap<Material, i32>, level: i32) -> Box<dyn Item> {
+        for (material, count) in self.ingredients {
+            let left = materials.entry(*material).or_default();
+            *left -= count;
+            if *left <= 0 {
+                materials.remove(material);
+            }
+        }
+        (self.brew)(level)
+    }
+}
+
+/// The witch's recipes, from the simplest.
+pub const RECIPES: [Recipe; 7] = [
+    Recipe {
+        name: "potion",
+        ingredients: &[(Material::Herb, 2)],
+        brew: |level| Box::new(Potion::new(level)),
+    },
+    Recipe {
+        name: "remedy",
+        ingredients: &[(Material::Herb, 1), (Material::Bone, 1)],
+        brew: |_| Box::new(Remedy::new()),
+    },
+    Recipe {
+        name: "ether",
+        ingredients: &[(Material::Herb, 1), (Material::Ember, 1)],
+        brew: |level| Box::new(Ether::new(level)),
+    },
+    Recipe {
+        name: "might-elixir",
+        ingredients: &[(Material::Herb, 1), (Material::Fang, 2)],
+        brew: |_| Box::new(Elixir::new(Stat::Attack)),
+    },
+    Recipe {
+        name: "haste-elixir",
+        ingredients: &[
+            (Material::Herb, 1),
+            (Material::Fang, 1),
+            (Material::Ember, 1),
+        ],
+        brew: |_| Box::new(Elixir::new(Stat::Speed)),
+    },
+    Recipe {
+        name: "poison-oil",
+        ingredients: &[(Material::Mushroom, 1), (Material::Fang, 1)],
+        brew: |_| Box::new(Coating::new(StatusEffect::Poison)),
+    },
+    Recipe {
+        name: "fire-oil",
+        ingredients: &[(Material::Ember, 2)],
+        brew: |_| Box::new(Coating::new(StatusEffect::Burn)),
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::class::Class;
+
+    #[test]
+    fn test_dropped_by() {
+        let wolf = Class::enemy_by_name("dire wolf").unwrap();
+        let wolf = Character::new(wolf.clone(), 1);
+        assert_eq!(Some(Material::Fang), dropped_by(&wolf));
+
+        let golem = Class::enemy_by_name("golem").unwrap();
+        assert_eq!(None, dropped_by(&Character::new(golem.clone(), 1)));
+    }
+
+    #[test]
+    fn test_from() {
+        assert_eq!(Material::Mushroom, Material::from("mushroom").unwrap());
+        assert_eq!(Material::Sapphire, Material::from("saphire").unwrap());
+        assert!(Material::from("gold").is_err());
+    }
+
+    #[test]
+    fn test_brew() {
+        let recipe = Recipe::by_name("might-elixir").unwrap();
+        let mut materials = BTreeMap::from([(Material::Herb, 1), (Material::Fang, 1)]);
+        assert!(!recipe.can_brew(&materials));
+
+        materials.insert(Material::Fang, 3);
+        assert!(recipe.can_brew(&materials));
+        let elixir = recipe.brew(&mut materials, 5);
+        assert_eq!("might-elixir", elixir.to_string());
+        assert!(!materials.contains_key(&Material::Herb));
+        assert_eq!(1, materials[&Material::Fang]);
+    }
+}
@@ -1,17 +1,24 @@
 use core::fmt;
 
 use crate::character::class as character;
+use crate::character::{morale, Buff, StatusEffect};
 use crate::game;
 use crate::location;
 use crate::log;
 use serde::{Deserialize, Serialize};
 
 pub mod chest;
+pub mod custom;
 pub mod equipment;
+pub mod filter;
+pub mod heirloom;
 pub mod key;
+pub mod material;
 pub mod ring;
 pub mod shop;
+pub mod stash;
 pub mod stone;
+pub mod throwable;
 pub mod amulet;
 
 
@@ -21,6 +28,18 @@ pub trait Item: fmt::Display {
     fn apply(&mut self, game: &mut game::Game);
     fn key(&self) -> key::Key;
     fn describe(&self) -> String;
+
+    /// Heirlooms aren't dropped in the tombstone when the hero dies,
+    /// they are passed on to the next one instead.
+    fn is_heirloom(&self) -> bool {
+        false
+    }
+
+    /// The shop price carried by the item itself, for those made by the
+    /// player whose definition may change after they're found.
+    fn price(&self) -> Option<i32> {
+        None
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -121,6 +140,214 @@ impl fmt::Display for Remedy {
     }
 }
 
+/// Food for long expeditions, eaten every few directories away from home.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Ration {}
+
+impl Ration {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[typetag::serde]
+impl Item for Ration {
+    fn apply(&mut self, game: &mut game::Game) {
+        game.steps_since_meal = 0;
+        let rested = std::mem::take(&mut game.player.fatigued);
+        log::heal_item(&game.player, "ration", 0, 0, rested);
+    }
+
+    fn key(&self) -> key::Key {
+        key::Key::Ration
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "feeds the hero for {} directories away from home",
+            game::RATION_DISTANCE
+        )
+    }
+}
+
+impl fmt::Display for Ration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ration")
+    }
+}
+
+/// Tools to mend the equipment away from home.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RepairKit {}
+
+/// Durability points restored by a repair kit to each equipped piece.
+pub const REPAIR_KIT_POINTS: i32 = 50;
+
+impl RepairKit {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[typetag::serde]
+impl Item for RepairKit {
+    fn apply(&mut self, game: &mut game::Game) {
+        let player = &mut game.player;
+        for piece in player.sword.iter_mut().chain(player.shield.iter_mut()) {
+            piece.repair(REPAIR_KIT_POINTS);
+        }
+        log::repaired(&game.player, 0);
+    }
+
+    fn key(&self) -> key::Key {
+        key::Key::RepairKit
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "restores {} durability points of the sword and shield",
+            REPAIR_KIT_POINTS
+        )
+    }
+}
+
+impl fmt::Display for RepairKit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "repair-kit")
+    }
+}
+
+/// A tool to mine ore and gems in the mountains. It isn't used up, but
+/// may break while mining.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pickaxe {}
+
+impl Pickaxe {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[typetag::serde]
+impl Item for Pickaxe {
+    /// The pickaxe is swung with the mine command, using it just puts it
+    /// back in the inventory.
+    fn apply(&mut self, game: &mut game::Game) {
+        game.add_item(Box::new(self.clone()));
+    }
+
+    fn key(&self) -> key::Key {
+        key::Key::Pickaxe
+    }
+
+    fn describe(&self) -> String {
+        String::from("mines ore and gems in system directories")
+    }
+}
+
+impl fmt::Display for Pickaxe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pickaxe")
+    }
+}
+
+/// Ammunition for the bow, one shot at the start of each battle.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Arrow {}
+
+impl Arrow {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[typetag::serde]
+impl Item for Arrow {
+    /// Arrows are shot by the bow, using one just puts it back in the
+    /// quiver.
+    fn apply(&mut self, game: &mut game::Game) {
+        game.add_item(Box::new(self.clone()));
+    }
+
+    fn key(&self) -> key::Key {
+        key::Key::Arrow
+    }
+
+    fn describe(&self) -> String {
+        String::from("shot by the bow at the start of a battle")
+    }
+}
+
+impl fmt::Display for Arrow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "arrow")
+    }
+}
+
+/// A book of lore that teaches the hero an extra skill point.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Tome {}
+
+impl Tome {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[typetag::serde]
+impl Item for Tome {
+    fn apply(&mut self, game: &mut game::Game) {
+        game.player.skill_points += 1;
+        log::skill_point(&game.player);
+    }
+
+    fn key(&self) -> key::Key {
+        key::Key::Tome
+    }
+
+    fn describe(&self) -> String {
+        String::from("grants a skill point")
+    }
+}
+
+impl fmt::Display for Tome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tome")
+    }
+}
+
+/// A pint that lifts the hero's spirits.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Ale {}
+
+impl Ale {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[typetag::serde]
+impl Item for Ale {
+    fn apply(&mut self, game: &mut game::Game) {
+        let raised = game.player.update_morale(morale::ALE);
+        log::morale(&game.player, raised);
+    }
+
+    fn key(&self) -> key::Key {
+        key::Key::Ale
+    }
+
+    fn describe(&self) -> String {
+        format!("raises morale by {}", morale::ALE)
+    }
+}
+
+impl fmt::Display for Ale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ale")
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Ether {
     level: i32,
@@ -160,3 +387,106 @@ impl Item for Ether {
         format!("restores level {} amount mp", self.level)
     }
 }
+
+/// An oil rubbed on the hero's weapon, so its attacks can poison or burn
+/// the enemy until the end of the next battle.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Coating {
+    status: StatusEffect,
+}
+
+impl Coating {
+    pub fn new(status: StatusEffect) -> Self {
+        Self { status }
+    }
+}
+
+impl fmt::Display for Coating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+#[typetag::serde]
+impl Item for Coating {
+    fn apply(&mut self, game: &mut game::Game) {
+        game.player.coating = Some(self.status);
+        log::coated(&game.player, self.status);
+    }
+
+    fn key(&self) -> key::Key {
+        match self.status {
+            StatusEffect::Poison => key::Key::PoisonOil,
+            StatusEffect::Burn => key::Key::FireOil,
+        }
+    }
+
+    fn describe(&self) -> String {
+        let status = match self.status {
+            StatusEffect::Poison => "poison",
+            StatusEffect::Burn => "burn",
+        };
+        format!(
+            "attacks {} the enemy until the end of the next battle",
+            status
+        )
+    }
+}
+
+/// How much an elixir boosts its stat, as a percentage.
+pub const ELIXIR_PERCENT: i32 = 30;
+
+/// Battle turns an elixir lasts.
+pub const ELIXIR_TURNS: i32 = 5;
+
+/// Brewed by the witch, boosts the attack or speed of the hero for the
+/// next few battle turns.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Elixir {
+    stat: custom::Stat,
+}
+
+impl Elixir {
+    pub fn new(stat: custom::Stat) -> Self {
+        Self { stat }
+    }
+}
+
+impl fmt::Display for Elixir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+#[typetag::serde]
+impl Item for Elixir {
+    fn apply(&mut self, game: &mut game::Game) {
+        let buff = match self.stat {
+            custom::Stat::Attack => Buff {
+                attack: ELIXIR_PERCENT,
+                speed: 0,
+            },
+            custom::Stat::Speed => Buff {
+                attack: 0,
+                speed: ELIXIR_PERCENT,
+            },
+        };
+        game.player.boost = Some((buff, ELIXIR_TURNS));
+        log::boosted(&self.to_string(), &buff, ELIXIR_TURNS);
+    }
+
+    fn key(&self) -> key::Key {
+        match self.stat {
+            custom::Stat::Attack => key::Key::MightElixir,
+            custom::Stat::Speed => key::Key::HasteElixir,
+        }
+    }
+
+    fn describe(&self) -> String {
+        let stat = match self.stat {
+            custom::Stat::Attack => "attack",
+            custom::Stat::Speed => "speed",
+        };
+        format!("{} +{}% for {} turns", stat, ELIXIR_PERCENT, ELIXIR_TURNS)
+    }
+}
@@ -31,6 +31,7 @@ pub enum Ring {
     Chest,
     Gold,
     Diamond,
+    Shadow,
 }
 
 impl Ring {
@@ -95,6 +96,7 @@ impl Item for Ring {
             Ring::Chest => "doubles chest finding frequency",
             Ring::Gold => "doubles gold gained in battles and chests",
             Ring::Diamond => "looks expensive",
+            Ring::Shadow => "doubles experience gained in battles",
         };
         str.to_string()
     }
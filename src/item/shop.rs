@@ -1,23 +1,27 @@
 use std::fmt::Display;
 
+use super::custom;
 use super::equipment::Equipment;
 use super::key::Key;
 use super::ring::Ring;
+use super::stone;
+use super::throwable::Throwable;
 use super::Item;
-use crate::character::Character;
+use crate::character::mount::{self, Mount};
+use crate::character::npc::Encounter;
+use crate::character::StatusEffect;
+use crate::error::{bail, GameError, Result};
 use crate::game::Game;
+use crate::guild::Guild;
 use crate::log;
 use crate::quest;
-use anyhow::{bail, Result};
 use std::collections::HashMap;
 
 /// Print the list of available items and their price.
 pub fn list(game: &Game) -> Result<()> {
-    if !game.location.is_home() {
-        bail!("Shop is only allowed at home.");
-    }
+    check_location(game)?;
 
-    let items = available_items(&game.player)
+    let items = available_items(game)
         .iter()
         .map(|s| (s.cost(), s.to_string()))
         .collect();
@@ -30,18 +34,16 @@ pub fn list(game: &Game) -> Result<()> {
 /// not available), but will keep the shopped items so far.
 /// Will bail on error only after reporting what was bought.
 pub fn buy(game: &mut Game, item_keys: &[Key]) -> Result<()> {
-    if !game.location.is_home() {
-        bail!("Shop is only allowed at home.");
-    }
+    check_location(game)?;
 
     let mut item_counts = HashMap::new();
     let mut total_cost = 0;
-    let mut error = String::from("");
+    let mut error = None;
 
     // Buy one at a time and break on first error
     for key in item_keys {
         // get list every time to prevent e.g. buying the sword twice
-        let item = available_items(&game.player)
+        let item = available_items(game)
             .into_iter()
             .find(|s| s.to_key() == *key);
 
@@ -49,7 +51,7 @@ pub fn buy(game: &mut Game, item_keys: &[Key]) -> Result<()> {
             let item_cost = item.cost();
 
             if game.gold < item_cost {
-                error = "Not enough gold.".to_string();
+                error = Some(GameError::InsufficientGold);
                 break;
             }
             game.gold -= item_cost;
@@ -59,33 +61,186 @@ pub fn buy(game: &mut Game, item_keys: &[Key]) -> Result<()> {
             *item_counts.entry(key.clone()).or_insert(0) += 1;
             quest::item_bought(game, item.to_key());
         } else {
-            error = format!("{} not available.", key);
+            error = Some(GameError::Other(format!("{} not available.", key)));
             break;
         }
     }
 
     // log what could be bought even if there was an error
     log::shop_buy(total_cost, &item_counts);
-    if !error.is_empty() {
-        bail!(error);
+    match error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Percentage of an item's price it's worth when traded in.
+pub const TRADE_RATE: i32 = 50;
+
+/// Percentage points added to the trade rate per bounty board
+/// reputation point.
+pub const TRADE_REPUTATION_BONUS: i32 = 2;
+
+/// The best trade rate, however high the reputation.
+pub const MAX_TRADE_RATE: i32 = 90;
+
+/// Trade one of the given inventory items for as many of the item to
+/// receive as its value covers, at the pawnshop at home or with a
+/// travelling merchant. The merchant only carries basic supplies and
+/// moves on after the deal.
+pub fn trade(game: &mut Game, give: &Key, receive: &Key) -> Result<()> {
+    let merchant = matches!(game.in_encounter, Some(Encounter::Merchant));
+    if !game.location.is_home() && !merchant {
+        bail!("Items can only be traded at the pawnshop at home or with a merchant.");
+    }
+    if !game.inventory().contains_key(give) {
+        bail!("No {} in the inventory to trade.", give);
+    }
+    if give == receive {
+        bail!("Can't trade an item for itself.");
+    }
+    let Some(price) = appraise(game, give) else {
+        bail!("The {} can't be traded.", give);
+    };
+    let Some(wanted) = available_items(game)
+        .into_iter()
+        .find(|item| item.to_key() == *receive)
+    else {
+        bail!("{} not available.", receive);
+    };
+
+    let cost = wanted.cost();
+    if cost <= 0 {
+        bail!("The {} can't be traded for.", receive);
+    }
+
+    let value = price * trade_rate(game) / 100;
+    let count = if receive.is_weapon() || receive.is_shield() {
+        std::cmp::min(1, value / cost)
+    } else {
+        value / cost
+    };
+    if count == 0 {
+        bail!("The {} isn't worth a {}.", give, receive);
+    }
+
+    game.take_item(give);
+    for _ in 0..count {
+        wanted.add_to(game);
+    }
+    log::traded(give, receive, count);
+    if merchant {
+        game.in_encounter = None;
     }
     Ok(())
 }
 
-/// Build a list of items currently available at the shop
-fn available_items(player: &Character) -> Vec<Box<dyn Shoppable>> {
-    let mut items = Vec::<Box<dyn Shoppable>>::new();
-    let level = player.rounded_level();
+/// The percentage of an item's price paid for it in a trade, better for
+/// heroes with a good reputation.
+fn trade_rate(game: &Game) -> i32 {
+    let rate = TRADE_RATE + game.bounties.reputation * TRADE_REPUTATION_BONUS;
+    std::cmp::min(rate, MAX_TRADE_RATE)
+}
+
+/// The gold an inventory item is worth when traded in, if it can be.
+pub fn trade_value(game: &Game, key: &Key) -> Option<i32> {
+    appraise(game, key).map(|price| price * trade_rate(game) / 100)
+}
 
-    let sword = Equipment::sword(level);
-    if sword.is_upgrade_from(&player.sword) {
-        items.push(Box::new(sword));
+/// The price of an inventory item at the shop, whether it's on sale or
+/// not. Equipment, heirlooms, elixirs and level stones aren't traded.
+pub fn appraise(game: &Game, key: &Key) -> Option<i32> {
+    let level = game.player.rounded_level();
+    let price = match key {
+        Key::Potion => super::Potion::new(level).cost(),
+        Key::Ether => super::Ether::new(level).cost(),
+        Key::Remedy => super::Remedy::new().cost(),
+        Key::Ration => super::Ration::new().cost(),
+        Key::Escape => super::Escape::new().cost(),
+        Key::RepairKit => super::RepairKit::new().cost(),
+        Key::Pickaxe => super::Pickaxe::new().cost(),
+        Key::Arrow => super::Arrow::new().cost(),
+        Key::Tome => super::Tome::new().cost(),
+        Key::Ale => super::Ale::new().cost(),
+        Key::PoisonOil => super::Coating::new(StatusEffect::Poison).cost(),
+        Key::FireOil => super::Coating::new(StatusEffect::Burn).cost(),
+        Key::Bomb => Throwable::Bomb.cost(),
+        Key::FlashPowder => Throwable::FlashPowder.cost(),
+        Key::HolyWater => Throwable::HolyWater.cost(),
+        // all the stones sell for the same
+        Key::PowerStone | Key::MagicStone | Key::SpeedStone | Key::HealthStone => {
+            stone::Power.cost()
+        }
+        Key::Ring(ring) => ring.cost(),
+        // carried copies keep the price they were found with
+        Key::Custom(name) => game
+            .inventory
+            .get(key)
+            .and_then(|items| items.first())
+            .and_then(|item| item.price())
+            .or_else(|| custom::find(name).map(|item| item.price))?,
+        Key::LevelStone
+        | Key::MightElixir
+        | Key::HasteElixir
+        | Key::Sword
+        | Key::Axe
+        | Key::Dagger
+        | Key::Staff
+        | Key::Bow
+        | Key::Wand
+        | Key::Buckler
+        | Key::Shield
+        | Key::TowerShield
+        | Key::Horse
+        | Key::Drake
+        | Key::Amulet => return None,
+    };
+    Some(price)
+}
+
+/// Equipment upgrades on sale that the hero can afford. Weapons and
+/// shields of another kind than the equipped ones don't count as upgrades.
+pub fn affordable_upgrades(game: &Game) -> Vec<Key> {
+    if check_location(game).is_err() {
+        return Vec::new();
     }
+    let weapon = game
+        .player
+        .sword
+        .as_ref()
+        .map_or(Key::Sword, |sword| sword.key());
+    let shield = game
+        .player
+        .shield
+        .as_ref()
+        .map_or(Key::Shield, |shield| shield.key());
+    available_items(game)
+        .into_iter()
+        .filter(|item| {
+            let key = item.to_key();
+            (key == weapon || key == shield) && item.cost() <= game.gold
+        })
+        .map(|item| item.to_key())
+        .collect()
+}
 
-    let shield = Equipment::shield(level);
-    if shield.is_upgrade_from(&player.shield) {
-        items.push(Box::new(shield));
+/// The full shop is at home, outposts offer only basic supplies.
+/// There's no shop at all if the run has the no shop mutator.
+fn check_location(game: &Game) -> Result<()> {
+    if !game.mutators.shop_open() {
+        bail!("The shop is closed for this run.");
     }
+    if !game.location.is_home() && !game.at_outpost() {
+        bail!("Shop is only allowed at home or at outposts.");
+    }
+    Ok(())
+}
+
+/// Build a list of items currently available at the shop
+fn available_items(game: &Game) -> Vec<Box<dyn Shoppable>> {
+    let mut items = Vec::<Box<dyn Shoppable>>::new();
+    let player = &game.player;
+    let level = player.rounded_level();
 
     let potion = super::Potion::new(level);
     items.push(Box::new(potion));
@@ -96,13 +251,87 @@ fn available_items(player: &Character) -> Vec<Box<dyn Shoppable>> {
     let remedy = super::Remedy::new();
     items.push(Box::new(remedy));
 
+    let ration = super::Ration::new();
+    items.push(Box::new(ration));
+
+    if !game.location.is_home() {
+        return items;
+    }
+
+    // buying a piece of any kind replaces the equipped one, gems and all,
+    // so only upgrades are offered. Staves and wands need magic to be used
+    for kind in [
+        Key::Sword,
+        Key::Axe,
+        Key::Dagger,
+        Key::Staff,
+        Key::Bow,
+        Key::Wand,
+    ] {
+        let weapon = Equipment::weapon(kind, level);
+        let usable = !weapon.is_magic() || player.class.is_magic();
+        if usable && weapon.is_upgrade_from(&player.sword) {
+            items.push(Box::new(weapon));
+        }
+    }
+
+    for kind in [Key::Buckler, Key::Shield, Key::TowerShield] {
+        let shield = Equipment::armor(kind, level);
+        if shield.is_upgrade_from(&player.shield) {
+            items.push(Box::new(shield));
+        }
+    }
+
+    // one mount at a time, a drake replaces a horse
+    let riding = player.mount.as_ref().map(|mount| mount.kind);
+    if riding.is_none() {
+        items.push(Box::new(Mount::new(mount::Kind::Horse)));
+    }
+    if riding != Some(mount::Kind::Drake) && player.level >= mount::DRAKE_LEVEL {
+        items.push(Box::new(Mount::new(mount::Kind::Drake)));
+    }
+
     let escape = super::Escape::new();
     items.push(Box::new(escape));
 
+    let repair_kit = super::RepairKit::new();
+    items.push(Box::new(repair_kit));
+
+    let pickaxe = super::Pickaxe::new();
+    items.push(Box::new(pickaxe));
+
+    let arrow = super::Arrow::new();
+    items.push(Box::new(arrow));
+
+    items.push(Box::new(super::Coating::new(StatusEffect::Poison)));
+    items.push(Box::new(super::Coating::new(StatusEffect::Burn)));
+
+    items.push(Box::new(Throwable::Bomb));
+    items.push(Box::new(Throwable::FlashPowder));
+    items.push(Box::new(Throwable::HolyWater));
+
+    let ale = super::Ale::new();
+    items.push(Box::new(ale));
+
+    let tome = super::Tome::new();
+    items.push(Box::new(tome));
+
+    for item in custom::all().iter().filter(|item| item.shop) {
+        items.push(Box::new(custom::Custom::new(item.clone())));
+    }
+
     if player.level >= 25 {
         items.push(Box::new(Ring::Diamond));
     }
 
+    // each guild sells a stone to its members
+    match game.guild.as_ref().map(|membership| membership.guild) {
+        Some(Guild::Warriors) => items.push(Box::new(stone::Power)),
+        Some(Guild::Mages) => items.push(Box::new(stone::Magic)),
+        Some(Guild::Thieves) => items.push(Box::new(stone::Speed)),
+        None => {}
+    }
+
     items
 }
 
@@ -119,8 +348,8 @@ impl Shoppable for Equipment {
 
     fn add_to(&self, game: &mut Game) {
         match self.key() {
-            Key::Sword => game.player.sword = Some(self.clone()),
-            Key::Shield => game.player.shield = Some(self.clone()),
+            key if key.is_weapon() => game.player.sword = Some(self.clone()),
+            key if key.is_shield() => game.player.shield = Some(self.clone()),
             _ => {}
         }
     }
@@ -130,6 +359,24 @@ impl Shoppable for Equipment {
     }
 }
 
+impl Shoppable for Mount {
+    fn cost(&self) -> i32 {
+        match self.kind {
+            mount::Kind::Horse => 1500,
+            mount::Kind::Drake => 6000,
+        }
+    }
+
+    fn add_to(&self, game: &mut Game) {
+        game.player.mount = Some(self.clone());
+        game.update_load();
+    }
+
+    fn to_key(&self) -> Key {
+        self.key()
+    }
+}
+
 impl Shoppable for super::Potion {
     fn cost(&self) -> i32 {
         self.level * 200
@@ -172,6 +419,130 @@ impl Shoppable for super::Remedy {
     }
 }
 
+impl Shoppable for super::Ration {
+    fn cost(&self) -> i32 {
+        50
+    }
+
+    fn add_to(&self, game: &mut Game) {
+        game.add_item(Box::new(self.clone()));
+    }
+
+    fn to_key(&self) -> Key {
+        self.key()
+    }
+}
+
+impl Shoppable for super::RepairKit {
+    fn cost(&self) -> i32 {
+        300
+    }
+
+    fn add_to(&self, game: &mut Game) {
+        game.add_item(Box::new(self.clone()));
+    }
+
+    fn to_key(&self) -> Key {
+        self.key()
+    }
+}
+
+impl Shoppable for super::Ale {
+    fn cost(&self) -> i32 {
+        100
+    }
+
+    fn add_to(&self, game: &mut Game) {
+        game.add_item(Box::new(self.clone()));
+    }
+
+    fn to_key(&self) -> Key {
+        self.key()
+    }
+}
+
+impl Shoppable for super::Arrow {
+    fn cost(&self) -> i32 {
+        20
+    }
+
+    fn add_to(&self, game: &mut Game) {
+        game.add_item(Box::new(self.clone()));
+    }
+
+    fn to_key(&self) -> Key {
+        self.key()
+    }
+}
+
+impl Shoppable for super::Pickaxe {
+    fn cost(&self) -> i32 {
+        400
+    }
+
+    fn add_to(&self, game: &mut Game) {
+        game.add_item(Box::new(self.clone()));
+    }
+
+    fn to_key(&self) -> Key {
+        self.key()
+    }
+}
+
+/// The price of a weapon oil, cheap enough to coat the sword before a
+/// tough battle.
+pub const COATING_COST: i32 = 150;
+
+impl Shoppable for super::Coating {
+    fn cost(&self) -> i32 {
+        COATING_COST
+    }
+
+    fn add_to(&self, game: &mut Game) {
+        game.add_item(Box::new(self.clone()));
+    }
+
+    fn to_key(&self) -> Key {
+        self.key()
+    }
+}
+
+impl Shoppable for Throwable {
+    fn cost(&self) -> i32 {
+        match self {
+            Throwable::Bomb => 300,
+            Throwable::FlashPowder => 250,
+            Throwable::HolyWater => 200,
+        }
+    }
+
+    fn add_to(&self, game: &mut Game) {
+        game.add_item(Box::new(*self));
+    }
+
+    fn to_key(&self) -> Key {
+        self.key()
+    }
+}
+
+/// The price of a skill point, steep so levelling up stays the main way
+/// to earn them.
+pub const TOME_COST: i32 = 5000;
+
+impl Shoppable for super::Tome {
+    fn cost(&self) -> i32 {
+        TOME_COST
+    }
+
+    fn add_to(&self, game: &mut Game) {
+        game.add_item(Box::new(self.clone()));
+    }
+
+    fn to_key(&self) -> Key {
+        self.key()
+    }
+}
+
 impl Shoppable for super::Ether {
     fn cost(&self) -> i32 {
         self.level * 250
@@ -200,6 +571,62 @@ impl Shoppable for Ring {
     }
 }
 
+impl Shoppable for custom::Custom {
+    fn cost(&self) -> i32 {
+        self.definition.price
+    }
+
+    fn add_to(&self, game: &mut Game) {
+        game.add_item(Box::new(self.clone()));
+    }
+
+    fn to_key(&self) -> Key {
+        self.key()
+    }
+}
+
+impl Shoppable for stone::Power {
+    fn cost(&self) -> i32 {
+        5000
+    }
+
+    fn add_to(&self, game: &mut Game) {
+        game.add_item(Box::new(self.clone()));
+    }
+
+    fn to_key(&self) -> Key {
+        self.key()
+    }
+}
+
+impl Shoppable for stone::Magic {
+    fn cost(&self) -> i32 {
+        5000
+    }
+
+    fn add_to(&self, game: &mut Game) {
+        game.add_item(Box::new(self.clone()));
+    }
+
+    fn to_key(&self) -> Key {
+        self.key()
+    }
+}
+
+impl Shoppable for stone::Speed {
+    fn cost(&self) -> i32 {
+        5000
+    }
+
+    fn add_to(&self, game: &mut Game) {
+        game.add_item(Box::new(self.clone()));
+    }
+
+    fn to_key(&self) -> Key {
+        self.key()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::Potion;
@@ -241,6 +668,83 @@ mod tests {
         assert_eq!(2, *game.inventory().get(&Key::Potion).unwrap());
     }
 
+    #[test]
+    fn buy_at_outpost() {
+        let mut game = Game::new();
+        game.gold = 1000;
+        game.location = crate::location::tests::location_from("/camp");
+        assert!(buy(&mut game, &[Key::Potion]).is_err());
+
+        game.outposts.push(game.location.clone());
+        assert!(buy(&mut game, &[Key::Potion]).is_ok());
+        assert!(buy(&mut game, &[Key::Shield]).is_err());
+        assert_eq!(800, game.gold);
+    }
+
+    #[test]
+    fn buy_skill_point() {
+        let mut game = Game::new();
+        game.gold = TOME_COST;
+        assert!(buy(&mut game, &[Key::Tome]).is_ok());
+        assert_eq!(0, game.gold);
+
+        let points = game.player.skill_points;
+        game.use_item(Key::Tome).unwrap();
+        assert_eq!(points + 1, game.player.skill_points);
+    }
+
+    #[test]
+    fn buy_guild_item() {
+        let mut game = Game::new();
+        game.gold = 10000;
+        assert!(buy(&mut game, &[Key::PowerStone]).is_err());
+
+        game.join_guild(Guild::Warriors).unwrap();
+        assert!(buy(&mut game, &[Key::PowerStone]).is_ok());
+        assert!(buy(&mut game, &[Key::MagicStone]).is_err());
+        assert_eq!(5000, game.gold);
+    }
+
+    #[test]
+    fn buy_mount() {
+        let mut game = Game::new();
+        game.gold = 10000;
+        let capacity = game.player.carry_capacity();
+        assert!(buy(&mut game, &[Key::Drake]).is_err());
+
+        assert!(buy(&mut game, &[Key::Horse]).is_ok());
+        assert_eq!(capacity + 20, game.player.carry_capacity());
+        assert!(buy(&mut game, &[Key::Horse]).is_err());
+
+        // drakes replace horses once the hero is experienced enough
+        game.player.add_experience(100000);
+        assert!(buy(&mut game, &[Key::Drake]).is_ok());
+        assert_eq!(Key::Drake, game.player.mount.as_ref().unwrap().key());
+        assert_eq!(2500, game.gold);
+    }
+
+    #[test]
+    fn weapons_offered() {
+        let mut game = Game::new();
+        let offered = |game: &Game| -> Vec<Key> {
+            available_items(game)
+                .iter()
+                .map(|item| item.to_key())
+                .filter(Key::is_weapon)
+                .collect()
+        };
+        // warriors can't use staves and wands
+        assert_eq!(
+            vec![Key::Sword, Key::Axe, Key::Dagger, Key::Bow],
+            offered(&game)
+        );
+
+        // only upgrades replace the equipped weapon
+        game.gold = 500;
+        buy(&mut game, &[Key::Axe]).unwrap();
+        assert!(offered(&game).is_empty());
+    }
+
     #[test]
     fn buy_until_not_available() {
         let mut game = Game::new();
@@ -265,4 +769,33 @@ mod tests {
         assert_eq!(1, *game.inventory().get(&Key::Potion).unwrap());
         assert!(game.player.shield.is_some());
     }
+
+    #[test]
+    fn trade_items() {
+        let mut game = Game::new();
+        game.location = crate::location::tests::location_from("~/road");
+        game.add_item(Box::new(super::super::Escape::new()));
+        assert!(trade(&mut game, &Key::Escape, &Key::Potion).is_err());
+
+        // the merchant pays half of the price, and leaves
+        game.in_encounter = Some(Encounter::Merchant);
+        trade(&mut game, &Key::Escape, &Key::Potion).unwrap();
+        assert!(!game.inventory().contains_key(&Key::Escape));
+        assert_eq!(2, *game.inventory().get(&Key::Potion).unwrap());
+        assert!(game.in_encounter.is_none());
+
+        // only basic supplies on the road
+        game.in_encounter = Some(Encounter::Merchant);
+        assert!(trade(&mut game, &Key::Potion, &Key::Escape).is_err());
+        assert!(trade(&mut game, &Key::Potion, &Key::Remedy).is_err());
+        assert!(trade(&mut game, &Key::Potion, &Key::Ration).is_ok());
+        assert_eq!(2, *game.inventory().get(&Key::Ration).unwrap());
+
+        // reputation improves the rate at the pawnshop
+        game.location = crate::location::Location::home();
+        game.bounties.reputation = 20;
+        assert_eq!(MAX_TRADE_RATE, trade_rate(&game));
+        trade(&mut game, &Key::Potion, &Key::Ration).unwrap();
+        assert_eq!(5, *game.inventory().get(&Key::Ration).unwrap());
+    }
 }
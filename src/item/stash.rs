@@ -0,0 +1,120 @@
+use super::key::Key;
+use super::Item;
+use crate::error::{bail, GameError, Result};
+use crate::game::Game;
+use crate::log;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Items and gold shared by all the heroes. It's stored apart from the
+/// game data, so it survives resets and hero switches.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Stash {
+    pub gold: i32,
+    items: HashMap<Key, Vec<Box<dyn Item>>>,
+}
+
+impl Stash {
+    /// Move the given items and gold from the hero to the stash.
+    /// Stops at the first item the hero doesn't have, keeping the ones
+    /// deposited so far.
+    pub fn deposit(&mut self, game: &mut Game, item_keys: &[Key], gold: i32) -> Result<()> {
+        check_location(game)?;
+        if gold < 0 || gold > game.gold {
+            bail!(GameError::InsufficientGold);
+        }
+        game.gold -= gold;
+        self.gold += gold;
+
+        let mut item_counts = HashMap::new();
+        for key in item_keys {
+            if let Some(item) = game.take_item(key) {
+                self.items.entry(key.clone()).or_default().push(item);
+                *item_counts.entry(key.clone()).or_insert(0) += 1;
+            } else {
+                log::stash_moved(&item_counts, -gold);
+                bail!(GameError::UnknownItem(key.to_string()));
+            }
+        }
+        log::stash_moved(&item_counts, -gold);
+        Ok(())
+    }
+
+    /// Move the given items and gold from the stash to the hero.
+    /// Stops at the first item not in the stash, keeping the ones
+    /// withdrawn so far.
+    pub fn withdraw(&mut self, game: &mut Game, item_keys: &[Key], gold: i32) -> Result<()> {
+        check_location(game)?;
+        if gold < 0 || gold > self.gold {
+            bail!("Not enough gold in the stash.");
+        }
+        self.gold -= gold;
+        game.gold += gold;
+
+        let mut item_counts = HashMap::new();
+        for key in item_keys {
+            if let Some(item) = self.take(key) {
+                game.add_item(item);
+                *item_counts.entry(key.clone()).or_insert(0) += 1;
+            } else {
+                log::stash_moved(&item_counts, gold);
+                bail!("{} not in the stash.", key);
+            }
+        }
+        log::stash_moved(&item_counts, gold);
+        Ok(())
+    }
+
+    pub fn items(&self) -> HashMap<Key, i32> {
+        self.items
+            .iter()
+            .map(|(k, v)| (k.clone(), v.len() as i32))
+            .collect()
+    }
+
+    fn take(&mut self, key: &Key) -> Option<Box<dyn Item>> {
+        let items = self.items.get_mut(key)?;
+        let item = items.pop();
+        if items.is_empty() {
+            self.items.remove(key);
+        }
+        item
+    }
+}
+
+fn check_location(game: &Game) -> Result<()> {
+    if !game.location.is_home() {
+        bail!(GameError::NotAtHome("use the stash"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Potion;
+    use super::*;
+
+    #[test]
+    fn deposit_and_withdraw() {
+        let mut stash = Stash::default();
+        let mut game = Game::new();
+        game.gold = 100;
+        game.add_item(Box::new(Potion::new(1)));
+        game.add_item(Box::new(Potion::new(1)));
+
+        assert!(stash.deposit(&mut game, &[Key::Potion], 200).is_err());
+        assert!(stash.deposit(&mut game, &[Key::Potion], 60).is_ok());
+        assert_eq!(40, game.gold);
+        assert_eq!(1, *game.inventory().get(&Key::Potion).unwrap());
+
+        // another hero picks them up
+        let mut game = Game::new();
+        assert!(stash
+            .withdraw(&mut game, &[Key::Potion, Key::Potion], 60)
+            .is_err());
+        assert_eq!(60, game.gold);
+        assert_eq!(1, *game.inventory().get(&Key::Potion).unwrap());
+        assert!(stash.items().is_empty());
+    }
+}
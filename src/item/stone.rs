@@ -88,6 +88,7 @@ impl Item for Level {
     fn apply(&mut self, game: &mut game::Game) {
         game.player.raise_level();
         log(game, "level", 1);
+        game.track_mastery(1, 0);
         quest::level_up(game, 1);
     }
 
@@ -0,0 +1,76 @@
+use super::{key, Item};
+use crate::character::enemy;
+use crate::game;
+use crate::log;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Percentage of the enemy max hp blown away by a bomb.
+pub const BOMB_DAMAGE: i32 = 25;
+
+/// Percentage of an undead enemy max hp burnt away by holy water.
+pub const HOLY_WATER_DAMAGE: i32 = 50;
+
+/// Items thrown at the enemy in the middle of a battle, without the
+/// enemy getting a turn to strike back. Bosses shrug off the damage, which
+/// would otherwise cut through their huge hp.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Throwable {
+    /// Hurts any enemy but the bosses, regardless of its defense.
+    Bomb,
+    /// Blinds the enemy so the next attempt to flee succeeds.
+    FlashPowder,
+    /// Hurts the undead badly, harmless to the rest.
+    HolyWater,
+}
+
+impl fmt::Display for Throwable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+#[typetag::serde]
+impl Item for Throwable {
+    fn apply(&mut self, game: &mut game::Game) {
+        let Some(enemy) = &game.in_combat else {
+            return;
+        };
+        let max_hp = enemy.max_hp();
+        let boss = enemy::is_boss(&enemy.class);
+        match self {
+            Throwable::Bomb if !boss => game.hurt_enemy(max_hp * BOMB_DAMAGE / 100),
+            Throwable::FlashPowder => {
+                log::thrown(enemy, "blinded");
+                game.blinded = true;
+            }
+            Throwable::HolyWater if !boss && enemy::is_undead(&enemy.class) => {
+                game.hurt_enemy(max_hp * HOLY_WATER_DAMAGE / 100)
+            }
+            Throwable::Bomb | Throwable::HolyWater => log::thrown(enemy, "unharmed"),
+        }
+    }
+
+    fn key(&self) -> key::Key {
+        match self {
+            Throwable::Bomb => key::Key::Bomb,
+            Throwable::FlashPowder => key::Key::FlashPowder,
+            Throwable::HolyWater => key::Key::HolyWater,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Throwable::Bomb => format!(
+                "deals {}% of the enemy max hp, except to bosses",
+                BOMB_DAMAGE
+            ),
+            Throwable::FlashPowder => String::from("blinds the enemy to flee from battle"),
+            Throwable::HolyWater => format!(
+                "deals {}% of the max hp of undead enemies",
+                HOLY_WATER_DAMAGE
+            ),
+        }
+    }
+}
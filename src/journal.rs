@@ -0,0 +1,262 @@
+use crate::character::class::Category;
+use crate::character::Character;
+use crate::game::DEFAULT_NAME;
+use crate::location::Location;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How many entries are kept, older ones are forgotten.
+const MAX_ENTRIES: usize = 200;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A record of the notable things that happened to the heroes, kept across
+/// deaths so it can be told as a story.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Journal {
+    entries: Vec<Entry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    time: u64,
+    /// The name given to the hero, missing in older entries.
+    #[serde(default)]
+    name: String,
+    hero: String,
+    location: Location,
+    event: Event,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum Event {
+    Victory { enemy: String, level: i32 },
+    LevelUp { level: i32 },
+    Chest,
+    Death,
+}
+
+/// A flat, serializable view of a journal entry, for exporting.
+#[derive(Serialize)]
+pub struct Record {
+    pub time: u64,
+    pub name: String,
+    pub hero: String,
+    pub location: String,
+    pub event: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enemy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<i32>,
+}
+
+impl std::fmt::Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} at {}",
+            hero_name(&self.name, &self.hero),
+            self.event,
+            self.location
+        )?;
+        if let Some(enemy) = &self.enemy {
+            write!(f, ": {}", enemy)?;
+        }
+        if let Some(level) = self.level {
+            write!(f, " level {}", level)?;
+        }
+        Ok(())
+    }
+}
+
+impl Journal {
+    pub fn record(&mut self, name: &str, hero: &Character, location: &Location, event: Event) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        self.entries.push(Entry {
+            time,
+            name: name.to_string(),
+            hero: hero.name(),
+            location: location.clone(),
+            event,
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+    }
+
+    /// Only victories against the rarer enemies are worth telling.
+    pub fn record_victory(
+        &mut self,
+        name: &str,
+        hero: &Character,
+        location: &Location,
+        enemy: &Character,
+    ) {
+        if enemy.class.category != Category::Common {
+            let event = Event::Victory {
+                enemy: enemy.name(),
+                level: enemy.level,
+            };
+            self.record(name, hero, location, event);
+        }
+    }
+
+    /// Distances from home of the most recent deaths, newest first.
+    pub fn death_distances(&self, count: usize) -> Vec<i32> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(|entry| matches!(entry.event, Event::Death))
+            .take(count)
+            .map(|entry| entry.location.distance_from_home().len())
+            .collect()
+    }
+
+    pub fn records(&self) -> Vec<Record> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let (event, enemy, level) = match &entry.event {
+                    Event::Victory { enemy, level } => {
+                        ("victory", Some(enemy.clone()), Some(*level))
+                    }
+                    Event::LevelUp { level } => ("level up", None, Some(*level)),
+                    Event::Chest => ("chest", None, None),
+                    Event::Death => ("death", None, None),
+                };
+                Record {
+                    time: entry.time,
+                    name: entry.name.clone(),
+                    hero: entry.hero.clone(),
+                    location: entry.location.path_string(),
+                    event,
+                    enemy,
+                    level,
+                }
+            })
+            .collect()
+    }
+
+    /// Turn the journal entries into prose, one sentence per entry.
+    /// The wording is picked from the entries themselves, so the same
+    /// journal always tells the same story.
+    pub fn story(&self) -> Vec<String> {
+        let start = self.entries.first().map_or(0, |e| e.time);
+        let mut last_day = 0;
+
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                // the clock may have been set back since the first entry
+                let day = entry.time.saturating_sub(start) / SECONDS_PER_DAY + 1;
+                let when = if day != last_day {
+                    last_day = day;
+                    on_day(day)
+                } else {
+                    String::from(pick(&["Later", "Then", "Soon after"], index))
+                };
+                let place = place(&entry.location, index);
+                let hero = hero_name(&entry.name, &entry.hero);
+
+                match &entry.event {
+                    Event::Victory { enemy, level } => {
+                        let verb = pick(&["faced", "defeated", "struck down"], index);
+                        format!(
+                            "{}, {}, {} {} a level {} {}.",
+                            when, place, hero, verb, level, enemy
+                        )
+                    }
+                    Event::LevelUp { level } => {
+                        format!(
+                            "{}, {}, {} grew stronger, reaching level {}.",
+                            when, place, hero, level
+                        )
+                    }
+                    Event::Chest => {
+                        let what = pick(&["a forgotten chest", "a hidden stash"], index);
+                        format!("{}, {}, {} found {}.", when, place, hero, what)
+                    }
+                    Event::Death => {
+                        format!("{}, {}, {} fell, never to rise again.", when, place, hero)
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// How the hero is referred to: by its name and class, or just the class
+/// for unnamed heroes and older entries.
+fn hero_name(name: &str, class: &str) -> String {
+    if name.is_empty() || name == DEFAULT_NAME {
+        format!("the {}", class)
+    } else {
+        format!("{} the {}", name, class)
+    }
+}
+
+fn place(location: &Location, index: usize) -> String {
+    if location.is_home() {
+        String::from("at home")
+    } else if location.distance_from_home().len() > 5 {
+        format!("{} {}", pick(&["deep within", "far into"], index), location)
+    } else {
+        format!("{} {}", pick(&["in", "somewhere in"], index), location)
+    }
+}
+
+fn pick<'a>(options: &[&'a str], index: usize) -> &'a str {
+    options[index % options.len()]
+}
+
+fn on_day(day: u64) -> String {
+    let words = [
+        "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth",
+        "tenth",
+    ];
+    if let Some(word) = words.get(day as usize - 1) {
+        format!("On the {} day", word)
+    } else {
+        format!("On day {}", day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_story() {
+        let mut journal = Journal::default();
+        let hero = Character::player();
+        let home = Location::home();
+
+        let goblin = Character::new(
+            crate::character::class::Class::random(Category::Common).clone(),
+            1,
+        );
+        journal.record_victory(DEFAULT_NAME, &hero, &home, &goblin);
+        assert!(journal.story().is_empty());
+
+        journal.record(DEFAULT_NAME, &hero, &home, Event::LevelUp { level: 2 });
+        journal.record("aria", &hero, &home, Event::Death);
+
+        let story = journal.story();
+        assert_eq!(
+            "On the first day, at home, the warrior grew stronger, reaching level 2.",
+            story[0]
+        );
+        assert_eq!(
+            "Then, at home, aria the warrior fell, never to rise again.",
+            story[1]
+        );
+
+        // entries older than the first one still happen on the first day
+        journal.entries[1].time = 0;
+        assert!(journal.story()[1].starts_with("Then,"));
+    }
+}
@@ -1,6 +1,27 @@
 use crate::datafile::rpg_dir;
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
-use std::path;
+use std::collections::HashSet;
+use std::{fs, path};
+
+/// Filesystem types of remote mounts, which the game treats as continents
+/// across the sea.
+const NETWORK_FILESYSTEMS: [&str; 9] = [
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "fuse.sshfs",
+    "afs",
+    "ceph",
+    "glusterfs",
+];
+
+/// How far from home the shores of an overseas continent are.
+pub const OVERSEAS_DISTANCE: i32 = 10;
+
+static MOUNTS: OnceCell<Vec<Mount>> = OnceCell::new();
 
 #[derive(Serialize, Deserialize, Debug, Eq, Clone)]
 pub struct Location {
@@ -36,6 +57,11 @@ impl Location {
         }
     }
 
+    /// A stable fantasy name for the location, e.g. "The Sunken Archive of Vel".
+    pub fn fantasy_name(&self) -> String {
+        crate::naming::fantasy_name(&self.path_string())
+    }
+
     pub fn is_home(&self) -> bool {
         self.path == dirs::home_dir().unwrap()
     }
@@ -69,9 +95,166 @@ impl Location {
         Distance::from(len)
     }
 
+    /// The distance from home, or from the shore plus the width of the sea
+    /// for locations overseas.
     pub fn distance_from_home(&self) -> Distance {
-        self.distance_from(&Location::home())
+        if let Some(shore) = self.continent() {
+            Distance::from(OVERSEAS_DISTANCE + self.distance_from(&shore).len())
+        } else {
+            self.distance_from(&Location::home())
+        }
+    }
+
+    /// The root of the network mount this location is in, if any. Mounts
+    /// containing home are part of the mainland.
+    pub fn continent(&self) -> Option<Self> {
+        let home = Location::home();
+        mounts()
+            .iter()
+            .filter(|mount| NETWORK_FILESYSTEMS.contains(&mount.fs_type.as_str()))
+            .filter(|mount| {
+                self.path.starts_with(&mount.path) && !home.path.starts_with(&mount.path)
+            })
+            .max_by_key(|mount| mount.path.components().count())
+            .map(|mount| Self {
+                path: mount.path.clone(),
+            })
+    }
+
+    /// Whether this location is in a tmpfs mount, other than the root one.
+    pub fn in_tmpfs(&self) -> bool {
+        mounts().iter().any(|mount| {
+            mount.fs_type == "tmpfs"
+                && mount.path != path::Path::new("/")
+                && self.path.starts_with(&mount.path)
+        })
+    }
+
+    /// Whether the innermost mount containing this location is read-only.
+    pub fn in_read_only_mount(&self) -> bool {
+        mounts()
+            .iter()
+            .filter(|mount| self.path.starts_with(&mount.path))
+            .max_by_key(|mount| mount.path.components().count())
+            .is_some_and(|mount| mount.read_only)
+    }
+
+    /// Walk the directories around this one, closest first, and return the
+    /// first one matching the predicate along with its distance in steps.
+    /// Moving to the parent or to a subdirectory counts as one step, hidden
+    /// and unreadable directories are skipped.
+    pub fn find_nearby<P>(&self, max_steps: i32, predicate: P) -> Option<(Self, i32)>
+    where
+        P: Fn(&Self) -> bool,
+    {
+        let mut visited = HashSet::new();
+        visited.insert(self.clone());
+        let mut frontier = vec![self.clone()];
+
+        for steps in 1..=max_steps {
+            let mut next = Vec::new();
+            for location in &frontier {
+                for neighbor in location.neighbors() {
+                    if visited.insert(neighbor.clone()) {
+                        if predicate(&neighbor) {
+                            return Some((neighbor, steps));
+                        }
+                        next.push(neighbor);
+                    }
+                }
+            }
+            frontier = next;
+        }
+        None
+    }
+
+    /// The parent and subdirectories of this location.
+    fn neighbors(&self) -> Vec<Self> {
+        let mut neighbors: Vec<Self> = self.parent().into_iter().collect();
+        neighbors.extend(self.subdirectories());
+        neighbors
+    }
+
+    /// Whether the path matches the given glob pattern, where `*` and `?`
+    /// match within a single directory name, `**` any number of nested
+    /// directories and a leading `~` stands for home.
+    pub fn matches(&self, pattern: &str) -> bool {
+        let pattern = match pattern.strip_prefix('~') {
+            Some(rest) => format!("{}{}", Location::home().path_string(), rest),
+            None => pattern.to_string(),
+        };
+        let pattern: Vec<&str> = pattern.split('/').filter(|part| !part.is_empty()).collect();
+        let path = self.path_string();
+        let path: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+        glob_match(&pattern, &path)
+    }
+
+    /// Whether this location is the given one or one of its subdirectories.
+    pub fn is_within(&self, other: &Self) -> bool {
+        self.path.starts_with(&other.path)
+    }
+
+    pub fn path(&self) -> &path::Path {
+        &self.path
+    }
+
+    pub fn parent(&self) -> Option<Self> {
+        self.path.parent().map(|parent| Self {
+            path: parent.to_path_buf(),
+        })
     }
+
+    /// The readable, non hidden, subdirectories of this location, sorted.
+    pub fn subdirectories(&self) -> Vec<Self> {
+        let Ok(entries) = fs::read_dir(&self.path) else {
+            return Vec::new();
+        };
+        let mut children: Vec<path::PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir() && !is_hidden(path))
+            .collect();
+        children.sort();
+        children.into_iter().map(|path| Self { path }).collect()
+    }
+}
+
+/// A mounted filesystem.
+struct Mount {
+    path: path::PathBuf,
+    fs_type: String,
+    read_only: bool,
+}
+
+/// The mounted filesystems, read once from /proc/mounts. Empty where it
+/// isn't available.
+fn mounts() -> &'static [Mount] {
+    MOUNTS.get_or_init(|| parse_mounts(&fs::read_to_string("/proc/mounts").unwrap_or_default()))
+}
+
+fn parse_mounts(table: &str) -> Vec<Mount> {
+    table
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields[..] {
+                [_, path, fs_type, ref rest @ ..] => Some(Mount {
+                    // spaces in paths are escaped as octal codes
+                    path: path::PathBuf::from(path.replace("\\040", " ")),
+                    fs_type: fs_type.to_string(),
+                    read_only: rest
+                        .first()
+                        .is_some_and(|options| options.split(',').any(|option| option == "ro")),
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn is_hidden(path: &path::Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
 }
 
 /// To match the `cd` behavior, when the path '-' is passed try to
@@ -112,6 +295,34 @@ impl std::fmt::Display for Location {
     }
 }
 
+fn glob_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| glob_match(rest, &path[skip..])),
+        Some((part, rest)) => {
+            !path.is_empty()
+                && name_match(part.as_bytes(), path[0].as_bytes())
+                && glob_match(rest, &path[1..])
+        }
+    }
+}
+
+/// Match a single directory name against a pattern with `*` and `?`.
+fn name_match(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| name_match(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && name_match(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && name_match(rest, &name[1..]),
+    }
+}
+
+/// Furthest distance from home considered near.
+pub const NEAR_DISTANCE: i32 = 6;
+
+/// Furthest distance from home considered mid, anything beyond is far.
+pub const MID_DISTANCE: i32 = 15;
+
 /// Some decisions are made branching on whether the distance from the home dir
 /// is small, medium or large. This enum encapsulate the definition of those.
 pub enum Distance {
@@ -123,8 +334,8 @@ pub enum Distance {
 impl Distance {
     pub fn from(len: i32) -> Self {
         match len {
-            n if n <= 6 => Self::Near(len),
-            n if n <= 15 => Self::Mid(len),
+            n if n <= NEAR_DISTANCE => Self::Near(len),
+            n if n <= MID_DISTANCE => Self::Mid(len),
             _ => Self::Far(len),
         }
     }
@@ -142,6 +353,19 @@ impl Distance {
 pub mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_mounts() {
+        let table =
+            "server:/export /mnt/remote\\040share nfs4 rw 0 0\ntmpfs /dev/shm tmpfs rw 0 0\n/dev/sr0 /media/cd iso9660 ro,nosuid 0 0\n";
+        let mounts = parse_mounts(table);
+        assert_eq!(3, mounts.len());
+        assert_eq!(path::Path::new("/mnt/remote share"), mounts[0].path);
+        assert_eq!("nfs4", mounts[0].fs_type);
+        assert!(!mounts[0].read_only);
+        assert_eq!("tmpfs", mounts[1].fs_type);
+        assert!(mounts[2].read_only);
+    }
+
     #[test]
     fn test_from() {
         assert_ne!(Location::from("/").unwrap(), Location::home());
@@ -204,6 +428,18 @@ pub mod tests {
         assert_eq!(Location::home().distance_from_home().len(), 0);
     }
 
+    #[test]
+    fn test_matches() {
+        let home = Location::home().path_string();
+        let modules = location_from(&format!("{}/code/app/node_modules/lib", home));
+        assert!(modules.matches("**/node_modules/**"));
+        assert!(modules.matches("~/code/**"));
+        assert!(modules.matches("~/c*/a?p/**/lib"));
+        assert!(!modules.matches("~/dungeon/**"));
+        assert!(!modules.matches("~/code"));
+        assert!(location_from(&format!("{}/dungeon", home)).matches("~/dungeon/**"));
+    }
+
     /// test-only equivalent for Location::from, specifically to bypass
     /// path existence checks.
     pub fn location_from(path: &str) -> Location {
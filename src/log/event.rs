@@ -0,0 +1,654 @@
+use crate::arena::Arena;
+use crate::artifact::Artifact;
+use crate::bounty::{Board, Bounty};
+use crate::character::class::Class;
+use crate::character::enemy::{Danger, Hunt, Invasion};
+use crate::character::mount::Mount;
+use crate::character::npc::Encounter;
+use crate::character::{AttackType, Buff, Character, StatBreakdown, StatusEffect};
+use crate::cooking::Meal;
+use crate::game::{Game, Progress};
+use crate::guild::Membership;
+use crate::insurance::Policy;
+use crate::item::equipment::Equipment;
+use crate::item::filter;
+use crate::item::key::Key;
+use crate::item::material::Material;
+use crate::item::stash::Stash;
+use crate::location::Location;
+use crate::lore::Codex;
+use crate::mutator::Mutators;
+use crate::simulation::Forecast;
+use crate::speedrun::{Speedrun, Split};
+use crate::stats::Stats;
+use crate::terrain::Terrain;
+use crate::title::Title;
+use crate::weather::Weather;
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Something that happened in the game, or was asked of it, published to
+/// the log sinks for them to render or record as they see fit.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    EnemyAppears {
+        #[serde(serialize_with = "combatant")]
+        enemy: &'a Character,
+        #[serde(serialize_with = "path")]
+        location: &'a Location,
+    },
+    Attack {
+        #[serde(serialize_with = "combatant")]
+        character: &'a Character,
+        #[serde(serialize_with = "attack_name")]
+        attack: &'a AttackType,
+        damage: i32,
+        mp_cost: i32,
+    },
+    StatusEffect {
+        #[serde(serialize_with = "combatant")]
+        character: &'a Character,
+        hp: i32,
+        mp: i32,
+    },
+    BattleWon {
+        #[serde(rename = "player", serialize_with = "player")]
+        game: &'a Game,
+        xp: i32,
+        levels_up: i32,
+        gold: i32,
+        items: &'a HashMap<Key, i32>,
+    },
+    BattleLost {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+    },
+    Chest {
+        items: &'a HashMap<Key, i32>,
+        gold: i32,
+    },
+    Narration {
+        #[serde(serialize_with = "combatant")]
+        attacker: &'a Character,
+        #[serde(serialize_with = "combatant")]
+        receiver: &'a Character,
+        #[serde(serialize_with = "attack_name")]
+        attack: &'a AttackType,
+    },
+    TrapDetected,
+    TrapSprung {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        damage: i32,
+    },
+    TrapDisarmed {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+    },
+    Tombstone {
+        owner: Option<&'a str>,
+        items: &'a HashMap<Key, i32>,
+        gold: i32,
+    },
+    Danger {
+        danger: &'a Danger,
+    },
+    Hunt {
+        hunt: &'a Hunt,
+        steps: i32,
+    },
+    Invasion {
+        invasion: &'a Invasion,
+    },
+    Terrain {
+        terrain: Terrain,
+    },
+    Anomaly {
+        buff: &'a Buff,
+    },
+    Gathered {
+        material: Material,
+        count: i32,
+    },
+    Foraged {
+        rations: i32,
+    },
+    Cooked {
+        meal: &'a Meal,
+        discovered: bool,
+    },
+    Burnt,
+    Cookbook {
+        #[serde(serialize_with = "hero")]
+        game: &'a Game,
+    },
+    Socketed {
+        piece: &'a Equipment,
+        gem: Material,
+    },
+    PickaxeBroke,
+    Brewed {
+        item: &'a str,
+    },
+    Recipes {
+        #[serde(serialize_with = "hero")]
+        game: &'a Game,
+    },
+    Thrown {
+        #[serde(serialize_with = "combatant")]
+        enemy: &'a Character,
+        effect: &'a str,
+    },
+    Coated {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        status: StatusEffect,
+    },
+    Boosted {
+        item: &'a str,
+        buff: &'a Buff,
+        turns: i32,
+    },
+    Weather {
+        weather: Weather,
+        changed: bool,
+    },
+    BugFixed {
+        #[serde(serialize_with = "combatant")]
+        bug: &'a Character,
+    },
+    Commits {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        count: usize,
+        xp: i32,
+        levels_up: i32,
+    },
+    EquipmentBroke {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        piece: &'a Key,
+    },
+    Morale {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        change: i32,
+    },
+    Trembles {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+    },
+    SkillPoint {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+    },
+    ClassMastered {
+        class: &'a str,
+        #[serde(serialize_with = "class_names")]
+        unlocked: &'a [&'a Class],
+    },
+    Respec {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        points: i32,
+        cost: i32,
+    },
+    Repaired {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        cost: i32,
+    },
+    Fatigued {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+    },
+    OfflineGold {
+        hours: i32,
+        gold: i32,
+    },
+    Voyage {
+        #[serde(serialize_with = "path")]
+        location: &'a Location,
+        fare: i32,
+    },
+    Cleared {
+        #[serde(serialize_with = "path")]
+        location: &'a Location,
+    },
+    Banish {
+        #[serde(serialize_with = "path")]
+        location: &'a Location,
+        cost: i32,
+    },
+    OutpostBuilt {
+        #[serde(serialize_with = "path")]
+        location: &'a Location,
+        cost: i32,
+    },
+    OutpostList {
+        #[serde(serialize_with = "paths")]
+        outposts: &'a [Location],
+    },
+    Guild {
+        membership: &'a Membership,
+    },
+    ArenaWave {
+        wave: i32,
+    },
+    ArenaWaveCleared {
+        arena: &'a Arena,
+        payout: i32,
+    },
+    ArenaCashedOut {
+        arena: &'a Arena,
+    },
+    BossRushCleared {
+        bosses: usize,
+        rounds: i32,
+    },
+    Bounties {
+        board: &'a Board,
+    },
+    BountyAccepted {
+        bounty: &'a Bounty,
+    },
+    BountiesTurnedIn {
+        gold: i32,
+        reputation: i32,
+        total: i32,
+    },
+    GuildRankUp {
+        membership: &'a Membership,
+        reward: i32,
+    },
+    Exploration {
+        directories: i32,
+        progress: &'a Progress,
+    },
+    Grind {
+        progress: &'a Progress,
+    },
+    Secret {
+        secret: &'a str,
+    },
+    Codex {
+        codex: &'a Codex,
+    },
+    Story {
+        sentences: &'a [String],
+    },
+    Manual {
+        paragraphs: &'a [String],
+    },
+    Stats {
+        stats: &'a Stats,
+    },
+    Split {
+        split: Split,
+        elapsed: u64,
+    },
+    Splits {
+        run: &'a Speedrun,
+    },
+    Mutators {
+        mutators: &'a Mutators,
+    },
+    Exported {
+        file: &'a str,
+    },
+    Insurance {
+        policy: &'a Policy,
+        price: i32,
+    },
+    InsuranceLapsed,
+    Mercy,
+    InsuranceClaim {
+        items: &'a HashMap<Key, i32>,
+        gold: i32,
+    },
+    Stash {
+        stash: &'a Stash,
+    },
+    StashMoved {
+        items: &'a HashMap<Key, i32>,
+        gold: i32,
+    },
+    HeroList {
+        #[serde(serialize_with = "hero")]
+        active: &'a Game,
+        #[serde(serialize_with = "heroes")]
+        others: &'a [Game],
+    },
+    TitleEarned {
+        title: Title,
+    },
+    Titles {
+        #[serde(serialize_with = "hero")]
+        game: &'a Game,
+    },
+    Victory {
+        epilogue: &'a [String],
+        champions: usize,
+        unlocked: bool,
+    },
+    NewGamePlus {
+        cycle: i32,
+    },
+    ArtifactFound {
+        artifact: Artifact,
+    },
+    ArtifactsComplete,
+    Artifacts {
+        #[serde(serialize_with = "hero")]
+        game: &'a Game,
+    },
+    Map {
+        stats: &'a Stats,
+        heat: bool,
+    },
+    PartyJoined {
+        #[serde(serialize_with = "combatant")]
+        member: &'a Character,
+    },
+    PartyLeft {
+        #[serde(serialize_with = "combatant")]
+        member: &'a Character,
+    },
+    PartyXp {
+        #[serde(serialize_with = "combatant")]
+        member: &'a Character,
+        xp: i32,
+        levels_up: i32,
+    },
+    Party {
+        #[serde(serialize_with = "hero")]
+        game: &'a Game,
+    },
+    MercenaryHired {
+        #[serde(serialize_with = "combatant")]
+        mercenary: &'a Character,
+        fee: i32,
+    },
+    MercenaryPaid {
+        #[serde(serialize_with = "combatant")]
+        mercenary: &'a Character,
+        wage: i32,
+    },
+    MercenaryLeft {
+        #[serde(serialize_with = "combatant")]
+        mercenary: &'a Character,
+    },
+    MercenaryDied {
+        #[serde(serialize_with = "combatant")]
+        mercenary: &'a Character,
+    },
+    Bribe {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        amount: i32,
+    },
+    RunAway {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        success: bool,
+    },
+    Pursuit {
+        #[serde(serialize_with = "combatant")]
+        enemy: &'a Character,
+    },
+    Escape {
+        #[serde(serialize_with = "combatant")]
+        enemy: &'a Character,
+    },
+    Ambush {
+        #[serde(serialize_with = "combatant")]
+        enemy: &'a Character,
+        #[serde(serialize_with = "path")]
+        location: &'a Location,
+    },
+    Shoot {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        weapon: &'a Key,
+    },
+    Surprise {
+        #[serde(serialize_with = "combatant")]
+        attacker: &'a Character,
+    },
+    Defend {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        recovered_mp: i32,
+    },
+    Taunt {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        rounds: i32,
+    },
+    MountHurt {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        mount: &'a Mount,
+        damage: i32,
+    },
+    Forecast {
+        #[serde(serialize_with = "combatant")]
+        enemy: &'a Character,
+        forecast: &'a Forecast,
+    },
+    LootSkipped {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        skipped: &'a filter::Skipped,
+    },
+    Rage {
+        #[serde(serialize_with = "combatant")]
+        enemy: &'a Character,
+    },
+    EnemyFlees {
+        #[serde(serialize_with = "combatant")]
+        enemy: &'a Character,
+    },
+    Parry {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+    },
+    AutoPotion {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        threshold: i32,
+    },
+    HealItem {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        item: &'a str,
+        recovered_hp: i32,
+        recovered_mp: i32,
+        healed: bool,
+    },
+    Heal {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        #[serde(serialize_with = "path")]
+        location: &'a Location,
+        recovered_hp: i32,
+        recovered_mp: i32,
+        healed: bool,
+    },
+    ChangeClass {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        lost_xp: i32,
+    },
+    StatIncrease {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+        stat: &'a str,
+        increase: i32,
+    },
+    Status {
+        #[serde(serialize_with = "hero")]
+        game: &'a Game,
+    },
+    StatDetail {
+        breakdown: &'a [StatBreakdown],
+    },
+    ShopList {
+        #[serde(serialize_with = "hero")]
+        game: &'a Game,
+        items: &'a [(i32, String)],
+    },
+    ShopBuy {
+        cost: i32,
+        items: &'a HashMap<Key, i32>,
+    },
+    Traded {
+        give: &'a Key,
+        receive: &'a Key,
+        count: i32,
+    },
+    QuestList {
+        quests: &'a [(bool, String)],
+    },
+    Warning {
+        text: &'a str,
+    },
+    Hint {
+        text: &'a str,
+    },
+    Tip {
+        text: &'a str,
+    },
+    QuestDone {
+        reward: i32,
+    },
+    NpcEncounter {
+        encounter: &'a Encounter,
+    },
+    SkillList {
+        #[serde(serialize_with = "combatant")]
+        player: &'a Character,
+    },
+}
+
+impl Event<'_> {
+    /// The event as a JSON object, tagged with its name, for sinks that
+    /// record it instead of printing it.
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    /// Whether the event only adds detail to what happened, so it's left
+    /// out of the quiet output.
+    pub fn is_detail(&self) -> bool {
+        matches!(
+            self,
+            Event::Attack { .. }
+                | Event::Narration { .. }
+                | Event::TrapDetected
+                | Event::Danger { .. }
+                | Event::Invasion { .. }
+                | Event::Terrain { .. }
+                | Event::Anomaly { .. }
+                | Event::Gathered { .. }
+                | Event::Foraged { .. }
+                | Event::Boosted { .. }
+                | Event::Weather { .. }
+                | Event::BugFixed { .. }
+                | Event::Commits { .. }
+                | Event::EquipmentBroke { .. }
+                | Event::Repaired { .. }
+                | Event::Fatigued { .. }
+                | Event::OfflineGold { .. }
+                | Event::Voyage { .. }
+                | Event::Cleared { .. }
+                | Event::GuildRankUp { .. }
+                | Event::Split { .. }
+                | Event::Exported { .. }
+                | Event::Mercy
+                | Event::TitleEarned { .. }
+                | Event::Victory { .. }
+                | Event::NewGamePlus { .. }
+                | Event::ArtifactFound { .. }
+                | Event::ArtifactsComplete
+                | Event::PartyXp { .. }
+                | Event::MercenaryPaid { .. }
+                | Event::Escape { .. }
+                | Event::LootSkipped { .. }
+                | Event::AutoPotion { .. }
+                | Event::Warning { .. }
+                | Event::Hint { .. }
+                | Event::Tip { .. }
+                | Event::QuestDone { .. }
+        )
+    }
+
+    /// Whether the event is flavor or advice rather than game output, so
+    /// it's left out of the plain output meant for scripts.
+    pub fn is_decoration(&self) -> bool {
+        matches!(
+            self,
+            Event::Narration { .. } | Event::Hint { .. } | Event::Tip { .. }
+        )
+    }
+}
+
+fn combatant<S: Serializer>(character: &&Character, serializer: S) -> Result<S::Ok, S::Error> {
+    character_json(character).serialize(serializer)
+}
+
+fn player<S: Serializer>(game: &&Game, serializer: S) -> Result<S::Ok, S::Error> {
+    combatant(&&game.player, serializer)
+}
+
+/// The hero along with where it is and its gold.
+fn hero<S: Serializer>(game: &&Game, serializer: S) -> Result<S::Ok, S::Error> {
+    hero_json(game).serialize(serializer)
+}
+
+fn heroes<S: Serializer>(games: &&[Game], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(games.iter().map(hero_json))
+}
+
+fn hero_json(game: &Game) -> Value {
+    json!({
+        "name": game.name,
+        "player": character_json(&game.player),
+        "location": game.location.path_string(),
+        "gold": game.gold,
+    })
+}
+
+fn character_json(character: &Character) -> Value {
+    json!({
+        "name": character.name(),
+        "level": character.level,
+        "hp": character.current_hp,
+        "max_hp": character.max_hp(),
+        "status_effect": character.status_effect,
+    })
+}
+
+fn path<S: Serializer>(location: &&Location, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&location.path_string())
+}
+
+fn paths<S: Serializer>(locations: &&[Location], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(locations.iter().map(Location::path_string))
+}
+
+fn class_names<S: Serializer>(classes: &&[&Class], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(classes.iter().map(|class| &class.name))
+}
+
+fn attack_name<S: Serializer>(attack: &&AttackType, serializer: S) -> Result<S::Ok, S::Error> {
+    let name = match attack {
+        AttackType::Regular => "regular",
+        AttackType::Critical => "critical",
+        AttackType::Effect(_) => "effect",
+        AttackType::Miss => "miss",
+    };
+    serializer.serialize_str(name)
+}
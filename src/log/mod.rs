@@ -0,0 +1,620 @@
+use crate::arena::Arena;
+use crate::artifact::Artifact;
+use crate::bounty::{Board, Bounty};
+use crate::character::class::Class;
+use crate::character::enemy::{Danger, Hunt, Invasion};
+use crate::character::mount::Mount;
+use crate::character::npc::Encounter;
+use crate::character::{AttackType, Buff, Character, StatBreakdown, StatusEffect};
+use crate::config::Config;
+use crate::cooking::Meal;
+use crate::game::{Game, Progress};
+use crate::guild::Membership;
+use crate::insurance::Policy;
+use crate::item::equipment::Equipment;
+use crate::item::filter;
+use crate::item::key::Key;
+use crate::item::material::Material;
+use crate::item::stash::Stash;
+use crate::location::Location;
+use crate::lore::Codex;
+use crate::mutator::Mutators;
+use crate::simulation::Forecast;
+use crate::speedrun::{Speedrun, Split};
+use crate::stats::Stats;
+use crate::terrain::Terrain;
+use crate::title::Title;
+use crate::weather::Weather;
+use event::Event;
+use std::collections::HashMap;
+
+mod event;
+mod render;
+mod sink;
+
+pub use render::{format_inventory, DEFAULT_PACE_MS};
+pub use sink::mute;
+#[cfg(unix)]
+pub use sink::record;
+
+/// Set the global output preferences. A non-zero pace is the delay in
+/// milliseconds between battle turns.
+pub fn init(quiet: bool, plain: bool, names: bool, a11y: bool, pace: u64) {
+    render::init(names, a11y, pace);
+    sink::init(quiet, plain, Config::get().notifications);
+}
+
+pub fn enemy_appears(enemy: &Character, location: &Location) {
+    sink::emit(&Event::EnemyAppears { enemy, location });
+}
+
+pub fn attack(character: &Character, attack: &AttackType, damage: i32, mp_cost: i32) {
+    sink::emit(&Event::Attack {
+        character,
+        attack,
+        damage,
+        mp_cost,
+    });
+}
+
+pub fn status_effect(character: &Character, hp: i32, mp: i32) {
+    sink::emit(&Event::StatusEffect { character, hp, mp });
+}
+
+pub fn battle_won(game: &Game, xp: i32, levels_up: i32, gold: i32, items: &HashMap<Key, i32>) {
+    sink::emit(&Event::BattleWon {
+        game,
+        xp,
+        levels_up,
+        gold,
+        items,
+    });
+}
+
+pub fn battle_lost(player: &Character) {
+    sink::emit(&Event::BattleLost { player });
+}
+
+pub fn chest(items: &HashMap<Key, i32>, gold: i32) {
+    sink::emit(&Event::Chest { items, gold });
+}
+
+pub fn narration(attacker: &Character, receiver: &Character, attack: &AttackType) {
+    if Config::get().narration {
+        sink::emit(&Event::Narration {
+            attacker,
+            receiver,
+            attack,
+        });
+    }
+}
+
+pub fn trap_detected() {
+    sink::emit(&Event::TrapDetected);
+}
+
+pub fn trap_sprung(player: &Character, damage: i32) {
+    sink::emit(&Event::TrapSprung { player, damage });
+}
+
+pub fn trap_disarmed(player: &Character) {
+    sink::emit(&Event::TrapDisarmed { player });
+}
+
+pub fn tombstone(owner: Option<&str>, items: &HashMap<Key, i32>, gold: i32) {
+    sink::emit(&Event::Tombstone { owner, items, gold });
+}
+
+pub fn danger(danger: &Danger) {
+    sink::emit(&Event::Danger { danger });
+}
+
+pub fn hunt(hunt: &Hunt, steps: i32) {
+    sink::emit(&Event::Hunt { hunt, steps });
+}
+
+pub fn invasion(invasion: &Invasion) {
+    sink::emit(&Event::Invasion { invasion });
+}
+
+pub fn terrain(terrain: Terrain) {
+    sink::emit(&Event::Terrain { terrain });
+}
+
+/// Warn about entering an anomaly, showing how it shifted the hero stats.
+pub fn anomaly(buff: &Buff) {
+    sink::emit(&Event::Anomaly { buff });
+}
+
+pub fn gathered(material: Material, count: i32) {
+    sink::emit(&Event::Gathered { material, count });
+}
+
+pub fn foraged(rations: i32) {
+    sink::emit(&Event::Foraged { rations });
+}
+
+pub fn cooked(meal: &Meal, discovered: bool) {
+    sink::emit(&Event::Cooked { meal, discovered });
+}
+
+pub fn burnt() {
+    sink::emit(&Event::Burnt);
+}
+
+/// The meals found out so far, along with their ingredients.
+pub fn cookbook(game: &Game) {
+    sink::emit(&Event::Cookbook { game });
+}
+
+pub fn socketed(piece: &Equipment, gem: Material) {
+    sink::emit(&Event::Socketed { piece, gem });
+}
+
+pub fn pickaxe_broke() {
+    sink::emit(&Event::PickaxeBroke);
+}
+
+pub fn brewed(item: &str) {
+    sink::emit(&Event::Brewed { item });
+}
+
+/// The witch's recipes, marking those the gathered materials are enough for.
+pub fn recipes(game: &Game) {
+    sink::emit(&Event::Recipes { game });
+}
+
+pub fn thrown(enemy: &Character, effect: &str) {
+    sink::emit(&Event::Thrown { enemy, effect });
+}
+
+pub fn coated(player: &Character, status: StatusEffect) {
+    sink::emit(&Event::Coated { player, status });
+}
+
+pub fn boosted(item: &str, buff: &Buff, turns: i32) {
+    sink::emit(&Event::Boosted { item, buff, turns });
+}
+
+/// Describe the weather, either reporting it at a location or announcing
+/// that it just changed. Clear skies go unmentioned.
+pub fn weather(weather: Weather, changed: bool) {
+    sink::emit(&Event::Weather { weather, changed });
+}
+
+pub fn bug_fixed(bug: &Character) {
+    sink::emit(&Event::BugFixed { bug });
+}
+
+pub fn commits(player: &Character, count: usize, xp: i32, levels_up: i32) {
+    sink::emit(&Event::Commits {
+        player,
+        count,
+        xp,
+        levels_up,
+    });
+}
+
+pub fn equipment_broke(player: &Character, piece: &Key) {
+    sink::emit(&Event::EquipmentBroke { player, piece });
+}
+
+pub fn morale(player: &Character, change: i32) {
+    sink::emit(&Event::Morale { player, change });
+}
+
+pub fn trembles(player: &Character) {
+    sink::emit(&Event::Trembles { player });
+}
+
+pub fn skill_point(player: &Character) {
+    sink::emit(&Event::SkillPoint { player });
+}
+
+pub fn class_mastered(class: &str, unlocked: &[&Class]) {
+    sink::emit(&Event::ClassMastered { class, unlocked });
+}
+
+pub fn respec(player: &Character, points: i32, cost: i32) {
+    sink::emit(&Event::Respec {
+        player,
+        points,
+        cost,
+    });
+}
+
+/// Report the equipment repaired, for the given cost if any.
+pub fn repaired(player: &Character, cost: i32) {
+    sink::emit(&Event::Repaired { player, cost });
+}
+
+pub fn fatigued(player: &Character) {
+    sink::emit(&Event::Fatigued { player });
+}
+
+pub fn offline_gold(hours: i32, gold: i32) {
+    sink::emit(&Event::OfflineGold { hours, gold });
+}
+
+pub fn voyage(location: &Location, fare: i32) {
+    sink::emit(&Event::Voyage { location, fare });
+}
+
+pub fn cleared(location: &Location) {
+    sink::emit(&Event::Cleared { location });
+}
+
+pub fn banish(location: &Location, cost: i32) {
+    sink::emit(&Event::Banish { location, cost });
+}
+
+pub fn outpost_built(location: &Location, cost: i32) {
+    sink::emit(&Event::OutpostBuilt { location, cost });
+}
+
+pub fn outpost_list(outposts: &[Location]) {
+    sink::emit(&Event::OutpostList { outposts });
+}
+
+pub fn guild(membership: &Membership) {
+    sink::emit(&Event::Guild { membership });
+}
+
+pub fn arena_wave(wave: i32) {
+    sink::emit(&Event::ArenaWave { wave });
+}
+
+pub fn arena_wave_cleared(arena: &Arena, payout: i32) {
+    sink::emit(&Event::ArenaWaveCleared { arena, payout });
+}
+
+pub fn arena_cashed_out(arena: &Arena) {
+    sink::emit(&Event::ArenaCashedOut { arena });
+}
+
+pub fn boss_rush_cleared(bosses: usize, rounds: i32) {
+    sink::emit(&Event::BossRushCleared { bosses, rounds });
+}
+
+pub fn bounties(board: &Board) {
+    sink::emit(&Event::Bounties { board });
+}
+
+pub fn bounty_accepted(bounty: &Bounty) {
+    sink::emit(&Event::BountyAccepted { bounty });
+}
+
+pub fn bounties_turned_in(gold: i32, reputation: i32, total: i32) {
+    sink::emit(&Event::BountiesTurnedIn {
+        gold,
+        reputation,
+        total,
+    });
+}
+
+pub fn guild_rank_up(membership: &Membership, reward: i32) {
+    sink::emit(&Event::GuildRankUp { membership, reward });
+}
+
+pub fn exploration(directories: i32, progress: &Progress) {
+    sink::emit(&Event::Exploration {
+        directories,
+        progress,
+    });
+}
+
+pub fn grind(progress: &Progress) {
+    sink::emit(&Event::Grind { progress });
+}
+
+pub fn secret(secret: &str) {
+    sink::emit(&Event::Secret { secret });
+}
+
+pub fn codex(codex: &Codex) {
+    sink::emit(&Event::Codex { codex });
+}
+
+pub fn story(sentences: &[String]) {
+    sink::emit(&Event::Story { sentences });
+}
+
+pub fn manual(paragraphs: &[String]) {
+    sink::emit(&Event::Manual { paragraphs });
+}
+
+pub fn stats(stats: &Stats) {
+    sink::emit(&Event::Stats { stats });
+}
+
+pub fn split(split: Split, elapsed: u64) {
+    sink::emit(&Event::Split { split, elapsed });
+}
+
+pub fn splits(run: &Speedrun) {
+    sink::emit(&Event::Splits { run });
+}
+
+pub fn mutators(mutators: &Mutators) {
+    sink::emit(&Event::Mutators { mutators });
+}
+
+pub fn exported(file: &str) {
+    sink::emit(&Event::Exported { file });
+}
+
+pub fn insurance(policy: &Policy, price: i32) {
+    sink::emit(&Event::Insurance { policy, price });
+}
+
+pub fn insurance_lapsed() {
+    sink::emit(&Event::InsuranceLapsed);
+}
+
+pub fn mercy() {
+    sink::emit(&Event::Mercy);
+}
+
+pub fn insurance_claim(items: &HashMap<Key, i32>, gold: i32) {
+    sink::emit(&Event::InsuranceClaim { items, gold });
+}
+
+pub fn stash(stash: &Stash) {
+    sink::emit(&Event::Stash { stash });
+}
+
+pub fn stash_moved(items: &HashMap<Key, i32>, gold: i32) {
+    sink::emit(&Event::StashMoved { items, gold });
+}
+
+pub fn hero_list(active: &Game, others: &[Game]) {
+    sink::emit(&Event::HeroList { active, others });
+}
+
+pub fn title_earned(title: Title) {
+    sink::emit(&Event::TitleEarned { title });
+}
+
+/// List every title, marking the earned ones and the one shown.
+pub fn titles(game: &Game) {
+    sink::emit(&Event::Titles { game });
+}
+
+/// Tell the end of the story, from the latest journal entries, after
+/// gorthaur is defeated.
+pub fn victory(epilogue: &[String], champions: usize, unlocked: bool) {
+    sink::emit(&Event::Victory {
+        epilogue,
+        champions,
+        unlocked,
+    });
+}
+
+pub fn new_game_plus(cycle: i32) {
+    sink::emit(&Event::NewGamePlus { cycle });
+}
+
+pub fn artifact_found(artifact: Artifact) {
+    sink::emit(&Event::ArtifactFound { artifact });
+}
+
+pub fn artifacts_complete() {
+    sink::emit(&Event::ArtifactsComplete);
+}
+
+pub fn artifacts(game: &Game) {
+    sink::emit(&Event::Artifacts { game });
+}
+
+pub fn map(stats: &Stats, heat: bool) {
+    sink::emit(&Event::Map { stats, heat });
+}
+
+pub fn party_joined(member: &Character) {
+    sink::emit(&Event::PartyJoined { member });
+}
+
+pub fn party_left(member: &Character) {
+    sink::emit(&Event::PartyLeft { member });
+}
+
+pub fn party_xp(member: &Character, xp: i32, levels_up: i32) {
+    sink::emit(&Event::PartyXp {
+        member,
+        xp,
+        levels_up,
+    });
+}
+
+pub fn party(game: &Game) {
+    sink::emit(&Event::Party { game });
+}
+
+pub fn mercenary_hired(mercenary: &Character, fee: i32) {
+    sink::emit(&Event::MercenaryHired { mercenary, fee });
+}
+
+pub fn mercenary_paid(mercenary: &Character, wage: i32) {
+    sink::emit(&Event::MercenaryPaid { mercenary, wage });
+}
+
+pub fn mercenary_left(mercenary: &Character) {
+    sink::emit(&Event::MercenaryLeft { mercenary });
+}
+
+pub fn mercenary_died(mercenary: &Character) {
+    sink::emit(&Event::MercenaryDied { mercenary });
+}
+
+pub fn bribe(player: &Character, amount: i32) {
+    sink::emit(&Event::Bribe { player, amount });
+}
+
+pub fn run_away(player: &Character, success: bool) {
+    sink::emit(&Event::RunAway { player, success });
+}
+
+pub fn pursuit(enemy: &Character) {
+    sink::emit(&Event::Pursuit { enemy });
+}
+
+pub fn escape(enemy: &Character) {
+    sink::emit(&Event::Escape { enemy });
+}
+
+pub fn ambush(enemy: &Character, location: &Location) {
+    sink::emit(&Event::Ambush { enemy, location });
+}
+
+pub fn shoot(player: &Character, weapon: &Key) {
+    sink::emit(&Event::Shoot { player, weapon });
+}
+
+pub fn surprise(attacker: &Character) {
+    sink::emit(&Event::Surprise { attacker });
+}
+
+pub fn defend(player: &Character, recovered_mp: i32) {
+    sink::emit(&Event::Defend {
+        player,
+        recovered_mp,
+    });
+}
+
+pub fn taunt(player: &Character, rounds: i32) {
+    sink::emit(&Event::Taunt { player, rounds });
+}
+
+pub fn mount_hurt(player: &Character, mount: &Mount, damage: i32) {
+    sink::emit(&Event::MountHurt {
+        player,
+        mount,
+        damage,
+    });
+}
+
+pub fn forecast(enemy: &Character, forecast: &Forecast) {
+    sink::emit(&Event::Forecast { enemy, forecast });
+}
+
+pub fn loot_skipped(player: &Character, skipped: &filter::Skipped) {
+    sink::emit(&Event::LootSkipped { player, skipped });
+}
+
+pub fn rage(enemy: &Character) {
+    sink::emit(&Event::Rage { enemy });
+}
+
+pub fn enemy_flees(enemy: &Character) {
+    sink::emit(&Event::EnemyFlees { enemy });
+}
+
+pub fn parry(player: &Character) {
+    sink::emit(&Event::Parry { player });
+}
+
+pub fn auto_potion(player: &Character, threshold: i32) {
+    sink::emit(&Event::AutoPotion { player, threshold });
+}
+
+pub fn heal_item(
+    player: &Character,
+    item: &str,
+    recovered_hp: i32,
+    recovered_mp: i32,
+    healed: bool,
+) {
+    sink::emit(&Event::HealItem {
+        player,
+        item,
+        recovered_hp,
+        recovered_mp,
+        healed,
+    });
+}
+
+pub fn heal(
+    player: &Character,
+    location: &Location,
+    recovered_hp: i32,
+    recovered_mp: i32,
+    healed: bool,
+) {
+    sink::emit(&Event::Heal {
+        player,
+        location,
+        recovered_hp,
+        recovered_mp,
+        healed,
+    });
+}
+
+pub fn change_class(player: &Character, lost_xp: i32) {
+    sink::emit(&Event::ChangeClass { player, lost_xp });
+}
+
+pub fn stat_increase(player: &Character, stat: &str, increase: i32) {
+    sink::emit(&Event::StatIncrease {
+        player,
+        stat,
+        increase,
+    });
+}
+
+/// Print the hero status according to options
+pub fn status(game: &Game) {
+    sink::emit(&Event::Status { game });
+}
+
+/// Print how each hero stat adds up from its sources.
+pub fn stat_detail(breakdown: &[StatBreakdown]) {
+    sink::emit(&Event::StatDetail { breakdown });
+}
+
+pub fn shop_list(game: &Game, items: Vec<(i32, String)>) {
+    sink::emit(&Event::ShopList {
+        game,
+        items: &items,
+    });
+}
+
+pub fn shop_buy(cost: i32, items: &HashMap<Key, i32>) {
+    sink::emit(&Event::ShopBuy { cost, items });
+}
+
+pub fn traded(give: &Key, receive: &Key, count: i32) {
+    sink::emit(&Event::Traded {
+        give,
+        receive,
+        count,
+    });
+}
+
+pub fn quest_list(quests: Vec<(bool, String)>) {
+    sink::emit(&Event::QuestList { quests: &quests });
+}
+
+/// Printed to stderr, since it can show up in the output of pwd.
+pub fn warning(text: &str) {
+    sink::emit(&Event::Warning { text });
+}
+
+pub fn hint(text: &str) {
+    sink::emit(&Event::Hint { text });
+}
+
+pub fn tip(text: &str) {
+    sink::emit(&Event::Tip { text });
+}
+
+pub fn quest_done(reward: i32) {
+    sink::emit(&Event::QuestDone { reward });
+}
+
+pub fn npc_encounter(encounter: &Encounter) {
+    sink::emit(&Event::NpcEncounter { encounter });
+}
+
+pub fn skill_list(player: &Character) {
+    sink::emit(&Event::SkillList { player });
+}
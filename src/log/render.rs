@@ -0,0 +1,2242 @@
+use super::event::Event;
+use crate::arena::Arena;
+use crate::artifact::Artifact;
+use crate::bounty::{Board, Bounty};
+use crate::character::class::{Category, Class, Skill, SkillType, MAX_SKILL_RANK};
+use crate::character::enemy::{self, Danger, Hunt, Invasion};
+use crate::character::morale::Mood;
+use crate::character::mount::Mount;
+use crate::character::AttackType;
+use crate::character::{Buff, Character, StatBreakdown, StatusEffect, FATIGUE_PENALTY};
+use crate::config::Config;
+use crate::cooking::{self, Meal};
+use crate::game::{Game, Progress, DEFAULT_NAME};
+use crate::guild::Membership;
+use crate::item::equipment::Equipment;
+use crate::item::filter;
+use crate::item::key::Key;
+use crate::item::material::{self, Material};
+use crate::location::Location;
+use crate::mercy;
+use crate::mutator::{Mutator, Mutators};
+use crate::narration;
+use crate::simulation::Forecast;
+use crate::speedrun::{Speedrun, Split};
+use crate::terrain::Terrain;
+use crate::title::Title;
+use crate::weather::Weather;
+use colored::*;
+use once_cell::sync::OnceCell;
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use strum::IntoEnumIterator;
+
+// This are initialized based on input args and then act as constants
+// this prevents having to pass around the flags or lazily parsing the opts
+static NAMES: OnceCell<bool> = OnceCell::new();
+static A11Y: OnceCell<bool> = OnceCell::new();
+static PACE: OnceCell<u64> = OnceCell::new();
+
+/// Battle turns printed so far by the current command.
+static TURNS: AtomicU32 = AtomicU32::new(0);
+
+/// Milliseconds between battle turns when pacing is requested with the
+/// flag but not set in the config.
+pub const DEFAULT_PACE_MS: u64 = 300;
+
+/// Turns after which a fight is long enough to show a spinner while
+/// waiting for the next one.
+const LONG_FIGHT_TURNS: u32 = 6;
+
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// How much of the terminal output is printed, chosen by the sink.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Style {
+    /// The full output, with portraits, bars and paced battles.
+    Rich,
+    /// Tab separated, easy to parse output for scripts.
+    Plain,
+    /// Only the outcome of each action, in short lines.
+    Quiet,
+}
+
+/// Set the rendering preferences that don't depend on the sink. A non-zero
+/// pace is the delay in milliseconds between battle turns.
+pub fn init(names: bool, a11y: bool, pace: u64) {
+    NAMES.set(names).unwrap();
+    A11Y.set(a11y).unwrap();
+    PACE.set(pace).unwrap();
+    if a11y {
+        // nothing should be signaled by color alone
+        colored::control::set_override(false);
+    }
+}
+
+fn names() -> bool {
+    *NAMES.get().unwrap_or(&false)
+}
+
+/// Screen reader friendly output: sentences instead of bars, colors
+/// and aligned columns.
+fn a11y() -> bool {
+    *A11Y.get().unwrap_or(&false)
+}
+
+/// Print the event to the terminal in the given style.
+pub fn render(event: &Event, style: Style) {
+    match *event {
+        Event::EnemyAppears { enemy, location } => enemy_appears(enemy, location, style),
+        Event::Attack {
+            character,
+            attack: kind,
+            damage,
+            mp_cost,
+        } => attack(character, kind, damage, mp_cost, style),
+        Event::StatusEffect { character, hp, mp } => status_effect(character, hp, mp),
+        Event::BattleWon {
+            game,
+            xp,
+            levels_up,
+            gold,
+            items,
+        } => battle_won(game, xp, levels_up, gold, items),
+        Event::BattleLost { player } => battle_lost(player),
+        Event::Chest { items, gold } => chest(items, gold),
+        Event::Narration {
+            attacker,
+            receiver,
+            attack: kind,
+        } => narration(attacker, receiver, kind),
+        Event::TrapDetected => trap_detected(),
+        Event::TrapSprung { player, damage } => trap_sprung(player, damage),
+        Event::TrapDisarmed { player } => trap_disarmed(player),
+        Event::Tombstone { owner, items, gold } => tombstone(owner, items, gold),
+        Event::Danger { danger: risk } => danger(risk),
+        Event::Hunt {
+            hunt: quarry,
+            steps,
+        } => hunt(quarry, steps),
+        Event::Invasion { invasion: raid } => invasion(raid),
+        Event::Terrain { terrain: land } => terrain(land),
+        Event::Anomaly { buff } => anomaly(buff),
+        Event::Gathered { material, count } => gathered(material, count),
+        Event::Foraged { rations } => foraged(rations),
+        Event::Cooked { meal, discovered } => cooked(meal, discovered),
+        Event::Burnt => burnt(),
+        Event::Cookbook { game } => cookbook(game),
+        Event::Socketed { piece, gem } => socketed(piece, gem),
+        Event::PickaxeBroke => pickaxe_broke(),
+        Event::Brewed { item } => brewed(item),
+        Event::Recipes { game } => recipes(game),
+        Event::Thrown { enemy, effect } => thrown(enemy, effect),
+        Event::Coated {
+            player,
+            status: effect,
+        } => coated(player, effect),
+        Event::Boosted { item, buff, turns } => boosted(item, buff, turns),
+        Event::Weather {
+            weather: current,
+            changed,
+        } => weather(current, changed),
+        Event::BugFixed { bug } => bug_fixed(bug),
+        Event::Commits {
+            player,
+            count,
+            xp,
+            levels_up,
+        } => commits(player, count, xp, levels_up),
+        Event::EquipmentBroke { player, piece } => equipment_broke(player, piece),
+        Event::Morale { player, change } => morale(player, change),
+        Event::Trembles { player } => trembles(player),
+        Event::SkillPoint { player } => skill_point(player),
+        Event::ClassMastered { class, unlocked } => class_mastered(class, unlocked),
+        Event::Respec {
+            player,
+            points,
+            cost,
+        } => respec(player, points, cost),
+        Event::Repaired { player, cost } => repaired(player, cost),
+        Event::Fatigued { player } => fatigued(player),
+        Event::OfflineGold { hours, gold } => offline_gold(hours, gold),
+        Event::Voyage { location, fare } => voyage(location, fare),
+        Event::Cleared { location } => cleared(location),
+        Event::Banish { location, cost } => banish(location, cost),
+        Event::OutpostBuilt { location, cost } => outpost_built(location, cost),
+        Event::OutpostList { outposts } => outpost_list(outposts),
+        Event::Guild { membership } => guild(membership),
+        Event::ArenaWave { wave } => arena_wave(wave),
+        Event::ArenaWaveCleared { arena, payout } => arena_wave_cleared(arena, payout),
+        Event::ArenaCashedOut { arena } => arena_cashed_out(arena),
+        Event::BossRushCleared { bosses, rounds } => boss_rush_cleared(bosses, rounds),
+        Event::Bounties { board } => bounties(board),
+        Event::BountyAccepted { bounty } => bounty_accepted(bounty),
+        Event::BountiesTurnedIn {
+            gold,
+            reputation,
+            total,
+        } => bounties_turned_in(gold, reputation, total),
+        Event::GuildRankUp { membership, reward } => guild_rank_up(membership, reward),
+        Event::Exploration {
+            directories,
+            progress,
+        } => exploration(directories, progress),
+        Event::Grind { progress } => grind(progress),
+        Event::Secret { secret: text } => secret(text),
+        Event::Codex { codex: lore } => codex(lore),
+        Event::Story { sentences } => story(sentences),
+        Event::Manual { paragraphs } => manual(paragraphs),
+        Event::Stats { stats: totals } => stats(totals),
+        Event::Split {
+            split: segment,
+            elapsed,
+        } => split(segment, elapsed),
+        Event::Splits { run } => splits(run),
+        Event::Mutators { mutators: active } => mutators(active),
+        Event::Exported { file } => exported(file),
+        Event::Insurance { policy, price } => insurance(policy, price),
+        Event::InsuranceLapsed => insurance_lapsed(),
+        Event::Mercy => mercy(),
+        Event::InsuranceClaim { items, gold } => insurance_claim(items, gold),
+        Event::Stash { stash: stored } => stash(stored),
+        Event::StashMoved { items, gold } => stash_moved(items, gold),
+        Event::HeroList { active, others } => hero_list(active, others),
+        Event::TitleEarned { title } => title_earned(title),
+        Event::Titles { game } => titles(game),
+        Event::Victory {
+            epilogue,
+            champions,
+            unlocked,
+        } => victory(epilogue, champions, unlocked),
+        Event::NewGamePlus { cycle } => new_game_plus(cycle),
+        Event::ArtifactFound { artifact } => artifact_found(artifact),
+        Event::ArtifactsComplete => artifacts_complete(),
+        Event::Artifacts { game } => artifacts(game),
+        Event::Map {
+            stats: totals,
+            heat,
+        } => map(totals, heat),
+        Event::PartyJoined { member } => party_joined(member),
+        Event::PartyLeft { member } => party_left(member),
+        Event::PartyXp {
+            member,
+            xp,
+            levels_up,
+        } => party_xp(member, xp, levels_up),
+        Event::Party { game } => party(game),
+        Event::MercenaryHired { mercenary, fee } => mercenary_hired(mercenary, fee),
+        Event::MercenaryPaid { mercenary, wage } => mercenary_paid(mercenary, wage),
+        Event::MercenaryLeft { mercenary } => mercenary_left(mercenary),
+        Event::MercenaryDied { mercenary } => mercenary_died(mercenary),
+        Event::Bribe { player, amount } => bribe(player, amount),
+        Event::RunAway { player, success } => run_away(player, success),
+        Event::Pursuit { enemy } => pursuit(enemy),
+        Event::Escape { enemy } => escape(enemy),
+        Event::Ambush { enemy, location } => ambush(enemy, location),
+        Event::Shoot { player, weapon } => shoot(player, weapon),
+        Event::Surprise { attacker } => surprise(attacker),
+        Event::Defend {
+            player,
+            recovered_mp,
+        } => defend(player, recovered_mp),
+        Event::Taunt { player, rounds } => taunt(player, rounds),
+        Event::MountHurt {
+            player,
+            mount,
+            damage,
+        } => mount_hurt(player, mount, damage),
+        Event::Forecast {
+            enemy,
+            forecast: outcome,
+        } => forecast(enemy, outcome),
+        Event::LootSkipped { player, skipped } => loot_skipped(player, skipped),
+        Event::Rage { enemy } => rage(enemy),
+        Event::EnemyFlees { enemy } => enemy_flees(enemy),
+        Event::Parry { player } => parry(player),
+        Event::AutoPotion { player, threshold } => auto_potion(player, threshold),
+        Event::HealItem {
+            player,
+            item,
+            recovered_hp,
+            recovered_mp,
+            healed,
+        } => heal_item(player, item, recovered_hp, recovered_mp, healed),
+        Event::Heal {
+            player,
+            location,
+            recovered_hp,
+            recovered_mp,
+            healed,
+        } => heal(player, location, recovered_hp, recovered_mp, healed),
+        Event::ChangeClass { player, lost_xp } => change_class(player, lost_xp),
+        Event::StatIncrease {
+            player,
+            stat,
+            increase,
+        } => stat_increase(player, stat, increase),
+        Event::Status { game } => status(game, style),
+        Event::StatDetail { breakdown } => stat_detail(breakdown, style),
+        Event::ShopList { game, items } => shop_list(game, items),
+        Event::ShopBuy { cost, items } => shop_buy(cost, items),
+        Event::Traded {
+            give,
+            receive,
+            count,
+        } => traded(give, receive, count),
+        Event::QuestList { quests } => quest_list(quests),
+        Event::Warning { text } => warning(text),
+        Event::Hint { text } => hint(text),
+        Event::Tip { text } => tip(text),
+        Event::QuestDone { reward } => quest_done(reward),
+        Event::NpcEncounter { encounter } => npc_encounter(encounter),
+        Event::SkillList { player } => skill_list(player),
+    }
+}
+
+/// A one line summary of the events worth telling the player about when
+/// they are not looking at the terminal, if any.
+pub fn notification(event: &Event) -> Option<String> {
+    let text = match *event {
+        Event::BattleLost { player } => format!("{} died", player.name()),
+        Event::BattleWon {
+            game, levels_up, ..
+        } if levels_up > 0 => format!("{} reached level {}", game.player.name(), game.player.level),
+        Event::QuestDone { reward } => format!("quest completed, +{}g", reward),
+        Event::TitleEarned { title } => format!("title earned: {}", title.name()),
+        Event::ClassMastered { class, .. } => format!("{} mastered", class),
+        Event::Victory { .. } => String::from("gorthaur has fallen"),
+        Event::ArtifactFound { artifact } => format!("artifact found: {}", artifact.name()),
+        Event::BossRushCleared { bosses, .. } => {
+            format!("boss rush cleared, {} bosses down", bosses)
+        }
+        _ => return None,
+    };
+    Some(text)
+}
+
+fn enemy_appears(enemy: &Character, location: &Location, style: Style) {
+    if style == Style::Rich && !a11y() {
+        if let Some(portrait) = portrait(enemy) {
+            print!("{}", portrait.yellow());
+        }
+    }
+    log(enemy, location, "");
+}
+
+fn attack(character: &Character, attack: &AttackType, damage: i32, mp_cost: i32, style: Style) {
+    if style == Style::Rich {
+        pace_turn();
+    }
+    battle_log(
+        character,
+        &format_attack(character, attack, damage, mp_cost),
+    );
+}
+
+fn status_effect(character: &Character, hp: i32, mp: i32) {
+    if hp != 0 || mp != 0 {
+        let emoji = character
+            .status_effect
+            .map_or("", |s| status_effect_params(s).1);
+
+        battle_log(
+            character,
+            &format_stat_change(character, hp, mp, false, emoji),
+        );
+    }
+}
+
+fn battle_won(game: &Game, xp: i32, levels_up: i32, gold: i32, items: &HashMap<Key, i32>) {
+    battle_log(
+        &game.player,
+        &format!(
+            "{}{}{}",
+            format!("+{}xp", xp).bold(),
+            level_up(levels_up),
+            format_ls("", items, gold)
+        ),
+    );
+    short_status(game);
+}
+
+fn battle_lost(player: &Character) {
+    battle_log(player, "\u{1F480}");
+}
+
+fn chest(items: &HashMap<Key, i32>, gold: i32) {
+    println!("{}", format_ls("\u{1F4E6}", items, gold));
+}
+
+/// Legendary and boss enemies are introduced with their portrait. Bosses
+/// derived from other classes, like the shadow, use the one of the class
+/// with their name, if any.
+fn portrait(enemy: &Character) -> Option<String> {
+    let class = &enemy.class;
+    if class.category != Category::Legendary && !enemy::is_boss(class) {
+        return None;
+    }
+    class
+        .portrait
+        .clone()
+        .or_else(|| Class::enemy_by_name(&class.name).and_then(|class| class.portrait.clone()))
+}
+
+fn narration(attacker: &Character, receiver: &Character, attack: &AttackType) {
+    println!(
+        "    {}",
+        narration::narrate(attacker, receiver, attack).italic()
+    );
+}
+
+fn trap_detected() {
+    println!(
+        "{} there's a trap on the chest here, disarm it or leave it be",
+        "trap!".yellow().bold()
+    );
+}
+
+fn trap_sprung(player: &Character, damage: i32) {
+    let (_, emoji) = status_effect_params(StatusEffect::Poison);
+    battle_log(
+        player,
+        &format!(
+            "{} {}",
+            "trap!".red().bold(),
+            format_stat_change(player, -damage, 0, false, emoji)
+        ),
+    );
+}
+
+fn trap_disarmed(player: &Character) {
+    battle_log(player, &"+trap disarmed".green());
+}
+
+fn tombstone(owner: Option<&str>, items: &HashMap<Key, i32>, gold: i32) {
+    let emoji = match owner {
+        Some(owner) if owner != DEFAULT_NAME => format!("\u{1FAA6}  {}", owner),
+        _ => String::from("\u{1FAA6} "),
+    };
+    println!("{}", format_ls(&emoji, items, gold));
+}
+
+fn danger(danger: &Danger) {
+    let mut text = if danger.cleared {
+        String::from("cleared, no enemies around")
+    } else if danger.odds.0 == 0 {
+        String::from("no enemies around")
+    } else {
+        format!(
+            "enemies lv:{}-{} chance:{}/{}",
+            danger.min_level, danger.max_level, danger.odds.0, danger.odds.1
+        )
+    };
+
+    if danger.elite {
+        text.push_str(&" elite zone".bright_red().to_string());
+    }
+    for (boss, steps) in &danger.bosses {
+        let hint = if *steps == 0 {
+            format!(" {} lurks here!", boss).bright_red().to_string()
+        } else {
+            format!(" {} lurks {} steps deeper", boss, steps)
+                .red()
+                .to_string()
+        };
+        text.push_str(&hint);
+    }
+    println!("\u{2694}\u{FE0F}  {}", text);
+}
+
+fn hunt(hunt: &Hunt, steps: i32) {
+    println!(
+        "\u{1F43E} {} tracks lead to {} ({} steps away)",
+        hunt.family.yellow().bold(),
+        format_location(&hunt.location),
+        steps
+    );
+}
+
+fn invasion(invasion: &Invasion) {
+    println!(
+        "\u{1F4E2} a horde of {} is invading directories at distance {}-{}! ({}m left)",
+        invasion.family.red().bold(),
+        invasion.min_distance,
+        invasion.max_distance,
+        invasion.minutes_left()
+    );
+}
+
+fn terrain(terrain: Terrain) {
+    let description = match terrain {
+        Terrain::Plain => return,
+        Terrain::Swamp => "the hero is slowed down",
+        Terrain::Fortress => "tougher enemies, richer rewards",
+        Terrain::Sanctuary => "no enemies dare to come in",
+        Terrain::Anomaly => "nothing stays the same here",
+    };
+    println!(
+        "\u{1F5FA}\u{FE0F}  {}: {}",
+        terrain.name(),
+        description.dimmed()
+    );
+}
+
+fn anomaly(buff: &Buff) {
+    println!(
+        "\u{26A0}\u{FE0F}  {} reality warps around the hero: att {:+}% spd {:+}%",
+        "anomaly!".yellow().bold(),
+        buff.attack,
+        buff.speed
+    );
+}
+
+fn gathered(material: Material, count: i32) {
+    println!("   {}", format!("+{}x{}", material, count).green());
+}
+
+fn foraged(rations: i32) {
+    if rations > 0 {
+        println!("   {}", format!("+rationx{}", rations).green());
+    }
+}
+
+fn cooked(meal: &Meal, discovered: bool) {
+    println!(
+        "The hero cooks a warm {}: att {:+}% spd {:+}% for {} battles.",
+        meal.name.bold(),
+        meal.buff.attack,
+        meal.buff.speed,
+        cooking::MEAL_BATTLES
+    );
+    if discovered {
+        println!("   {}", "new recipe written down in the cookbook!".yellow());
+    }
+}
+
+fn burnt() {
+    println!("The ingredients burn into an inedible mush.");
+}
+
+fn cookbook(game: &Game) {
+    for meal in &cooking::MEALS {
+        if game.cookbook.contains(meal.name) {
+            let ingredients: Vec<_> = meal
+                .ingredients
+                .iter()
+                .map(|(material, count)| format!("{}x{}", material, count))
+                .collect();
+            println!(
+                "  {} {:<6}{:<24}att {:+}% spd {:+}%",
+                "✔".green(),
+                meal.name,
+                ingredients.join(" "),
+                meal.buff.attack,
+                meal.buff.speed
+            );
+        } else {
+            println!("  {} {}", "□".dimmed(), "???".dimmed());
+        }
+    }
+    println!("\n  {}", format_materials(game));
+}
+
+fn socketed(piece: &Equipment, gem: Material) {
+    println!(
+        "   {} set in {}: {}",
+        gem.to_string().bold(),
+        piece,
+        piece.describe()
+    );
+}
+
+fn pickaxe_broke() {
+    println!("   {}", "the pickaxe broke!".red());
+}
+
+fn brewed(item: &str) {
+    println!(
+        "The witch brews a bubbling {} and hands it to you.",
+        item.bold()
+    );
+}
+
+fn recipes(game: &Game) {
+    for recipe in &material::RECIPES {
+        let ingredients: Vec<_> = recipe
+            .ingredients
+            .iter()
+            .map(|(material, count)| format!("{}x{}", material, count))
+            .collect();
+        let ingredients = ingredients.join(" ");
+        if recipe.can_brew(&game.materials) {
+            println!("  {} {:<14}{}", "✔".green(), recipe.name, ingredients);
+        } else {
+            println!(
+                "  {} {:<14}{}",
+                "□".dimmed(),
+                recipe.name.dimmed(),
+                ingredients.dimmed()
+            );
+        }
+    }
+    println!("\n  {}", format_materials(game));
+}
+
+fn format_materials(game: &Game) -> String {
+    let materials: Vec<_> = game
+        .materials
+        .iter()
+        .map(|(material, count)| format!("{}x{}", material, count))
+        .collect();
+    format!("materials:{{{}}}", materials.join(","))
+}
+
+fn thrown(enemy: &Character, effect: &str) {
+    battle_log(enemy, &effect.bold());
+}
+
+fn coated(player: &Character, status: StatusEffect) {
+    let (name, emoji) = status_effect_params(status);
+    battle_log(player, &format!("{} weapon coated with {}", emoji, name));
+}
+
+fn boosted(item: &str, buff: &Buff, turns: i32) {
+    println!(
+        "   {} att {:+}% spd {:+}% for {} turns",
+        item.green(),
+        buff.attack,
+        buff.speed,
+        turns
+    );
+}
+
+fn weather(weather: Weather, changed: bool) {
+    if weather == Weather::Clear {
+        return;
+    }
+    let emoji = match weather {
+        Weather::Rain => "\u{1F327}\u{FE0F} ",
+        Weather::Fog => "\u{1F32B}\u{FE0F} ",
+        _ => "\u{26C8}\u{FE0F} ",
+    };
+    if changed {
+        println!("{} the weather turns to {}", emoji, weather.name().bold());
+    } else {
+        println!("{} {}", emoji, weather.name());
+    }
+}
+
+fn bug_fixed(bug: &Character) {
+    battle_log(bug, &"fixed! the changes were committed".green());
+}
+
+fn commits(player: &Character, count: usize, xp: i32, levels_up: i32) {
+    battle_log(
+        player,
+        &format!(
+            "{} {} new commits{}",
+            format!("+{}xp", xp).bold(),
+            count,
+            level_up(levels_up)
+        ),
+    );
+}
+
+fn equipment_broke(player: &Character, piece: &Key) {
+    battle_log(player, &format!("{} {} broke!", "ouch!".red(), piece));
+}
+
+fn morale(player: &Character, change: i32) {
+    battle_log(
+        player,
+        &format!("{:+} morale", change).bright_blue().to_string(),
+    );
+}
+
+fn trembles(player: &Character) {
+    battle_log(player, &"trembles...".dimmed().to_string());
+}
+
+fn skill_point(player: &Character) {
+    battle_log(player, &"+1 skill point".cyan().to_string());
+}
+
+fn class_mastered(class: &str, unlocked: &[&Class]) {
+    let names: Vec<_> = unlocked.iter().map(|class| class.name.as_str()).collect();
+    if names.is_empty() {
+        println!("{} mastered!", class.bold());
+    } else {
+        println!(
+            "{} mastered! unlocked {}",
+            class.bold(),
+            names.join(", ").cyan()
+        );
+    }
+}
+
+fn respec(player: &Character, points: i32, cost: i32) {
+    battle_log(
+        player,
+        &format!(
+            "+{} skill points, skills unlearned {}",
+            points,
+            format_gold_signed(-cost)
+        ),
+    );
+}
+
+fn repaired(player: &Character, cost: i32) {
+    let cost = if cost > 0 {
+        format!(" {}", format_gold_signed(-cost))
+    } else {
+        String::new()
+    };
+    battle_log(player, &format!("+equipment repaired{}", cost));
+}
+
+fn fatigued(player: &Character) {
+    battle_log(
+        player,
+        &format!(
+            "{} out of rations, att and spd -{}%",
+            "fatigued!".red(),
+            FATIGUE_PENALTY
+        ),
+    );
+}
+
+fn offline_gold(hours: i32, gold: i32) {
+    println!(
+        "\u{23F3} away for {}h, the outposts collected {}",
+        hours,
+        format_gold_signed(gold)
+    );
+}
+
+fn voyage(location: &Location, fare: i32) {
+    println!(
+        "\u{26F5} sailed overseas to {} {}",
+        format_location(location),
+        format_gold_signed(-fare)
+    );
+}
+
+fn cleared(location: &Location) {
+    println!(
+        "\u{1F3F3}\u{FE0F}  {} is cleared of enemies",
+        format_location(location)
+    );
+}
+
+fn banish(location: &Location, cost: i32) {
+    println!(
+        "\u{1F56F}\u{FE0F}  {} the enemies of {} are banished forever",
+        format_gold_signed(-cost),
+        format_location(location)
+    );
+}
+
+fn outpost_built(location: &Location, cost: i32) {
+    println!(
+        "\u{26FA} {} outpost built at {}",
+        format_gold_signed(-cost),
+        format_location(location)
+    );
+}
+
+fn outpost_list(outposts: &[Location]) {
+    for outpost in outposts {
+        println!("  \u{26FA} {}", format_location(outpost));
+    }
+}
+
+fn guild(membership: &Membership) {
+    println!(
+        "\u{1F6E1}\u{FE0F}  {} guild rank {} ({})",
+        membership.guild,
+        membership.rank,
+        membership.guild.describe().dimmed()
+    );
+    println!("  {} {}", "□".dimmed(), membership.quest());
+}
+
+fn arena_wave(wave: i32) {
+    println!("\u{2694}\u{FE0F}  arena wave {}", wave);
+}
+
+fn arena_wave_cleared(arena: &Arena, payout: i32) {
+    println!(
+        "   wave {} cleared {}, pot {}",
+        arena.wave,
+        format_gold_signed(payout),
+        format_gold(arena.pot)
+    );
+}
+
+fn arena_cashed_out(arena: &Arena) {
+    println!(
+        "   left the arena after {} waves {}",
+        arena.wave,
+        format_gold_signed(arena.pot)
+    );
+}
+
+fn boss_rush_cleared(bosses: usize, rounds: i32) {
+    println!(
+        "\u{1F3C6} boss rush cleared: {} bosses in {} rounds",
+        bosses, rounds
+    );
+}
+
+fn bounties(board: &Board) {
+    println!("\u{1F4DC} bounty board, reputation {}", board.reputation);
+    for (number, bounty) in board.offers.iter().enumerate() {
+        println!("  {}. {}", number + 1, format_bounty(bounty));
+    }
+    for bounty in &board.active {
+        if bounty.done {
+            println!("  {} {}", "✔".green(), format_bounty(bounty).dimmed());
+        } else {
+            println!("  {} {}", "□".dimmed(), format_bounty(bounty));
+        }
+    }
+}
+
+fn bounty_accepted(bounty: &Bounty) {
+    println!("  {} {}", "□".dimmed(), format_bounty(bounty));
+}
+
+fn bounties_turned_in(gold: i32, reputation: i32, total: i32) {
+    println!(
+        "   {} +{} reputation ({})",
+        format_gold_signed(gold),
+        reputation,
+        total
+    );
+}
+
+fn format_bounty(bounty: &Bounty) -> String {
+    format!(
+        "{} {} +{} reputation",
+        bounty.contract,
+        format_gold(bounty.gold),
+        bounty.reputation
+    )
+}
+
+fn guild_rank_up(membership: &Membership, reward: i32) {
+    println!(
+        "   {} {} guild rank {}!",
+        format_gold_signed(reward),
+        membership.guild,
+        membership.rank
+    );
+}
+
+fn exploration(directories: i32, progress: &Progress) {
+    println!(
+        "explored {} directories: {} battles won, {} chests found, {}, {:+} levels",
+        directories,
+        progress.battles_won,
+        progress.chests_found,
+        format_gold_signed(progress.gold),
+        progress.level
+    );
+}
+
+fn grind(progress: &Progress) {
+    println!(
+        "grinded {} battles: {}, {:+} levels",
+        progress.battles_won,
+        format_gold_signed(progress.gold),
+        progress.level
+    );
+}
+
+fn secret(secret: &str) {
+    println!("{} {}", "A secret is revealed:".purple().bold(), secret);
+}
+
+fn codex(codex: &crate::lore::Codex) {
+    let topics = codex.topics();
+    if topics.is_empty() {
+        println!("No lore heard yet.");
+    }
+    for (topic, heard) in topics {
+        println!(
+            "{} {}/{}",
+            topic.name.bold(),
+            heard.len(),
+            topic.lines.len()
+        );
+        for line in heard {
+            println!("  {}", line);
+        }
+        if codex.is_complete(topic) {
+            println!("  {}", topic.secret.purple());
+        }
+    }
+}
+
+fn story(sentences: &[String]) {
+    if sentences.is_empty() {
+        println!("The story is yet to be written.");
+    }
+    for sentence in sentences {
+        println!("{}", sentence);
+    }
+}
+
+fn manual(paragraphs: &[String]) {
+    println!("{}", paragraphs.join("\n\n"));
+}
+
+fn stats(stats: &crate::stats::Stats) {
+    for (name, value) in stats.summary() {
+        println!("{:<18}{}", name, value);
+    }
+    if !stats.bestiary.is_empty() {
+        println!("{}", "bestiary".bold());
+        for (enemy, count) in &stats.bestiary {
+            println!("  {:<16}{}", enemy, count);
+        }
+    }
+    if !stats.hall_of_fame.is_empty() {
+        println!("{}", "hall of fame".bold());
+        for (index, champion) in stats.hall_of_fame.iter().enumerate() {
+            let cycle = if champion.cycle > 0 {
+                format!(" new game+ {}", champion.cycle)
+            } else {
+                String::new()
+            };
+            println!(
+                "  {}. {} the {} level {}, after {} deaths{}",
+                index + 1,
+                champion.name,
+                champion.class,
+                champion.level,
+                champion.deaths,
+                cycle
+            );
+        }
+    }
+}
+
+fn split(split: Split, elapsed: u64) {
+    println!(
+        "\u{23F1}\u{FE0F}  {} at {}",
+        split,
+        format_duration(elapsed).bold()
+    );
+}
+
+fn splits(run: &Speedrun) {
+    for (split, elapsed) in run.splits() {
+        println!("{:<22}{}", split.to_string(), format_duration(*elapsed));
+    }
+    println!("{:<22}{}", "current", format_duration(run.elapsed()));
+
+    let checksum = format!("{:016x}", run.checksum());
+    if run.is_valid() {
+        println!("checksum {}", checksum.green());
+    } else {
+        println!("checksum {} (modified)", checksum.red());
+    }
+}
+
+fn format_duration(seconds: u64) -> String {
+    format!(
+        "{}h {:02}m {:02}s",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+fn mutators(mutators: &Mutators) {
+    for mutator in Mutator::all() {
+        let line = format!("{:<8}{}", mutator.name(), mutator.describe());
+        if mutators.is_enabled(mutator) {
+            println!("  {} {}", "✔".green(), line);
+        } else {
+            println!("  {} {}", "□".dimmed(), line.dimmed());
+        }
+    }
+    println!(
+        "score multiplier: x{:.2}",
+        mutators.score_multiplier() as f64 / 100.0
+    );
+}
+
+fn exported(file: &str) {
+    println!("\u{1F4BE} exported to {}", file);
+}
+
+fn insurance(policy: &crate::insurance::Policy, price: i32) {
+    let item = policy
+        .item
+        .as_ref()
+        .map_or(String::new(), |key| format!(" and {}", key));
+    println!(
+        "\u{1F4DC} {} insured {}% of gold{}",
+        format_gold_signed(-price),
+        crate::insurance::GOLD_COVERAGE,
+        item
+    );
+}
+
+fn insurance_lapsed() {
+    println!(
+        "\u{1F4DC} {}",
+        "insurance lapsed, premium unpaid".bright_red()
+    );
+}
+
+fn mercy() {
+    println!(
+        "{} after so many early deaths, +{}% xp and gold and fewer enemies near home until level {}",
+        "spared!".green().bold(),
+        mercy::BONUS,
+        mercy::MAX_LEVEL + 1
+    );
+}
+
+fn insurance_claim(items: &HashMap<Key, i32>, gold: i32) {
+    println!("{}", format_ls("\u{1F4DC}", items, gold));
+}
+
+fn stash(stash: &crate::item::stash::Stash) {
+    println!(
+        "{}",
+        format_ls("\u{1F5C3}\u{FE0F} ", &stash.items(), stash.gold)
+    );
+}
+
+fn stash_moved(items: &HashMap<Key, i32>, gold: i32) {
+    if !items.is_empty() || gold != 0 {
+        println!("  {}", format_ls("", items, gold));
+    }
+}
+
+fn hero_list(active: &Game, others: &[Game]) {
+    println!("{} {}", "*".bold(), format_hero(active));
+    for hero in others {
+        println!("  {}", format_hero(hero));
+    }
+}
+
+fn format_hero(game: &Game) -> String {
+    format!(
+        "{:<12} {}@{}{}",
+        game.name,
+        format_character(&game.player).trim_start(),
+        format_location(&game.location),
+        format_title(game)
+    )
+}
+
+fn format_title(game: &Game) -> String {
+    game.title.map_or(String::new(), |title| {
+        format!(" {}", title.name().magenta().italic())
+    })
+}
+
+fn title_earned(title: Title) {
+    println!(
+        "\u{1F3C5} new title earned: {}",
+        title.name().magenta().bold()
+    );
+}
+
+fn titles(game: &Game) {
+    for title in Title::iter() {
+        let earned = game.titles.contains(&title);
+        let mark = if game.title == Some(title) {
+            "*".bold()
+        } else if earned {
+            "✔".green()
+        } else {
+            "□".dimmed()
+        };
+        let name = format!("{:<16} {:<20}", title.id(), title.name());
+        let name = if earned { name.bold() } else { name.dimmed() };
+        println!("  {} {}  {}", mark, name, title.describe().dimmed());
+    }
+}
+
+fn victory(epilogue: &[String], champions: usize, unlocked: bool) {
+    println!(
+        "\n{} gorthaur has fallen!\n",
+        "\u{1F451} VICTORY".yellow().bold()
+    );
+    for sentence in epilogue {
+        println!("  {}", sentence.italic());
+    }
+    println!(
+        "\n  The hero is the champion number {} in the hall of fame.",
+        champions
+    );
+    if unlocked {
+        println!(
+            "  {} the boss rush, and a new game+ with {}.",
+            "unlocked:".green().bold(),
+            "rpg reset --plus".bold()
+        );
+    }
+}
+
+fn new_game_plus(cycle: i32) {
+    println!(
+        "\u{1F504} new game+ {}: enemies grow {} levels stronger",
+        cycle,
+        cycle * crate::game::CYCLE_LEVEL_BONUS
+    );
+}
+
+fn artifact_found(artifact: Artifact) {
+    println!(
+        "\u{1F3FA} legendary artifact found: {}",
+        artifact.name().magenta().bold()
+    );
+}
+
+fn artifacts_complete() {
+    println!(
+        "{} all artifacts gathered, morgoth awaits {} directories away from home",
+        "\u{1F451}".bold(),
+        enemy::GORTHAUR_DISTANCE
+    );
+}
+
+fn artifacts(game: &Game) {
+    for artifact in Artifact::iter() {
+        let found = game.artifacts.contains(&artifact);
+        let mark = if found { "✔".green() } else { "□".dimmed() };
+        let name = format!("{:<24}", artifact.name());
+        let name = if found { name.bold() } else { name.dimmed() };
+        println!("  {} {}  {}", mark, name, artifact.describe().dimmed());
+    }
+    println!(
+        "\n  {}/{} found",
+        game.artifacts.len(),
+        Artifact::iter().count()
+    );
+}
+
+/// How many places each heatmap section ranks.
+const HEATMAP_PLACES: usize = 10;
+
+/// Width in characters of the longest heatmap bar.
+const HEATMAP_WIDTH: i32 = 20;
+
+fn map(stats: &crate::stats::Stats, heat: bool) {
+    if stats.places.is_empty() {
+        println!("no places visited yet");
+        return;
+    }
+    if heat {
+        heat_section(
+            "most traveled",
+            &stats.hottest(|p| p.visits, HEATMAP_PLACES),
+        );
+        heat_section("most deadly", &stats.hottest(|p| p.deaths, HEATMAP_PLACES));
+        return;
+    }
+    println!("{:<40}{:>8}{:>9}{:>8}", "", "visits", "battles", "deaths");
+    for (path, place) in &stats.places {
+        println!(
+            "{:<40}{:>8}{:>9}{:>8}",
+            path, place.visits, place.battles, place.deaths
+        );
+    }
+}
+
+fn heat_section(title: &str, places: &[(&str, i32)]) {
+    println!("{}", title.bold());
+    let Some(max) = places.first().map(|(_, count)| *count) else {
+        println!("  {}", "none yet".dimmed());
+        return;
+    };
+    for (path, count) in places {
+        let width = std::cmp::max(1, count * HEATMAP_WIDTH / max) as usize;
+        let bar = "█".repeat(width);
+        let bar = if *count * 3 > max * 2 {
+            bar.red()
+        } else if *count * 3 > max {
+            bar.yellow()
+        } else {
+            bar.green()
+        };
+        println!("  {:<40}{:<21}{}", path, bar, count);
+    }
+}
+
+fn party_joined(member: &Character) {
+    battle_log(member, "joined the party");
+}
+
+fn party_left(member: &Character) {
+    battle_log(member, "left the party");
+}
+
+fn party_xp(member: &Character, xp: i32, levels_up: i32) {
+    battle_log(
+        member,
+        &format!("{}{}", format!("+{}xp", xp).bold(), level_up(levels_up)),
+    );
+}
+
+fn party(game: &Game) {
+    for member in &game.party {
+        battle_log(member, "");
+    }
+}
+
+fn mercenary_hired(mercenary: &Character, fee: i32) {
+    battle_log(mercenary, &format!("{} hired", format_gold_signed(-fee)));
+}
+
+fn mercenary_paid(mercenary: &Character, wage: i32) {
+    battle_log(mercenary, &format_gold_signed(-wage).to_string());
+}
+
+fn mercenary_left(mercenary: &Character) {
+    battle_log(mercenary, &"left unpaid".bright_red().to_string());
+}
+
+fn mercenary_died(mercenary: &Character) {
+    battle_log(mercenary, "\u{1F480}");
+}
+
+fn bribe(player: &Character, amount: i32) {
+    if amount > 0 {
+        let suffix = format!("bribed {}", format_gold_signed(-amount));
+        battle_log(player, &suffix);
+    } else {
+        battle_log(player, "can't bribe!");
+    }
+}
+
+fn run_away(player: &Character, success: bool) {
+    if success {
+        battle_log(player, "fled!");
+    } else {
+        battle_log(player, "can't run!");
+    }
+}
+
+fn pursuit(enemy: &Character) {
+    battle_log(enemy, "gives chase!");
+}
+
+fn escape(enemy: &Character) {
+    battle_log(enemy, &"lost track of the hero".dimmed());
+}
+
+fn ambush(enemy: &Character, location: &Location) {
+    log(enemy, location, &"ambush!".bold().to_string());
+}
+
+fn shoot(player: &Character, weapon: &Key) {
+    battle_log(player, &format!("shoots the {} first!", weapon).bold());
+}
+
+fn surprise(attacker: &Character) {
+    battle_log(attacker, &"strikes first!".bold());
+}
+
+fn defend(player: &Character, recovered_mp: i32) {
+    if recovered_mp > 0 {
+        battle_log(
+            player,
+            &format!(
+                "{} {}",
+                "defends".bold(),
+                format!("+{}mp", recovered_mp).purple()
+            ),
+        );
+    } else {
+        battle_log(player, &"defends".bold());
+    }
+}
+
+fn taunt(player: &Character, rounds: i32) {
+    battle_log(
+        player,
+        &format!("{} ({} attacks)", "taunts the enemy".bold(), rounds),
+    );
+}
+
+fn mount_hurt(player: &Character, mount: &Mount, damage: i32) {
+    if mount.current_hp > 0 {
+        battle_log(
+            player,
+            &format!("{} {}", mount, format!("-{}hp", damage).red()),
+        );
+    } else {
+        battle_log(player, &format!("{} {}", mount, "bolted!".red().bold()));
+    }
+}
+
+fn forecast(enemy: &Character, forecast: &Forecast) {
+    let chance = format!("{}% to win", forecast.win_chance);
+    let chance = match forecast.win_chance {
+        80.. => chance.green(),
+        40.. => chance.yellow(),
+        _ => chance.red(),
+    };
+    battle_log(
+        enemy,
+        &format!(
+            "{} in ~{} rounds, expected {} {}",
+            chance.bold(),
+            forecast.rounds,
+            format!("+{}xp", forecast.xp).bold(),
+            format_gold_signed(forecast.gold)
+        ),
+    );
+}
+
+fn loot_skipped(player: &Character, skipped: &filter::Skipped) {
+    if skipped.is_empty() {
+        return;
+    }
+    let mut items: Vec<String> = skipped
+        .items
+        .iter()
+        .map(|(key, count)| format!("{}x{}", key, count))
+        .collect();
+    items.sort();
+    let mut line = format!("skipped {}", items.join(" "));
+    if skipped.gold > 0 {
+        line.push_str(&format!(" for {}", format_gold(skipped.gold)));
+    }
+    battle_log(player, &line.dimmed().to_string());
+}
+
+fn rage(enemy: &Character) {
+    battle_log(enemy, &"flies into a rage!".red().bold());
+}
+
+fn enemy_flees(enemy: &Character) {
+    battle_log(enemy, &"fled to call for help!".bold());
+}
+
+fn parry(player: &Character) {
+    battle_log(player, &"parried! strikes back".bold());
+}
+
+fn auto_potion(player: &Character, threshold: i32) {
+    battle_log(
+        player,
+        &format!("hp below {}%, auto-potion", threshold).dimmed(),
+    );
+}
+
+fn heal_item(player: &Character, item: &str, recovered_hp: i32, recovered_mp: i32, healed: bool) {
+    let color = if recovered_mp > 0 { "purple" } else { "green" };
+
+    if recovered_hp > 0 || recovered_mp > 0 || healed {
+        battle_log(
+            player,
+            &format_stat_change(
+                player,
+                recovered_hp,
+                recovered_mp,
+                healed,
+                &item.color(color),
+            ),
+        );
+    }
+}
+
+fn heal(
+    player: &Character,
+    location: &Location,
+    recovered_hp: i32,
+    recovered_mp: i32,
+    healed: bool,
+) {
+    if recovered_hp > 0 || recovered_mp > 0 || healed {
+        log(
+            player,
+            location,
+            &format_stat_change(player, recovered_hp, recovered_mp, healed, ""),
+        );
+    }
+}
+
+fn change_class(player: &Character, lost_xp: i32) {
+    if lost_xp > 0 {
+        battle_log(player, &format!("-{}xp", lost_xp).bright_red());
+    }
+}
+
+fn stat_increase(player: &Character, stat: &str, increase: i32) {
+    let suffix = if stat == "level" {
+        level_up(increase)
+    } else {
+        format!("+{}{}", increase, stat).cyan().to_string()
+    };
+    battle_log(player, &suffix);
+}
+
+fn status(game: &Game, style: Style) {
+    match style {
+        Style::Plain => plain_status(game),
+        Style::Quiet => short_status(game),
+        Style::Rich if a11y() => a11y_status(game),
+        Style::Rich => long_status(game),
+    }
+}
+
+fn stat_detail(breakdown: &[StatBreakdown], style: Style) {
+    if style == Style::Plain {
+        for stat in breakdown {
+            println!(
+                "{}\tbase={}\tlevel={}\tequip={}\trings={}\tbuffs={}\ttotal={}",
+                stat.name,
+                stat.base,
+                stat.level,
+                stat.equipment,
+                stat.rings,
+                stat.buffs,
+                stat.total()
+            );
+        }
+    } else if a11y() {
+        for stat in breakdown {
+            println!(
+                "{} {}: base {}, level {}, equipment {}, rings {}, buffs {}.",
+                stat.name,
+                stat.total(),
+                stat.base,
+                stat.level,
+                stat.equipment,
+                stat.rings,
+                stat.buffs
+            );
+        }
+    } else {
+        println!(
+            "    {:<5}{:>6}{:>7}{:>7}{:>7}{:>7}{:>7}",
+            "", "base", "level", "equip", "rings", "buffs", "total"
+        );
+        for stat in breakdown {
+            println!(
+                "    {:<5}{:>6}{:>7}{:>7}{:>7}{:>7}{:>7}",
+                stat.name,
+                stat.base,
+                format_bonus(stat.level),
+                format_bonus(stat.equipment),
+                format_bonus(stat.rings),
+                format_bonus(stat.buffs),
+                stat.total().to_string().bold()
+            );
+        }
+    }
+}
+
+/// A stat contribution with its sign, dimmed when it adds nothing.
+fn format_bonus(bonus: i32) -> ColoredString {
+    match bonus {
+        0 => "-".dimmed(),
+        b if b > 0 => format!("+{}", b).normal(),
+        b => b.to_string().red(),
+    }
+}
+
+fn shop_list(game: &Game, items: &[(i32, String)]) {
+    for (cost, item) in items {
+        println!("    {:<15}  {}", item, format_gold(*cost));
+    }
+
+    println!("\n    funds: {}", format_gold(game.gold));
+}
+
+fn shop_buy(cost: i32, items: &HashMap<Key, i32>) {
+    if !items.is_empty() {
+        println!("  {}", format_ls("", items, -cost));
+    }
+}
+
+fn traded(give: &Key, receive: &Key, count: i32) {
+    println!("  {} {} for {}x{}", "traded".bold(), give, receive, count);
+}
+
+fn quest_list(quests: &[(bool, String)]) {
+    for (completed, quest) in quests {
+        if *completed {
+            println!("  {} {}", "✔".green(), quest.dimmed());
+        } else {
+            println!("  {} {}", "□".dimmed(), quest);
+        }
+    }
+}
+
+fn warning(text: &str) {
+    eprintln!("{} {}", "warning:".yellow(), text);
+}
+
+fn hint(text: &str) {
+    println!("   {} {}", "hint:".yellow(), text);
+}
+
+fn tip(text: &str) {
+    println!("   {} {}", "tip:".yellow(), text);
+}
+
+fn quest_done(reward: i32) {
+    println!("   {} quest completed!", format_gold_signed(reward));
+}
+
+fn npc_encounter(encounter: &crate::character::npc::Encounter) {
+    match encounter {
+        crate::character::npc::Encounter::Gambler => {
+            println!(
+                "{} {}",
+                "A goblin with a wide grin shuffles a deck of cards.".yellow(),
+                "Wanna bet?".bold()
+            );
+        }
+        crate::character::npc::Encounter::Witch => {
+            println!(
+                "{} {}",
+                "A witch cackles over her cauldron.".purple(),
+                "Care for a potion?".bold()
+            );
+        }
+        crate::character::npc::Encounter::GhostlyMaiden => {
+            println!(
+                "{} {}",
+                "A ghostly maiden drifts through the air.".cyan(),
+                "Listen to my tale...".bold()
+            );
+        }
+        crate::character::npc::Encounter::Blacksmith => {
+            println!(
+                "{} {}",
+                "A dwarf blacksmith hammers away at a portable anvil.".yellow(),
+                "Need your gear fixed?".bold()
+            );
+        }
+        crate::character::npc::Encounter::Merchant => {
+            println!(
+                "{} {}",
+                "A travelling merchant unloads a heavy pack.".green(),
+                "Got anything to trade?".bold()
+            );
+        }
+    }
+}
+
+fn skill_list(player: &Character) {
+    println!("Available skills ({} skill points):", player.skill_points);
+    for (skill_type, title) in [
+        (SkillType::Active, "Active"),
+        (SkillType::Passive, "Passive"),
+    ] {
+        let skills: Vec<_> = player
+            .class
+            .skills
+            .iter()
+            .filter(|skill| skill.skill_type == skill_type)
+            .collect();
+        if skills.is_empty() {
+            continue;
+        }
+        println!("  {}:", title);
+        for skill in skills {
+            println!("    {}", format_skill(player, skill));
+        }
+    }
+}
+
+fn format_skill(player: &Character, skill: &Skill) -> String {
+    let rank = player.skill_rank(&skill.name);
+    let unlocked = if rank > 0 {
+        "✔".green()
+    } else {
+        "□".dimmed()
+    };
+
+    let mut details = format!("Lvl {}", skill.level_requirement);
+    let cost = skill.rank_cost(rank.max(1));
+    if cost > 0 {
+        details.push_str(&format!(", {} MP", cost));
+    }
+    if skill.skill_type == SkillType::Active && rank > 0 {
+        details.push_str(&format!(", rank {}/{}", rank, MAX_SKILL_RANK));
+        if rank < MAX_SKILL_RANK {
+            details.push_str(&format!(
+                ", next at Lvl {}",
+                skill.rank_requirement(rank + 1)
+            ));
+        }
+    }
+
+    format!(
+        "{} {}: {} ({})",
+        unlocked,
+        skill.name.bold(),
+        skill.description,
+        details
+    )
+}
+
+fn level_up(levels_up: i32) -> String {
+    if levels_up > 0 {
+        let plus = (0..levels_up).map(|_| "+").collect::<String>();
+        format!(" {}level", plus).cyan().to_string()
+    } else {
+        "".to_string()
+    }
+}
+
+fn long_status(game: &Game) {
+    let player = &game.player;
+
+    let modes = active_modes(game)
+        .iter()
+        .map(|mode| format!(" [{}]", mode).red().bold().to_string())
+        .collect::<String>();
+    let name = if game.completed {
+        format!("\u{1F451} {}", game.name).yellow().bold()
+    } else {
+        game.name.bold()
+    };
+    println!(
+        "{} {}@{}{}{}",
+        name,
+        format_character(player).trim_start(),
+        format_location(&game.location),
+        format_title(game),
+        modes
+    );
+
+    let mut bars = format!(
+        "    hp:{} {}/{}",
+        hp_display(player, 10),
+        player.current_hp,
+        player.max_hp()
+    );
+    if player.class.is_magic() {
+        bars.push_str(&format!(
+            "   mp:{} {}/{}",
+            mp_display(player, 10),
+            player.current_mp,
+            player.max_mp()
+        ));
+    }
+    println!("{}", bars);
+    println!(
+        "    xp:{} {}/{}",
+        xp_display(player, 10),
+        player.xp,
+        player.xp_for_next()
+    );
+
+    let badge = player.status_effect.map_or(String::new(), |status| {
+        format!("   [{}]", format_status_effect(status))
+            .bright_red()
+            .to_string()
+    });
+    println!(
+        "    att:{}  mag:{}  def:{}  spd:{}{}",
+        player.physical_attack(),
+        player.magic_attack(),
+        player.deffense(),
+        player.speed(),
+        badge
+    );
+    println!(
+        "    {}  {}",
+        format_equipment(player),
+        format_gold(game.gold)
+    );
+    println!("    {}", format_inventory(game));
+    if !game.materials.is_empty() {
+        println!("    {}", format_materials(game));
+    }
+    println!(
+        "    load:{} {}/{}",
+        load_display(game, 10),
+        game.load(),
+        game.player.carry_capacity()
+    );
+    if let Some(mount) = &player.mount {
+        println!("    {}: {}/{}hp", mount, mount.current_hp, mount.max_hp());
+    }
+
+    let buffs = active_buffs(game);
+    if !buffs.is_empty() {
+        println!("    buffs:{{{}}}", buffs.join(",").green());
+    }
+    party(game);
+    if let Some(mercenary) = &game.mercenary {
+        battle_log(mercenary, "");
+    }
+}
+
+fn a11y_status(game: &Game) {
+    let player = &game.player;
+
+    println!(
+        "{}, {} at {}{}.",
+        game.name,
+        format_character(player),
+        format_location(&game.location),
+        if game.ironman { ", ironman mode" } else { "" }
+    );
+    if let Some(title) = game.title {
+        println!("Known as {}.", title.name());
+    }
+    println!(
+        "HP {} of {}. MP {} of {}. XP {} of {}.",
+        player.current_hp,
+        player.max_hp(),
+        player.current_mp,
+        player.max_mp(),
+        player.xp,
+        player.xp_for_next()
+    );
+    if let Some(status) = player.status_effect {
+        println!("Status {}.", status_effect_params(status).0);
+    }
+    println!(
+        "Attack {}, magic {}, defense {}, speed {}.",
+        player.physical_attack(),
+        player.magic_attack(),
+        player.deffense(),
+        player.speed()
+    );
+    println!("Equipment: {}.", format_equipment(player));
+    println!("Inventory: {}.", format_inventory(game));
+    println!(
+        "Load {} of {}{}.",
+        game.load(),
+        game.player.carry_capacity(),
+        if game.player.encumbered {
+            ", encumbered"
+        } else {
+            ""
+        }
+    );
+    if let Some(mount) = &player.mount {
+        println!(
+            "Riding a {}, hp {} of {}.",
+            mount,
+            mount.current_hp,
+            mount.max_hp()
+        );
+    }
+    println!("Gold: {}.", game.gold);
+    party(game);
+    if let Some(mercenary) = &game.mercenary {
+        battle_log(mercenary, "");
+    }
+}
+
+fn short_status(game: &Game) {
+    let player = &game.player;
+
+    let suffix = if let Some(status) = player.status_effect {
+        let (_, emoji) = status_effect_params(status);
+        emoji
+    } else {
+        ""
+    };
+    log(player, &game.location, suffix);
+}
+
+/// The same data of the long status, as tab separated key=value pairs.
+fn plain_status(game: &Game) {
+    let player = &game.player;
+
+    let mut pairs = vec![
+        format!("name={}", player.name()),
+        format!("level={}", player.level),
+        format!("location={}", game.location),
+        format!("hp={}/{}", player.current_hp, player.max_hp()),
+        format!("mp={}/{}", player.current_mp, player.max_mp()),
+        format!("xp={}/{}", player.xp, player.xp_for_next()),
+        format!("att={}", player.physical_attack()),
+        format!("mag={}", player.magic_attack()),
+        format!("def={}", player.deffense()),
+        format!("spd={}", player.speed()),
+    ];
+    if let Some(status) = player.status_effect {
+        pairs.push(format!("status={}", status_effect_params(status).0));
+    }
+    pairs.push(format!("equip={}", equipment_list(player).join(",")));
+    pairs.push(format!("items={}", inventory_list(game).join(",")));
+    if let Some(mount) = &player.mount {
+        pairs.push(format!(
+            "mount={}:{}/{}",
+            mount,
+            mount.current_hp,
+            mount.max_hp()
+        ));
+    }
+    pairs.push(format!("morale={}", player.morale));
+    pairs.push(format!("gold={}", game.gold));
+    pairs.push(format!("buffs={}", active_buffs(game).join(",")));
+    pairs.push(format!("modes={}", active_modes(game).join(",")));
+    pairs.push(format!("hero={}", game.name));
+    pairs.push(format!(
+        "title={}",
+        game.title.map_or("", |title| title.name())
+    ));
+
+    println!("{}", pairs.join("\t"));
+}
+
+fn format_ls(emoji: &str, items: &HashMap<Key, i32>, gold: i32) -> String {
+    let mut string = format!("{} ", emoji);
+
+    if gold != 0 {
+        string.push_str(&format!("{} ", format_gold_signed(gold)));
+    }
+    for (key, count) in items {
+        string.push_str(&format!("+{}x{} ", key, count));
+    }
+    string
+}
+
+// HELPERS
+/// Generic log function. At the moment all output of the game is structured as
+/// of a player status at some location, with an optional event suffix.
+fn log(character: &Character, location: &Location, suffix: &str) {
+    println!(
+        "{}{}{}{}{}{} {}",
+        format_character(character),
+        hp_display(character, 4),
+        mp_display(character, 4),
+        xp_display(character, 4),
+        if a11y() { ", at " } else { "@" },
+        format_location(location),
+        suffix
+    );
+}
+
+/// Wait before printing a battle turn, if pacing is enabled, so fights
+/// unfold progressively. Long fights spin while waiting. Only called for
+/// the rich output, and never paces output that isn't going to a terminal.
+fn pace_turn() {
+    let pace = *PACE.get().unwrap_or(&0);
+    if pace == 0 || !std::io::stdout().is_terminal() {
+        return;
+    }
+    let turn = TURNS.fetch_add(1, Ordering::Relaxed);
+    if turn == 0 {
+        return;
+    }
+    if turn < LONG_FIGHT_TURNS || a11y() {
+        std::thread::sleep(Duration::from_millis(pace));
+        return;
+    }
+
+    let frames = std::cmp::max(1, pace / 75);
+    let mut stdout = std::io::stdout();
+    for frame in 0..frames {
+        print!("\r{}", SPINNER[frame as usize % SPINNER.len()]);
+        stdout.flush().ok();
+        std::thread::sleep(Duration::from_millis(pace / frames));
+    }
+    print!("\r \r");
+}
+
+fn battle_log(character: &Character, suffix: &str) {
+    println!(
+        "{}{} {}",
+        format_character(character),
+        hp_display(character, 4),
+        suffix
+    );
+}
+
+fn format_location(location: &Location) -> String {
+    if names() && !location.is_home() {
+        let name = format!("({})", location.fantasy_name());
+        format!("{} {}", location, name.dimmed())
+    } else {
+        location.to_string()
+    }
+}
+
+fn format_character(character: &Character) -> String {
+    if a11y() {
+        return format!("{} level {}", character.display_name(), character.level);
+    }
+
+    let name = match &character.class.icon {
+        Some(icon) if Config::get().icons => format!("{} {}", icon, character.display_name()),
+        _ => character.display_name(),
+    };
+    let name = format!("{:>8}", name);
+    let name = if character.name() == "shadow" {
+        name.dimmed()
+    } else if character.is_player() {
+        name.bold()
+    } else {
+        name.yellow().bold()
+    };
+    format!("{}[{}]", name, character.level)
+}
+
+fn format_equipment(character: &Character) -> String {
+    let fragments = equipment_list(character);
+    if a11y() {
+        return list_sentence(fragments);
+    }
+    format!("equip:{{{}}}", fragments.join(","))
+}
+
+fn equipment_list(character: &Character) -> Vec<String> {
+    let mut fragments = Vec::new();
+
+    if let Some(sword) = &character.sword {
+        fragments.push(sword.to_string());
+    }
+
+    if let Some(shield) = &character.shield {
+        fragments.push(shield.to_string());
+    }
+
+    if let Some(ring) = &character.left_ring {
+        fragments.push(ring.to_string());
+    }
+
+    if let Some(ring) = &character.right_ring {
+        fragments.push(ring.to_string());
+    }
+
+    if let Some(amulet) = &character.amulet {
+        fragments.push(amulet.to_string());
+    }
+    fragments
+}
+
+pub fn format_inventory(game: &Game) -> String {
+    let items = inventory_list(game);
+    if a11y() {
+        return list_sentence(items);
+    }
+    format!("item:{{{}}}", items.join(","))
+}
+
+fn inventory_list(game: &Game) -> Vec<String> {
+    let mut items = game
+        .inventory()
+        .iter()
+        .map(|(k, v)| format!("{}x{}", k, v))
+        .collect::<Vec<String>>();
+    items.sort();
+    items
+}
+
+/// Ongoing effects that benefit the hero, besides the equipment.
+fn active_buffs(game: &Game) -> Vec<String> {
+    let mut buffs = Vec::new();
+    if let Some(membership) = &game.guild {
+        buffs.push(format!("{} guild", membership.guild));
+    }
+    if game.insurance.is_some() {
+        buffs.push(String::from("insured"));
+    }
+    if let Some(hunt) = &game.hunt {
+        buffs.push(format!("hunting {}", hunt.family));
+    }
+    if game.player.enemies_evaded() {
+        buffs.push(String::from("evading"));
+    }
+    if game.player.double_chests() {
+        buffs.push(String::from("double chests"));
+    }
+    if let Some(status) = game.player.coating {
+        let (name, _) = status_effect_params(status);
+        buffs.push(format!("{} coating", name));
+    }
+    if let Some((_, battles)) = game.player.meal {
+        buffs.push(format!("well fed ({} battles)", battles));
+    }
+    if game.player.fatigued {
+        buffs.push(String::from("fatigued"));
+    }
+    match game.player.mood() {
+        Mood::Inspired => buffs.push(String::from("inspired")),
+        Mood::Shaken => buffs.push(String::from("shaken")),
+        Mood::Steady => {}
+    }
+    if game.player.encumbered {
+        buffs.push(String::from("encumbered"));
+    }
+    if game.mercy.active {
+        buffs.push(String::from("spared"));
+    }
+    let buff = &game.player.buff;
+    if *buff != Buff::default() {
+        buffs.push(format!("att{:+}% spd{:+}%", buff.attack, buff.speed));
+    }
+    buffs
+}
+
+/// Run modes worth a reminder on every status.
+fn active_modes(game: &Game) -> Vec<&'static str> {
+    let mut modes = Vec::new();
+    if game.ironman {
+        modes.push("ironman");
+    }
+    if game.speedrun.is_some() {
+        modes.push("speedrun");
+    }
+    if game.challenge.is_some() {
+        modes.push("challenge");
+    }
+    if game.cycle > 0 {
+        modes.push("new game+");
+    }
+    modes
+}
+
+/// A comma separated list for screen readers, or "none" if empty.
+fn list_sentence(fragments: Vec<String>) -> String {
+    if fragments.is_empty() {
+        String::from("none")
+    } else {
+        fragments.join(", ")
+    }
+}
+
+fn format_attack(receiver: &Character, attack: &AttackType, damage: i32, mp_cost: i32) -> String {
+    let magic_effect = if mp_cost > 0 {
+        format!("\u{2728} -{}mp ", mp_cost).purple().to_string()
+    } else {
+        String::from("")
+    };
+
+    match attack {
+        AttackType::Regular => format_hp_change(receiver, -damage, &magic_effect),
+        AttackType::Critical => {
+            format_hp_change(receiver, -damage, &format!("{}critical!", magic_effect))
+        }
+        AttackType::Effect(status_effect) => {
+            format_hp_change(receiver, -damage, &format_status_effect(*status_effect))
+        }
+        AttackType::Miss => format!("{}dodged!", magic_effect),
+    }
+}
+
+fn format_stat_change(
+    receiver: &Character,
+    hp: i32,
+    mp: i32,
+    healed: bool,
+    suffix: &str,
+) -> String {
+    let mut healed_text = String::new();
+    let mut mp_text = String::new();
+
+    if mp != 0 {
+        mp_text = format!("{:+}mp ", mp);
+    }
+    if healed {
+        healed_text = String::from("+healed ");
+    }
+
+    format!(
+        "{}{}{}{}",
+        &format_hp_change(receiver, hp, ""),
+        mp_text.purple(),
+        healed_text.green(),
+        suffix
+    )
+}
+
+fn format_hp_change(receiver: &Character, amount: i32, suffix: &str) -> String {
+    if amount != 0 {
+        let color = if receiver.is_player() {
+            if amount < 0 {
+                "bright red"
+            } else {
+                "green"
+            }
+        } else {
+            "white"
+        };
+        format!("{:+}hp {}", amount, suffix)
+            .color(color)
+            .to_string()
+    } else {
+        String::from("")
+    }
+}
+
+fn format_status_effect(status_effect: StatusEffect) -> String {
+    let (name, emoji) = status_effect_params(status_effect);
+    format!("{} {}!", emoji, name)
+}
+
+fn status_effect_params(status_effect: StatusEffect) -> (&'static str, &'static str) {
+    match status_effect {
+        StatusEffect::Burn => ("burn", "\u{1F525}"),
+        StatusEffect::Poison => ("poison", "\u{2620}\u{FE0F} "),
+    }
+}
+
+fn hp_display(character: &Character, slots: i32) -> String {
+    if a11y() {
+        return format!(", HP {} of {}", character.current_hp, character.max_hp());
+    }
+    bar_display(
+        slots,
+        character.current_hp,
+        character.max_hp(),
+        "green",
+        "red",
+    )
+}
+
+fn mp_display(character: &Character, slots: i32) -> String {
+    let current_mp = if character.class.is_magic() {
+        character.current_mp
+    } else {
+        0
+    };
+    if a11y() {
+        return if character.class.is_magic() {
+            format!(", MP {} of {}", current_mp, character.max_mp())
+        } else {
+            String::new()
+        };
+    }
+
+    bar_display(
+        slots,
+        current_mp,
+        character.max_mp(),
+        "purple",
+        "bright black",
+    )
+}
+
+fn xp_display(character: &Character, slots: i32) -> String {
+    if character.is_player() && a11y() {
+        format!(", XP {} of {}", character.xp, character.xp_for_next())
+    } else if character.is_player() {
+        bar_display(
+            slots,
+            character.xp,
+            character.xp_for_next(),
+            "cyan",
+            "bright black",
+        )
+    } else {
+        // enemies don't have experience
+        String::new()
+    }
+}
+
+/// The weight carried by the hero, turning red past its capacity.
+fn load_display(game: &Game, slots: i32) -> String {
+    let capacity = game.player.carry_capacity();
+    let color = if game.player.encumbered {
+        "red"
+    } else {
+        "white"
+    };
+    bar_display(
+        slots,
+        std::cmp::min(game.load(), capacity),
+        capacity,
+        color,
+        "bright black",
+    )
+}
+
+fn bar_display(
+    slots: i32,
+    current: i32,
+    total: i32,
+    current_color: &str,
+    missing_color: &str,
+) -> String {
+    let (filled, rest) = bar_slots(slots, total, current);
+    let current = (0..filled)
+        .map(|_| "x")
+        .collect::<String>()
+        .color(current_color);
+    let missing = (0..rest)
+        .map(|_| "-")
+        .collect::<String>()
+        .color(missing_color);
+    format!("[{}{}]", current, missing)
+}
+
+fn bar_slots(slots: i32, total: i32, current: i32) -> (i32, i32) {
+    let units = (current as f64 * slots as f64 / total as f64).ceil() as i32;
+    (units, slots - units)
+}
+
+fn format_gold(gold: i32) -> ColoredString {
+    format!("{}g", gold).yellow()
+}
+
+fn format_gold_signed(gold: i32) -> ColoredString {
+    format!("{:+}g", gold).yellow()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_character() {
+        let mut orc = Character::new(Class::enemy_by_name("orc").unwrap().clone(), 1);
+        orc.class.display_name = Some(String::from("grunt"));
+        orc.class.icon = Some(String::from("\u{1F479}"));
+        assert!(format_character(&orc).contains("\u{1F479} grunt"));
+        assert_eq!("orc", orc.name());
+    }
+
+    #[test]
+    fn test_portrait() {
+        let guardian = Character::new(Class::enemy_by_name("guardian").unwrap().clone(), 1);
+        assert!(portrait(&guardian).unwrap().contains("[=====]"));
+
+        let mut shadow = Character::player();
+        shadow.class.name = String::from("shadow");
+        assert!(portrait(&shadow).is_some());
+
+        let orc = Character::new(Class::enemy_by_name("orc").unwrap().clone(), 1);
+        assert!(portrait(&orc).is_none());
+    }
+
+    #[test]
+    fn test_bar_slots() {
+        // simple case 1:1 between points and slots
+        let slots = 4;
+        let total = 4;
+        assert_eq!((0, 4), bar_slots(slots, total, 0));
+        assert_eq!((1, 3), bar_slots(slots, total, 1));
+        assert_eq!((2, 2), bar_slots(slots, total, 2));
+        assert_eq!((3, 1), bar_slots(slots, total, 3));
+        assert_eq!((4, 0), bar_slots(slots, total, 4));
+
+        let total = 10;
+        assert_eq!((0, 4), bar_slots(slots, total, 0));
+        assert_eq!((1, 3), bar_slots(slots, total, 1));
+        assert_eq!((1, 3), bar_slots(slots, total, 2));
+        assert_eq!((2, 2), bar_slots(slots, total, 3));
+        assert_eq!((2, 2), bar_slots(slots, total, 4));
+        assert_eq!((2, 2), bar_slots(slots, total, 5));
+        assert_eq!((3, 1), bar_slots(slots, total, 6));
+        assert_eq!((3, 1), bar_slots(slots, total, 7));
+        // this one I would maybe like to show as 3, 1
+        assert_eq!((4, 0), bar_slots(slots, total, 8));
+        assert_eq!((4, 0), bar_slots(slots, total, 9));
+        assert_eq!((4, 0), bar_slots(slots, total, 10));
+    }
+}
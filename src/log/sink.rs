@@ -0,0 +1,172 @@
+use super::event::Event;
+use super::render::{self, Style};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::io::{IsTerminal, Write};
+use std::rc::Rc;
+
+/// A destination for game events, e.g. the terminal or a JSON recorder.
+pub trait Sink {
+    fn handle(&mut self, event: &Event);
+}
+
+thread_local! {
+    /// Sinks currently subscribed to the game events, in the order they
+    /// receive them. Events are printed to the terminal unless told
+    /// otherwise.
+    static SINKS: RefCell<Vec<Box<dyn Sink>>> = RefCell::new(vec![Box::new(Terminal {
+        style: Style::Rich,
+    })]);
+}
+
+/// Subscribe the sinks for the given output preferences, replacing the
+/// default terminal one.
+pub fn init(quiet: bool, plain: bool, notifications: bool) {
+    let style = if plain { Style::Plain } else { Style::Rich };
+    let mut sinks: Vec<Box<dyn Sink>> = if quiet {
+        let style = if plain { Style::Plain } else { Style::Quiet };
+        vec![Box::new(Quiet { style })]
+    } else {
+        vec![Box::new(Terminal { style })]
+    };
+    if notifications && std::io::stderr().is_terminal() {
+        sinks.push(Box::new(Notifications));
+    }
+    SINKS.with(|current| *current.borrow_mut() = sinks);
+}
+
+/// Publish the event to all the subscribed sinks.
+pub fn emit(event: &Event) {
+    SINKS.with(|sinks| {
+        for sink in sinks.borrow_mut().iter_mut() {
+            sink.handle(event);
+        }
+    });
+}
+
+/// Run the given function recording the events it emits as JSON, on top of
+/// delivering them to the rest of the sinks. Only the daemon records them,
+/// and it's not available everywhere.
+#[cfg_attr(not(unix), allow(dead_code))]
+pub fn record<T>(f: impl FnOnce() -> T) -> (T, Vec<Value>) {
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let json = Json {
+        events: events.clone(),
+    };
+    SINKS.with(|sinks| sinks.borrow_mut().push(Box::new(json)));
+    let result = f();
+    SINKS.with(|sinks| sinks.borrow_mut().pop());
+
+    let events = events.take();
+    (result, events)
+}
+
+/// Run the given function without delivering the events it emits to any
+/// sink, e.g. to simulate battles behind the scenes.
+pub fn mute<T>(f: impl FnOnce() -> T) -> T {
+    let sinks = SINKS.with(|sinks| sinks.replace(Vec::new()));
+    let result = f();
+    SINKS.with(|current| *current.borrow_mut() = sinks);
+    result
+}
+
+/// Prints the events in the game's usual colored, human readable format,
+/// or in the plain one for scripts, which leaves the decorations out.
+struct Terminal {
+    style: Style,
+}
+
+impl Sink for Terminal {
+    fn handle(&mut self, event: &Event) {
+        if self.style == Style::Plain && event.is_decoration() {
+            return;
+        }
+        render::render(event, self.style);
+    }
+}
+
+/// Prints only the outcome of each action, leaving out the events that
+/// just add detail to it.
+struct Quiet {
+    style: Style,
+}
+
+impl Sink for Quiet {
+    fn handle(&mut self, event: &Event) {
+        if event.is_detail() {
+            return;
+        }
+        render::render(event, self.style);
+    }
+}
+
+/// Raises a desktop notification for the milestones of the game, through
+/// the terminal's OSC 9 escape sequence, so they aren't missed while
+/// working on something else.
+struct Notifications;
+
+impl Sink for Notifications {
+    fn handle(&mut self, event: &Event) {
+        if let Some(text) = render::notification(event) {
+            let _ = write!(std::io::stderr(), "\x1b]9;{}\x07", text);
+        }
+    }
+}
+
+/// Collects the events as JSON objects.
+struct Json {
+    events: Rc<RefCell<Vec<Value>>>,
+}
+
+impl Sink for Json {
+    fn handle(&mut self, event: &Event) {
+        self.events.borrow_mut().push(event.to_json());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_record() {
+        let items = HashMap::new();
+        let ((), events) = record(|| {
+            emit(&Event::Chest {
+                items: &items,
+                gold: 10,
+            })
+        });
+        assert_eq!(1, events.len());
+        assert_eq!("chest", events[0]["event"]);
+        assert_eq!(10, events[0]["gold"]);
+    }
+
+    #[test]
+    fn test_filters() {
+        let items = HashMap::new();
+        let chest = Event::Chest {
+            items: &items,
+            gold: 10,
+        };
+        let hint = Event::Hint { text: "rest" };
+        assert!(!chest.is_detail());
+        assert!(!chest.is_decoration());
+        assert!(hint.is_detail());
+        assert!(hint.is_decoration());
+        assert!(Event::Attack {
+            character: &crate::character::Character::player(),
+            attack: &crate::character::AttackType::Regular,
+            damage: 1,
+            mp_cost: 0,
+        }
+        .is_detail());
+
+        let done = Event::QuestDone { reward: 100 };
+        assert_eq!("quest_done", done.to_json()["event"]);
+        assert_eq!(100, done.to_json()["reward"]);
+        assert!(render::notification(&done).is_some());
+        assert!(render::notification(&chest).is_none());
+    }
+}
@@ -0,0 +1,146 @@
+use crate::randomizer::{random, Randomizer};
+use serde::{Deserialize, Serialize};
+use std::cmp::min;
+
+/// A set of lore lines about the same subject. When all of them are heard,
+/// the topic's secret is revealed.
+pub struct Topic {
+    pub name: &'static str,
+    pub lines: &'static [&'static str],
+    pub secret: &'static str,
+}
+
+const TOPICS: [Topic; 5] = [
+    Topic {
+        name: "legends",
+        lines: &[
+            "She whispers of a hidden treasure in a nearby cave.",
+            "She speaks of a great evil that slumbers deep within the earth.",
+            "She warns of a powerful dragon that guards the mountain pass.",
+        ],
+        secret:
+            "Not every chest is found on the first look, but every place is searched only once.",
+    },
+    Topic {
+        name: "the amulet",
+        lines: &[
+            "She sings of an amulet lost by a careless hero.",
+            "She says the amulet hums louder the further it is from home.",
+            "She claims the amulet's bearer draws the attention of a guardian.",
+        ],
+        secret: "Once the amulet is found, the guardian waits ten steps away from home.",
+    },
+    Topic {
+        name: "the rings",
+        lines: &[
+            "She counts the rings forged in the old days, one of each kind.",
+            "She mourns a ring that made its wearer vanish from sight.",
+            "She warns that one ring rules them all.",
+        ],
+        secret:
+            "There's only one of each ring per world: a ring lost in a tombstone is not made again.",
+    },
+    Topic {
+        name: "the shadow",
+        lines: &[
+            "She tells of a hero who fought against their own shadow.",
+            "She says shadows are strongest where one feels safest.",
+        ],
+        secret: "The shadow only lurks at home, and is always a few levels ahead of the hero.",
+    },
+    Topic {
+        name: "gorthaur",
+        lines: &[
+            "She speaks of a dark lord awaiting his ring in the deeps.",
+            "She says the road to him is a hundred steps long.",
+            "She shivers at the memory of his twofold strength.",
+        ],
+        secret: "Gorthaur appears a hundred steps away from home, only to the ruling ring bearer.",
+    },
+];
+
+/// Every unique lore line heard from the ghostly maiden.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Codex {
+    heard: Vec<String>,
+}
+
+impl Codex {
+    /// Pick a lore line to be told, preferring the ones not heard yet, and
+    /// add it to the codex. If it completes a topic, its secret is returned too.
+    pub fn listen(&mut self) -> (&'static str, Option<&'static str>) {
+        let all: Vec<&'static str> = TOPICS
+            .iter()
+            .flat_map(|t| t.lines.iter().copied())
+            .collect();
+        let unheard: Vec<&'static str> = all
+            .iter()
+            .copied()
+            .filter(|line| !self.has_heard(line))
+            .collect();
+        let options = if unheard.is_empty() { all } else { unheard };
+        let index = random().range(options.len() as i32) as usize;
+        let line = options[min(index, options.len() - 1)];
+
+        if self.has_heard(line) {
+            return (line, None);
+        }
+        self.heard.push(line.to_string());
+
+        let topic = TOPICS.iter().find(|t| t.lines.contains(&line)).unwrap();
+        let secret = if self.is_complete(topic) {
+            Some(topic.secret)
+        } else {
+            None
+        };
+        (line, secret)
+    }
+
+    /// The lines heard so far for each topic, skipping topics not yet discovered.
+    pub fn topics(&self) -> Vec<(&'static Topic, Vec<&'static str>)> {
+        TOPICS
+            .iter()
+            .map(|topic| {
+                let heard = topic
+                    .lines
+                    .iter()
+                    .copied()
+                    .filter(|line| self.has_heard(line))
+                    .collect::<Vec<_>>();
+                (topic, heard)
+            })
+            .filter(|(_, heard)| !heard.is_empty())
+            .collect()
+    }
+
+    pub fn is_complete(&self, topic: &Topic) -> bool {
+        topic.lines.iter().all(|line| self.has_heard(line))
+    }
+
+    fn has_heard(&self, line: &str) -> bool {
+        self.heard.iter().any(|heard| heard == line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_topic() {
+        let mut codex = Codex::default();
+        let total: usize = TOPICS.iter().map(|t| t.lines.len()).sum();
+
+        let mut secrets = 0;
+        for _ in 0..total {
+            let (_, secret) = codex.listen();
+            if secret.is_some() {
+                secrets += 1;
+            }
+        }
+
+        assert_eq!(total, codex.heard.len());
+        assert_eq!(TOPICS.len(), secrets);
+        assert!(codex.topics().iter().all(|(t, _)| codex.is_complete(t)));
+    }
+}
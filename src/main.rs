@@ -1,17 +1,46 @@
+use config::Config;
 use game::Game;
 
+mod arena;
+mod artifact;
+mod biome;
+mod bounty;
+mod challenge;
 mod character;
 mod command;
+mod config;
+mod cooking;
+#[cfg(unix)]
+mod daemon;
 mod datafile;
+mod error;
+mod fuzzy;
 mod game;
+mod git;
+mod guild;
+mod hint;
+mod insurance;
 mod item;
+mod journal;
 mod location;
 mod log;
+mod lore;
+mod manual;
+mod mercy;
+mod mutator;
+mod naming;
+mod narration;
 mod quest;
 mod randomizer;
+mod simulation;
+mod speedrun;
+mod stats;
+mod terrain;
+mod title;
+mod weather;
 
 use anyhow::Result;
-use clap::{crate_version, Parser};
+use clap::{crate_version, CommandFactory, Parser};
 
 /// Your filesystem as a dungeon!
 #[derive(Parser)]
@@ -27,34 +56,92 @@ struct Opts {
     /// Print machine-readable output when possible.
     #[arg(long, global = true)]
     plain: bool,
+
+    /// Show a fantasy name next to each directory.
+    #[arg(long, global = true)]
+    names: bool,
+
+    /// Print screen reader friendly output, without bars or colors.
+    #[arg(long, global = true)]
+    a11y: bool,
+
+    /// Print battle turns progressively instead of all at once.
+    #[arg(long, global = true)]
+    pace: bool,
 }
 
 fn main() {
     if let Err(err) = run_game() {
-        // don't print a new line if error message is empty
-        if !err.to_string().is_empty() {
+        // death is already reported by the battle log
+        if !error::is_dead(&err) {
             println!("{}", err);
-        };
+        }
 
-        std::process::exit(1);
+        std::process::exit(error::exit_code(&err));
     }
 }
 
+/// Replace the subcommand with its invocation if it's one of the aliases
+/// defined in the config. Only the first word that isn't a flag is
+/// considered, and aliases aren't expanded recursively.
+fn expand_alias(mut args: Vec<String>) -> Vec<String> {
+    let Some(position) = args.iter().skip(1).position(|arg| !arg.starts_with('-')) else {
+        return args;
+    };
+    let position = position + 1;
+    if Opts::command().find_subcommand(&args[position]).is_some() {
+        return args;
+    }
+    if let Some(expansion) = Config::get().alias(&args[position]) {
+        args.splice(position..=position, expansion);
+    }
+    args
+}
+
 /// Loads or creates a new game, executes the received command and saves.
 /// Inner errors are bubbled up.
 fn run_game() -> Result<()> {
-    let opts: Opts = Opts::parse();
-    log::init(opts.quiet, opts.plain);
+    if let Err(err) = datafile::load_config() {
+        // a broken config shouldn't block reset --hard, which is meant
+        // to recover from broken data files. The defaults are used instead
+        let hard_reset = Opts::try_parse_from(std::env::args())
+            .is_ok_and(|opts| matches!(opts.cmd, Some(command::Command::Reset { hard: true, .. })));
+        if !hard_reset {
+            return Err(err);
+        }
+    }
+    let opts = Opts::parse_from(expand_alias(std::env::args().collect()));
+    let a11y = opts.a11y || Config::get().a11y;
+    let pace = match Config::get().battle_pace_ms {
+        0 if opts.pace => log::DEFAULT_PACE_MS,
+        pace => pace,
+    };
+    log::init(opts.quiet, opts.plain, opts.names, a11y, pace);
     datafile::load_classes();
+    datafile::load_items();
 
     // reset --hard is a special case, it needs to work when we
     // fail to deserialize the game data -- e.g. on backward
     // incompatible changes
-    if let Some(command::Command::Reset { hard: true }) = opts.cmd {
+    if let Some(command::Command::Reset { hard: true, .. }) = opts.cmd {
         datafile::remove();
     }
 
-    let mut game = datafile::load()?.unwrap_or_else(Game::new);
+    let mut game = match datafile::load()? {
+        Some(game) => game,
+        None => {
+            // first-time players, with no saved game, get the guided tutorial
+            let mut game = Game::new();
+            let tip = game.quests.start_tutorial();
+            // pwd output is read by the shell integration, keep it clean
+            if let Some(tip) =
+                tip.filter(|_| !matches!(opts.cmd, Some(command::Command::PrintWorkDir)))
+            {
+                log::tip(tip);
+            }
+            game
+        }
+    };
 
     let cmd_result = command::run(opts.cmd, &mut game);
 
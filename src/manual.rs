@@ -0,0 +1,347 @@
+use crate::arena;
+use crate::artifact;
+use crate::bounty;
+use crate::character::class::{Category, Class};
+use crate::character::{enemy, GOLD_PER_LEVEL, MAX_PARRY, PARRY_SHIELD, PARRY_SPEED};
+use crate::game::{BOSS_RUSH_HEAL, BRIBE_COST, CYCLE_LEVEL_BONUS, TRAP_DAMAGE, VOYAGE_FARE};
+use crate::guild::RANK_REWARD;
+use crate::insurance::GOLD_COVERAGE;
+use crate::item::ring::Ring;
+use crate::item::Item;
+use crate::location::{Distance, MID_DISTANCE, NEAR_DISTANCE, OVERSEAS_DISTANCE};
+use crate::mercy;
+use crate::quest::REVENGE_REWARD_PER_LEVEL;
+use crate::randomizer::{
+    enemy_odds, AMBUSH_ODDS, AMBUSH_SPEED_RATIO, BRIBE_ODDS, CRITICAL_ODDS, DETECT_ODDS,
+    DISARM_ODDS, ENEMY_LEVEL_VARIANCE, PIRATE_ODDS, PURSUIT_ODDS, SKILLED_DISARM_ODDS, TRAP_ODDS,
+};
+use crate::stats;
+use crate::terrain::{
+    ANOMALY_LEVEL_SPREAD, ANOMALY_MAX_DISTANCE, ANOMALY_STAT_SHIFT, ELITE_LEVEL_FLOOR,
+    FORTRESS_GOLD_BONUS, FORTRESS_LEVEL_BONUS, SWAMP_SPEED_PENALTY,
+};
+use crate::weather::{Weather, EMPOWERED_LEVEL_BONUS};
+use anyhow::{bail, Result};
+use std::cmp::min;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Subjects of the in-game manual. The explanations are built from the
+/// same constants the game rules use, so they stay accurate.
+#[derive(Clone, Copy, PartialEq, Debug, EnumIter)]
+pub enum Topic {
+    Combat,
+    Death,
+    Rings,
+    Classes,
+    Quests,
+    Weather,
+    Terrain,
+}
+
+impl Topic {
+    pub fn from(name: &str) -> Result<Self> {
+        if let Some(topic) = Self::iter().find(|t| t.name() == name.to_lowercase()) {
+            Ok(topic)
+        } else {
+            bail!("topic {} not found", name)
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Topic::Combat => "combat",
+            Topic::Death => "death",
+            Topic::Rings => "rings",
+            Topic::Classes => "classes",
+            Topic::Quests => "quests",
+            Topic::Weather => "weather",
+            Topic::Terrain => "terrain",
+        }
+    }
+
+    /// The paragraphs explaining this topic.
+    pub fn explain(&self) -> Vec<String> {
+        match self {
+            Topic::Combat => combat(),
+            Topic::Death => death(),
+            Topic::Rings => rings(),
+            Topic::Classes => classes(),
+            Topic::Quests => quests(),
+            Topic::Weather => weather(),
+            Topic::Terrain => terrain(),
+        }
+    }
+}
+
+fn combat() -> Vec<String> {
+    let riposte: i32 = Class::player_by_name("warrior").map_or(0, |class| {
+        class.skills.iter().map(|skill| skill.passive.parry).sum()
+    });
+    let odds = |len| {
+        let (numerator, denominator) = enemy_odds(&Distance::from(len));
+        format!("{} in {}", numerator, denominator)
+    };
+
+    vec![
+        format!(
+            "Enemies show up more often the further the hero gets from home: {} up to {} directories away, {} up to {} and {} beyond that.",
+            odds(1),
+            NEAR_DISTANCE,
+            odds(NEAR_DISTANCE + 1),
+            MID_DISTANCE,
+            odds(MID_DISTANCE + 1)
+        ),
+        format!(
+            "Enemy levels grow with the distance too, varying up to {} levels above or below the expected one.",
+            ENEMY_LEVEL_VARIANCE
+        ),
+        format!(
+            "Attacks have a {} in {} chance of being critical. Faster characters can dodge the attacks of slower ones.",
+            CRITICAL_ODDS.0, CRITICAL_ODDS.1
+        ),
+        format!(
+            "A hero with a shield can parry an enemy blow and strike back: {}% of the time with a shield of its level or above, {}% more when faster than the enemy, and {}% more with the warrior's riposte, for a best case of {}%.",
+            PARRY_SHIELD,
+            PARRY_SPEED,
+            riposte,
+            min(MAX_PARRY, PARRY_SHIELD + PARRY_SPEED + riposte)
+        ),
+        format!(
+            "Defeated enemies reward about {} gold for each level they have over the hero. A bribe costs {}% of that reward and works {} in {} times; a failed bribe leaves the hero exposed to an attack.",
+            GOLD_PER_LEVEL, BRIBE_COST, BRIBE_ODDS.0, BRIBE_ODDS.1
+        ),
+        format!(
+            "A failed escape lets the enemy open the next round with a strike that can't miss. Likewise, a hero at least {} times faster than an enemy catches it by surprise {} in {} times, getting a free first strike.",
+            AMBUSH_SPEED_RATIO, AMBUSH_ODDS.0, AMBUSH_ODDS.1
+        ),
+        format!(
+            "An enemy left behind, by fleeing or walking away from the battle, gives chase {} in {} times, twice as often if it's faster than the hero, and strikes first when it catches up. Enemies never follow the hero into safe places.",
+            PURSUIT_ODDS.0, PURSUIT_ODDS.1
+        ),
+        format!(
+            "Git repositories with uncommitted changes are infested with bugs, one level stronger for each changed file, turning into regressions from {} changes on. Committing the changes fixes a bug in the middle of a battle.",
+            enemy::REGRESSION_CHANGES
+        ),
+        format!(
+            "After {} victories at the same directory, or a boss kill, it stays cleared of enemies for a while.",
+            enemy::CLEAR_VICTORIES
+        ),
+        format!(
+            "{} in {} chests hide a trap, noticed {} in {} times and always by thieves that learned to disarm them. A noticed trap can be disarmed {} in {} times, {} in {} for those thieves, or left alone. A trap that goes off takes {}% of the hero's max hp and poisons it, but the chest is opened anyway.",
+            TRAP_ODDS.0,
+            TRAP_ODDS.1,
+            DETECT_ODDS.0,
+            DETECT_ODDS.1,
+            DISARM_ODDS.0,
+            DISARM_ODDS.1,
+            SKILLED_DISARM_ODDS.0,
+            SKILLED_DISARM_ODDS.1,
+            TRAP_DAMAGE
+        ),
+        format!(
+            "The arena at home sends waves of stronger and stronger enemies, without healing in between, rare ones from wave {} and a legendary one every {} waves. Each wave cleared adds {} gold per wave and hero level to the pot, collected by cashing out or moving, and lost if the hero dies.",
+            arena::RARE_WAVE,
+            arena::BOSS_WAVE,
+            arena::PAYOUT_PER_LEVEL
+        ),
+        format!(
+            "Defeating gorthaur wins the game, telling the end of the story and adding the hero to the hall of fame in the stats. It unlocks the boss rush, also started at home, which pits the hero against every boss it has defeated before, back to back, recovering only {}% of its max hp in between. These rematches don't drop heirlooms or the shadow ring, nor count towards mastering a class. The fewest rounds taken to clear it is kept in the stats, and clearing it is a speedrun split.",
+            BOSS_RUSH_HEAL
+        ),
+        format!(
+            "It also unlocks new game+, started at home with reset --plus: the hero keeps its level and belongings, the world forgets its dead, and enemies grow {} levels stronger with each new cycle.",
+            CYCLE_LEVEL_BONUS
+        ),
+    ]
+}
+
+fn death() -> Vec<String> {
+    vec![
+        String::from("When the hero dies, its gold, equipment and items, except heirlooms, are left in a tombstone at the place of death, waiting to be picked up."),
+        format!(
+            "Once a hero is gone for good, it haunts that place as a ghost {} levels above it, carrying {}% of its gold instead of the tombstone.",
+            enemy::GHOST_LEVEL_BONUS,
+            enemy::GHOST_GOLD
+        ),
+        format!(
+            "An insurance policy bought at home returns {}% of the gold and one chosen item after death.",
+            GOLD_COVERAGE
+        ),
+        String::from("The hardcore tier sets what's lost on death. At tier 1 only the gold goes to the tombstone, and at tier 2 the equipment and items go with it. The materials in the pouch are lost at every tier."),
+        String::from("At tier 3, the default, death is permanent: a new level 1 hero of the same class starts over, keeping only the heirlooms, quests, tombstones and the history of past heroes."),
+        format!(
+            "After {} heroes in a row die at level {} or below, the next one is spared: it wins {}% more xp and gold and meets fewer enemies up to {} directories from home, until it outgrows the early levels. There's no mercy in ironman mode.",
+            mercy::DEATHS,
+            mercy::MAX_LEVEL,
+            mercy::BONUS,
+            mercy::DISTANCE
+        ),
+    ]
+}
+
+fn rings() -> Vec<String> {
+    let mut paragraphs = vec![
+        String::from("Up to two rings can be worn at the same time. There's only one of each ring per world."),
+        format!(
+            "Stat rings increase their stat by {}% each, so wearing two of the same kind doubles the effect.",
+            (Ring::Attack.factor() * 100.0) as i32
+        ),
+    ];
+    paragraphs.extend(
+        Ring::iter()
+            .filter(|ring| *ring != Ring::Void)
+            .map(|ring| format!("{}: {}.", ring, ring.describe())),
+    );
+    paragraphs.push(String::from(
+        "The amulet of power is worn around the neck alongside the rings. Once per battle it lets the hero survive a killing blow at 1hp, and it's passed on to the next hero on death.",
+    ));
+    paragraphs
+}
+
+fn classes() -> Vec<String> {
+    let mut names: Vec<_> = Class::names(Category::Player).into_iter().collect();
+    names.sort();
+
+    let mut paragraphs = vec![String::from(
+        "Each class starts with different stats, which grow by a fixed amount, give or take, on every level up. Magic classes use mp to attack.",
+    ), format!(
+        "Advanced classes are unlocked for every hero once a base class is mastered, by gaining {} levels and beating {} boss playing it.",
+        stats::MASTERY_LEVELS, stats::MASTERY_BOSSES
+    )];
+    for name in names {
+        let class = Class::player_by_name(&name).unwrap();
+        let mp = class
+            .mp
+            .as_ref()
+            .map_or(String::new(), |mp| format!(", mp {}+{}", mp.0, mp.1));
+        let requires = class.requires.as_ref().map_or(String::new(), |base| {
+            format!(" Requires mastering {}.", base)
+        });
+        paragraphs.push(format!(
+            "{}: hp {}+{}{}, strength {}+{}, speed {}+{} per level.{}",
+            class.name,
+            class.hp.0,
+            class.hp.1,
+            mp,
+            class.strength.0,
+            class.strength.1,
+            class.speed.0,
+            class.speed.1,
+            requires
+        ));
+    }
+    paragraphs.push(String::from(
+        "More enemies can be added by dropping yaml files with class definitions in the enemies directory of the data dir. Enemies can list the terrains they are found in and a loot table of items with the percentage chance to drop them.",
+    ));
+    paragraphs
+}
+
+fn quests() -> Vec<String> {
+    vec![
+        String::from("Quests unlock as the hero levels up or completes other quests, and reward gold when finished. Use the todo command to list them."),
+        format!(
+            "The guardian appears beyond {} directories from home while its quest is pending, and gorthaur waits {} directories deep for the bearer of the ruling ring.",
+            enemy::GUARDIAN_DISTANCE,
+            enemy::GORTHAUR_DISTANCE
+        ),
+        format!(
+            "Defeating an enemy of the class that killed a hero pays {} gold per level of the killer.",
+            REVENGE_REWARD_PER_LEVEL
+        ),
+        format!(
+            "There are {} legendary artifacts hidden behind the bosses, the amulet quest and a dungeon {} directories deep; the artifacts command lists them. They are kept across deaths, and once all are gathered morgoth, the true final boss, waits {} directories from home.",
+            artifact::Artifact::iter().count(),
+            artifact::LANTERN_DISTANCE,
+            enemy::GORTHAUR_DISTANCE
+        ),
+        format!(
+            "While the quest to beat your own shadow is pending, the shadow shows up at home {} in {} times and drops the shadow ring, which doubles the experience gained in battles.",
+            enemy::HUNTED_SHADOW_ODDS.0,
+            enemy::HUNTED_SHADOW_ODDS.1
+        ),
+        format!(
+            "The bounty board at home offers contracts to kill enemies, deliver parcels or explore far from home, replaced every day. Up to {} can be taken at once, and turning them in at home pays gold and reputation; each reputation point makes new contracts pay {}% more.",
+            bounty::MAX_ACTIVE,
+            bounty::REPUTATION_BONUS
+        ),
+        String::from("Pilgrimage quests lead to well-known system directories such as / and /usr/share, paying more the longer the journey and twice as much for those in elite zones."),
+        format!(
+            "Guild quests pay {} gold for each rank reached.",
+            RANK_REWARD
+        ),
+    ]
+}
+
+fn weather() -> Vec<String> {
+    let mut paragraphs = vec![String::from(
+        "The weather changes every hour or so and is the same everywhere. It's reported when it turns and when looking around with ls.",
+    )];
+    for weather in [Weather::Rain, Weather::Fog, Weather::Storm] {
+        let (numerator, denominator) = weather.miss_odds().unwrap_or((0, 1));
+        let flee = match weather.flee_bonus() {
+            bonus if bonus > 0 => "easier",
+            bonus if bonus < 0 => "harder",
+            _ => "as easy as usual",
+        };
+        paragraphs.push(format!(
+            "{}: attacks miss {} in {} more times and running away is {}; {} enemies show up more often.",
+            weather.name(),
+            numerator,
+            denominator,
+            flee,
+            weather.family().unwrap_or("no")
+        ));
+    }
+    paragraphs.push(format!(
+        "Fog hides the bosses lurking ahead, and storms make storm elementals {} levels stronger.",
+        EMPOWERED_LEVEL_BONUS
+    ));
+    paragraphs
+}
+
+fn terrain() -> Vec<String> {
+    vec![
+        String::from("The permissions of a directory decide its terrain, shown when looking around with ls."),
+        format!(
+            "Swamps are world-writable directories, where the hero is {}% slower.",
+            SWAMP_SPEED_PENALTY
+        ),
+        format!(
+            "Fortresses are read-only directories. Their enemies are {} levels stronger, but battles and chests there yield {}% more gold.",
+            FORTRESS_LEVEL_BONUS, FORTRESS_GOLD_BONUS
+        ),
+        String::from("Sanctuaries are directories with the sticky bit set, where enemies never show up."),
+        format!(
+            "System directories owned by root, outside of home, are elite zones guarded by constructs. Their enemies and treasure are at least level {}, no matter how close to home they are.",
+            ELITE_LEVEL_FLOOR
+        ),
+        format!(
+            "The temporary directory and tmpfs mounts are anomalies. Any enemy can show up there, up to {} levels above the hero, treasure comes from anywhere up to {} directories deep, and on every visit the hero's attack and speed shift up to {}% either way.",
+            ANOMALY_LEVEL_SPREAD, ANOMALY_MAX_DISTANCE, ANOMALY_STAT_SHIFT
+        ),
+        format!(
+            "Network mounts are continents across the sea. Sailing there costs {} gold and pirates attack {} in {} times. Once ashore, distances count from the shore, as if it was {} directories away from home.",
+            VOYAGE_FARE, PIRATE_ODDS.0, PIRATE_ODDS.1, OVERSEAS_DISTANCE
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topics() {
+        assert_eq!(Topic::Rings, Topic::from("Rings").unwrap());
+        assert!(Topic::from("cooking").is_err());
+
+        let combat = Topic::Combat.explain().join("\n");
+        assert!(combat.contains(&format!("up to {} directories away", NEAR_DISTANCE)));
+        assert!(combat.contains(&format!("{}%", BRIBE_COST)));
+
+        for topic in Topic::iter() {
+            assert!(!topic.explain().is_empty());
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::location::Distance;
+use crate::randomizer;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// The highest level at which a hero's death counts as an early one.
+pub const MAX_LEVEL: i32 = 5;
+
+/// How many heroes in a row have to die early for the next one to be spared.
+pub const DEATHS: u32 = 3;
+
+/// Extra experience and gold, as a percentage, won by a spared hero.
+pub const BONUS: i32 = 25;
+
+/// How far from home enemies show up less often for a spared hero.
+pub const DISTANCE: i32 = 5;
+
+/// The (numerator, denominator) chance of an enemy still showing up near
+/// home for a spared hero.
+pub const SPAWN_ODDS: (u32, u32) = (1, 2);
+
+/// Help for the hero that follows a streak of early deaths, lasting until
+/// it makes it past the early levels. Not granted in ironman mode.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Mercy {
+    /// Heroes that died in a row before making it past the early levels.
+    deaths: u32,
+
+    /// Whether the current hero is spared.
+    pub active: bool,
+}
+
+impl Mercy {
+    /// Track the death of a hero at the given level. Returns whether the
+    /// next hero is spared.
+    pub fn hero_died(&mut self, level: i32) -> bool {
+        self.active = false;
+        if level > MAX_LEVEL {
+            self.deaths = 0;
+            return false;
+        }
+        self.deaths += 1;
+        if self.deaths < DEATHS {
+            return false;
+        }
+        self.deaths = 0;
+        true
+    }
+
+    /// End the mercy once the hero reaches the given level, past the early ones.
+    pub fn level_reached(&mut self, level: i32) {
+        if level > MAX_LEVEL {
+            self.active = false;
+        }
+    }
+
+    /// The given experience or gold reward, increased for a spared hero.
+    pub fn reward(&self, value: i32) -> i32 {
+        if self.active {
+            value + value * BONUS / 100
+        } else {
+            value
+        }
+    }
+
+    /// Whether the mercy applies at the given distance from home.
+    pub fn applies(&self, distance: &Distance) -> bool {
+        self.active && distance.len() <= DISTANCE
+    }
+
+    /// Whether an enemy about to show up at the given distance from home
+    /// stays away to spare the hero.
+    pub fn spares(&self, distance: &Distance) -> bool {
+        let (numerator, denominator) = SPAWN_ODDS;
+        self.applies(distance) && !randomizer::rng().gen_ratio(numerator, denominator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mercy() {
+        let mut mercy = Mercy::default();
+        assert!(!mercy.hero_died(1));
+        assert!(!mercy.hero_died(MAX_LEVEL));
+
+        // a hero making it past the early levels breaks the streak
+        assert!(!mercy.hero_died(MAX_LEVEL + 1));
+        assert!(!mercy.hero_died(1));
+        assert!(!mercy.hero_died(1));
+        assert!(mercy.hero_died(1));
+
+        mercy.active = true;
+        assert_eq!(125, mercy.reward(100));
+        mercy.level_reached(MAX_LEVEL);
+        assert!(mercy.active);
+        mercy.level_reached(MAX_LEVEL + 1);
+        assert!(!mercy.active);
+        assert_eq!(100, mercy.reward(100));
+
+        // the streak starts over after sparing a hero
+        assert!(!mercy.hero_died(1));
+    }
+}
@@ -0,0 +1,127 @@
+use crate::character::Character;
+use crate::error::{bail, Result};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Optional rules selected at the start of a run to make it harder, in
+/// exchange for a higher score.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, EnumIter)]
+pub enum Mutator {
+    ToughEnemies,
+    NoShop,
+    GoldDecay,
+}
+
+impl Mutator {
+    pub fn from(name: &str) -> Result<Self> {
+        if let Some(mutator) = Self::iter().find(|m| m.name() == name.to_lowercase()) {
+            Ok(mutator)
+        } else {
+            bail!("mutator {} not found", name)
+        }
+    }
+
+    pub fn all() -> Vec<Self> {
+        Self::iter().collect()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mutator::ToughEnemies => "tough",
+            Mutator::NoShop => "noshop",
+            Mutator::GoldDecay => "decay",
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Mutator::ToughEnemies => "enemies have +50% hp",
+            Mutator::NoShop => "the shop is closed",
+            Mutator::GoldDecay => "gold halves every time the hero returns home",
+        }
+    }
+
+    /// Percentage added to the score multiplier when enabled.
+    fn score_bonus(&self) -> i32 {
+        match self {
+            Mutator::ToughEnemies => 50,
+            Mutator::NoShop => 50,
+            Mutator::GoldDecay => 25,
+        }
+    }
+}
+
+/// The mutators enabled for the current run. All the game rules affected
+/// by mutators ask this struct, instead of checking each one separately.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Mutators {
+    enabled: Vec<Mutator>,
+}
+
+impl Mutators {
+    /// Enable the given mutators, each one counting once towards the score
+    /// however many times it's repeated.
+    pub fn new(mutators: Vec<Mutator>) -> Self {
+        let mut enabled = Vec::new();
+        for mutator in mutators {
+            if !enabled.contains(&mutator) {
+                enabled.push(mutator);
+            }
+        }
+        Self { enabled }
+    }
+
+    pub fn is_enabled(&self, mutator: Mutator) -> bool {
+        self.enabled.contains(&mutator)
+    }
+
+    /// Adjust the stats of a freshly spawned enemy.
+    pub fn apply_to_enemy(&self, enemy: &mut Character) {
+        if self.is_enabled(Mutator::ToughEnemies) {
+            enemy.scale_max_hp(150);
+        }
+    }
+
+    pub fn shop_open(&self) -> bool {
+        !self.is_enabled(Mutator::NoShop)
+    }
+
+    /// The gold left after the hero returns home.
+    pub fn gold_at_home(&self, gold: i32) -> i32 {
+        if self.is_enabled(Mutator::GoldDecay) {
+            gold / 2
+        } else {
+            gold
+        }
+    }
+
+    /// The score multiplier, as a percentage, earned by the enabled mutators.
+    pub fn score_multiplier(&self) -> i32 {
+        100 + self.enabled.iter().map(Mutator::score_bonus).sum::<i32>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mutators() {
+        let mutators = Mutators::new(vec![Mutator::from("tough").unwrap(), Mutator::NoShop]);
+        assert!(!mutators.shop_open());
+        assert_eq!(10, mutators.gold_at_home(10));
+        assert_eq!(200, mutators.score_multiplier());
+        assert!(Mutator::from("easy").is_err());
+
+        let repeated = Mutators::new(vec![Mutator::GoldDecay, Mutator::GoldDecay]);
+        assert_eq!(125, repeated.score_multiplier());
+        assert_eq!(5, repeated.gold_at_home(10));
+
+        let mut enemy = Character::player();
+        let hp = enemy.max_hp();
+        mutators.apply_to_enemy(&mut enemy);
+        assert_eq!(hp * 3 / 2, enemy.max_hp());
+        assert_eq!(enemy.max_hp(), enemy.current_hp);
+    }
+}
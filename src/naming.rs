@@ -0,0 +1,92 @@
+//! Stable fantasy names for directories, derived from their paths.
+//! The same path always gets the same name, across runs and platforms.
+
+const ADJECTIVES: [&str; 12] = [
+    "Sunken",
+    "Whispering",
+    "Forgotten",
+    "Shattered",
+    "Gilded",
+    "Hollow",
+    "Burning",
+    "Silent",
+    "Frozen",
+    "Verdant",
+    "Crimson",
+    "Ashen",
+];
+
+const PLACES: [&str; 12] = [
+    "Archive",
+    "Crypt",
+    "Halls",
+    "Keep",
+    "Vault",
+    "Grove",
+    "Spire",
+    "Catacombs",
+    "Bastion",
+    "Sanctum",
+    "Mire",
+    "Citadel",
+];
+
+const SYLLABLES: [&str; 16] = [
+    "vel", "dor", "mir", "ka", "thal", "or", "ia", "zar", "en", "ul", "gro", "syl", "ne", "bar",
+    "eth", "ru",
+];
+
+/// Generate a name like "The Sunken Archive of Vel" for the given path.
+pub fn fantasy_name(path: &str) -> String {
+    let mut hash = fnv1a(path.as_bytes());
+    let mut next = |max: usize| {
+        let value = (hash % max as u64) as usize;
+        hash /= max as u64;
+        value
+    };
+
+    let adjective = ADJECTIVES[next(ADJECTIVES.len())];
+    let place = PLACES[next(PLACES.len())];
+    let syllables = 1 + next(2);
+    let mut name = String::new();
+    for _ in 0..syllables {
+        name.push_str(SYLLABLES[next(SYLLABLES.len())]);
+    }
+
+    format!("The {} {} of {}", adjective, place, capitalize(&name))
+}
+
+/// The std hasher is not guaranteed to be stable between releases,
+/// so a simple FNV-1a is used instead.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    chars.next().map_or(String::new(), |first| {
+        first.to_uppercase().collect::<String>() + chars.as_str()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stable_names() {
+        let name = fantasy_name("/home/user/projects/api");
+        assert_eq!(name, fantasy_name("/home/user/projects/api"));
+        assert_ne!(name, fantasy_name("/home/user/projects/web"));
+        assert!(name.starts_with("The "));
+        assert_eq!(
+            "The Sunken Archive of Vel".split(' ').count(),
+            name.split(' ').count()
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::character::{AttackType, Character};
+use crate::randomizer;
+use rand::Rng;
+
+/// What each enemy family fights with, matched against the enemy name.
+const WEAPONS: [(&str, &str); 16] = [
+    ("goblin", "rusty blade"),
+    ("orc", "club"),
+    ("skeleton", "bony fingers"),
+    ("wolf", "fangs"),
+    ("elemental", "raw fury"),
+    ("troll", "fists"),
+    ("dragon", "claws"),
+    ("snake", "fangs"),
+    ("basilisk", "gaze"),
+    ("zombie", "rotting hands"),
+    ("vampire", "fangs"),
+    ("lich", "cold spells"),
+    ("golem", "stone fists"),
+    ("minotaur", "horns"),
+    ("wyvern", "talons"),
+    ("griffin", "talons"),
+];
+
+const DEFAULT_WEAPON: &str = "attack";
+
+const ENEMY_HITS: [&str; 3] = [
+    "The {enemy}'s {weapon} catches the {hero} off guard.",
+    "The {enemy} presses forward, its {weapon} finding its mark.",
+    "The {hero} staggers under the {enemy}'s {weapon}.",
+];
+
+const ENEMY_CRITICALS: [&str; 2] = [
+    "The {enemy}'s {weapon} strikes with terrible force!",
+    "The {hero} reels as the {enemy}'s {weapon} lands squarely!",
+];
+
+const ENEMY_EFFECTS: [&str; 2] = [
+    "The {enemy}'s {weapon} leaves a wound that won't stop aching.",
+    "Something foul lingers where the {enemy}'s {weapon} struck.",
+];
+
+const ENEMY_MISSES: [&str; 3] = [
+    "The {enemy}'s {weapon} whistles past the {hero}'s ear.",
+    "The {hero} sidesteps the {enemy}'s {weapon} just in time.",
+    "The {enemy} lunges, but its {weapon} finds only air.",
+];
+
+const HERO_HITS: [&str; 3] = [
+    "The {hero}'s strike bites into the {enemy}.",
+    "The {hero} drives the {enemy} back with a steady blow.",
+    "The {enemy} grunts as the {hero}'s attack connects.",
+];
+
+const HERO_CRITICALS: [&str; 2] = [
+    "The {hero} finds a gap in the {enemy}'s guard!",
+    "A perfect blow! The {enemy} howls in pain.",
+];
+
+const HERO_EFFECTS: [&str; 2] = [
+    "The {hero}'s attack leaves the {enemy} writhing.",
+    "The {enemy} recoils, afflicted by the {hero}'s strike.",
+];
+
+const HERO_MISSES: [&str; 3] = [
+    "The {enemy} dodges, and the {hero}'s blow meets only air.",
+    "The {hero} swings wide of the {enemy}.",
+    "The {enemy} slips away from the {hero}'s attack.",
+];
+
+/// A line of flavor text describing the outcome of an attack, worded
+/// after the enemy involved and how the attack went.
+pub fn narrate(attacker: &Character, receiver: &Character, attack: &AttackType) -> String {
+    let (hero, enemy) = if attacker.is_player() {
+        (attacker, receiver)
+    } else {
+        (receiver, attacker)
+    };
+
+    let templates: &[&str] = match (attacker.is_player(), attack) {
+        (false, AttackType::Regular) => &ENEMY_HITS,
+        (false, AttackType::Critical) => &ENEMY_CRITICALS,
+        (false, AttackType::Effect(_)) => &ENEMY_EFFECTS,
+        (false, AttackType::Miss) => &ENEMY_MISSES,
+        (true, AttackType::Regular) => &HERO_HITS,
+        (true, AttackType::Critical) => &HERO_CRITICALS,
+        (true, AttackType::Effect(_)) => &HERO_EFFECTS,
+        (true, AttackType::Miss) => &HERO_MISSES,
+    };
+    let template = templates[randomizer::rng().gen_range(0..templates.len())];
+
+    template
+        .replace("{hero}", &hero.name())
+        .replace("{enemy}", &enemy.name())
+        .replace("{weapon}", weapon(&enemy.name()))
+}
+
+fn weapon(enemy: &str) -> &'static str {
+    WEAPONS
+        .iter()
+        .find(|(family, _)| enemy.contains(family))
+        .map_or(DEFAULT_WEAPON, |(_, weapon)| weapon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::class::Class;
+
+    #[test]
+    fn test_narrate() {
+        let hero = Character::player();
+        let orc = Character::new(Class::enemy_by_name("orc").unwrap().clone(), 1);
+
+        let line = narrate(&orc, &hero, &AttackType::Miss);
+        assert!(ENEMY_MISSES.iter().any(|t| line
+            == t.replace("{hero}", "warrior")
+                .replace("{enemy}", "orc")
+                .replace("{weapon}", "club")));
+
+        let line = narrate(&hero, &orc, &AttackType::Critical);
+        assert!(!line.contains('{'));
+
+        assert_eq!("fangs", weapon("dire wolf"));
+        assert_eq!(DEFAULT_WEAPON, weapon("chimera"));
+    }
+}
@@ -37,6 +37,17 @@ pub fn dev() -> Box<dyn Quest> {
     })
 }
 
+pub fn revenge(name: &str) -> Box<dyn Quest> {
+    let mut to_beat = HashSet::new();
+    to_beat.insert(name.to_string());
+
+    Box::new(BeatEnemyClass {
+        to_beat,
+        total: 1,
+        description: format!("avenge your fallen hero by beating a {}", name),
+    })
+}
+
 pub fn at_distance(distance: i32) -> Box<dyn Quest> {
     Box::new(BeatEnemyDistance { distance })
 }
@@ -0,0 +1,40 @@
+use super::{Event, Quest};
+use serde::{Deserialize, Serialize};
+
+/// Make a given amount of commits to the git repositories the hero visited.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MakeCommits {
+    remaining: usize,
+    total: usize,
+}
+
+impl MakeCommits {
+    pub fn new(total: usize) -> Self {
+        Self {
+            remaining: total,
+            total,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Quest for MakeCommits {
+    fn description(&self) -> String {
+        let progress = self.total - self.remaining;
+        format!(
+            "make {} git commits {}/{}",
+            self.total, progress, self.total
+        )
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        if let Event::Committed { count } = event {
+            self.remaining = self.remaining.saturating_sub(*count);
+        }
+        self.remaining == 0
+    }
+
+    fn tip(&self) -> Option<&'static str> {
+        Some("commits made to the git repositories the hero visited count as experience")
+    }
+}
@@ -0,0 +1,69 @@
+use super::beat_enemy;
+use super::{Event, Quest};
+use crate::guild::Guild;
+use crate::item::key::Key;
+use serde::{Deserialize, Serialize};
+
+/// Return the quest assigned by the guild to reach the rank after the given one.
+/// Guilds rotate between two kinds of quests, getting harder as the rank goes up.
+pub fn for_rank(guild: Guild, rank: i32) -> Box<dyn Quest> {
+    let first_of_pair = rank % 2 == 1;
+    match guild {
+        Guild::Warriors if first_of_pair => repeat(Task::WinBattles, rank * 2 + 1),
+        Guild::Mages if first_of_pair => repeat(Task::UseEthers, rank + 1),
+        Guild::Thieves if first_of_pair => repeat(Task::FindChests, rank + 1),
+        Guild::Thieves => repeat(Task::WinBattles, rank * 2),
+        Guild::Warriors | Guild::Mages => beat_enemy::at_distance(rank + 5),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+enum Task {
+    WinBattles,
+    FindChests,
+    UseEthers,
+}
+
+/// A task that needs to be done a given amount of times.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Repeat {
+    task: Task,
+    remaining: i32,
+    total: i32,
+}
+
+fn repeat(task: Task, total: i32) -> Box<dyn Quest> {
+    Box::new(Repeat {
+        task,
+        remaining: total,
+        total,
+    })
+}
+
+#[typetag::serde]
+impl Quest for Repeat {
+    fn description(&self) -> String {
+        let (verb, task) = match self.task {
+            Task::WinBattles => ("win", "battles"),
+            Task::FindChests => ("find", "chests"),
+            Task::UseEthers => ("use", "ethers"),
+        };
+        let progress = self.total - self.remaining;
+        format!(
+            "{} {} {} {}/{}",
+            verb, self.total, task, progress, self.total
+        )
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        let matches = match self.task {
+            Task::WinBattles => matches!(event, Event::BattleWon { .. }),
+            Task::FindChests => matches!(event, Event::ChestFound),
+            Task::UseEthers => matches!(event, Event::ItemUsed { item: Key::Ether }),
+        };
+        if matches {
+            self.remaining -= 1;
+        }
+        self.remaining <= 0
+    }
+}
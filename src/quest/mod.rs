@@ -8,7 +8,10 @@ use core::fmt;
 use serde::{Deserialize, Serialize};
 
 mod beat_enemy;
+mod commit;
+mod guild;
 mod level;
+mod pilgrimage;
 mod ring;
 mod tutorial;
 mod find_amulet;
@@ -24,6 +27,11 @@ pub trait Quest {
     /// Update the quest progress based on the given event and
     /// return whether the quest was finished.
     fn handle(&mut self, event: &Event) -> bool;
+
+    /// A hint shown when the quest becomes available.
+    fn tip(&self) -> Option<&'static str> {
+        None
+    }
 }
 
 impl fmt::Display for dyn Quest {
@@ -32,6 +40,12 @@ impl fmt::Display for dyn Quest {
     }
 }
 
+/// Gold rewarded per level of the killer when completing a revenge quest.
+pub const REVENGE_REWARD_PER_LEVEL: i32 = 1000;
+
+/// Gold rewarded for each step of the guided tutorial.
+const TUTORIAL_REWARD: i32 = 50;
+
 /// Keeps a TODO list of quests for the game.
 /// Each quest is unlocked at a certain level and has completion reward.
 #[derive(Serialize, Deserialize, Default)]
@@ -93,6 +107,12 @@ pub fn item_added(game: &mut game::Game, item: Key) {
     handle(game, Event::ItemAdded { item });
 }
 
+pub fn moved(game: &mut game::Game) {
+    let home = game.location.is_home();
+    let location = game.location.clone();
+    handle(game, Event::Moved { home, location });
+}
+
 pub fn chest(game: &mut game::Game) {
     handle(game, Event::ChestFound);
 }
@@ -101,14 +121,31 @@ pub fn tombstone(game: &mut game::Game) {
     handle(game, Event::TombtsoneFound);
 }
 
+pub fn committed(game: &mut game::Game, count: usize) {
+    handle(game, Event::Committed { count });
+}
+
 pub fn game_reset(game: &mut game::Game) {
     handle(game, Event::GameReset);
 }
 
+pub fn guild_quest(guild: crate::guild::Guild, rank: i32) -> Box<dyn Quest> {
+    guild::for_rank(guild, rank)
+}
+
+/// Add a quest to defeat an enemy of the same class that killed the hero.
+pub fn revenge(game: &mut game::Game, killer: &Character) {
+    game.quests.add_revenge(killer);
+}
+
 fn handle(game: &mut game::Game, event: Event) {
     // it would be preferable to have quests decoupled from the game struct
     // but that makes event handling much more complicated
     game.gold += game.quests.handle(&event);
+    if let Some(membership) = &mut game.guild {
+        game.gold += membership.handle(&event);
+    }
+    game.bounties.handle(&event);
 }
 
 pub enum Event<'a> {
@@ -130,8 +167,15 @@ pub enum Event<'a> {
     ItemAdded {
         item: Key,
     },
+    Moved {
+        home: bool,
+        location: Location,
+    },
     ChestFound,
     TombtsoneFound,
+    Committed {
+        count: usize,
+    },
     GameReset,
 }
 
@@ -143,6 +187,41 @@ impl QuestList {
         quests
     }
 
+    /// Add the guided tutorial for first-time players.
+    /// Returns the tip of its first step.
+    pub fn start_tutorial(&mut self) -> Option<&'static str> {
+        let chain: [Box<dyn Quest>; 4] = [
+            Box::new(tutorial::LeaveHome),
+            Box::new(tutorial::FirstBattle),
+            Box::new(tutorial::BuyPotion),
+            Box::new(tutorial::ReturnHome::default()),
+        ];
+        let first_tip = chain[0].tip();
+        let mut previous: Option<String> = None;
+        for quest in chain {
+            let status = match previous {
+                Some(description) => Status::LockedByQuest(description),
+                None => Status::Unlocked,
+            };
+            previous = Some(quest.description());
+            self.quests.push((status, TUTORIAL_REWARD, quest));
+        }
+        first_tip
+    }
+
+    /// Add the quests rewarding commits to git repositories, for players
+    /// that opted into them. Returns the tip of the first one.
+    pub fn start_git(&mut self) -> Option<&'static str> {
+        let quests = [(1, 100), (10, 1000), (100, 10000)];
+        let first_tip = commit::MakeCommits::new(1).tip();
+        for (commits, reward) in quests {
+            let quest = commit::MakeCommits::new(commits);
+            self.quests
+                .push((Status::Unlocked, reward, Box::new(quest)));
+        }
+        first_tip
+    }
+
     /// Load the quests for a new game
     fn setup(&mut self) {
         self.quests
@@ -187,6 +266,10 @@ impl QuestList {
         ));
         self.quests
             .push((Status::Locked(5), 1000, beat_enemy::at_distance(10)));
+        for (reward, pilgrimage) in pilgrimage::all() {
+            self.quests
+                .push((Status::Locked(5), reward, Box::new(pilgrimage)));
+        }
 
         self.quests.push((
             Status::Locked(10),
@@ -214,7 +297,7 @@ impl QuestList {
             Box::new(ring::FindAllRings::new()),
         ));
         self.quests
-            .push((Status::Locked(15), 20000, beat_enemy::shadow()));
+            .push((Status::Locked(10), 20000, beat_enemy::shadow()));
         self.quests
             .push((Status::Locked(15), 20000, beat_enemy::dev()));
 
@@ -227,15 +310,33 @@ impl QuestList {
             .push((Status::Locked(50), 1000000, ring::gorthaur()));
     }
 
+    /// Unless there's one pending already for the same class, add a
+    /// revenge quest rewarding gold based on the killer's level.
+    fn add_revenge(&mut self, killer: &Character) {
+        let quest = beat_enemy::revenge(&killer.name());
+        let description = quest.description();
+        let pending = self
+            .quests
+            .iter()
+            .any(|(status, _, q)| *status != Status::Completed && q.description() == description);
+
+        if !pending {
+            let reward = killer.level * REVENGE_REWARD_PER_LEVEL;
+            self.quests.push((Status::Unlocked, reward, quest));
+        }
+    }
+
     /// Pass the event to each of the quests, moving the completed ones to DONE.
     /// The total gold reward is returned.
     fn handle(&mut self, event: &Event) -> i32 {
         self.unlock_quests(event);
 
         let mut total_reward = 0;
+        let mut completed = false;
 
         for (status, reward, quest) in &mut self.quests {
-            if let Status::Completed = status {
+            // chained quests only make progress once the previous one is done
+            if matches!(status, Status::Completed | Status::LockedByQuest(_)) {
                 continue;
             }
 
@@ -243,10 +344,16 @@ impl QuestList {
             if is_done {
                 total_reward += *reward;
                 log::quest_done(*reward);
-                *status = Status::Completed
+                *status = Status::Completed;
+                completed = true;
             }
         }
 
+        // quests locked by the ones just completed are available right away
+        if completed {
+            self.unlock_quests(event);
+        }
+
         total_reward
     }
 
@@ -269,10 +376,13 @@ impl QuestList {
             .map(|(_, _, q)| q.description())
             .collect();
 
-        for (status, _, _) in &mut self.quests {
+        for (status, _, quest) in &mut self.quests {
             if let Status::LockedByQuest(req) = status {
                 if completed_quests.contains(req) {
                     *status = Status::Unlocked;
+                    if let Some(tip) = quest.tip() {
+                        log::tip(tip);
+                    }
                 }
             }
         }
@@ -301,6 +411,59 @@ mod tests {
     use crate::item::Item;
     use crate::location::tests::location_from;
 
+    #[test]
+    fn test_tutorial() {
+        let mut quests = QuestList { quests: Vec::new() };
+        assert!(quests.start_tutorial().is_some());
+        assert_eq!(1, count_status(&quests, Status::Unlocked));
+
+        // coming back home doesn't count before leaving it
+        quests.handle(&Event::Moved {
+            home: true,
+            location: Location::home(),
+        });
+        assert_eq!(0, count_status(&quests, Status::Completed));
+
+        // each step is available right after completing the previous one
+        let reward = quests.handle(&Event::Moved {
+            home: false,
+            location: location_from("~/dungeon"),
+        });
+        assert_eq!(TUTORIAL_REWARD, reward);
+        assert_eq!(
+            vec![
+                (true, "tutorial: move to another directory".to_string()),
+                (false, "tutorial: win your first battle".to_string())
+            ],
+            quests.list()
+        );
+
+        // later steps don't make progress out of order
+        quests.handle(&Event::ItemBought { item: Key::Potion });
+        assert_eq!(1, count_status(&quests, Status::Completed));
+
+        // the last step needs another trip away from home
+        let enemy = Character::player();
+        quests.handle(&Event::BattleWon {
+            enemy: &enemy,
+            location: location_from("~/dungeon"),
+        });
+        quests.handle(&Event::ItemBought { item: Key::Potion });
+        assert_eq!(3, count_status(&quests, Status::Completed));
+        let home = Event::Moved {
+            home: true,
+            location: Location::home(),
+        };
+        quests.handle(&home);
+        assert_eq!(3, count_status(&quests, Status::Completed));
+        quests.handle(&Event::Moved {
+            home: false,
+            location: location_from("~/dungeon"),
+        });
+        quests.handle(&home);
+        assert_eq!(4, count_status(&quests, Status::Completed));
+    }
+
     #[test]
     fn test_quest_status() {
         let mut quests = QuestList { quests: Vec::new() };
@@ -414,8 +577,11 @@ mod tests {
         let mut game = game::Game::new();
         game.quests.quests = vec![(Status::Unlocked, 1, Box::new(ring::FindAllRings::new()))];
 
+        // the shadow ring is not needed
         for ring in item::ring::Ring::set() {
-            game.add_item(Box::new(ring));
+            if ring != item::ring::Ring::Shadow {
+                game.add_item(Box::new(ring));
+            }
         }
 
         assert_eq!(Status::Completed, game.quests.quests[0].0);
@@ -436,7 +602,7 @@ mod tests {
         // ruling ring required to spawn the enemy
         game.player.left_ring = Some(item::ring::Ring::Ruling);
 
-        let mut enemy = enemy::spawn(&game.location, &game.player).unwrap();
+        let mut enemy = enemy::spawn(&mut game).unwrap();
 
         // increase many levels to force the player's victory
         for _ in 0..200 {
@@ -444,11 +610,28 @@ mod tests {
         }
         enemy.current_hp = 10;
 
-        game.battle(&mut enemy, false, false).unwrap();
+        game.battle(enemy).unwrap();
 
         assert_eq!(Status::Completed, game.quests.quests[0].0);
     }
 
+    #[test]
+    fn revenge() {
+        let mut game = game::Game::new();
+        game.quests.quests = vec![];
+        let killer = Character::new(class::Class::random(class::Category::Common).clone(), 3);
+
+        super::revenge(&mut game, &killer);
+        super::revenge(&mut game, &killer);
+        assert_eq!(1, game.quests.quests.len());
+        assert_eq!(3000, game.quests.quests[0].1);
+
+        game.reset();
+        battle_won(&mut game, &killer, 0);
+        assert_eq!(Status::Completed, game.quests.quests[0].0);
+        assert_eq!(3000, game.gold);
+    }
+
     fn count_status(quests: &QuestList, status: Status) -> usize {
         quests
             .quests
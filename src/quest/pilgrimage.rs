@@ -0,0 +1,99 @@
+use super::{Event, Quest};
+use crate::location::Location;
+use crate::terrain;
+use serde::{Deserialize, Serialize};
+
+/// Well-known system paths worth a pilgrimage, along with the quest description.
+const LANDMARKS: [(&str, &str); 5] = [
+    ("/", "stand at the root of the world /"),
+    ("/usr/share", "make a pilgrimage to /usr/share"),
+    ("/etc", "consult the scrolls of /etc"),
+    ("/var/log", "read the chronicles of /var/log"),
+    ("/usr/bin", "visit the forges of /usr/bin"),
+];
+
+/// Landmarks closer than this to home are too short a trip for a pilgrimage.
+const MIN_DISTANCE: i32 = 2;
+
+/// Gold rewarded per step of the journey from home to the landmark.
+const REWARD_PER_STEP: i32 = 200;
+
+/// Reward multiplier for landmarks in elite zones.
+const ELITE_MULTIPLIER: i32 = 2;
+
+/// Reach a landmark of the system.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pilgrimage {
+    location: Location,
+    description: String,
+}
+
+#[typetag::serde]
+impl Quest for Pilgrimage {
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        matches!(event, Event::Moved { location, .. } if *location == self.location)
+    }
+}
+
+/// The pilgrimages to the landmarks found in this system, away from home,
+/// each with a reward scaled by the length and danger of the journey.
+pub fn all() -> Vec<(i32, Pilgrimage)> {
+    LANDMARKS
+        .iter()
+        .filter_map(|(path, description)| {
+            let location = Location::from(path).ok()?;
+            to(location, description)
+        })
+        .collect()
+}
+
+/// The pilgrimage to the given landmark, unless it's too close to home.
+fn to(location: Location, description: &str) -> Option<(i32, Pilgrimage)> {
+    if location.is_within(&Location::home()) || location.distance_from_home().len() < MIN_DISTANCE {
+        return None;
+    }
+    let pilgrimage = Pilgrimage {
+        location,
+        description: description.to_string(),
+    };
+    Some((reward(&pilgrimage.location), pilgrimage))
+}
+
+fn reward(location: &Location) -> i32 {
+    let reward = location.distance_from_home().len() * REWARD_PER_STEP;
+    if terrain::is_elite_zone(location) {
+        reward * ELITE_MULTIPLIER
+    } else {
+        reward
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::location::tests::location_from;
+
+    #[test]
+    fn test_pilgrimage() {
+        let home = Location::home().path_string();
+        assert!(to(location_from(&format!("{}/shrine", home)), "").is_none());
+
+        let shrine = location_from("/rpg-test/pilgrimage/shrine");
+        let (reward, mut pilgrimage) = to(shrine.clone(), "visit the shrine").unwrap();
+        // fake paths are never elite zones
+        assert_eq!(shrine.distance_from_home().len() * REWARD_PER_STEP, reward);
+
+        assert!(!pilgrimage.handle(&Event::Moved {
+            home: true,
+            location: Location::home()
+        }));
+        assert!(pilgrimage.handle(&Event::Moved {
+            home: false,
+            location: shrine
+        }));
+    }
+}
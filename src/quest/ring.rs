@@ -30,16 +30,34 @@ pub struct FindAllRings {
 impl FindAllRings {
     pub fn new() -> Self {
         Self {
-            to_find: Ring::set(),
+            to_find: Ring::set().into_iter().filter(is_collectible).collect(),
         }
     }
+
+    /// The rings still missing, ignoring the shadow ring that older
+    /// quests may still be waiting for.
+    fn missing(&self) -> usize {
+        self.to_find
+            .iter()
+            .filter(|ring| is_collectible(ring))
+            .count()
+    }
+}
+
+/// The shadow ring is a reward for the shadow quest rather than a find,
+/// and saves that already completed it could never get it.
+fn is_collectible(ring: &Ring) -> bool {
+    *ring != Ring::Shadow
 }
 
 #[typetag::serde]
 impl Quest for FindAllRings {
     fn description(&self) -> String {
-        let total = Ring::set().len();
-        let already_found = total - self.to_find.len();
+        let total = Ring::set()
+            .iter()
+            .filter(|ring| is_collectible(ring))
+            .count();
+        let already_found = total - self.missing();
         format!("find all rings {}/{}", already_found, total)
     }
 
@@ -50,7 +68,7 @@ impl Quest for FindAllRings {
         {
             self.to_find.remove(ring);
         }
-        self.to_find.is_empty()
+        self.missing() == 0
     }
 }
 
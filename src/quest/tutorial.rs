@@ -77,3 +77,89 @@ impl Quest for VisitTomb {
         matches!(event, Event::TombtsoneFound)
     }
 }
+
+// GUIDED TUTORIAL
+// A short chain of quests for first-time players, each one unlocked by the
+// previous one and showing a tip about how to complete it.
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LeaveHome;
+
+#[typetag::serde]
+impl Quest for LeaveHome {
+    fn description(&self) -> String {
+        "tutorial: move to another directory".to_string()
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        matches!(event, Event::Moved { home: false, .. })
+    }
+
+    fn tip(&self) -> Option<&'static str> {
+        Some("directories are dungeons: move around with `rpg cd <dir>`, enemies lurk away from home")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FirstBattle;
+
+#[typetag::serde]
+impl Quest for FirstBattle {
+    fn description(&self) -> String {
+        "tutorial: win your first battle".to_string()
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        matches!(event, Event::BattleWon { .. })
+    }
+
+    fn tip(&self) -> Option<&'static str> {
+        Some("keep moving until an enemy shows up, or look for one with `rpg battle`")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BuyPotion;
+
+#[typetag::serde]
+impl Quest for BuyPotion {
+    fn description(&self) -> String {
+        "tutorial: buy a potion".to_string()
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        matches!(event, Event::ItemBought { item: Key::Potion })
+    }
+
+    fn tip(&self) -> Option<&'static str> {
+        Some("the shop is at home: go back with `rpg cd` and spend the gold won with `rpg buy potion`")
+    }
+}
+
+/// Only completed by coming back home after leaving it at this step.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ReturnHome {
+    #[serde(default)]
+    ventured: bool,
+}
+
+#[typetag::serde]
+impl Quest for ReturnHome {
+    fn description(&self) -> String {
+        "tutorial: venture out and return home".to_string()
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        if let Event::Moved { home, .. } = event {
+            if !home {
+                self.ventured = true;
+            }
+            return *home && self.ventured;
+        }
+        false
+    }
+
+    fn tip(&self) -> Option<&'static str> {
+        Some("head out again, and come back home with `rpg cd` when hurt to restore hp and mp")
+    }
+}
@@ -1,10 +1,59 @@
 #![allow(dead_code)]
 
 use crate::character::StatusEffect;
+use crate::item::equipment;
 use crate::location;
-use rand::Rng;
+use crate::weather::Weather;
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use std::cell::RefCell;
 use std::cmp::max;
 
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Reseed the random number generator, so the same sequence of commands
+/// produces the same outcomes, e.g. in challenge runs.
+pub fn seed(value: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(value));
+}
+
+/// Run the given function with the random number generator reseeded,
+/// restoring the game's own sequence afterwards so it's left untouched.
+pub fn seeded<T>(value: u64, f: impl FnOnce() -> T) -> T {
+    let saved = RNG.with(|rng| rng.replace(StdRng::seed_from_u64(value)));
+    let result = f();
+    RNG.with(|rng| *rng.borrow_mut() = saved);
+    result
+}
+
+/// The random number generator used across the game. Unlike
+/// `rand::thread_rng`, it can be reseeded.
+pub fn rng() -> SharedRng {
+    SharedRng
+}
+
+pub struct SharedRng;
+
+impl RngCore for SharedRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
+
 /// This trait exposes functions to deal with any element of the game that
 /// needs to incorporate randomness.
 /// It basically wraps all calls to the rand crate, allowing to replace it with a
@@ -20,6 +69,7 @@ pub trait Randomizer {
         enemy_level: i32,
         player_speed: i32,
         enemy_speed: i32,
+        bonus: i32,
     ) -> bool;
 
     fn enemy_level(&self, level: i32) -> i32;
@@ -28,10 +78,46 @@ pub trait Randomizer {
 
     fn is_miss(&self, attacker_speed: i32, receiver: &crate::character::Character) -> bool;
 
+    fn is_weather_miss(&self, weather: Weather) -> bool;
+
+    fn dodges(&self, chance: i32) -> bool;
+
+    fn parries(&self, chance: i32) -> bool;
+
     fn is_critical(&self) -> bool;
 
     fn counter_attack(&self) -> bool;
 
+    fn invasion_starts(&self) -> bool;
+
+    fn enemy_pursues(&self, player_speed: i32, enemy_speed: i32) -> bool;
+
+    fn hero_ambushes(&self, player_speed: i32, enemy_speed: i32) -> bool;
+
+    fn pirates_attack(&self) -> bool;
+
+    fn forage_ambushed(&self) -> bool;
+
+    fn pickaxe_breaks(&self) -> bool;
+
+    fn golem_wakes(&self) -> bool;
+
+    fn enemy_flees(&self) -> bool;
+
+    fn rides_past(&self, chance: i32) -> bool;
+
+    fn mount_hit(&self) -> bool;
+
+    fn trembles(&self) -> bool;
+
+    fn sockets(&self) -> usize;
+
+    fn chest_trapped(&self) -> bool;
+
+    fn trap_detected(&self, skilled: bool) -> bool;
+
+    fn disarm_succeeds(&self, skilled: bool) -> bool;
+
     fn inflicted(&self, status: Option<(StatusEffect, u32)>) -> Option<StatusEffect>;
 
     fn gold_gained(&self, base: i32) -> i32;
@@ -58,22 +144,89 @@ pub fn random() -> TestRandomizer {
     TestRandomizer {}
 }
 
+/// How many levels above or below the expected one a spawned enemy can be.
+pub const ENEMY_LEVEL_VARIANCE: i32 = 4;
+
+/// The (numerator, denominator) chance of an enemy accepting a bribe.
+pub const BRIBE_ODDS: (u32, u32) = (1, 2);
+
+/// The (numerator, denominator) chance of an attack being critical.
+pub const CRITICAL_ODDS: (u32, u32) = (1, 20);
+
+/// The (numerator, denominator) chance of an enemy pursuing a hero that got
+/// away from it. Doubled for enemies faster than the hero.
+pub const PURSUIT_ODDS: (u32, u32) = (1, 3);
+
+/// How many times faster than an enemy the hero needs to be to catch it
+/// by surprise.
+pub const AMBUSH_SPEED_RATIO: i32 = 2;
+
+/// The (numerator, denominator) chance of a fast enough hero catching an
+/// enemy by surprise.
+pub const AMBUSH_ODDS: (u32, u32) = (1, 2);
+
+/// The (numerator, denominator) chance of pirates attacking during a voyage.
+pub const PIRATE_ODDS: (u32, u32) = (1, 3);
+
+/// The (numerator, denominator) chance of foraging drawing a nearby enemy,
+/// if there's one around at the usual odds of enemies showing up there.
+pub const FORAGE_AMBUSH_ODDS: (u32, u32) = (1, 8);
+
+/// The (numerator, denominator) chance of the pickaxe breaking on a swing.
+pub const PICKAXE_BREAK_ODDS: (u32, u32) = (1, 15);
+
+/// The (numerator, denominator) chance of mining waking a golem up.
+pub const GOLEM_ODDS: (u32, u32) = (1, 6);
+
+/// The (numerator, denominator) chance of a badly wounded coward enemy
+/// running away from the battle in its turn.
+pub const ENEMY_FLEE_ODDS: (u32, u32) = (1, 2);
+
+/// The (numerator, denominator) chance of an enemy blow landing on the
+/// hero's mount instead of the hero.
+pub const MOUNT_HIT_ODDS: (u32, u32) = (1, 5);
+
+/// The (numerator, denominator) chance of a shaken hero refusing to take
+/// a risk.
+pub const TREMBLE_ODDS: (u32, u32) = (1, 3);
+
+/// The (numerator, denominator) chance of each of the possible sockets
+/// being carved in a new sword or shield.
+pub const SOCKET_ODDS: (u32, u32) = (1, 3);
+
+/// The (numerator, denominator) chance of a chest hiding a trap.
+pub const TRAP_ODDS: (u32, u32) = (1, 5);
+
+/// The (numerator, denominator) chance of the hero noticing a trap before
+/// opening a chest. Heroes skilled with traps always do.
+pub const DETECT_ODDS: (u32, u32) = (1, 2);
+
+/// The (numerator, denominator) chance of disarming a detected trap, for
+/// heroes without and with the skill to do so.
+pub const DISARM_ODDS: (u32, u32) = (1, 3);
+pub const SKILLED_DISARM_ODDS: (u32, u32) = (4, 5);
+
+/// The (numerator, denominator) chance of an enemy appearing at the given distance.
+pub fn enemy_odds(distance: &location::Distance) -> (u32, u32) {
+    match distance {
+        location::Distance::Near(_) => (1, 3),
+        location::Distance::Mid(_) => (1, 2),
+        location::Distance::Far(_) => (2, 3),
+    }
+}
+
 pub struct DefaultRandomizer;
 
 impl Randomizer for DefaultRandomizer {
     fn should_enemy_appear(&self, distance: &location::Distance) -> bool {
-        let mut rng = rand::thread_rng();
-
-        match distance {
-            location::Distance::Near(_) => rng.gen_ratio(1, 3),
-            location::Distance::Mid(_) => rng.gen_ratio(1, 2),
-            location::Distance::Far(_) => rng.gen_ratio(2, 3),
-        }
+        let mut rng = rng();
+        let (numerator, denominator) = enemy_odds(distance);
+        rng.gen_ratio(numerator, denominator)
     }
 
     fn bribe_succeeds(&self) -> bool {
-        let mut rng = rand::thread_rng();
-        rng.gen_ratio(1, 2)
+        let mut rng = rng();
+        rng.gen_ratio(BRIBE_ODDS.0, BRIBE_ODDS.1)
     }
 
     fn run_away_succeeds(
@@ -82,25 +235,30 @@ impl Randomizer for DefaultRandomizer {
         enemy_level: i32,
         player_speed: i32,
         enemy_speed: i32,
+        bonus: i32,
     ) -> bool {
         let level_contrib = if player_level > enemy_level { 1 } else { 0 };
 
         let speed_contrib = if player_speed > enemy_speed { 2 } else { 0 };
 
-        let mut rng = rand::thread_rng();
-        rng.gen_ratio(1 + level_contrib + speed_contrib, 5)
+        let numerator = (1 + level_contrib + speed_contrib + bonus).clamp(1, 5);
+        let mut rng = rng();
+        rng.gen_ratio(numerator as u32, 5)
     }
 
     fn enemy_level(&self, level: i32) -> i32 {
-        let mut rng = rand::thread_rng();
-        max(1, level + rng.gen_range(-4..5))
+        let mut rng = rng();
+        max(
+            1,
+            level + rng.gen_range(-ENEMY_LEVEL_VARIANCE..=ENEMY_LEVEL_VARIANCE),
+        )
     }
 
     /// add +/- 20% variance to a the damage
     fn damage(&self, value: i32) -> i32 {
         let value = value as f64;
 
-        let mut rng = rand::thread_rng();
+        let mut rng = rng();
         let min_val = (value * 0.8).floor() as i32;
         let max_val = (value * 1.2).ceil() as i32;
         max(1, rng.gen_range(min_val..=max_val))
@@ -111,25 +269,129 @@ impl Randomizer for DefaultRandomizer {
         if receiver_speed > attacker_speed {
             let ratio = receiver_speed / attacker_speed;
             let ratio = max(1, 5 - ratio) as u32;
-            let mut rng = rand::thread_rng();
+            let mut rng = rng();
             return rng.gen_ratio(1, ratio);
         }
         false
     }
 
+    fn dodges(&self, chance: i32) -> bool {
+        let mut rng = rng();
+        rng.gen_range(0..100) < chance
+    }
+
+    fn parries(&self, chance: i32) -> bool {
+        let mut rng = rng();
+        rng.gen_range(0..100) < chance
+    }
+
+    fn is_weather_miss(&self, weather: Weather) -> bool {
+        let mut rng = rng();
+        weather
+            .miss_odds()
+            .is_some_and(|(numerator, denominator)| rng.gen_ratio(numerator, denominator))
+    }
+
     fn is_critical(&self) -> bool {
-        let mut rng = rand::thread_rng();
-        rng.gen_ratio(1, 20)
+        let mut rng = rng();
+        rng.gen_ratio(CRITICAL_ODDS.0, CRITICAL_ODDS.1)
     }
 
     fn counter_attack(&self) -> bool {
-        let mut rng = rand::thread_rng();
+        let mut rng = rng();
         rng.gen_ratio(1, 2)
     }
 
+    fn invasion_starts(&self) -> bool {
+        let mut rng = rng();
+        rng.gen_ratio(1, 100)
+    }
+
+    fn enemy_pursues(&self, player_speed: i32, enemy_speed: i32) -> bool {
+        let speed_contrib = if enemy_speed > player_speed { 2 } else { 1 };
+
+        let mut rng = rng();
+        rng.gen_ratio(PURSUIT_ODDS.0 * speed_contrib, PURSUIT_ODDS.1)
+    }
+
+    fn hero_ambushes(&self, player_speed: i32, enemy_speed: i32) -> bool {
+        if player_speed < enemy_speed * AMBUSH_SPEED_RATIO {
+            return false;
+        }
+        let mut rng = rng();
+        rng.gen_ratio(AMBUSH_ODDS.0, AMBUSH_ODDS.1)
+    }
+
+    fn pirates_attack(&self) -> bool {
+        let mut rng = rng();
+        rng.gen_ratio(PIRATE_ODDS.0, PIRATE_ODDS.1)
+    }
+
+    fn forage_ambushed(&self) -> bool {
+        let mut rng = rng();
+        rng.gen_ratio(FORAGE_AMBUSH_ODDS.0, FORAGE_AMBUSH_ODDS.1)
+    }
+
+    fn pickaxe_breaks(&self) -> bool {
+        let mut rng = rng();
+        rng.gen_ratio(PICKAXE_BREAK_ODDS.0, PICKAXE_BREAK_ODDS.1)
+    }
+
+    fn golem_wakes(&self) -> bool {
+        let mut rng = rng();
+        rng.gen_ratio(GOLEM_ODDS.0, GOLEM_ODDS.1)
+    }
+
+    fn enemy_flees(&self) -> bool {
+        let mut rng = rng();
+        rng.gen_ratio(ENEMY_FLEE_ODDS.0, ENEMY_FLEE_ODDS.1)
+    }
+
+    fn rides_past(&self, chance: i32) -> bool {
+        let mut rng = rng();
+        rng.gen_range(0..100) < chance
+    }
+
+    fn mount_hit(&self) -> bool {
+        let mut rng = rng();
+        rng.gen_ratio(MOUNT_HIT_ODDS.0, MOUNT_HIT_ODDS.1)
+    }
+
+    fn trembles(&self) -> bool {
+        let mut rng = rng();
+        rng.gen_ratio(TREMBLE_ODDS.0, TREMBLE_ODDS.1)
+    }
+
+    fn sockets(&self) -> usize {
+        let mut rng = rng();
+        (0..equipment::MAX_SOCKETS)
+            .filter(|_| rng.gen_ratio(SOCKET_ODDS.0, SOCKET_ODDS.1))
+            .count()
+    }
+
+    fn chest_trapped(&self) -> bool {
+        let mut rng = rng();
+        rng.gen_ratio(TRAP_ODDS.0, TRAP_ODDS.1)
+    }
+
+    fn trap_detected(&self, skilled: bool) -> bool {
+        let mut rng = rng();
+        skilled || rng.gen_ratio(DETECT_ODDS.0, DETECT_ODDS.1)
+    }
+
+    fn disarm_succeeds(&self, skilled: bool) -> bool {
+        let (numerator, denominator) = if skilled {
+            SKILLED_DISARM_ODDS
+        } else {
+            DISARM_ODDS
+        };
+        let mut rng = rng();
+        rng.gen_ratio(numerator, denominator)
+    }
+
     fn inflicted(&self, status: Option<(StatusEffect, u32)>) -> Option<StatusEffect> {
         if let Some((status, ratio)) = status {
-            let mut rng = rand::thread_rng();
+            let mut rng = rng();
             if rng.gen_ratio(1, ratio) {
                 return Some(status);
             }
@@ -138,7 +400,7 @@ impl Randomizer for DefaultRandomizer {
     }
 
     fn gold_gained(&self, base: i32) -> i32 {
-        let mut rng = rand::thread_rng();
+        let mut rng = rng();
         let min = (base as f64 * 0.6) as i32;
         let max = (base as f64 * 1.3) as i32;
         rng.gen_range(min..=max)
@@ -148,17 +410,17 @@ impl Randomizer for DefaultRandomizer {
         let min_value = max(1, increase / 2);
         let max_value = 3 * increase / 2;
 
-        let mut rng = rand::thread_rng();
+        let mut rng = rng();
         rng.gen_range(min_value..=max_value)
     }
 
     fn range(&self, max: i32) -> i32 {
-        let mut rng = rand::thread_rng();
+        let mut rng = rng();
         rng.gen_range(0..max)
     }
 
     fn gold_chest(&self, distance: &location::Distance) -> bool {
-        let mut rng = rand::thread_rng();
+        let mut rng = rng();
 
         match distance {
             location::Distance::Near(_) => rng.gen_ratio(6, 30),
@@ -168,7 +430,7 @@ impl Randomizer for DefaultRandomizer {
     }
 
     fn equipment_chest(&self, distance: &location::Distance) -> bool {
-        let mut rng = rand::thread_rng();
+        let mut rng = rng();
 
         match distance {
             location::Distance::Near(_) => rng.gen_ratio(1, 30),
@@ -178,7 +440,7 @@ impl Randomizer for DefaultRandomizer {
     }
 
     fn ring_chest(&self, distance: &location::Distance) -> bool {
-        let mut rng = rand::thread_rng();
+        let mut rng = rng();
 
         match distance {
             location::Distance::Near(_) => false,
@@ -188,7 +450,7 @@ impl Randomizer for DefaultRandomizer {
     }
 
     fn item_chest(&self, distance: &location::Distance) -> bool {
-        let mut rng = rand::thread_rng();
+        let mut rng = rng();
 
         match distance {
             location::Distance::Near(_) => rng.gen_ratio(1, 50),
@@ -217,6 +479,7 @@ impl Randomizer for TestRandomizer {
         _enemy_level: i32,
         _player_speed: i32,
         _enemy_speed: i32,
+        _bonus: i32,
     ) -> bool {
         false
     }
@@ -233,6 +496,18 @@ impl Randomizer for TestRandomizer {
         false
     }
 
+    fn is_weather_miss(&self, _weather: Weather) -> bool {
+        false
+    }
+
+    fn dodges(&self, _chance: i32) -> bool {
+        false
+    }
+
+    fn parries(&self, _chance: i32) -> bool {
+        false
+    }
+
     fn is_critical(&self) -> bool {
         false
     }
@@ -241,6 +516,66 @@ impl Randomizer for TestRandomizer {
         true
     }
 
+    fn invasion_starts(&self) -> bool {
+        false
+    }
+
+    fn enemy_pursues(&self, _player_speed: i32, _enemy_speed: i32) -> bool {
+        false
+    }
+
+    fn hero_ambushes(&self, _player_speed: i32, _enemy_speed: i32) -> bool {
+        false
+    }
+
+    fn pirates_attack(&self) -> bool {
+        false
+    }
+
+    fn forage_ambushed(&self) -> bool {
+        false
+    }
+
+    fn pickaxe_breaks(&self) -> bool {
+        false
+    }
+
+    fn golem_wakes(&self) -> bool {
+        false
+    }
+
+    fn enemy_flees(&self) -> bool {
+        true
+    }
+
+    fn rides_past(&self, _chance: i32) -> bool {
+        true
+    }
+
+    fn mount_hit(&self) -> bool {
+        false
+    }
+
+    fn trembles(&self) -> bool {
+        true
+    }
+
+    fn sockets(&self) -> usize {
+        equipment::MAX_SOCKETS
+    }
+
+    fn chest_trapped(&self) -> bool {
+        false
+    }
+
+    fn trap_detected(&self, _skilled: bool) -> bool {
+        true
+    }
+
+    fn disarm_succeeds(&self, skilled: bool) -> bool {
+        skilled
+    }
+
     fn inflicted(&self, _status: Option<(StatusEffect, u32)>) -> Option<StatusEffect> {
         None
     }
@@ -0,0 +1,174 @@
+use crate::character::behavior::{self, Action};
+use crate::character::Character;
+use crate::game::Game;
+use crate::log;
+use crate::randomizer;
+use serde::Serialize;
+
+/// How many battles are simulated to forecast the outcome of a fight.
+pub const FORECAST_BATTLES: u64 = 300;
+
+/// Rounds after which a simulated battle is called off as a draw.
+pub const MAX_ROUNDS: i32 = 100;
+
+/// How a simulated battle ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Ending {
+    Won,
+    Lost,
+    /// The enemy ran away, or the battle dragged on for too long.
+    Draw,
+}
+
+/// How a simulated battle ended, after how many rounds, and the experience
+/// the hero earned with its attacks.
+#[derive(Debug, Clone, Copy)]
+pub struct Outcome {
+    pub ending: Ending,
+    pub rounds: i32,
+    pub xp: i32,
+}
+
+/// The expected outcome of fighting an enemy, out of many simulated
+/// battles.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Forecast {
+    /// Percentage of the battles won.
+    pub win_chance: i32,
+    /// Experience and gold earned on average, counting the lost battles
+    /// as earning nothing.
+    pub xp: i32,
+    pub gold: i32,
+    /// How many rounds the battles last on average.
+    pub rounds: i32,
+}
+
+/// Fight a headless battle between copies of the given characters, without
+/// items, skills or surprises, until one of them falls. The hero's allies
+/// join the fight but the enemy only strikes the hero.
+pub fn battle(hero: &Character, allies: &[Character], enemy: &Character) -> Outcome {
+    let mut hero = hero.clone();
+    let mut allies = allies.to_vec();
+    let mut enemy = enemy.clone();
+    let mut xp = 0;
+
+    let outcome = |ending, rounds, xp| Outcome { ending, rounds, xp };
+    log::mute(|| {
+        for round in 1..=MAX_ROUNDS {
+            let (gained, died) = hero.attack(&mut enemy);
+            xp += gained;
+            if died.is_err() {
+                return outcome(Ending::Won, round, xp);
+            }
+            for ally in allies.iter_mut() {
+                if let (_, Err(_)) = ally.attack(&mut enemy) {
+                    return outcome(Ending::Won, round, xp);
+                }
+            }
+
+            let action = enemy.class.behavior.decide(&enemy);
+            enemy.defending = false;
+            behavior::act(&mut enemy, action);
+            if action == Action::Flee {
+                return outcome(Ending::Draw, round, 0);
+            }
+            if action.attacks() {
+                if let (_, Err(_)) = enemy.attack(&mut hero) {
+                    return outcome(Ending::Lost, round, 0);
+                }
+            }
+
+            if hero.apply_status_effects().is_err() {
+                return outcome(Ending::Lost, round, 0);
+            }
+            if enemy.apply_status_effects().is_err() {
+                return outcome(Ending::Won, round, xp);
+            }
+            hero.wear_off_boost();
+            enemy.wear_off_boost();
+        }
+        outcome(Ending::Draw, MAX_ROUNDS, 0)
+    })
+}
+
+/// Forecast fighting the given enemy by simulating many seeded battles,
+/// so the same enemy always gets the same forecast. The game's own random
+/// sequence is left untouched.
+pub fn forecast(game: &Game, enemy: &Character) -> Forecast {
+    let allies: Vec<Character> = game
+        .party
+        .iter()
+        .chain(game.mercenary.iter())
+        .cloned()
+        .collect();
+
+    let mut won = 0;
+    let mut xp = 0;
+    let mut gold = 0;
+    let mut rounds = 0;
+    for seed in 0..FORECAST_BATTLES {
+        randomizer::seeded(seed, || {
+            let outcome = battle(&game.player, &allies, enemy);
+            rounds += outcome.rounds;
+            if outcome.ending == Ending::Won {
+                won += 1;
+                xp += outcome.xp;
+                gold += game.player.gold_gained(enemy.level);
+            }
+        });
+    }
+
+    let battles = FORECAST_BATTLES as i32;
+    Forecast {
+        win_chance: won * 100 / battles,
+        xp: xp / battles,
+        gold: gold / battles,
+        rounds: rounds / battles,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::class::Class;
+
+    #[test]
+    fn test_battle() {
+        let warrior = Class::player_by_name("warrior").unwrap().clone();
+        let hero = Character::new(warrior.clone(), 10);
+        let weak = Character::new(warrior.clone(), 1);
+        let strong = Character::new(warrior, 30);
+
+        let outcome = battle(&hero, &[], &weak);
+        assert_eq!(Ending::Won, outcome.ending);
+        assert!(outcome.xp > 0);
+        let outcome = battle(&hero, &[], &strong);
+        assert_eq!(Ending::Lost, outcome.ending);
+        assert_eq!(0, outcome.xp);
+
+        // allies tip the balance
+        let allies: Vec<Character> = (0..5).map(|_| hero.clone()).collect();
+        let outcome = battle(&hero, &allies, &strong);
+        assert_eq!(Ending::Won, outcome.ending);
+    }
+
+    #[test]
+    fn test_forecast() {
+        let mut game = Game::new();
+        let goblin = Class::enemy_by_name("goblin").unwrap().clone();
+        let enemy = Character::new(goblin, 1);
+        let hp = game.player.current_hp;
+
+        let odds = forecast(&game, &enemy);
+        assert_eq!(100, odds.win_chance);
+        assert!(odds.xp > 0);
+        assert!(odds.gold > 0);
+        // the simulated battles don't touch the real characters
+        assert_eq!(hp, game.player.current_hp);
+        assert_eq!(enemy.max_hp(), enemy.current_hp);
+
+        game.player.current_hp = 1;
+        let enemy = Character::new(Class::enemy_by_name("troll").unwrap().clone(), 20);
+        assert_eq!(0, forecast(&game, &enemy).win_chance);
+    }
+}
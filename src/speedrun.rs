@@ -0,0 +1,105 @@
+use crate::config;
+use crate::naming;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// Milestones timed during a speedrun.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Split {
+    Level10,
+    LegendaryKill,
+    Gorthaur,
+    BossRush,
+}
+
+impl fmt::Display for Split {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Split::Level10 => "first level 10",
+            Split::LegendaryKill => "first legendary kill",
+            Split::Gorthaur => "gorthaur defeated",
+            Split::BossRush => "boss rush cleared",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A timed run, started when speedrun mode is enabled. Each split records
+/// the seconds elapsed since the start. The run keeps a checksum of its
+/// data to spot accidental edits to the save file. It's not keyed, so it
+/// doesn't prevent deliberate ones.
+#[derive(Serialize, Deserialize)]
+pub struct Speedrun {
+    started_at: u64,
+    splits: Vec<(Split, u64)>,
+    #[serde(alias = "signature")]
+    checksum: u64,
+}
+
+impl Speedrun {
+    pub fn start() -> Self {
+        let mut run = Self {
+            started_at: config::now(),
+            splits: Vec::new(),
+            checksum: 0,
+        };
+        run.checksum = run.compute_checksum();
+        run
+    }
+
+    /// Record the split if it wasn't reached before, returning the
+    /// elapsed time.
+    pub fn split(&mut self, split: Split) -> Option<u64> {
+        if self.splits.iter().any(|(s, _)| *s == split) {
+            return None;
+        }
+        let elapsed = self.elapsed();
+        self.splits.push((split, elapsed));
+        self.checksum = self.compute_checksum();
+        Some(elapsed)
+    }
+
+    pub fn splits(&self) -> &[(Split, u64)] {
+        &self.splits
+    }
+
+    pub fn elapsed(&self) -> u64 {
+        config::now().saturating_sub(self.started_at)
+    }
+
+    /// Whether the run data matches its checksum.
+    pub fn is_valid(&self) -> bool {
+        self.checksum == self.compute_checksum()
+    }
+
+    pub fn checksum(&self) -> u64 {
+        self.checksum
+    }
+
+    fn compute_checksum(&self) -> u64 {
+        let mut data = self.started_at.to_string();
+        for (split, elapsed) in &self.splits {
+            data.push_str(&format!("|{:?}:{}", split, elapsed));
+        }
+        naming::fnv1a(data.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_splits() {
+        let mut run = Speedrun::start();
+        assert!(run.is_valid());
+
+        assert!(run.split(Split::Level10).is_some());
+        assert!(run.split(Split::Level10).is_none());
+        assert_eq!(1, run.splits().len());
+        assert!(run.is_valid());
+
+        run.splits[0].1 += 1;
+        assert!(!run.is_valid());
+    }
+}
@@ -0,0 +1,266 @@
+use crate::character::class::Class;
+use crate::journal::Journal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Lifetime statistics of all the heroes, kept across deaths.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Stats {
+    pub battles_won: i32,
+    pub deaths: i32,
+    pub gold_earned: i32,
+    pub chests_found: i32,
+    pub highest_level: i32,
+    pub deepest_distance: i32,
+    pub skills_used: i32,
+    pub bribes: i32,
+    pub best_arena_wave: i32,
+
+    /// Fewest battle rounds taken to clear a boss rush, zero if never cleared.
+    pub best_boss_rush: i32,
+
+    /// How many times each enemy class was defeated.
+    pub bestiary: BTreeMap<String, i32>,
+
+    /// Progress towards mastering each player class.
+    pub mastery: BTreeMap<String, Mastery>,
+
+    /// The heroes that defeated gorthaur, in order.
+    pub hall_of_fame: Vec<Champion>,
+
+    /// Activity at each visited directory, by path.
+    pub places: BTreeMap<String, Place>,
+}
+
+/// What happened at a directory over all the heroes' lifetimes.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Place {
+    pub visits: i32,
+    pub battles: i32,
+    pub deaths: i32,
+}
+
+/// A hero that won the game by defeating gorthaur.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Champion {
+    pub name: String,
+    pub class: String,
+    pub level: i32,
+
+    /// Heroes fallen before this one won.
+    pub deaths: i32,
+
+    /// The new game+ cycle it won at, zero for the first playthrough.
+    pub cycle: i32,
+}
+
+/// Levels gained playing a class needed to master it.
+pub const MASTERY_LEVELS: i32 = 20;
+
+/// Bosses beaten playing a class needed to master it.
+pub const MASTERY_BOSSES: i32 = 1;
+
+/// Milestones reached by the heroes while playing a class.
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct Mastery {
+    pub levels: i32,
+    pub bosses: i32,
+}
+
+impl Mastery {
+    pub fn is_complete(&self) -> bool {
+        self.levels >= MASTERY_LEVELS && self.bosses >= MASTERY_BOSSES
+    }
+}
+
+impl Stats {
+    pub fn battle_won(&mut self, enemy: &str, gold: i32) {
+        self.battles_won += 1;
+        self.gold_earned += gold;
+        *self.bestiary.entry(enemy.to_string()).or_default() += 1;
+    }
+
+    pub fn level_reached(&mut self, level: i32) {
+        self.highest_level = std::cmp::max(self.highest_level, level);
+    }
+
+    /// Record the levels gained and bosses beaten playing the given class.
+    /// Returns whether that made the class mastered.
+    pub fn class_played(&mut self, class: &str, levels: i32, bosses: i32) -> bool {
+        let mastery = self.mastery.entry(class.to_string()).or_default();
+        let already = mastery.is_complete();
+        mastery.levels += levels;
+        mastery.bosses += bosses;
+        !already && mastery.is_complete()
+    }
+
+    pub fn mastered(&self, class: &str) -> bool {
+        self.mastery.get(class).is_some_and(Mastery::is_complete)
+    }
+
+    /// Whether the given class can be played: base classes always can,
+    /// advanced ones once their base class is mastered.
+    pub fn unlocks(&self, class: &Class) -> bool {
+        class
+            .requires
+            .as_ref()
+            .is_none_or(|base| self.mastered(base))
+    }
+
+    pub fn arena_wave_reached(&mut self, wave: i32) {
+        self.best_arena_wave = std::cmp::max(self.best_arena_wave, wave);
+    }
+
+    pub fn boss_rush_cleared(&mut self, rounds: i32) {
+        if self.best_boss_rush == 0 || rounds < self.best_boss_rush {
+            self.best_boss_rush = rounds;
+        }
+    }
+
+    pub fn distance_reached(&mut self, distance: i32) {
+        self.deepest_distance = std::cmp::max(self.deepest_distance, distance);
+    }
+
+    pub fn place_visited(&mut self, path: &str) {
+        self.places.entry(path.to_string()).or_default().visits += 1;
+    }
+
+    pub fn place_fought(&mut self, path: &str) {
+        self.places.entry(path.to_string()).or_default().battles += 1;
+    }
+
+    pub fn place_died(&mut self, path: &str) {
+        self.places.entry(path.to_string()).or_default().deaths += 1;
+    }
+
+    /// The places with the highest non-zero value of the given counter,
+    /// up to `limit` of them, hottest first.
+    pub fn hottest(&self, counter: fn(&Place) -> i32, limit: usize) -> Vec<(&str, i32)> {
+        let mut places: Vec<_> = self
+            .places
+            .iter()
+            .map(|(path, place)| (path.as_str(), counter(place)))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        // stable sort keeps ties in path order
+        places.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        places.truncate(limit);
+        places
+    }
+
+    /// The statistics as (name, value) pairs, in display order.
+    pub fn summary(&self) -> Vec<(&'static str, i32)> {
+        vec![
+            ("battles won", self.battles_won),
+            ("deaths", self.deaths),
+            ("gold earned", self.gold_earned),
+            ("chests found", self.chests_found),
+            ("highest level", self.highest_level),
+            ("deepest distance", self.deepest_distance),
+            ("skills used", self.skills_used),
+            ("bribes paid", self.bribes),
+            ("best arena wave", self.best_arena_wave),
+            ("best boss rush rounds", self.best_boss_rush),
+        ]
+    }
+}
+
+/// Everything included in a stats export.
+#[derive(Serialize)]
+struct Export<'a> {
+    stats: &'a Stats,
+    journal: Vec<crate::journal::Record>,
+}
+
+pub fn to_json(stats: &Stats, journal: &Journal) -> String {
+    let export = Export {
+        stats,
+        journal: journal.records(),
+    };
+    serde_json::to_string_pretty(&export).unwrap()
+}
+
+/// Export as `section,name,value` rows: one per statistic, one per
+/// bestiary entry and one per journal entry, named after its timestamp.
+pub fn to_csv(stats: &Stats, journal: &Journal) -> String {
+    let mut rows = vec![String::from("section,name,value")];
+    for (name, value) in stats.summary() {
+        rows.push(format!("stats,{},{}", name, value));
+    }
+    for (enemy, count) in &stats.bestiary {
+        rows.push(format!("bestiary,{},{}", csv_field(enemy), count));
+    }
+    for record in journal.records() {
+        rows.push(format!(
+            "journal,{},{}",
+            record.time,
+            csv_field(&record.to_string())
+        ));
+    }
+    rows.join("\n") + "\n"
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv() {
+        let mut stats = Stats::default();
+        stats.battle_won("goblin", 10);
+        stats.battle_won("goblin", 20);
+        stats.battle_won("orc, the brave", 5);
+
+        let csv = to_csv(&stats, &Journal::default());
+        assert!(csv.starts_with("section,name,value\nstats,battles won,3\n"));
+        assert!(csv.contains("stats,gold earned,35\n"));
+        assert!(csv.contains("bestiary,goblin,2\n"));
+        assert!(csv.contains("bestiary,\"orc, the brave\",1\n"));
+    }
+
+    #[test]
+    fn test_mastery() {
+        let mut stats = Stats::default();
+        let paladin = Class::player_by_name("paladin").unwrap();
+        let warrior = Class::player_by_name("warrior").unwrap();
+        assert!(stats.unlocks(warrior));
+        assert!(!stats.unlocks(paladin));
+
+        assert!(!stats.class_played("warrior", MASTERY_LEVELS, 0));
+        assert!(!stats.class_played("thief", 0, MASTERY_BOSSES));
+        assert!(!stats.unlocks(paladin));
+
+        assert!(stats.class_played("warrior", 0, MASTERY_BOSSES));
+        assert!(stats.unlocks(paladin));
+        assert!(!stats.unlocks(Class::player_by_name("assassin").unwrap()));
+
+        // only reported the first time
+        assert!(!stats.class_played("warrior", 1, 1));
+    }
+
+    #[test]
+    fn test_hottest() {
+        let mut stats = Stats::default();
+        stats.place_visited("~/a");
+        stats.place_visited("~/b");
+        stats.place_visited("~/b");
+        stats.place_visited("~/c");
+        stats.place_died("~/c");
+
+        let visits = stats.hottest(|place| place.visits, 2);
+        assert_eq!(vec![("~/b", 2), ("~/a", 1)], visits);
+        let deaths = stats.hottest(|place| place.deaths, 5);
+        assert_eq!(vec![("~/c", 1)], deaths);
+    }
+}
@@ -0,0 +1,208 @@
+use crate::character::Buff;
+use crate::location::Location;
+use crate::randomizer;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::fs;
+
+/// How much slower, as a percentage, the hero is in a swamp.
+pub const SWAMP_SPEED_PENALTY: i32 = 25;
+
+/// How many levels above the expected one enemies guarding a fortress are.
+pub const FORTRESS_LEVEL_BONUS: i32 = 2;
+
+/// Extra gold, as a percentage, found in fortresses.
+pub const FORTRESS_GOLD_BONUS: i32 = 50;
+
+/// The lowest level of the enemies and loot found in elite zones.
+pub const ELITE_LEVEL_FLOOR: i32 = 20;
+
+/// How far, as a percentage, anomalies can shift the hero stats either way.
+pub const ANOMALY_STAT_SHIFT: i32 = 50;
+
+/// How many levels above the hero the enemies of an anomaly can be.
+pub const ANOMALY_LEVEL_SPREAD: i32 = 10;
+
+/// The furthest distance from home whose treasure can show up in an anomaly.
+pub const ANOMALY_MAX_DISTANCE: i32 = 30;
+
+thread_local! {
+    /// The terrain at the hero's location, read when computing its speed.
+    static CURRENT: Cell<Terrain> = const { Cell::new(Terrain::Plain) };
+}
+
+/// The kind of land a directory is, derived from its permissions.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Terrain {
+    Plain,
+    /// World-writable directories, where the hero is slowed down.
+    Swamp,
+    /// Read-only directories, guarded by tougher enemies but with richer
+    /// rewards.
+    Fortress,
+    /// Sticky-bit directories, where enemies don't show up.
+    Sanctuary,
+    /// Temporary directories, where enemies, treasure and the hero stats
+    /// are scrambled on every visit.
+    Anomaly,
+}
+
+impl Terrain {
+    /// The terrain of the given location, plain if its metadata can't be read.
+    pub fn of(location: &Location) -> Self {
+        if is_anomaly(location) {
+            return Terrain::Anomaly;
+        }
+        fs::metadata(location.path())
+            .map_or(Terrain::Plain, |metadata| Self::from_metadata(&metadata))
+    }
+
+    #[cfg(unix)]
+    fn from_metadata(metadata: &fs::Metadata) -> Self {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = metadata.permissions().mode();
+        if mode & 0o1000 != 0 {
+            Terrain::Sanctuary
+        } else if mode & 0o002 != 0 {
+            Terrain::Swamp
+        } else if metadata.permissions().readonly() {
+            Terrain::Fortress
+        } else {
+            Terrain::Plain
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn from_metadata(metadata: &fs::Metadata) -> Self {
+        if metadata.permissions().readonly() {
+            Terrain::Fortress
+        } else {
+            Terrain::Plain
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Terrain::Plain => "plain",
+            Terrain::Swamp => "swamp",
+            Terrain::Fortress => "fortress",
+            Terrain::Sanctuary => "sanctuary",
+            Terrain::Anomaly => "anomaly",
+        }
+    }
+
+    /// Levels added to the enemies found here.
+    pub fn level_bonus(&self) -> i32 {
+        if *self == Terrain::Fortress {
+            FORTRESS_LEVEL_BONUS
+        } else {
+            0
+        }
+    }
+
+    /// The given gold reward, increased if found in this terrain.
+    pub fn gold(&self, gold: i32) -> i32 {
+        if *self == Terrain::Fortress {
+            gold + gold * FORTRESS_GOLD_BONUS / 100
+        } else {
+            gold
+        }
+    }
+
+    /// The given hero speed, reduced if moving across this terrain.
+    pub fn speed(&self, speed: i32) -> i32 {
+        if *self == Terrain::Swamp {
+            std::cmp::max(1, speed - speed * SWAMP_SPEED_PENALTY / 100)
+        } else {
+            speed
+        }
+    }
+}
+
+/// Whether the location is the temporary directory or a tmpfs mount, or
+/// inside of them. Home is never an anomaly, even if it's under one.
+fn is_anomaly(location: &Location) -> bool {
+    if location.is_within(&Location::home()) {
+        return false;
+    }
+    let temp_dir = std::env::temp_dir();
+    location
+        .path()
+        .starts_with(dunce::canonicalize(&temp_dir).unwrap_or(temp_dir))
+        || location.in_tmpfs()
+}
+
+/// A random shift of the hero stats, for entering an anomaly.
+pub fn anomaly_buff() -> Buff {
+    let mut rng = randomizer::rng();
+    Buff {
+        attack: rng.gen_range(-ANOMALY_STAT_SHIFT..=ANOMALY_STAT_SHIFT),
+        speed: rng.gen_range(-ANOMALY_STAT_SHIFT..=ANOMALY_STAT_SHIFT),
+    }
+}
+
+/// Whether the location is a system directory owned by root, outside of
+/// home, where elite enemies and loot are found regardless of the distance.
+pub fn is_elite_zone(location: &Location) -> bool {
+    !location.is_within(&Location::home()) && owned_by_root(location)
+}
+
+#[cfg(unix)]
+fn owned_by_root(location: &Location) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(location.path()).is_ok_and(|metadata| metadata.uid() == 0)
+}
+
+#[cfg(not(unix))]
+fn owned_by_root(_location: &Location) -> bool {
+    false
+}
+
+/// The terrain the hero is currently at.
+pub fn current() -> Terrain {
+    CURRENT.with(Cell::get)
+}
+
+/// Update the current terrain after the hero moves to the given location.
+pub fn enter(location: &Location) {
+    let terrain = Terrain::of(location);
+    CURRENT.with(|current| current.set(terrain));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_terrain() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join("rpg-test-terrain");
+        let terrain = |name: &str, mode: u32| {
+            let path = root.join(name);
+            fs::create_dir_all(&path).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+            Terrain::from_metadata(&fs::metadata(path).unwrap())
+        };
+
+        assert_eq!(Terrain::Plain, terrain("plain", 0o755));
+        assert_eq!(Terrain::Swamp, terrain("swamp", 0o777));
+        assert_eq!(Terrain::Fortress, terrain("fortress", 0o555));
+        assert_eq!(Terrain::Sanctuary, terrain("sanctuary", 0o1777));
+
+        // the temporary directories themselves are anomalies
+        let plain = Location::from(root.join("plain").to_str().unwrap()).unwrap();
+        assert_eq!(Terrain::Anomaly, Terrain::of(&plain));
+
+        // the filesystem root belongs to root on any unix host
+        assert!(is_elite_zone(&Location::from("/").unwrap()));
+        assert!(!is_elite_zone(&Location::home()));
+
+        assert_eq!(9, Terrain::Swamp.speed(12));
+        assert_eq!(150, Terrain::Fortress.gold(100));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::error::{bail, Result};
+use crate::game::Game;
+use crate::log;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Battles to win without ever paying a bribe to be unbribable.
+const UNBRIBABLE_BATTLES: i32 = 100;
+
+/// Distance from home reached by wanderers.
+const WANDERER_DISTANCE: i32 = 20;
+
+/// Lifetime gold earned by the wealthy.
+const WEALTHY_GOLD: i32 = 100_000;
+
+/// Chests found by treasure hunters.
+const TREASURE_HUNTER_CHESTS: i32 = 50;
+
+/// Battles won by exterminators.
+const EXTERMINATOR_BATTLES: i32 = 1000;
+
+/// An epithet earned by the heroes for an achievement or boss kill.
+/// Earned titles are kept across deaths, and one of them can be chosen
+/// to be shown next to the hero.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum Title {
+    Unbribable,
+    Wanderer,
+    Wealthy,
+    TreasureHunter,
+    Exterminator,
+    ShadowSlayer,
+    DevSlayer,
+    RingBreaker,
+}
+
+impl Title {
+    /// The name used to choose the title with `rpg title`.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Title::Unbribable => "unbribable",
+            Title::Wanderer => "wanderer",
+            Title::Wealthy => "wealthy",
+            Title::TreasureHunter => "treasure-hunter",
+            Title::Exterminator => "exterminator",
+            Title::ShadowSlayer => "shadow-slayer",
+            Title::DevSlayer => "dev-slayer",
+            Title::RingBreaker => "ring-breaker",
+        }
+    }
+
+    pub fn from(id: &str) -> Result<Self> {
+        let id = id.to_lowercase();
+        if let Some(title) = Self::iter().find(|title| title.id() == id) {
+            Ok(title)
+        } else {
+            bail!("Unknown title {}.", id)
+        }
+    }
+
+    /// The epithet shown next to the hero.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Title::Unbribable => "the Unbribable",
+            Title::Wanderer => "the Wanderer",
+            Title::Wealthy => "the Wealthy",
+            Title::TreasureHunter => "Treasure Hunter",
+            Title::Exterminator => "the Exterminator",
+            Title::ShadowSlayer => "Slayer of Shadows",
+            Title::DevSlayer => "Bane of the Dev",
+            Title::RingBreaker => "Breaker of the Ring",
+        }
+    }
+
+    /// What it takes to earn the title.
+    pub fn describe(&self) -> String {
+        match self {
+            Title::Unbribable => format!(
+                "win {} battles without ever paying a bribe",
+                UNBRIBABLE_BATTLES
+            ),
+            Title::Wanderer => format!("reach {} directories away from home", WANDERER_DISTANCE),
+            Title::Wealthy => format!("earn {} gold", WEALTHY_GOLD),
+            Title::TreasureHunter => format!("find {} chests", TREASURE_HUNTER_CHESTS),
+            Title::Exterminator => format!("win {} battles", EXTERMINATOR_BATTLES),
+            Title::ShadowSlayer => String::from("defeat the shadow"),
+            Title::DevSlayer => String::from("defeat the dev"),
+            Title::RingBreaker => String::from("defeat gorthaur"),
+        }
+    }
+
+    fn is_earned(&self, game: &Game) -> bool {
+        let stats = &game.stats;
+        let defeated = |enemy: &str| stats.bestiary.contains_key(enemy);
+        match self {
+            Title::Unbribable => stats.battles_won >= UNBRIBABLE_BATTLES && stats.bribes == 0,
+            Title::Wanderer => stats.deepest_distance >= WANDERER_DISTANCE,
+            Title::Wealthy => stats.gold_earned >= WEALTHY_GOLD,
+            Title::TreasureHunter => stats.chests_found >= TREASURE_HUNTER_CHESTS,
+            Title::Exterminator => stats.battles_won >= EXTERMINATOR_BATTLES,
+            Title::ShadowSlayer => defeated("shadow"),
+            Title::DevSlayer => defeated("dev"),
+            Title::RingBreaker => defeated("gorthaur"),
+        }
+    }
+}
+
+/// Grant the titles newly earned by the heroes' achievements. The first
+/// one becomes the active title if there's none.
+pub fn award(game: &mut Game) {
+    for title in Title::iter() {
+        if !game.titles.contains(&title) && title.is_earned(game) {
+            game.titles.push(title);
+            game.title.get_or_insert(title);
+            log::title_earned(title);
+        }
+    }
+}
+
+/// Choose the title shown next to the hero, among the earned ones.
+/// "none" hides it.
+pub fn choose(game: &mut Game, id: &str) -> Result<()> {
+    if id == "none" {
+        game.title = None;
+        return Ok(());
+    }
+    let title = Title::from(id)?;
+    if !game.titles.contains(&title) {
+        bail!("The title {} wasn't earned yet.", title.id());
+    }
+    game.title = Some(title);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_titles() {
+        let mut game = Game::new();
+        award(&mut game);
+        assert!(game.titles.is_empty());
+        assert!(choose(&mut game, "wanderer").is_err());
+
+        game.stats.battles_won = UNBRIBABLE_BATTLES;
+        game.stats.bestiary.insert(String::from("shadow"), 1);
+        award(&mut game);
+        assert_eq!(vec![Title::Unbribable, Title::ShadowSlayer], game.titles);
+        assert_eq!(Some(Title::Unbribable), game.title);
+
+        choose(&mut game, "shadow-slayer").unwrap();
+        assert_eq!(Some(Title::ShadowSlayer), game.title);
+        choose(&mut game, "none").unwrap();
+        assert_eq!(None, game.title);
+
+        // a bribe doesn't take back an earned title
+        game.stats.bribes = 1;
+        award(&mut game);
+        assert_eq!(2, game.titles.len());
+        assert!(!Title::Unbribable.is_earned(&game));
+    }
+}
@@ -0,0 +1,129 @@
+use crate::config;
+use crate::game::Game;
+use crate::randomizer;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+
+/// For how long, in seconds, the weather holds before changing.
+const WEATHER_DURATION: u64 = 60 * 60;
+
+/// How many levels above the expected one enemies empowered by the weather are.
+pub const EMPOWERED_LEVEL_BONUS: i32 = 3;
+
+/// The (numerator, denominator) chance of an enemy belonging to the family
+/// favored by the weather.
+pub const FAMILY_ODDS: (u32, u32) = (1, 3);
+
+thread_local! {
+    /// The weather of the current session, read when resolving attacks.
+    static CURRENT: Cell<Weather> = const { Cell::new(Weather::Clear) };
+}
+
+/// The weather across the whole filesystem for a while. It affects how
+/// attacks land, which enemies show up and how easy it is to run away.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain,
+    Fog,
+    Storm,
+}
+
+impl Weather {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Weather::Clear => "clear",
+            Weather::Rain => "rain",
+            Weather::Fog => "fog",
+            Weather::Storm => "storm",
+        }
+    }
+
+    /// The (numerator, denominator) chance of any attack missing because
+    /// of the weather, on top of the regular dodges.
+    pub fn miss_odds(&self) -> Option<(u32, u32)> {
+        match self {
+            Weather::Clear => None,
+            Weather::Rain | Weather::Storm => Some((1, 10)),
+            Weather::Fog => Some((1, 5)),
+        }
+    }
+
+    /// Added to the chance of fleeing, out of 5: fog covers the escape and
+    /// storms get in the way.
+    pub fn flee_bonus(&self) -> i32 {
+        match self {
+            Weather::Fog => 1,
+            Weather::Storm => -1,
+            _ => 0,
+        }
+    }
+
+    /// The enemy family that thrives in this weather, if any.
+    pub fn family(&self) -> Option<&'static str> {
+        match self {
+            Weather::Clear => None,
+            Weather::Rain => Some("snake"),
+            Weather::Fog => Some("skeleton"),
+            Weather::Storm => Some("storm"),
+        }
+    }
+
+    /// Whether enemies of the given family are stronger in this weather.
+    pub fn empowers(&self, family: &str) -> bool {
+        *self == Weather::Storm && self.family() == Some(family)
+    }
+
+    /// Whether the weather hides the bosses ahead from the danger assessment.
+    pub fn hides_bosses(&self) -> bool {
+        *self == Weather::Fog
+    }
+}
+
+/// The weather of the current session.
+pub fn current() -> Weather {
+    CURRENT.with(Cell::get)
+}
+
+/// Roll a new weather once the current one expires, clear skies being
+/// the most common. Returns whether it changed.
+pub fn update(game: &mut Game) -> bool {
+    let changed = game.weather_until <= config::now();
+    if changed {
+        let options = [
+            Weather::Clear,
+            Weather::Clear,
+            Weather::Rain,
+            Weather::Fog,
+            Weather::Storm,
+        ];
+        game.weather = *options.choose(&mut randomizer::rng()).unwrap();
+        game.weather_until = config::now() + WEATHER_DURATION;
+    }
+    CURRENT.with(|current| current.set(game.weather));
+    changed && game.weather != Weather::Clear
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update() {
+        let mut game = Game::new();
+        game.weather = Weather::Storm;
+        game.weather_until = config::now() + WEATHER_DURATION;
+        assert!(!update(&mut game));
+        assert_eq!(Weather::Storm, game.weather);
+
+        game.weather_until = 0;
+        update(&mut game);
+        assert!(game.weather_until > config::now());
+
+        assert!(Weather::Storm.empowers("storm"));
+        assert!(!Weather::Rain.empowers("snake"));
+    }
+}